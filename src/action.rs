@@ -0,0 +1,398 @@
+//! Action vocabulary shared by the keymap and the app reducer, and the
+//! side effects the reducer asks the executor to perform.
+
+use std::path::PathBuf;
+
+use crate::config::{Remote, SplitPane, SplitPreset};
+use crate::types::{PickPurpose, SessionSnapshot, SessionTarget};
+
+/// A user intent, independent of the key that produced it. The meaning of
+/// mode-generic actions (`Submit`, `Cancel`, text editing) depends on the
+/// current [`crate::types::AppMode`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    SelectNext,
+    SelectPrevious,
+    SelectFirst,
+    SelectLast,
+    /// Move the cursor of the focused list down by this many rows.
+    SelectDown(usize),
+    SelectUp(usize),
+    /// Select the listed session with this number (1-based).
+    SelectNumber(usize),
+    /// Mark the selected session with this key.
+    SetMark(char),
+    /// Select the session marked with this key.
+    JumpToMark(char),
+    /// Next session list order: name, recent, created, windows.
+    CycleSort,
+    /// Show or hide sessions matching the config's `ignore` list.
+    ToggleIgnored,
+    /// Recreate the session killed last, if recent enough.
+    UndoKill,
+    /// Mouse pressed on the border between the lists and the preview.
+    StartResize,
+    /// Border dragged to this screen column.
+    ResizeLists(u16),
+    /// Border released; the width is saved.
+    EndResize,
+    /// Enter visual mode with the selected session marked.
+    StartVisual,
+    /// Mark or unmark the selected session in visual mode.
+    ToggleVisualMark,
+    /// First key of a two-key sequence (`gg`, `dd`) was pressed.
+    PendingKey(char),
+    /// Scroll the preview back into history by this many lines.
+    ScrollPreviewUp(usize),
+    /// Scroll the preview towards the live screen by this many lines.
+    ScrollPreviewDown(usize),
+    ToggleFocus,
+    ToggleHelp,
+    CloseHelp,
+    /// Clear a sticky error.
+    DismissError,
+    StartSearch,
+    StartWindowSearch,
+    PromptNewSession,
+    PromptRunCommand,
+    PromptSendKeys,
+    PromptSendText,
+    PromptNewWindowCommand,
+    /// Open the options of the selected session, or of the selected window
+    /// when the Windows panel has focus.
+    ShowOptions,
+    PromptRename,
+    /// Rename the selected session's active window (or the selected window
+    /// when the Windows panel has focus).
+    PromptRenameWindow,
+    PromptTag,
+    /// Zoom or unzoom the active pane of the selected window.
+    ToggleZoom,
+    /// Swap the selected window with the one above / below it.
+    SwapWindowUp,
+    SwapWindowDown,
+    /// Pick a session to move the selected window to.
+    PromptMoveWindow,
+    /// Pick a layout preset for the selected window.
+    PromptLayout,
+    /// Pick a pane of the selected window to kill.
+    RequestKillPane,
+    /// Pick a pane of the selected window to break out into its own window.
+    RequestBreakPane,
+    /// Pick a pane of the selected window, then a window to join it into.
+    RequestJoinPane,
+    /// Pick a dead pane of the selected window to restart.
+    RequestRespawnPane,
+    ToggleTagFilter,
+    /// Group the session list by top-level directory, or stop.
+    TogglePathGroups,
+    ReviewCleanup,
+    /// Ask for confirmation before killing the selected session.
+    RequestKill,
+    /// Ask for confirmation before killing the whole tmux server.
+    RequestKillServer,
+    /// Detach every client of the selected session (or of the session
+    /// whose clients popup is open).
+    DetachSelected,
+    /// Detach the clients of the open clients panel that sat idle past
+    /// `idle_detach_hours`.
+    DetachIdle,
+    /// Open the list of tmux server sockets.
+    ShowServers,
+    /// Add the highlighted socket to the sessions listed alongside the
+    /// managed server, or take it out again.
+    ToggleServerView,
+    /// Choose between the local server and the configured remote hosts.
+    ShowHosts,
+    /// Open the group choices of the selected session.
+    ShowGroup,
+    /// Open the activity timeline of the selected session.
+    ShowTimeline,
+    /// Compare the server with the snapshot saved when tmui last exited.
+    ShowChanges,
+    /// Open attach counts and time per session.
+    ShowStats,
+    /// Open the paste buffers, to paste into the selected session or
+    /// window.
+    ShowBuffers,
+    /// Delete the highlighted paste buffer.
+    DeleteSelectedBuffer,
+    /// Copy the highlighted paste buffer to the system clipboard.
+    YankBuffer,
+    /// Copy the text of the previewed window to the system clipboard.
+    YankPane,
+    /// Ask where to save the previewed window's scrollback.
+    PromptCaptureToFile,
+    /// Start or stop logging the selected window's active pane to a file.
+    TogglePaneLog,
+    /// Remove the highlighted socket if its server is gone.
+    RemoveStraySocket,
+    /// Open the popup listing clients attached to the selected session.
+    ShowClients,
+    /// Switch to (or attach) the selected session or window.
+    AttachSelected,
+    /// Attach to the selected session or window without being able to type.
+    AttachReadOnly,
+    /// Go back to the previously used session.
+    SwitchLastSession,
+    /// Open the new-session form (name, directory, command).
+    PromptSessionWizard,
+    /// Open the command palette.
+    OpenPalette,
+    InsertText(String),
+    DeleteChar,
+    /// Tab/Shift-Tab between the fields of a form.
+    FocusNextField,
+    FocusPreviousField,
+    Submit,
+    Cancel,
+    /// Create a numbered sibling of the conflicting session name.
+    ConflictCreateSibling,
+    /// Kill and recreate the conflicting session.
+    ConflictReplace,
+    FocusChanged(bool),
+}
+
+/// tmux or filesystem work requested by the reducer. The executor reports
+/// the outcome through the status line or error message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Effect {
+    CreateSession(String),
+    /// `new-session` started in `path` and running `command`, if given,
+    /// then split into `splits`.
+    CreateSessionWith {
+        name: String,
+        path: Option<String>,
+        command: Option<String>,
+        splits: Vec<SplitPane>,
+    },
+    ReplaceSession(SessionTarget),
+    RenameSession {
+        from: SessionTarget,
+        to: String,
+    },
+    /// `rename-window` on a `session:index` target.
+    RenameWindow {
+        target: String,
+        to: String,
+    },
+    KillSession(SessionTarget),
+    KillServer,
+    /// Kill each session on its own server.
+    CleanupSessions(Vec<SessionTarget>),
+    KillSessions(Vec<SessionTarget>),
+    /// Detach every client of each session.
+    DetachSessions(Vec<SessionTarget>),
+    DetachClients(SessionTarget),
+    /// Detach only the client on this tty, then reload `session`'s clients.
+    DetachClient {
+        session: SessionTarget,
+        tty: String,
+    },
+    /// `resize-pane -Z` on the active pane of a window target.
+    ToggleZoom(String),
+    /// `swap-window` between two windows (by id) of the same session.
+    SwapWindows {
+        source: String,
+        target: String,
+    },
+    /// `move-window` into the next free index of session `to`.
+    MoveWindow {
+        window_id: String,
+        to: String,
+    },
+    /// `select-layout` on a `session:index` target.
+    SelectLayout {
+        target: String,
+        layout: String,
+    },
+    /// `new-session -t`: session `name` sharing the windows (and group) of
+    /// `target`.
+    CreateGroupedSession {
+        name: String,
+        target: SessionTarget,
+    },
+    /// Detach clients idle past `idle_detach_after`, of `session` or of
+    /// every session; reopens the clients panel of `session`.
+    DetachIdleClients(Option<SessionTarget>),
+    /// Ask tmux whether session `name` exists, for the open name prompt.
+    CheckSessionName(String),
+    /// Open the buffers popup for pasting into `target`.
+    ListBuffers(String),
+    /// Load the contents of buffer `name` into the preview.
+    PreviewBuffer(String),
+    /// Copy paste buffer `name` to the system clipboard.
+    CopyBuffer(String),
+    /// Copy the plain text of `target`'s active pane to the system
+    /// clipboard.
+    CopyPane(String),
+    /// Write the full scrollback of `target`'s active pane to `path`.
+    CaptureToFile {
+        target: String,
+        path: PathBuf,
+    },
+    /// Append the output of `target`'s active pane to `path`.
+    StartPaneLog {
+        target: String,
+        path: PathBuf,
+    },
+    /// Stop logging `target`'s active pane.
+    StopPaneLog(String),
+    /// `paste-buffer` into the active pane of `target`.
+    PasteBuffer {
+        name: String,
+        target: String,
+    },
+    /// `delete-buffer`, then reload the popup for `target`.
+    DeleteBuffer {
+        name: String,
+        target: String,
+    },
+    /// Load `show-options` of `target` into the options picker.
+    ListOptions {
+        target: String,
+        window: bool,
+    },
+    /// `set-option`, then reload the options picker.
+    SetOption {
+        target: String,
+        window: bool,
+        name: String,
+        value: String,
+    },
+    /// Load the panes of a window and open the pane picker for `purpose`.
+    ListPanes {
+        window_id: String,
+        purpose: PickPurpose,
+    },
+    KillPane(String),
+    /// `respawn-pane -k`, restarting the pane's command.
+    RespawnPane(String),
+    /// `break-pane` into a new window of the same session.
+    BreakPane(String),
+    /// `join-pane` moving `pane_id` into window `target`.
+    JoinPane {
+        pane_id: String,
+        target: String,
+    },
+    /// Scan the socket directory and open the servers popup.
+    ListServers,
+    /// Snapshot the server and diff it against the saved one.
+    LoadChanges,
+    /// Credit current attachments, then open the usage popup.
+    LoadStats,
+    /// Manage the server on this socket from now on.
+    ConnectServer(PathBuf),
+    /// Manage the server on a remote host over ssh, or the local one
+    /// again with `None`.
+    ConnectRemote(Option<Remote>),
+    /// Delete a dead server's socket file, then rescan.
+    RemoveSocket(PathBuf),
+    /// Load the clients of a session and open the clients popup.
+    ListClients(SessionTarget),
+    RunCommand {
+        name: String,
+        command: String,
+    },
+    /// `new-window -d` in `session` running `command`.
+    NewWindow {
+        session: SessionTarget,
+        name: String,
+        command: String,
+    },
+    /// Recreate a killed session with empty shells in its windows'
+    /// directories.
+    RestoreSession(SessionSnapshot),
+    /// A background window in `session` laid out by `preset`.
+    NewSplitWindow {
+        session: SessionTarget,
+        preset: SplitPreset,
+    },
+    /// Type `text` into the active pane of `target`, then press Enter.
+    SendKeys {
+        target: String,
+        text: String,
+    },
+    /// Paste the file at `input`, or `input` itself when it names no file,
+    /// into the active pane of `target`.
+    SendText {
+        target: String,
+        input: String,
+    },
+    /// Switch the client inside tmux, or leave the TUI and attach outside.
+    /// The name may be a `session:index` window target.
+    Attach(SessionTarget),
+    /// Watch the target read-only: a popup inside tmux, `attach -r` outside.
+    AttachReadOnly(SessionTarget),
+    /// `switch-client -l`: back to this client's previous session.
+    SwitchLastClient,
+    SaveConfig,
+}
+
+/// Effects produced by one reducer step, run in order.
+impl Effect {
+    /// Effects about servers as a whole, which are not routed to the
+    /// server of the selected session.
+    pub fn is_server_wide(&self) -> bool {
+        matches!(
+            self,
+            Effect::ListServers
+                | Effect::ConnectServer(_)
+                | Effect::ConnectRemote(_)
+                | Effect::RemoveSocket(_)
+                | Effect::KillServer
+                | Effect::SaveConfig
+        )
+    }
+
+    /// Server of the one session an effect is about, where it runs
+    /// whatever is selected by then; `Some(None)` is the managed server.
+    pub fn session_server(&self) -> Option<Option<&str>> {
+        let target = match self {
+            Effect::ReplaceSession(target)
+            | Effect::RenameSession { from: target, .. }
+            | Effect::KillSession(target)
+            | Effect::DetachClients(target)
+            | Effect::DetachClient {
+                session: target, ..
+            }
+            | Effect::CreateGroupedSession { target, .. }
+            | Effect::DetachIdleClients(Some(target))
+            | Effect::ListClients(target)
+            | Effect::NewWindow {
+                session: target, ..
+            }
+            | Effect::NewSplitWindow {
+                session: target, ..
+            }
+            | Effect::Attach(target)
+            | Effect::AttachReadOnly(target) => target,
+            Effect::RestoreSession(snapshot) => return Some(snapshot.server.as_deref()),
+            _ => return None,
+        };
+        Some(target.server.as_deref())
+    }
+
+    /// Effects that only read from tmux or point tmui at another server.
+    /// The rest change a server, the config or other files, and are not
+    /// run during a replay.
+    pub fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            Effect::CheckSessionName(_)
+                | Effect::ListBuffers(_)
+                | Effect::PreviewBuffer(_)
+                | Effect::ListOptions { .. }
+                | Effect::ListPanes { .. }
+                | Effect::ListServers
+                | Effect::LoadChanges
+                | Effect::LoadStats
+                | Effect::ConnectServer(_)
+                | Effect::ConnectRemote(_)
+                | Effect::ListClients(_)
+        )
+    }
+}
+
+pub type Effects = Vec<Effect>;
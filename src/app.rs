@@ -1,14 +1,60 @@
 use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::Rect;
 
-use crate::config::Config;
-use crate::search::{self, MatchResult};
-use crate::tmux;
-use crate::types::{AppMode, AppResult, ConfirmAction, FocusPanel, InputPurpose, Session, Window};
+use crate::action::{Action, Effect, Effects};
+use crate::clipboard::{self, ClipboardMode};
+use crate::config::{AlternateScreen, Config, Remote, SessionNumbers, SessionSort, SplitPane};
+use crate::form::{self, Field, Form};
+use crate::heat::{self, HeatTracker};
+use crate::palette::{self, Command};
+use crate::ports;
+use crate::replay::Recorder;
+use crate::search::{self, MatchResult, WindowMatch};
+use crate::servers::{self, ServerSocket};
+use crate::snapshot::{self, Change, Snapshot};
+use crate::timeline::Timeline;
+use crate::tmux::{self, Capabilities};
+use crate::types::{
+    AppMode, AppResult, Client, ConfirmAction, ConfirmStyle, FocusPanel, FormPurpose, InputPurpose,
+    Pane, PasteBuffer, PickPurpose, Session, SessionSnapshot, SessionTarget, TmuxOption, Window,
+};
+use crate::ui::ColorMode;
 
 const DOUBLE_TAP_WINDOW: Duration = Duration::from_millis(500);
+/// How long `u` can still bring back a killed session.
+const UNDO_KILL_WINDOW: Duration = Duration::from_secs(60);
+/// j/k presses closer together than this are a held key repeating.
+const KEY_REPEAT_WINDOW: Duration = Duration::from_millis(100);
+const PORT_SCAN_INTERVAL: Duration = Duration::from_secs(10);
+/// How often `idle_detach_hours` is enforced.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(300);
+/// History lines captured while the preview is scrolled back.
+const SCROLLBACK_LINES: i64 = 1000;
+/// Presets offered by `L`, in `select-layout` spelling.
+const LAYOUTS: [&str; 5] = [
+    "even-horizontal",
+    "even-vertical",
+    "main-horizontal",
+    "main-vertical",
+    "tiled",
+];
+/// Lines moved by Ctrl-u/Ctrl-d; PageUp/PageDown move twice as far.
+const PREVIEW_HALF_PAGE: usize = 10;
+
+/// An attach the event loop performs after leaving the TUI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingAttach {
+    pub target: String,
+    pub read_only: bool,
+    /// Server of the target when it is not the managed one.
+    pub server: Option<tmux::Socket>,
+}
 
 pub struct App {
     pub sessions: Vec<Session>,
@@ -19,19 +65,121 @@ pub struct App {
     pub status_message: String,
     pub preview_content: String,
     pub last_g_press: Option<Instant>,
+    /// Both by [`Session::key`], so same-named sessions on other servers
+    /// keep their own rows.
     pub expanded_sessions: HashSet<String>,
     pub session_windows: HashMap<String, Vec<Window>>,
     pub filtered_results: Vec<MatchResult>,
     pub search_active: bool,
     pub config: Config,
     pub tag_filter: Option<String>,
+    /// List sessions matching `ignore` in the config too.
+    pub show_ignored: bool,
     pub show_help: bool,
     pub error_message: Option<String>,
     pub error_time: Option<Instant>,
+    /// The error came from a failed action and stays until dismissed.
+    pub error_sticky: bool,
     pub focus: FocusPanel,
     pub selected_window: usize,
+    pub filtered_windows: Vec<WindowMatch>,
+    pub window_search_active: bool,
+    pub session_ports: HashMap<String, Vec<u16>>,
+    /// Clients shown by the clients popup.
+    pub clients: Vec<Client>,
+    pub selected_client: usize,
+    /// Sockets shown by the servers popup.
+    pub servers: Vec<ServerSocket>,
+    pub selected_server: usize,
+    /// Rows of the sessions and windows lists as last drawn, for paging.
+    pub list_rows: (usize, usize),
+    /// Screen as last drawn, for mouse hits.
+    pub screen: Rect,
+    /// The lists/preview border is being dragged.
+    resizing: bool,
+    /// Directory of server snapshots; `None` keeps none (tests, replay).
+    pub snapshot_dir: Option<PathBuf>,
+    /// Save `mru` and `visits` to the config on attach and exit; off in
+    /// tests and replays.
+    pub save_usage: bool,
+    /// Differences shown by `E`, and when the snapshot they start from
+    /// was taken.
+    pub changes: Vec<Change>,
+    pub changes_since: i64,
+    /// Session killed last and when, for `u`.
+    last_killed: Option<(SessionSnapshot, Instant)>,
+    /// Highlighted row of the command palette.
+    pub palette_selected: usize,
+    /// Set when tmui switched to another server, so the event loop can
+    /// drop its control client for the old one.
+    pub server_changed: bool,
+    /// Host whose server is managed over ssh; `None` for the local one.
+    pub remote_host: Option<String>,
+    /// Set by `--record`: input and state changes are appended to it.
+    pub recorder: Option<Recorder>,
+    /// Set by `--replay`: effects that are not read-only are collected
+    /// here instead of run, so playing back a recording changes nothing.
+    pub dry_run: Option<Vec<Effect>>,
+    /// Choices of the open picker popup.
+    pub pick_items: Vec<String>,
+    pub pick_selected: usize,
+    /// Panes behind `pick_items` while a pane picker is open.
+    pub panes: Vec<Pane>,
+    /// Fields of the open form.
+    pub form: Form,
+    /// Options behind `pick_items` while the options picker is open.
+    pub options: Vec<TmuxOption>,
+    /// Name typed into a session prompt that tmux reported as taken.
+    pub name_taken: Option<String>,
+    /// What happened to each session while tmui watched.
+    pub timeline: Timeline,
+    pub buffers: Vec<PasteBuffer>,
+    pub selected_buffer: usize,
+    /// Contents of the highlighted buffer.
+    pub buffer_preview: String,
+    last_port_scan: Option<Instant>,
+    pub heat: HeatTracker,
+    /// How much styling the terminal can show.
+    pub color_mode: ColorMode,
+    /// Set the terminal title and cursor shape and write OSC 52 copies;
+    /// off for off-screen terminals.
+    pub terminal_chrome: bool,
+    /// Features supported by the connected tmux server.
+    pub capabilities: Capabilities,
+    /// Whether the terminal has focus; background refresh slows down while
+    /// it does not.
+    pub focused: bool,
+    /// Whether attaching means `switch-client` (inside a client of the
+    /// managed server) rather than exec'ing `tmux attach-session`.
+    pub inside_tmux: bool,
+    /// `$TMUX` points at a different server, so exec'ing `attach-session`
+    /// would nest tmux.
+    pub nested_client: bool,
+    /// Session of the client tmui runs in, when inside tmux.
+    pub current_session: Option<String>,
+    /// Terminal of the client tmui runs in, never detached as idle.
+    own_tty: Option<String>,
+    /// Attach waiting for the event loop to leave the TUI.
+    pub pending_attach: Option<PendingAttach>,
+    /// OSC 52 sequence waiting for the event loop to write it.
+    pub pending_osc52: Option<String>,
+    last_heat_sample: Option<Instant>,
+    last_idle_check: Option<Instant>,
     last_d_press: Option<Instant>,
+    /// Sessions marked in visual mode, by [`Session::key`].
+    pub visual_marked: HashSet<String>,
+    /// `m` or `'` was pressed; the next key names the mark.
+    pending_mark: Option<char>,
+    /// Direction, time and count of the j/k presses repeating so far.
+    nav_repeat: Option<(bool, Instant, usize)>,
     last_preview_update: Option<Instant>,
+    /// Lines scrolled back from the bottom of the preview; 0 follows the
+    /// live screen.
+    pub preview_scroll: usize,
+    preview_target: String,
+    /// Working directory of the previewed window, fetched when the Windows
+    /// panel selects a new one.
+    pub pane_path: Option<String>,
 }
 
 impl App {
@@ -52,54 +200,260 @@ impl App {
             search_active: false,
             config,
             tag_filter: None,
+            show_ignored: false,
             show_help: false,
             error_message: None,
             error_time: None,
+            error_sticky: false,
             focus: FocusPanel::Sessions,
             selected_window: 0,
+            filtered_windows: Vec::new(),
+            window_search_active: false,
+            session_ports: HashMap::new(),
+            clients: Vec::new(),
+            selected_client: 0,
+            servers: Vec::new(),
+            selected_server: 0,
+            palette_selected: 0,
+            list_rows: (0, 0),
+            screen: Rect::default(),
+            resizing: false,
+            last_killed: None,
+            snapshot_dir: None,
+            save_usage: false,
+            changes: Vec::new(),
+            changes_since: 0,
+            server_changed: false,
+            remote_host: None,
+            recorder: None,
+            dry_run: None,
+            pick_items: Vec::new(),
+            pick_selected: 0,
+            panes: Vec::new(),
+            form: Form::default(),
+            options: Vec::new(),
+            name_taken: None,
+            timeline: Timeline::default(),
+            buffers: Vec::new(),
+            selected_buffer: 0,
+            buffer_preview: String::new(),
+            last_port_scan: None,
+            heat: HeatTracker::default(),
+            capabilities: Capabilities::default(),
+            color_mode: ColorMode::default(),
+            terminal_chrome: false,
+            focused: true,
+            inside_tmux: tmux::is_inside_tmux(),
+            nested_client: false,
+            current_session: None,
+            pending_attach: None,
+            pending_osc52: None,
+            last_heat_sample: None,
+            last_idle_check: None,
+            own_tty: None,
             last_d_press: None,
+            pending_mark: None,
+            nav_repeat: None,
+            visual_marked: HashSet::new(),
             last_preview_update: None,
+            preview_scroll: 0,
+            preview_target: String::new(),
+            pane_path: None,
         }
     }
 
     pub fn visible_session_count(&self) -> usize {
         if self.search_active {
             self.filtered_results.len()
-        } else if self.tag_filter.is_some() {
+        } else if self.tag_filter.is_some() || self.hides_ignored() {
             self.tag_filtered_sessions().len()
         } else {
             self.sessions.len()
         }
     }
 
+    /// Whether sessions matching `ignore` are left out of the list.
+    pub fn hides_ignored(&self) -> bool {
+        !self.show_ignored && !self.config.ignore.is_empty()
+    }
+
+    /// Number of sessions matching `ignore`.
+    pub fn ignored_count(&self) -> usize {
+        self.sessions
+            .iter()
+            .filter(|s| self.config.is_ignored(&s.name))
+            .count()
+    }
+
+    /// Indices of the listed sessions in display order: narrowed by the
+    /// tag filter and the ignore list, sorted by `sort`, and ordered by
+    /// directory group with `group_by_path`.
     pub fn tag_filtered_sessions(&self) -> Vec<usize> {
-        if let Some(ref tag) = self.tag_filter {
-            let tagged = self.config.sessions_with_tag(tag);
-            self.sessions
+        let hide = self.hides_ignored();
+        let mut indices: Vec<usize> = self
+            .sessions
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| {
+                self.tag_filter
+                    .as_ref()
+                    .is_none_or(|tag| self.config.has_tag(&s.name, tag))
+            })
+            .filter(|(_, s)| !(hide && self.config.is_ignored(&s.name)))
+            .map(|(i, _)| i)
+            .collect();
+        let sessions = &self.sessions;
+        match self.config.sort.unwrap_or_default() {
+            SessionSort::Used => {
+                let mru = &self.config.mru;
+                indices.sort_by_key(|&i| {
+                    mru.iter()
+                        .position(|name| *name == sessions[i].name)
+                        .unwrap_or(usize::MAX)
+                })
+            }
+            SessionSort::Name => {}
+            SessionSort::Recent => {
+                indices.sort_by_key(|&i| std::cmp::Reverse(sessions[i].last_attached))
+            }
+            SessionSort::Created => {
+                indices.sort_by_key(|&i| std::cmp::Reverse(sessions[i].created))
+            }
+            SessionSort::Windows => {
+                indices.sort_by_key(|&i| std::cmp::Reverse(sessions[i].windows))
+            }
+        }
+        if self.config.group_by_path.unwrap_or(false) {
+            indices.sort_by_cached_key(|&i| self.path_group(&self.sessions[i]));
+        }
+        // Each server's sessions stay together, the managed server first.
+        let servers = self.extra_servers();
+        indices.sort_by_key(|&i| {
+            let server = sessions[i].server.as_ref();
+            server.map_or(0, |server| {
+                servers
+                    .iter()
+                    .position(|s| s == server)
+                    .map_or(usize::MAX, |p| p + 1)
+            })
+        });
+        indices
+    }
+
+    /// Directory group of `session` in the path-grouped view.
+    pub fn path_group(&self, session: &Session) -> String {
+        let home = std::env::var("HOME").unwrap_or_default();
+        path_group(&session.path, &home)
+    }
+
+    fn update_search_filter(&mut self) {
+        let windows = &self.session_windows;
+        self.filtered_results =
+            search::fuzzy_match_sessions_with(&self.sessions, &self.input_buffer, |session| {
+                let hosts: Vec<&str> = windows
+                    .get(&session.key())?
+                    .iter()
+                    .filter_map(|w| w.remote_host.as_deref())
+                    .collect();
+                (!hosts.is_empty()).then(|| hosts.join(" "))
+            });
+        let sessions = &self.sessions;
+        let config = &self.config;
+        if self.hides_ignored() {
+            self.filtered_results
+                .retain(|m| !config.is_ignored(&sessions[m.session_index].name));
+        }
+        if !self.input_buffer.is_empty() && !config.visits.is_empty() {
+            let now = unix_now();
+            search::boost_sessions(&mut self.filtered_results, |i| {
+                config
+                    .visits
+                    .get(&sessions[i].name)
+                    .map_or(0, |visit| visit.bonus(now))
+            });
+        }
+        self.selected = 0;
+    }
+
+    fn update_window_filter(&mut self) {
+        self.filtered_windows = self
+            .selected_windows()
+            .map(|windows| search::fuzzy_match_windows(windows, &self.input_buffer))
+            .unwrap_or_default();
+        self.selected_window = 0;
+    }
+
+    /// Windows of the selected session in display order, narrowed by the
+    /// window search while it is active.
+    pub fn visible_windows(&self) -> Vec<&Window> {
+        let Some(windows) = self.selected_windows() else {
+            return Vec::new();
+        };
+        if self.window_search_active {
+            self.filtered_windows
                 .iter()
-                .enumerate()
-                .filter(|(_, s)| tagged.contains(&s.name))
-                .map(|(i, _)| i)
+                .filter_map(|m| windows.get(m.window_index))
                 .collect()
         } else {
-            (0..self.sessions.len()).collect()
+            windows.iter().collect()
         }
     }
 
-    fn update_search_filter(&mut self) {
-        self.filtered_results = search::fuzzy_match_sessions(&self.sessions, &self.input_buffer);
-        self.selected = 0;
+    pub fn selected_window_entry(&self) -> Option<&Window> {
+        self.visible_windows().get(self.selected_window).copied()
+    }
+
+    /// Commands offered by the palette for the typed query.
+    pub fn palette_commands(&self) -> Vec<Command> {
+        palette::matching(&self.input_buffer, self.focus == FocusPanel::Windows)
+    }
+
+    /// The window shown in the preview: the selected one, or window 0 of
+    /// the selected session while the sessions panel has focus.
+    pub fn preview_window(&self) -> Option<&Window> {
+        match self.focus {
+            FocusPanel::Windows => self.selected_window_entry(),
+            FocusPanel::Sessions => self
+                .session_windows
+                .get(&self.selected_session()?.key())?
+                .iter()
+                .find(|w| w.index == 0),
+        }
+    }
+
+    /// The selected window, or the active window of the selected session
+    /// while the sessions panel has focus.
+    fn target_window(&self) -> Option<&Window> {
+        match self.focus {
+            FocusPanel::Windows => self.selected_window_entry(),
+            FocusPanel::Sessions => self
+                .session_windows
+                .get(&self.selected_session()?.key())?
+                .iter()
+                .find(|w| w.active),
+        }
     }
 
     pub async fn refresh_sessions(&mut self) -> AppResult<()> {
-        match tmux::list_sessions().await {
-            Ok(sessions) => {
-                self.sessions = sessions;
-            }
-            Err(_) => {
-                self.sessions.clear();
+        let mut sessions = tmux::on_server(None, tmux::list_sessions())
+            .await
+            .unwrap_or_default();
+        for server in self.extra_servers() {
+            let socket = Some(tmux::Socket::from_setting(&server));
+            let Ok(listed) = tmux::on_server(socket, tmux::list_sessions()).await else {
+                continue;
+            };
+            for mut session in listed {
+                session.id = format!("{server}/{}", session.id);
+                session.server = Some(server.clone());
+                sessions.push(session);
             }
         }
+        self.sessions = sessions;
+        self.timeline.observe_sessions(&self.sessions, unix_now());
+        if self.sessions.iter().any(|s| s.runner && s.server.is_none()) {
+            tmux::on_server(None, self.clean_finished_runners()).await;
+        }
         if self.sessions.is_empty() {
             self.selected = 0;
         } else if self.selected >= self.sessions.len() {
@@ -108,22 +462,249 @@ impl App {
         Ok(())
     }
 
+    /// Re-fetch window rows of every session with a single `list-windows -a`
+    /// so name and command changes show up live. Cached rows of sessions
+    /// that no longer exist are dropped.
+    pub async fn refresh_windows(&mut self) {
+        let existing: HashSet<String> = self.sessions.iter().map(Session::key).collect();
+        self.session_windows.retain(|key, _| existing.contains(key));
+
+        let mut listings = vec![(None, tmux::on_server(None, tmux::list_all_windows()).await)];
+        for server in self.extra_servers() {
+            let socket = Some(tmux::Socket::from_setting(&server));
+            let listing = tmux::on_server(socket, tmux::list_all_windows()).await;
+            listings.push((Some(server), listing));
+        }
+        let now = unix_now();
+        for (server, listing) in listings {
+            let Ok(all) = listing else {
+                continue;
+            };
+            for (name, windows) in all {
+                if let Some(session) = self
+                    .sessions
+                    .iter()
+                    .find(|s| s.name == name && s.server == server)
+                {
+                    self.timeline.observe_windows(&session.id, &windows, now);
+                    self.session_windows.insert(session.key(), windows);
+                }
+            }
+        }
+
+        let count = self.visible_windows().len();
+        if self.selected_window >= count {
+            self.selected_window = count.saturating_sub(1);
+        }
+    }
+
+    /// Rescan listening ports when port hints are enabled and the cached
+    /// scan is older than [`PORT_SCAN_INTERVAL`]. The scan reads local
+    /// sockets and /proc, so servers on ssh hosts get no hints.
+    pub async fn refresh_ports(&mut self) {
+        if !self.config.port_hints.unwrap_or(false)
+            || tmux::remote().is_some()
+            || self
+                .last_port_scan
+                .is_some_and(|t| t.elapsed() < PORT_SCAN_INTERVAL)
+        {
+            return;
+        }
+        self.last_port_scan = Some(Instant::now());
+
+        let Ok(pane_pids) = tmux::list_pane_pids().await else {
+            return;
+        };
+        if let Ok(ports) = ports::scan(&pane_pids).await {
+            self.session_ports = ports;
+        }
+    }
+
+    /// Windows to capture when the heat column is enabled and the last
+    /// sample is older than [`heat::SAMPLE_INTERVAL`]. The event loop
+    /// captures them in the background and hands the round to `heat`.
+    pub fn heat_targets(&mut self) -> Option<Vec<heat::Target>> {
+        if !self.config.heat_column.unwrap_or(false)
+            || self
+                .last_heat_sample
+                .is_some_and(|t| t.elapsed() < heat::SAMPLE_INTERVAL)
+        {
+            return None;
+        }
+        self.last_heat_sample = Some(Instant::now());
+
+        let targets = self
+            .session_windows
+            .iter()
+            .flat_map(|(key, windows)| {
+                let server = self.server_of(key);
+                windows.iter().map(move |window| heat::Target {
+                    session: key.clone(),
+                    server: server.clone(),
+                    window_id: window.id.clone(),
+                })
+            })
+            .collect();
+        Some(targets)
+    }
+
+    /// Detach forgotten clients every [`IDLE_CHECK_INTERVAL`] when
+    /// `idle_detach_hours` is set.
+    pub async fn enforce_idle_detach(&mut self) {
+        if self.config.idle_detach_hours.is_none()
+            || self.dry_run.is_some()
+            || self
+                .last_idle_check
+                .is_some_and(|t| t.elapsed() < IDLE_CHECK_INTERVAL)
+        {
+            return;
+        }
+        self.last_idle_check = Some(Instant::now());
+        self.detach_idle_clients(None).await;
+    }
+
+    /// Detach clients (of `session`, or all) idle past the configured
+    /// limit, except tmui's own.
+    async fn detach_idle_clients(&mut self, session: Option<&str>) {
+        let clients = match session {
+            Some(session) => tmux::list_clients(session).await,
+            None => tmux::list_all_clients().await,
+        };
+        let Ok(clients) = clients else {
+            return;
+        };
+        let idle_after = self.config.idle_detach_after().as_secs() as i64;
+        let now = unix_now();
+        let mut detached = Vec::new();
+        for client in clients {
+            if now - client.activity < idle_after || self.own_tty.as_ref() == Some(&client.tty) {
+                continue;
+            }
+            match tmux::detach_tty(&client.tty).await {
+                Ok(_) => detached.push(client.tty),
+                Err(e) => self.set_error(format!("Failed to detach {}: {e}", client.tty)),
+            }
+        }
+        let hours = idle_after / 3600;
+        if !detached.is_empty() {
+            self.status_message = format!("Detached {} idle over {hours}h", detached.join(", "));
+            let _ = self.refresh_sessions().await;
+        } else if session.is_some() {
+            self.status_message = format!("No clients idle over {hours}h");
+        }
+    }
+
+    /// Query the tmux version and warn about features it cannot support.
+    pub async fn detect_capabilities(&mut self) {
+        let version = tmux::server_version().await.ok();
+        self.capabilities = Capabilities::for_version(version);
+
+        let Some(version) = version else {
+            return;
+        };
+        if version < tmux::MIN_SUPPORTED_VERSION {
+            self.set_error(format!(
+                "tmux {version} is older than {}; some features may not work",
+                tmux::MIN_SUPPORTED_VERSION
+            ));
+        } else if self.config.control_mode.unwrap_or(false) && !self.capabilities.control_client {
+            self.status_message =
+                format!("Control mode needs tmux 3.2+ (found {version}); polling instead");
+        }
+    }
+
+    /// Work out whether attaching switches this client, would nest tmux,
+    /// or execs a fresh client, and which session the client is on.
+    pub async fn detect_client(&mut self) {
+        if self.inside_tmux && !tmux::server_args().is_empty() {
+            self.inside_tmux = tmux::is_inside_target_server().await;
+            self.nested_client = !self.inside_tmux;
+        }
+        if self.inside_tmux {
+            self.current_session = tmux::current_session()
+                .await
+                .ok()
+                .filter(|name| !name.is_empty());
+            self.own_tty = tmux::current_client_tty()
+                .await
+                .ok()
+                .filter(|tty| !tty.is_empty());
+        }
+    }
+
+    /// Kill runner sessions whose command has exited and report the result.
+    async fn clean_finished_runners(&mut self) {
+        let Ok(finished) = tmux::finished_runners().await else {
+            return;
+        };
+        for (name, status) in finished {
+            let is_runner = self
+                .sessions
+                .iter()
+                .any(|s| s.runner && s.server.is_none() && s.name == name);
+            if !is_runner || tmux::kill_session(&name).await.is_err() {
+                continue;
+            }
+            self.sessions
+                .retain(|s| s.name != name || s.server.is_some());
+            self.session_windows.remove(&name);
+            self.status_message = format!("Runner `{name}` finished (exit {status})");
+        }
+    }
+
     pub async fn refresh_preview(&mut self) -> AppResult<()> {
-        if let Some(session) = self.sessions.get(self.selected) {
-            let name = session.name.clone();
-
-            let window_index = match self.focus {
-                FocusPanel::Windows => self
-                    .session_windows
-                    .get(&name)
-                    .and_then(|wins| wins.get(self.selected_window))
-                    .map(|w| w.index)
-                    .unwrap_or(0),
-                FocusPanel::Sessions => 0,
+        let server = self.session_server();
+        tmux::on_server(server, self.capture_preview()).await
+    }
+
+    /// [`App::refresh_preview`] on a tick: skipped while the preview still
+    /// shows the selected target and is younger than `preview_interval_ms`.
+    pub async fn refresh_stale_preview(&mut self) -> AppResult<()> {
+        let fresh = self
+            .last_preview_update
+            .is_some_and(|t| t.elapsed() < self.config.preview_interval());
+        if fresh && self.preview_target_name().as_ref() == Some(&self.preview_target) {
+            return Ok(());
+        }
+        self.refresh_preview().await
+    }
+
+    /// `session:window` the preview should show for the current selection.
+    fn preview_target_name(&self) -> Option<String> {
+        let session = self.selected_session()?;
+        let window_index = match self.focus {
+            FocusPanel::Windows => self.selected_window_entry().map(|w| w.index).unwrap_or(0),
+            FocusPanel::Sessions => 0,
+        };
+        Some(format!("{}:{window_index}", session.name))
+    }
+
+    async fn capture_preview(&mut self) -> AppResult<()> {
+        if let Some(target) = self.preview_target_name() {
+            let (key, name) = match self.selected_session() {
+                Some(session) => (session.key(), session.name.clone()),
+                None => Default::default(),
             };
-            let target = format!("{name}:{window_index}");
-            match tmux::capture_pane(&target).await {
+            if target != self.preview_target {
+                self.preview_scroll = 0;
+                self.preview_target = target.clone();
+                self.pane_path = None;
+            }
+            if self.focus == FocusPanel::Windows && self.pane_path.is_none() {
+                self.pane_path = tmux::pane_current_path(&target)
+                    .await
+                    .ok()
+                    .filter(|path| !path.is_empty());
+            }
+            let escapes =
+                !self.config.plain_preview.unwrap_or(false) && self.capabilities.capture_escapes;
+            let start = (self.preview_scroll > 0).then_some(-SCROLLBACK_LINES);
+            let saved_screen = self.config.alternate_screen.unwrap_or_default()
+                == AlternateScreen::Shell
+                && self.preview_window().is_some_and(|w| w.alternate);
+            match tmux::capture_pane(&target, escapes, start, saved_screen).await {
                 Ok(content) => {
+                    self.preview_scroll = self.preview_scroll.min(content.lines().count());
                     self.preview_content = content;
                     self.last_preview_update = Some(Instant::now());
                 }
@@ -132,8 +713,8 @@ impl App {
                 }
             }
 
-            if let std::collections::hash_map::Entry::Vacant(e) = self.session_windows.entry(name) {
-                if let Ok(windows) = tmux::list_windows(e.key()).await {
+            if let std::collections::hash_map::Entry::Vacant(e) = self.session_windows.entry(key) {
+                if let Ok(windows) = tmux::list_windows(&name).await {
                     e.insert(windows);
                 }
             }
@@ -144,774 +725,5275 @@ impl App {
     }
 
     pub async fn handle_event(&mut self, event: Event) -> AppResult<()> {
-        match event {
-            Event::Key(key) => {
-                if key.kind != KeyEventKind::Press {
-                    return Ok(());
-                }
-
-                if self.show_help && key.code != KeyCode::Char('?') {
-                    self.show_help = false;
-                    return Ok(());
-                }
-
-                match self.mode.clone() {
-                    AppMode::Normal => self.handle_normal_mode(key).await?,
-                    AppMode::Search => self.handle_search_mode(key).await?,
-                    AppMode::Input(purpose) => self.handle_input_mode(key, purpose).await?,
-                    AppMode::Confirm(action) => self.handle_confirm_mode(key, action).await?,
-                }
-            }
-            Event::Resize(_, _) => {}
-            _ => {}
+        let action = match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => self.key_to_action(key),
+            Event::Paste(text) => paste_action(&text),
+            Event::FocusGained => Some(Action::FocusChanged(true)),
+            Event::FocusLost => Some(Action::FocusChanged(false)),
+            Event::Mouse(mouse) => self.mouse_action(mouse),
+            _ => None,
+        };
+        if let Some(action) = action {
+            let effects = self.update(action);
+            self.run_effects(effects).await;
         }
 
         Ok(())
     }
 
-    async fn handle_normal_mode(&mut self, key: KeyEvent) -> AppResult<()> {
-        if matches!(
-            key,
-            KeyEvent {
-                code: KeyCode::Char('c'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            }
-        ) {
-            self.should_quit = true;
-            self.clear_multi_key_state();
-            return Ok(());
+    /// Translate a key press into an action for the current mode. Two-key
+    /// sequences (`gg`, `dd`) are tracked here so [`App::update`] only sees
+    /// complete intents.
+    fn key_to_action(&mut self, key: KeyEvent) -> Option<Action> {
+        if self.show_help && key.code != KeyCode::Char('?') {
+            return Some(Action::CloseHelp);
+        }
+        if self.error_sticky && key.code == KeyCode::Esc {
+            return Some(Action::DismissError);
         }
 
-        match key.code {
-            KeyCode::Char('q') => {
-                self.should_quit = true;
-                self.clear_multi_key_state();
-            }
-            KeyCode::Char('j') | KeyCode::Down => {
-                match self.focus {
-                    FocusPanel::Sessions => self.select_next(),
-                    FocusPanel::Windows => self.select_next_window(),
+        match &self.mode {
+            AppMode::Normal => self.normal_key_action(key),
+            AppMode::Search => match (key.modifiers, key.code) {
+                (_, KeyCode::Down) => Some(Action::SelectNext),
+                (_, KeyCode::Up) => Some(Action::SelectPrevious),
+                (KeyModifiers::CONTROL, KeyCode::Char('t')) if !self.window_search_active => {
+                    Some(Action::PromptTag)
                 }
-                self.clear_multi_key_state();
-            }
-            KeyCode::Char('k') | KeyCode::Up => {
-                match self.focus {
-                    FocusPanel::Sessions => self.select_previous(),
-                    FocusPanel::Windows => self.select_previous_window(),
+                _ => prompt_key_action(key),
+            },
+            AppMode::Input(_) => prompt_key_action(key),
+            AppMode::Palette => match (key.modifiers, key.code) {
+                (_, KeyCode::Down) | (KeyModifiers::CONTROL, KeyCode::Char('n')) => {
+                    Some(Action::SelectNext)
                 }
-                self.clear_multi_key_state();
-            }
-            KeyCode::Char('G') => {
-                match self.focus {
-                    FocusPanel::Sessions => self.select_last(),
-                    FocusPanel::Windows => self.select_last_window(),
+                (_, KeyCode::Up) | (KeyModifiers::CONTROL, KeyCode::Char('p')) => {
+                    Some(Action::SelectPrevious)
                 }
-                self.clear_multi_key_state();
+                _ => prompt_key_action(key),
+            },
+            AppMode::Form(_) => form::key_action(key),
+            AppMode::Confirm(action) => match self.config.confirm_style(action) {
+                ConfirmStyle::Yes => match key.code {
+                    KeyCode::Char('y') | KeyCode::Enter => Some(Action::Submit),
+                    KeyCode::Char('n') | KeyCode::Esc => Some(Action::Cancel),
+                    _ => None,
+                },
+                ConfirmStyle::No => match key.code {
+                    KeyCode::Char(c) if c == action.confirm_key() => Some(Action::Submit),
+                    KeyCode::Char('n') | KeyCode::Esc | KeyCode::Enter => Some(Action::Cancel),
+                    _ => None,
+                },
+                ConfirmStyle::Type => prompt_key_action(key),
+            },
+            AppMode::Clients(_) => match key.code {
+                KeyCode::Char('j') | KeyCode::Down => Some(Action::SelectNext),
+                KeyCode::Char('k') | KeyCode::Up => Some(Action::SelectPrevious),
+                KeyCode::Char('d') | KeyCode::Enter => Some(Action::Submit),
+                KeyCode::Char('D') => Some(Action::DetachSelected),
+                KeyCode::Char('i') => Some(Action::DetachIdle),
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('c') => Some(Action::Cancel),
+                _ => None,
+            },
+            AppMode::Servers => match key.code {
+                KeyCode::Char('j') | KeyCode::Down => Some(Action::SelectNext),
+                KeyCode::Char('k') | KeyCode::Up => Some(Action::SelectPrevious),
+                KeyCode::Enter => Some(Action::Submit),
+                KeyCode::Char('x') => Some(Action::RemoveStraySocket),
+                KeyCode::Char('v') => Some(Action::ToggleServerView),
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('S') => Some(Action::Cancel),
+                _ => None,
+            },
+            AppMode::Visual => match key.code {
+                KeyCode::Char('j') | KeyCode::Down => Some(Action::SelectNext),
+                KeyCode::Char('k') | KeyCode::Up => Some(Action::SelectPrevious),
+                KeyCode::Char(' ') => Some(Action::ToggleVisualMark),
+                KeyCode::Char('d') | KeyCode::Char('x') => Some(Action::RequestKill),
+                KeyCode::Char('t') => Some(Action::PromptTag),
+                KeyCode::Char('D') => Some(Action::DetachSelected),
+                KeyCode::Esc | KeyCode::Char('V') | KeyCode::Char('q') => Some(Action::Cancel),
+                _ => None,
+            },
+            AppMode::Timeline(_) => match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('h') => Some(Action::Cancel),
+                _ => None,
+            },
+            AppMode::Changes => match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('E') => Some(Action::Cancel),
+                _ => None,
+            },
+            AppMode::Stats => match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('U') => Some(Action::Cancel),
+                _ => None,
+            },
+            AppMode::Buffers(_) => match key.code {
+                KeyCode::Char('j') | KeyCode::Down => Some(Action::SelectNext),
+                KeyCode::Char('k') | KeyCode::Up => Some(Action::SelectPrevious),
+                KeyCode::Enter | KeyCode::Char('p') => Some(Action::Submit),
+                KeyCode::Char('d') | KeyCode::Char('x') => Some(Action::DeleteSelectedBuffer),
+                KeyCode::Char('y') => Some(Action::YankBuffer),
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('=') => Some(Action::Cancel),
+                _ => None,
+            },
+            AppMode::Pick(_) => match key.code {
+                KeyCode::Char('j') | KeyCode::Down => Some(Action::SelectNext),
+                KeyCode::Char('k') | KeyCode::Up => Some(Action::SelectPrevious),
+                KeyCode::Enter => Some(Action::Submit),
+                KeyCode::Esc | KeyCode::Char('q') => Some(Action::Cancel),
+                _ => None,
+            },
+            AppMode::Conflict(_) => match key.code {
+                KeyCode::Char('a') | KeyCode::Enter => Some(Action::Submit),
+                KeyCode::Char('c') => Some(Action::ConflictCreateSibling),
+                KeyCode::Char('x') => Some(Action::ConflictReplace),
+                KeyCode::Esc | KeyCode::Char('n') => Some(Action::Cancel),
+                _ => None,
+            },
+        }
+    }
+
+    /// Dragging the border between the lists and the preview resizes them.
+    pub fn mouse_action(&self, mouse: MouseEvent) -> Option<Action> {
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let border = crate::ui::list_border(self.screen, &self.config)?;
+                (mouse.column + 1 == border || mouse.column == border)
+                    .then_some(Action::StartResize)
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.resizing => {
+                Some(Action::ResizeLists(mouse.column))
             }
+            MouseEventKind::Up(MouseButton::Left) if self.resizing => Some(Action::EndResize),
+            _ => None,
+        }
+    }
+
+    fn normal_key_action(&mut self, key: KeyEvent) -> Option<Action> {
+        if let Some(prefix) = self.pending_mark.take() {
+            return match key.code {
+                KeyCode::Char(c) if key.modifiers - KeyModifiers::SHIFT == KeyModifiers::NONE => {
+                    Some(match prefix {
+                        'm' => Action::SetMark(c),
+                        _ => Action::JumpToMark(c),
+                    })
+                }
+                _ => Some(Action::Cancel),
+            };
+        }
+        if key.modifiers == KeyModifiers::CONTROL {
+            self.clear_multi_key_state();
+            return match key.code {
+                KeyCode::Char('c') => Some(Action::Quit),
+                KeyCode::Char('u') => Some(Action::SelectUp(self.page_rows() / 2)),
+                KeyCode::Char('d') => Some(Action::SelectDown(self.page_rows() / 2)),
+                KeyCode::Char('y') => Some(Action::ScrollPreviewUp(PREVIEW_HALF_PAGE)),
+                KeyCode::Char('e') => Some(Action::ScrollPreviewDown(PREVIEW_HALF_PAGE)),
+                // Ctrl-^ arrives as Ctrl-6 on most terminals.
+                KeyCode::Char('^') | KeyCode::Char('6') => Some(Action::SwitchLastSession),
+                KeyCode::Char('p') => Some(Action::OpenPalette),
+                _ => None,
+            };
+        }
+
+        match key.code {
             KeyCode::Char('g') => {
+                self.last_d_press = None;
                 if is_double_tap(self.last_g_press) {
-                    match self.focus {
-                        FocusPanel::Sessions => self.select_first(),
-                        FocusPanel::Windows => self.selected_window = 0,
-                    }
                     self.last_g_press = None;
-                } else {
-                    self.last_g_press = Some(Instant::now());
+                    return Some(Action::SelectFirst);
                 }
-                self.last_d_press = None;
+                self.last_g_press = Some(Instant::now());
+                return Some(Action::PendingKey('g'));
             }
             KeyCode::Char('d') => {
+                self.last_g_press = None;
                 if is_double_tap(self.last_d_press) {
-                    if let Some(name) = self.selected_session_name() {
-                        self.mode = AppMode::Confirm(ConfirmAction::KillSession(name.clone()));
-                        self.status_message = format!("Kill `{name}`? (y/n)");
-                    } else {
-                        self.status_message = "No session selected".to_string();
-                    }
                     self.last_d_press = None;
-                } else {
-                    self.last_d_press = Some(Instant::now());
-                    self.status_message = "Kill session: press d again".to_string();
+                    return Some(Action::RequestKill);
                 }
-                self.last_g_press = None;
+                self.last_d_press = Some(Instant::now());
+                return Some(Action::PendingKey('d'));
             }
-            KeyCode::Char('D') => {
-                if let Some(name) = self.selected_session_name() {
-                    match tmux::detach_client(&name).await {
-                        Ok(_) => {
-                            self.status_message = format!("Detached clients from `{name}`");
-                            let _ = self.refresh_sessions().await;
-                        }
-                        Err(e) => {
-                            self.set_error(format!("Failed to detach: {e}"));
-                        }
-                    }
-                } else {
-                    self.status_message = "No session selected".to_string();
-                }
+            KeyCode::Char('j') | KeyCode::Down => {
                 self.clear_multi_key_state();
+                return Some(match self.repeat_step(true) {
+                    1 => Action::SelectNext,
+                    rows => Action::SelectDown(rows),
+                });
             }
-            KeyCode::Char('n') => {
-                self.mode = AppMode::Input(InputPurpose::NewSession);
-                self.input_buffer.clear();
-                self.status_message = "Create new session".to_string();
+            KeyCode::Char('k') | KeyCode::Up => {
                 self.clear_multi_key_state();
+                return Some(match self.repeat_step(false) {
+                    1 => Action::SelectPrevious,
+                    rows => Action::SelectUp(rows),
+                });
             }
-            KeyCode::Char('r') => {
-                if let Some(name) = self.selected_session_name() {
-                    self.mode = AppMode::Input(InputPurpose::RenameSession);
-                    self.input_buffer = name;
-                    self.status_message = "Rename selected session".to_string();
-                } else {
-                    self.status_message = "No session selected to rename".to_string();
+            _ => {}
+        }
+
+        self.clear_multi_key_state();
+        self.nav_repeat = None;
+        let action = match key.code {
+            KeyCode::Char('q') => Action::Quit,
+            KeyCode::Char('G') => Action::SelectLast,
+            KeyCode::Char('D') | KeyCode::Char('c') => Action::ShowClients,
+            KeyCode::Char('J') if self.focus == FocusPanel::Windows => Action::SwapWindowDown,
+            KeyCode::Char('K') if self.focus == FocusPanel::Windows => Action::SwapWindowUp,
+            KeyCode::Char('K') => Action::RequestKillServer,
+            KeyCode::Char('S') => Action::ShowServers,
+            KeyCode::Char('@') => Action::ShowHosts,
+            KeyCode::Char('=') => Action::ShowBuffers,
+            KeyCode::Char('+') => Action::ShowGroup,
+            KeyCode::Char('h') => Action::ShowTimeline,
+            KeyCode::Char('y') => Action::YankPane,
+            KeyCode::Char('>') => Action::PromptCaptureToFile,
+            KeyCode::Char('l') => Action::TogglePaneLog,
+            KeyCode::Char('C') => Action::ReviewCleanup,
+            KeyCode::Char('n') => Action::PromptNewSession,
+            KeyCode::Char('N') => Action::PromptSessionWizard,
+            KeyCode::Char('!') => Action::PromptRunCommand,
+            KeyCode::Char('i') => Action::PromptSendKeys,
+            KeyCode::Char('I') => Action::PromptSendText,
+            KeyCode::Char('w') => Action::PromptNewWindowCommand,
+            KeyCode::Char('o') => Action::ShowOptions,
+            KeyCode::Char('r') => Action::PromptRename,
+            KeyCode::Char('W') => Action::PromptRenameWindow,
+            KeyCode::Enter => Action::AttachSelected,
+            KeyCode::Char('A') => Action::AttachReadOnly,
+            KeyCode::Char('-') => Action::SwitchLastSession,
+            KeyCode::Char('/') if self.focus == FocusPanel::Windows => Action::StartWindowSearch,
+            KeyCode::Char('/') => Action::StartSearch,
+            KeyCode::Char('t') => Action::PromptTag,
+            KeyCode::Char('z') => Action::ToggleZoom,
+            KeyCode::Char('m') if self.focus == FocusPanel::Windows => Action::PromptMoveWindow,
+            KeyCode::Char(c @ ('m' | '\'')) => {
+                self.pending_mark = Some(c);
+                Action::PendingKey(c)
+            }
+            KeyCode::Char('L') if self.focus == FocusPanel::Windows => Action::PromptLayout,
+            KeyCode::Char('x') if self.focus == FocusPanel::Windows => Action::RequestKillPane,
+            KeyCode::Char('b') if self.focus == FocusPanel::Windows => Action::RequestBreakPane,
+            KeyCode::Char('M') if self.focus == FocusPanel::Windows => Action::RequestJoinPane,
+            KeyCode::Char('R') if self.focus == FocusPanel::Windows => Action::RequestRespawnPane,
+            KeyCode::Char('T') => Action::ToggleTagFilter,
+            KeyCode::Char('P') => Action::TogglePathGroups,
+            KeyCode::Char('V') => Action::StartVisual,
+            KeyCode::Char('u') => Action::UndoKill,
+            KeyCode::Char('s') => Action::CycleSort,
+            KeyCode::Char('H') => Action::ToggleIgnored,
+            KeyCode::Char('E') => Action::ShowChanges,
+            KeyCode::Char('U') => Action::ShowStats,
+            KeyCode::Char(c @ '1'..='9') if self.config.session_numbers.is_some() => {
+                Action::SelectNumber(c as usize - '0' as usize)
+            }
+            KeyCode::Tab => Action::ToggleFocus,
+            KeyCode::Char('?') => Action::ToggleHelp,
+            KeyCode::PageUp if key.modifiers == KeyModifiers::SHIFT => {
+                Action::ScrollPreviewUp(2 * PREVIEW_HALF_PAGE)
+            }
+            KeyCode::PageDown if key.modifiers == KeyModifiers::SHIFT => {
+                Action::ScrollPreviewDown(2 * PREVIEW_HALF_PAGE)
+            }
+            KeyCode::PageUp => Action::SelectUp(self.page_rows()),
+            KeyCode::PageDown => Action::SelectDown(self.page_rows()),
+            KeyCode::Home => Action::SelectFirst,
+            KeyCode::End => Action::SelectLast,
+            _ => return None,
+        };
+        Some(action)
+    }
+
+    /// Apply `action` to the app state without touching tmux, returning the
+    /// side effects to run afterwards.
+    pub fn update(&mut self, action: Action) -> Effects {
+        let mut effects = Effects::new();
+
+        match action {
+            Action::Quit => self.should_quit = true,
+            Action::SelectNext if matches!(self.mode, AppMode::Clients(_)) => {
+                if self.selected_client + 1 < self.clients.len() {
+                    self.selected_client += 1;
                 }
-                self.clear_multi_key_state();
             }
-            KeyCode::Enter => {
-                let target = self.attach_target();
-                if let Some(target) = target {
-                    if tmux::is_inside_tmux() {
-                        match tmux::switch_client(&target).await {
-                            Ok(_) => {
-                                self.should_quit = true;
-                            }
-                            Err(e) => {
-                                self.set_error(format!("Failed to switch: {e}"));
-                            }
-                        }
-                    } else {
-                        ratatui::restore();
-                        tmux::attach_session_exec(&target);
-                    }
-                } else {
-                    self.status_message = "No session selected".to_string();
+            Action::SelectPrevious if matches!(self.mode, AppMode::Clients(_)) => {
+                self.selected_client = self.selected_client.saturating_sub(1);
+            }
+            Action::SelectNext if self.mode == AppMode::Palette => {
+                if self.palette_selected + 1 < self.palette_commands().len() {
+                    self.palette_selected += 1;
                 }
-                self.clear_multi_key_state();
             }
-            KeyCode::Char('/') => {
-                self.focus = FocusPanel::Sessions;
-                self.mode = AppMode::Search;
-                self.input_buffer.clear();
-                self.search_active = true;
-                self.update_search_filter();
-                self.status_message = "Search mode".to_string();
-                self.clear_multi_key_state();
+            Action::SelectPrevious if self.mode == AppMode::Palette => {
+                self.palette_selected = self.palette_selected.saturating_sub(1);
             }
-            KeyCode::Char('t') => {
-                if let Some(name) = self.selected_session_name() {
-                    self.mode = AppMode::Input(InputPurpose::AddTag);
-                    self.input_buffer.clear();
-                    self.status_message = format!("Add tag to `{name}`");
+            Action::SelectNext if self.mode == AppMode::Servers => {
+                if self.selected_server + 1 < self.servers.len() {
+                    self.selected_server += 1;
+                }
+            }
+            Action::SelectPrevious if self.mode == AppMode::Servers => {
+                self.selected_server = self.selected_server.saturating_sub(1);
+            }
+            Action::SelectNext | Action::SelectPrevious
+                if matches!(self.mode, AppMode::Buffers(_)) =>
+            {
+                let selected = if action == Action::SelectNext {
+                    (self.selected_buffer + 1).min(self.buffers.len().saturating_sub(1))
                 } else {
+                    self.selected_buffer.saturating_sub(1)
+                };
+                if selected != self.selected_buffer {
+                    self.selected_buffer = selected;
+                    self.buffer_preview.clear();
+                    effects.push(Effect::PreviewBuffer(self.buffers[selected].name.clone()));
+                }
+            }
+            Action::ShowBuffers => match self.attach_target() {
+                Some(target) => {
+                    self.selected_buffer = 0;
+                    effects.push(Effect::ListBuffers(target));
+                }
+                None => self.status_message = "No session selected".to_string(),
+            },
+            Action::YankBuffer => {
+                if let Some(buffer) = self.buffers.get(self.selected_buffer) {
+                    effects.push(Effect::CopyBuffer(buffer.name.clone()));
+                }
+            }
+            Action::YankPane => {
+                if self.preview_target.is_empty() {
                     self.status_message = "No session selected".to_string();
+                } else {
+                    effects.push(Effect::CopyPane(self.preview_target.clone()));
                 }
-                self.clear_multi_key_state();
             }
-            KeyCode::Char('T') => {
-                if let Some(ref current) = self.tag_filter {
-                    self.status_message = format!("Tag filter `{current}` cleared");
-                    self.tag_filter = None;
-                    self.selected = 0;
+            Action::PromptCaptureToFile => {
+                if self.preview_target.is_empty() {
+                    self.status_message = "No session selected".to_string();
                 } else {
-                    let all_tags: Vec<String> = self
-                        .config
-                        .tags
-                        .values()
-                        .flatten()
-                        .cloned()
-                        .collect::<std::collections::HashSet<_>>()
-                        .into_iter()
-                        .collect();
-                    if all_tags.is_empty() {
-                        self.status_message = "No tags defined".to_string();
+                    let target = self.preview_target.clone();
+                    let path =
+                        self.config
+                            .log_dir()
+                            .join(target_file_name(&target, unix_now(), "txt"));
+                    self.input_buffer = path.display().to_string();
+                    self.status_message = format!("Save the scrollback of `{target}`");
+                    self.mode = AppMode::Input(InputPurpose::CaptureToFile { target });
+                }
+            }
+            Action::TogglePaneLog => match (self.selected_session(), self.target_window()) {
+                (Some(session), Some(window)) => {
+                    let target = format!("{}:{}", session.name, window.index);
+                    if window.piped {
+                        effects.push(Effect::StopPaneLog(target));
                     } else {
-                        self.mode = AppMode::Input(InputPurpose::FilterByTag);
-                        self.input_buffer.clear();
-                        self.status_message =
-                            format!("Filter by tag (available: {})", all_tags.join(", "));
+                        let path = self.config.log_dir().join(target_file_name(
+                            &target,
+                            unix_now(),
+                            "log",
+                        ));
+                        effects.push(Effect::StartPaneLog { target, path });
                     }
                 }
-                self.clear_multi_key_state();
+                _ => self.status_message = "No window selected".to_string(),
+            },
+            Action::DeleteSelectedBuffer => {
+                if let (AppMode::Buffers(target), Some(buffer)) =
+                    (&self.mode, self.buffers.get(self.selected_buffer))
+                {
+                    effects.push(Effect::DeleteBuffer {
+                        name: buffer.name.clone(),
+                        target: target.clone(),
+                    });
+                }
             }
-            KeyCode::Tab => {
-                self.focus = match self.focus {
-                    FocusPanel::Sessions => FocusPanel::Windows,
-                    FocusPanel::Windows => FocusPanel::Sessions,
-                };
-                self.clear_multi_key_state();
+            Action::SelectNext if matches!(self.mode, AppMode::Pick(_)) => {
+                if self.pick_selected + 1 < self.pick_items.len() {
+                    self.pick_selected += 1;
+                }
             }
-            KeyCode::Char('?') => {
-                self.show_help = !self.show_help;
-                self.clear_multi_key_state();
+            Action::SelectPrevious if matches!(self.mode, AppMode::Pick(_)) => {
+                self.pick_selected = self.pick_selected.saturating_sub(1);
             }
-            _ => {
-                self.clear_multi_key_state();
+            Action::DetachIdle => {
+                if let AppMode::Clients(session) = &self.mode {
+                    effects.push(Effect::DetachIdleClients(Some(session.clone())));
+                }
             }
-        }
-
-        Ok(())
-    }
-
-    async fn handle_search_mode(&mut self, key: KeyEvent) -> AppResult<()> {
-        match key.code {
-            KeyCode::Esc => {
-                self.mode = AppMode::Normal;
-                self.input_buffer.clear();
-                self.search_active = false;
-                self.filtered_results.clear();
-                self.status_message = "Search cancelled".to_string();
+            Action::UndoKill => match self.last_killed.take() {
+                Some((snapshot, at)) if at.elapsed() > UNDO_KILL_WINDOW => {
+                    self.status_message = format!("Too late to bring back `{}`", snapshot.name);
+                }
+                Some((snapshot, _)) if self.sessions.iter().any(|s| s.key() == snapshot.key()) => {
+                    self.status_message = format!("`{}` exists again", snapshot.name);
+                }
+                Some((snapshot, _)) => effects.push(Effect::RestoreSession(snapshot)),
+                None => self.status_message = "Nothing to undo".to_string(),
+            },
+            Action::StartResize => self.resizing = true,
+            Action::ResizeLists(column) if self.screen.width > 0 => {
+                let percent = u32::from(column) * 100 / u32::from(self.screen.width);
+                self.config.list_width = Some((percent as u16).clamp(10, 90));
             }
-            KeyCode::Enter => {
-                let target_name = if self.search_active && !self.filtered_results.is_empty() {
-                    let idx = self.selected.min(self.filtered_results.len() - 1);
-                    let session_idx = self.filtered_results[idx].session_index;
-                    self.sessions.get(session_idx).map(|s| s.name.clone())
-                } else {
-                    None
-                };
-                self.mode = AppMode::Normal;
-                self.input_buffer.clear();
-                self.search_active = false;
-                self.filtered_results.clear();
-
-                if let Some(name) = target_name {
-                    if tmux::is_inside_tmux() {
-                        match tmux::switch_client(&name).await {
-                            Ok(_) => {
-                                self.should_quit = true;
-                            }
-                            Err(e) => {
-                                self.set_error(format!("Failed to switch: {e}"));
-                            }
-                        }
-                    } else {
-                        ratatui::restore();
-                        tmux::attach_session_exec(&name);
+            Action::ResizeLists(_) => {}
+            Action::EndResize => {
+                self.resizing = false;
+                self.status_message = format!("Lists at {}% width", self.config.list_width());
+                effects.push(Effect::SaveConfig);
+            }
+            Action::StartVisual => match self.selected_session() {
+                Some(session) => {
+                    self.visual_marked = HashSet::from([session.key()]);
+                    self.focus = FocusPanel::Sessions;
+                    self.mode = AppMode::Visual;
+                    self.status_message =
+                        "Space: mark  d: kill  t: tag  D: detach  Esc: done".to_string();
+                }
+                None => self.status_message = "No session selected".to_string(),
+            },
+            Action::ToggleVisualMark => {
+                if let Some(key) = self.selected_session().map(Session::key) {
+                    if !self.visual_marked.remove(&key) {
+                        self.visual_marked.insert(key);
                     }
-                } else {
-                    self.status_message = "No match to attach".to_string();
                 }
             }
-            KeyCode::Backspace => {
-                self.input_buffer.pop();
-                self.search_active = true;
-                self.update_search_filter();
-            }
-            KeyCode::Down => {
-                let count = self.visible_session_count();
-                if count > 0 {
-                    self.selected = (self.selected + 1).min(count - 1);
+            Action::RequestKill if self.mode == AppMode::Visual => {
+                let targets = self.take_visual_marked();
+                if targets.is_empty() {
+                    self.status_message = "No sessions marked".to_string();
+                } else {
+                    let question = match self.own_session_note(&targets) {
+                        Some(note) => format!("Kill {} sessions, {note}?", targets.len()),
+                        None => format!("Kill {} sessions?", targets.len()),
+                    };
+                    self.request_confirm(ConfirmAction::KillSessions(targets), question);
                 }
             }
-            KeyCode::Up => {
-                if self.selected > 0 {
-                    self.selected -= 1;
+            Action::DetachSelected if self.mode == AppMode::Visual => {
+                let targets: Vec<SessionTarget> = self
+                    .take_visual_marked()
+                    .into_iter()
+                    .filter(|target| {
+                        self.sessions
+                            .iter()
+                            .any(|s| s.key() == target.key() && s.attached > 0)
+                    })
+                    .collect();
+                if targets.is_empty() {
+                    self.status_message = "No marked session is attached".to_string();
+                } else {
+                    let question = format!("Detach clients of {} sessions?", targets.len());
+                    self.request_confirm(ConfirmAction::DetachSessions(targets), question);
                 }
             }
-            KeyCode::Char(c) => {
-                self.input_buffer.push(c);
-                self.search_active = true;
-                self.update_search_filter();
+            Action::PromptTag if self.mode == AppMode::Visual => {
+                let names = self
+                    .take_visual_marked()
+                    .into_iter()
+                    .map(|target| target.name)
+                    .collect();
+                self.prompt_tag_sessions(names, "No sessions marked");
             }
-            _ => {}
-        }
-
-        Ok(())
-    }
-
-    async fn handle_input_mode(&mut self, key: KeyEvent, purpose: InputPurpose) -> AppResult<()> {
-        match key.code {
-            KeyCode::Esc => {
-                self.mode = AppMode::Normal;
-                self.input_buffer.clear();
-                self.status_message = "Input cancelled".to_string();
+            Action::PromptTag if self.mode == AppMode::Search && !self.window_search_active => {
+                let names = self
+                    .filtered_results
+                    .iter()
+                    .map(|result| self.sessions[result.session_index].name.clone())
+                    .collect();
+                self.end_session_search();
+                self.prompt_tag_sessions(names, "No matching sessions");
             }
-            KeyCode::Enter => {
-                let value = self.input_buffer.trim().to_string();
-                self.mode = AppMode::Normal;
-                self.status_message = match purpose {
-                    InputPurpose::NewSession => {
-                        if value.is_empty() {
-                            "Session name required".to_string()
-                        } else {
-                            match tmux::create_session(&value, None).await {
-                                Ok(_) => {
-                                    let _ = self.refresh_sessions().await;
-                                    format!("Created session `{value}`")
-                                }
-                                Err(e) => {
-                                    self.set_error(format!("Failed to create: {e}"));
-                                    String::new()
-                                }
-                            }
-                        }
+            Action::DetachSelected if matches!(self.mode, AppMode::Clients(_)) => {
+                if let AppMode::Clients(session) = std::mem::take(&mut self.mode) {
+                    self.clients.clear();
+                    effects.push(Effect::DetachClients(session));
+                }
+            }
+            Action::SelectNext => match self.focus {
+                FocusPanel::Sessions => self.select_next(),
+                FocusPanel::Windows => self.select_next_window(),
+            },
+            Action::SelectPrevious => match self.focus {
+                FocusPanel::Sessions => self.select_previous(),
+                FocusPanel::Windows => self.select_previous_window(),
+            },
+            Action::SelectDown(rows) => {
+                for _ in 0..rows.max(1) {
+                    match self.focus {
+                        FocusPanel::Sessions => self.select_next(),
+                        FocusPanel::Windows => self.select_next_window(),
                     }
-                    InputPurpose::RenameSession => {
-                        if value.is_empty() {
-                            "Session name required".to_string()
-                        } else if let Some(old_name) = self.selected_session_name() {
-                            match tmux::rename_session(&old_name, &value).await {
-                                Ok(_) => {
-                                    let _ = self.refresh_sessions().await;
-                                    format!("Renamed `{old_name}` → `{value}`")
-                                }
-                                Err(e) => {
-                                    self.set_error(format!("Failed to rename: {e}"));
-                                    String::new()
-                                }
-                            }
-                        } else {
-                            "No session selected".to_string()
-                        }
+                }
+            }
+            Action::SelectUp(rows) => {
+                for _ in 0..rows.max(1) {
+                    match self.focus {
+                        FocusPanel::Sessions => self.select_previous(),
+                        FocusPanel::Windows => self.select_previous_window(),
                     }
-                    InputPurpose::AddTag => {
-                        if value.is_empty() {
-                            "Tag name required".to_string()
-                        } else if let Some(session_name) = self.selected_session_name() {
-                            self.config.add_tag(&session_name, &value);
-                            let _ = self.config.save();
-                            format!("Tagged `{session_name}` with `{value}`")
-                        } else {
-                            "No session selected".to_string()
-                        }
+                }
+            }
+            Action::SelectNumber(number) => {
+                if number > self.visible_session_count() {
+                    self.status_message = format!("No session {number}");
+                } else {
+                    if self.selected != number - 1 {
+                        self.selected = number - 1;
+                        self.selected_window = 0;
                     }
-                    InputPurpose::FilterByTag => {
-                        if value.is_empty() {
-                            self.tag_filter = None;
-                            "Tag filter cleared".to_string()
-                        } else {
-                            self.tag_filter = Some(value.clone());
-                            self.selected = 0;
-                            format!("Filtering by tag `{value}`")
-                        }
+                    self.focus = FocusPanel::Sessions;
+                    if self.config.session_numbers == Some(SessionNumbers::Attach) {
+                        effects.extend(self.update(Action::AttachSelected));
                     }
-                };
-                self.input_buffer.clear();
+                }
             }
-            KeyCode::Backspace => {
-                self.input_buffer.pop();
+            Action::SelectFirst => match self.focus {
+                FocusPanel::Sessions => self.select_first(),
+                FocusPanel::Windows => self.selected_window = 0,
+            },
+            Action::SelectLast => match self.focus {
+                FocusPanel::Sessions => self.select_last(),
+                FocusPanel::Windows => self.select_last_window(),
+            },
+            Action::PendingKey('d') => {
+                self.status_message = "Kill session: press d again".to_string();
             }
-            KeyCode::Char(c) => {
-                self.input_buffer.push(c);
+            Action::PendingKey('m') => {
+                self.status_message = "Mark session: press a key".to_string();
             }
-            _ => {}
-        }
-
-        Ok(())
-    }
-
-    async fn handle_confirm_mode(&mut self, key: KeyEvent, action: ConfirmAction) -> AppResult<()> {
-        match key.code {
-            KeyCode::Char('y') | KeyCode::Enter => {
-                self.mode = AppMode::Normal;
-                self.status_message = match action {
-                    ConfirmAction::KillSession(name) => match tmux::kill_session(&name).await {
-                        Ok(_) => {
-                            let _ = self.refresh_sessions().await;
-                            format!("Killed session `{name}`")
-                        }
-                        Err(e) => {
-                            self.set_error(format!("Failed to kill: {e}"));
-                            String::new()
-                        }
-                    },
+            Action::PendingKey('\'') => {
+                self.status_message = "Jump to mark: press a key".to_string();
+            }
+            Action::PendingKey(_) => {}
+            Action::SetMark(key) => match self.selected_session_name() {
+                Some(name) => {
+                    self.config.set_mark(key, &name);
+                    self.status_message = format!("Marked `{name}` as '{key}");
+                    effects.push(Effect::SaveConfig);
+                }
+                None => self.status_message = "No session selected".to_string(),
+            },
+            Action::JumpToMark(key) => match self.config.mark(key).map(str::to_string) {
+                Some(name) if self.select_session_named(&name) => {
+                    self.focus = FocusPanel::Sessions;
+                    self.status_message = format!("'{key} → `{name}`");
+                }
+                Some(name) => self.status_message = format!("'{key} marks `{name}`, not listed"),
+                None => self.status_message = format!("No mark '{key}"),
+            },
+            Action::ToggleFocus => {
+                self.focus = match self.focus {
+                    FocusPanel::Sessions => FocusPanel::Windows,
+                    FocusPanel::Windows => FocusPanel::Sessions,
                 };
             }
-            KeyCode::Char('n') | KeyCode::Esc => {
-                self.mode = AppMode::Normal;
-                self.status_message = "Cancelled".to_string();
+            Action::ScrollPreviewUp(lines) => {
+                self.preview_scroll = self.preview_scroll.saturating_add(lines);
             }
-            _ => {}
-        }
-
-        Ok(())
-    }
-
-    /// Set a transient error message that auto-clears after 3 seconds.
-    pub fn set_error(&mut self, msg: String) {
-        self.error_message = Some(msg);
-        self.error_time = Some(Instant::now());
-    }
-
-    /// Clear expired error messages (called on tick).
-    pub fn tick_clear_errors(&mut self) {
-        if let Some(time) = self.error_time {
-            if time.elapsed() >= Duration::from_secs(3) {
+            Action::ScrollPreviewDown(lines) => {
+                self.preview_scroll = self.preview_scroll.saturating_sub(lines);
+            }
+            Action::ToggleHelp => self.show_help = !self.show_help,
+            Action::CloseHelp => self.show_help = false,
+            Action::DismissError => {
                 self.error_message = None;
                 self.error_time = None;
+                self.error_sticky = false;
+            }
+            Action::StartSearch => {
+                self.focus = FocusPanel::Sessions;
+                self.mode = AppMode::Search;
+                self.input_buffer.clear();
+                self.search_active = true;
+                self.update_search_filter();
+                self.status_message = "Search mode".to_string();
+            }
+            Action::StartWindowSearch => {
+                self.mode = AppMode::Search;
+                self.input_buffer.clear();
+                self.window_search_active = true;
+                self.update_window_filter();
+                self.status_message = "Search windows".to_string();
+            }
+            Action::PromptNewSession => {
+                self.mode = AppMode::Input(InputPurpose::NewSession);
+                self.input_buffer.clear();
+                self.status_message = "Create new session".to_string();
+            }
+            Action::PromptRunCommand => {
+                self.mode = AppMode::Input(InputPurpose::RunCommand);
+                self.input_buffer.clear();
+                self.status_message = "Run command in a throwaway session".to_string();
+            }
+            Action::PromptSendKeys => match self.attach_target() {
+                Some(target) => {
+                    self.mode = AppMode::Input(InputPurpose::SendKeys);
+                    self.input_buffer.clear();
+                    self.status_message = format!("Send keys to `{target}`");
+                }
+                None => self.status_message = "No session selected".to_string(),
+            },
+            Action::PromptSendText => match self.attach_target() {
+                Some(target) => {
+                    self.mode = AppMode::Input(InputPurpose::SendText);
+                    self.input_buffer.clear();
+                    self.status_message = format!("Paste a file or snippet into `{target}`");
+                }
+                None => self.status_message = "No session selected".to_string(),
+            },
+            Action::PromptNewWindowCommand => match self.selected_session_name() {
+                Some(name) => {
+                    self.mode = AppMode::Input(InputPurpose::NewWindowCommand);
+                    self.input_buffer.clear();
+                    self.status_message = format!("Run a command in a new window of `{name}`");
+                }
+                None => self.status_message = "No session selected".to_string(),
+            },
+            Action::ShowOptions => {
+                let window = self.focus == FocusPanel::Windows;
+                let target = if window {
+                    self.attach_target()
+                } else {
+                    self.selected_session_name()
+                };
+                match target {
+                    Some(target) => {
+                        self.pick_selected = 0;
+                        effects.push(Effect::ListOptions { target, window });
+                    }
+                    None => self.status_message = "No session selected".to_string(),
+                }
+            }
+            Action::PromptRename if self.focus == FocusPanel::Windows => {
+                if let Some(window) = self.selected_window_entry() {
+                    self.input_buffer = window.name.clone();
+                    self.mode = AppMode::Input(InputPurpose::RenameWindow);
+                    self.status_message = "Rename selected window".to_string();
+                } else {
+                    self.status_message = "No window selected to rename".to_string();
+                }
+            }
+            Action::PromptRenameWindow => {
+                if let Some(window) = self.target_window() {
+                    self.input_buffer = window.name.clone();
+                    self.mode = AppMode::Input(InputPurpose::RenameWindow);
+                    self.status_message = match self.focus {
+                        FocusPanel::Sessions => "Rename active window".to_string(),
+                        FocusPanel::Windows => "Rename selected window".to_string(),
+                    };
+                } else {
+                    self.status_message = "No window to rename".to_string();
+                }
+            }
+            Action::PromptRename => {
+                if let Some(name) = self.selected_session_name() {
+                    self.mode = AppMode::Input(InputPurpose::RenameSession);
+                    self.input_buffer = name;
+                    self.status_message = "Rename selected session".to_string();
+                } else {
+                    self.status_message = "No session selected to rename".to_string();
+                }
+            }
+            Action::PromptTag => {
+                if let Some(name) = self.selected_session_name() {
+                    self.mode = AppMode::Input(InputPurpose::AddTag);
+                    self.input_buffer.clear();
+                    self.status_message = format!("Add tag to `{name}`");
+                } else {
+                    self.status_message = "No session selected".to_string();
+                }
+            }
+            Action::ToggleZoom => match self.attach_target() {
+                Some(target) => effects.push(Effect::ToggleZoom(target)),
+                None => self.status_message = "No window selected".to_string(),
+            },
+            Action::SwapWindowUp => effects.extend(self.swap_selected_window(false)),
+            Action::SwapWindowDown => effects.extend(self.swap_selected_window(true)),
+            Action::PromptMoveWindow => self.prompt_move_window(),
+            Action::ShowGroup => self.show_group(),
+            Action::ShowChanges => effects.push(Effect::LoadChanges),
+            Action::ShowStats => effects.push(Effect::LoadStats),
+            Action::ShowTimeline => match self.selected_session() {
+                Some(session) => self.mode = AppMode::Timeline(session.id.clone()),
+                None => self.status_message = "No session selected".to_string(),
+            },
+            Action::PromptLayout => match self.attach_target() {
+                Some(target) if self.focus == FocusPanel::Windows => {
+                    self.pick_items = LAYOUTS.iter().map(|l| l.to_string()).collect();
+                    self.pick_selected = 0;
+                    self.mode = AppMode::Pick(PickPurpose::Layout { target });
+                    self.status_message = "Choose a layout".to_string();
+                }
+                _ => self.status_message = "No window selected".to_string(),
+            },
+            Action::RequestKillPane => {
+                effects.extend(self.list_selected_panes(|window| PickPurpose::KillPane { window }))
+            }
+            Action::RequestBreakPane => {
+                effects.extend(self.list_selected_panes(|window| PickPurpose::BreakPane { window }))
+            }
+            Action::RequestJoinPane => {
+                effects.extend(self.list_selected_panes(|window| PickPurpose::JoinPane { window }))
+            }
+            Action::RequestRespawnPane => effects
+                .extend(self.list_selected_panes(|window| PickPurpose::RespawnPane { window })),
+            Action::ToggleTagFilter => self.toggle_tag_filter(),
+            Action::ReviewCleanup => {
+                self.review_cleanup();
+                if self.mode == AppMode::Normal {
+                    self.status_message = "No sessions match cleanup policies".to_string();
+                }
+            }
+            Action::RequestKill => {
+                if let Some(target) = self.selected_target() {
+                    let name = target.label();
+                    let question = match self.own_session_note(std::slice::from_ref(&target)) {
+                        Some(note) => format!("Kill `{name}`, {note}?"),
+                        None => format!("Kill `{name}`?"),
+                    };
+                    self.request_confirm(ConfirmAction::KillSession(target), question);
+                } else {
+                    self.status_message = "No session selected".to_string();
+                }
+            }
+            Action::RequestKillServer => {
+                self.request_confirm(
+                    ConfirmAction::KillServer,
+                    "Kill the tmux server?".to_string(),
+                );
+            }
+            Action::DetachSelected => match self.selected_target() {
+                Some(target) => effects.push(Effect::DetachClients(target)),
+                None => self.status_message = "No session selected".to_string(),
+            },
+            Action::ShowServers => {
+                self.selected_server = 0;
+                effects.push(Effect::ListServers);
+            }
+            Action::ShowHosts => self.show_hosts(),
+            Action::TogglePathGroups => {
+                let name = self.selected_session_name();
+                self.config.group_by_path = Some(!self.config.group_by_path.unwrap_or(false));
+                if !name.is_some_and(|name| self.select_session_named(&name)) {
+                    self.selected = 0;
+                }
+                self.status_message = if self.config.group_by_path.unwrap_or(false) {
+                    "Grouping sessions by directory".to_string()
+                } else {
+                    "Sessions no longer grouped".to_string()
+                };
+                effects.push(Effect::SaveConfig);
+            }
+            Action::CycleSort => {
+                let name = self.selected_session_name();
+                let sort = self.config.sort.unwrap_or_default().next();
+                self.config.sort = Some(sort).filter(|s| *s != SessionSort::Used);
+                if !name.is_some_and(|name| self.select_session_named(&name)) {
+                    self.selected = 0;
+                }
+                self.status_message = format!("Sessions sorted by {}", sort.label());
+                effects.push(Effect::SaveConfig);
+            }
+            Action::ToggleIgnored if self.config.ignore.is_empty() => {
+                self.status_message = "No ignore patterns in the config".to_string();
+            }
+            Action::ToggleIgnored => {
+                let name = self.selected_session_name();
+                self.show_ignored = !self.show_ignored;
+                if !name.is_some_and(|name| self.select_session_named(&name)) {
+                    self.selected = 0;
+                }
+                let count = self.ignored_count();
+                self.status_message = if self.show_ignored {
+                    format!("Showing {count} ignored sessions")
+                } else {
+                    format!("Hiding {count} ignored sessions")
+                };
+            }
+            Action::ToggleServerView => match self.servers.get(self.selected_server) {
+                Some(server) if server.alive => {
+                    let name = server.name();
+                    if let Some(i) = self.config.servers.iter().position(|s| *s == name) {
+                        self.config.servers.remove(i);
+                        self.status_message = format!("No longer listing sessions of `{name}`");
+                    } else {
+                        self.status_message = format!("Listing sessions of `{name}` too");
+                        self.config.servers.push(name);
+                    }
+                    effects.push(Effect::SaveConfig);
+                }
+                Some(server) => {
+                    self.status_message = format!("`{}` has no server to list", server.name());
+                }
+                None => {}
+            },
+            Action::RemoveStraySocket => match self.servers.get(self.selected_server) {
+                Some(server) if server.alive => {
+                    self.status_message = format!(
+                        "`{}` is still running; connect with Enter and kill it with K",
+                        server.name()
+                    );
+                }
+                Some(server) => effects.push(Effect::RemoveSocket(server.path.clone())),
+                None => {}
+            },
+            Action::ShowClients => match self.selected_target() {
+                Some(target) => {
+                    self.selected_client = 0;
+                    effects.push(Effect::ListClients(target));
+                }
+                None => self.status_message = "No session selected".to_string(),
+            },
+            Action::AttachSelected => match self.attach_target() {
+                Some(target) => effects.push(Effect::Attach(self.on_selected_server(target))),
+                None => self.status_message = "No session selected".to_string(),
+            },
+            Action::AttachReadOnly => match self.attach_target() {
+                Some(target) => {
+                    effects.push(Effect::AttachReadOnly(self.on_selected_server(target)));
+                }
+                None => self.status_message = "No session selected".to_string(),
+            },
+            Action::SwitchLastSession if self.inside_tmux => effects.push(Effect::SwitchLastClient),
+            Action::SwitchLastSession => match self.last_used_session() {
+                Some(target) => effects.push(Effect::Attach(target)),
+                None => self.status_message = "No previously used session".to_string(),
+            },
+            Action::InsertText(text) => match self.mode {
+                AppMode::Search => {
+                    self.input_buffer.push_str(&text);
+                    self.update_active_search();
+                }
+                AppMode::Input(_) => {
+                    self.input_buffer.push_str(&text);
+                    effects.extend(self.typed_session_name().map(Effect::CheckSessionName));
+                }
+                AppMode::Form(_) => self.form.insert(&text),
+                AppMode::Confirm(_) => self.input_buffer.push_str(&text),
+                AppMode::Palette => {
+                    self.input_buffer.push_str(&text);
+                    self.palette_selected = 0;
+                }
+                _ => {}
+            },
+            Action::DeleteChar => match self.mode {
+                AppMode::Search => {
+                    self.input_buffer.pop();
+                    self.update_active_search();
+                }
+                AppMode::Input(_) => {
+                    self.input_buffer.pop();
+                    effects.extend(self.typed_session_name().map(Effect::CheckSessionName));
+                }
+                AppMode::Form(_) => self.form.delete_char(),
+                AppMode::Confirm(_) => {
+                    self.input_buffer.pop();
+                }
+                AppMode::Palette => {
+                    self.input_buffer.pop();
+                    self.palette_selected = 0;
+                }
+                _ => {}
+            },
+            Action::OpenPalette => {
+                self.input_buffer.clear();
+                self.palette_selected = 0;
+                self.mode = AppMode::Palette;
+            }
+            Action::FocusNextField => self.form.focus_next(),
+            Action::FocusPreviousField => self.form.focus_previous(),
+            Action::PromptSessionWizard => {
+                self.form = session_form(!self.config.splits.is_empty());
+                self.mode = AppMode::Form(FormPurpose::NewSession);
+                self.status_message = "New session: Tab moves between fields".to_string();
+            }
+            Action::Submit => effects = self.submit(),
+            Action::Cancel => self.cancel(),
+            Action::ConflictCreateSibling => {
+                if let AppMode::Conflict(name) = &self.mode {
+                    let sibling = next_free_name(&self.sessions, name);
+                    match self.config.naming.check(&sibling) {
+                        Ok(()) => {
+                            self.mode = AppMode::Normal;
+                            effects.push(Effect::CreateSession(sibling));
+                        }
+                        Err(reason) => self.status_message = reason,
+                    }
+                }
+            }
+            Action::ConflictReplace => {
+                if let AppMode::Conflict(name) = &self.mode {
+                    let target = self.on_selected_server(name.clone());
+                    let name = target.label();
+                    let question = match self.own_session_note(std::slice::from_ref(&target)) {
+                        Some(note) => format!("Replace `{name}` with a new session, {note}?"),
+                        None => format!("Replace `{name}` with a new session?"),
+                    };
+                    self.request_confirm(ConfirmAction::ReplaceSession(target), question);
+                }
             }
+            Action::FocusChanged(focused) => self.focused = focused,
         }
-    }
 
-    fn clear_multi_key_state(&mut self) {
-        self.last_g_press = None;
-        self.last_d_press = None;
+        effects
     }
 
-    fn selected_session_name(&self) -> Option<String> {
-        if self.search_active {
-            let idx = self
-                .selected
-                .min(self.filtered_results.len().saturating_sub(1));
-            self.filtered_results
-                .get(idx)
-                .and_then(|r| self.sessions.get(r.session_index))
-                .map(|s| s.name.clone())
-        } else if self.tag_filter.is_some() {
-            let indices = self.tag_filtered_sessions();
-            let idx = self.selected.min(indices.len().saturating_sub(1));
-            indices
-                .get(idx)
-                .and_then(|&i| self.sessions.get(i))
-                .map(|s| s.name.clone())
+    /// Re-run whichever search (sessions or windows) is active after the
+    /// query changed.
+    fn update_active_search(&mut self) {
+        if self.window_search_active {
+            self.update_window_filter();
         } else {
-            self.sessions
-                .get(self.selected)
-                .map(|session| session.name.clone())
+            self.search_active = true;
+            self.update_search_filter();
         }
     }
 
-    fn select_next(&mut self) {
-        let count = self.visible_session_count();
-        if count == 0 {
-            self.selected = 0;
-            return;
-        }
-        let prev = self.selected;
-        self.selected = (self.selected + 1).min(count - 1);
-        if self.selected != prev {
-            self.selected_window = 0;
+    /// Swap the selected window with its neighbour and keep the cursor on
+    /// it; nothing happens at either end or while the list is filtered.
+    fn swap_selected_window(&mut self, down: bool) -> Option<Effect> {
+        if self.window_search_active {
+            return None;
         }
+        let windows = self.selected_windows()?;
+        let neighbour = if down {
+            self.selected_window + 1
+        } else {
+            self.selected_window.checked_sub(1)?
+        };
+        let source = windows.get(self.selected_window)?.id.clone();
+        let target = windows.get(neighbour)?.id.clone();
+        self.selected_window = neighbour;
+        Some(Effect::SwapWindows { source, target })
     }
 
-    fn select_previous(&mut self) {
-        if self.selected > 0 {
-            self.selected -= 1;
-            self.selected_window = 0;
+    fn prompt_move_window(&mut self) {
+        let (Some(session), Some(window)) =
+            (self.selected_session_name(), self.selected_window_entry())
+        else {
+            self.status_message = "No window selected".to_string();
+            return;
+        };
+        let purpose = PickPurpose::MoveWindow {
+            window_id: window.id.clone(),
+            name: window.name.clone(),
+        };
+        self.pick_items = self
+            .sessions
+            .iter()
+            .map(|s| s.name.clone())
+            .filter(|name| *name != session)
+            .collect();
+        if self.pick_items.is_empty() {
+            self.status_message = "No other session to move to".to_string();
+            return;
         }
+        self.pick_selected = 0;
+        self.mode = AppMode::Pick(purpose);
+        self.status_message = "Move window to…".to_string();
     }
 
-    fn select_first(&mut self) {
-        if self.selected != 0 {
-            self.selected_window = 0;
-        }
-        self.selected = 0;
+    /// Ask the executor for the panes of the selected window, to pick one
+    /// for the purpose built from the window's name.
+    fn list_selected_panes(&mut self, purpose: fn(String) -> PickPurpose) -> Option<Effect> {
+        let Some(window) = self.selected_window_entry() else {
+            self.status_message = "No window selected".to_string();
+            return None;
+        };
+        Some(Effect::ListPanes {
+            window_id: window.id.clone(),
+            purpose: purpose(window.name.clone()),
+        })
     }
 
-    fn select_last(&mut self) {
-        let count = self.visible_session_count();
-        if count == 0 {
-            self.selected = 0;
+    /// Offer every other known window as the destination of `pane`.
+    fn prompt_join_target(&mut self, pane: &Pane) {
+        let mut window_ids = Vec::new();
+        self.pick_items.clear();
+        // Panes only move between windows of the same server.
+        let server = self.selected_session().and_then(|s| s.server.clone());
+        for session in self.sessions.iter().filter(|s| s.server == server) {
+            let Some(windows) = self.session_windows.get(&session.key()) else {
+                continue;
+            };
+            for window in windows.iter().filter(|w| w.id != pane.window_id) {
+                window_ids.push(window.id.clone());
+                self.pick_items
+                    .push(format!("{}:{} {}", session.name, window.index, window.name));
+            }
+        }
+        if window_ids.is_empty() {
+            self.status_message = "No other window to join".to_string();
             return;
         }
-
-        self.selected = count - 1;
+        self.pick_selected = 0;
+        self.mode = AppMode::Pick(PickPurpose::JoinPaneTo {
+            pane_id: pane.id.clone(),
+            window_ids,
+        });
+        self.status_message = format!("Join pane {} into…", pane.id);
     }
 
-    fn selected_windows(&self) -> Option<&Vec<Window>> {
-        self.selected_session_name()
-            .and_then(|name| self.session_windows.get(&name))
+    fn confirm_kill_pane(&mut self, pane: &Pane) {
+        let label = pane_label(pane);
+        let question = format!("Kill pane `{label}`?");
+        self.request_confirm(
+            ConfirmAction::KillPane {
+                id: pane.id.clone(),
+                label,
+            },
+            question,
+        );
     }
 
-    fn select_next_window(&mut self) {
-        if let Some(wins) = self.selected_windows() {
-            let count = wins.len();
-            if count > 0 {
-                self.selected_window = (self.selected_window + 1).min(count - 1);
-            }
-        }
+    /// Open the confirmation popup for `action`, telling how to answer it
+    /// in the configured style.
+    fn request_confirm(&mut self, action: ConfirmAction, question: String) {
+        let how = match self.config.confirm_style(&action) {
+            ConfirmStyle::Yes => "y/n".to_string(),
+            ConfirmStyle::No if action == ConfirmAction::KillServer => "Y to confirm".to_string(),
+            ConfirmStyle::No => "y to confirm, Enter cancels".to_string(),
+            ConfirmStyle::Type => format!("type `{}` to confirm", action.phrase()),
+        };
+        self.status_message = format!("{question} ({how})");
+        self.input_buffer.clear();
+        self.mode = AppMode::Confirm(action);
     }
 
-    fn select_previous_window(&mut self) {
-        if self.selected_window > 0 {
-            self.selected_window -= 1;
+    /// Session name the open prompt would create or rename to, after the
+    /// naming rule.
+    pub fn typed_session_name(&self) -> Option<String> {
+        let value = self.input_buffer.trim();
+        if value.is_empty() {
+            return None;
         }
-    }
-
-    fn select_last_window(&mut self) {
-        if let Some(wins) = self.selected_windows() {
-            if !wins.is_empty() {
-                self.selected_window = wins.len() - 1;
+        match &self.mode {
+            AppMode::Input(InputPurpose::NewSession | InputPurpose::GroupedSession { .. }) => {
+                self.config.naming.apply(value).ok()
             }
+            AppMode::Input(InputPurpose::RenameSession) => Some(value.to_string()),
+            _ => None,
         }
     }
 
-    fn attach_target(&self) -> Option<String> {
-        let session_name = self.selected_session_name()?;
-        match self.focus {
-            FocusPanel::Sessions => Some(session_name),
-            FocusPanel::Windows => {
-                let windows = self.session_windows.get(&session_name)?;
-                let win = windows.get(self.selected_window)?;
-                Some(format!("{}:{}", session_name, win.index))
-            }
-        }
+    /// Whether `name` is a listed session or tmux last reported it taken.
+    pub fn is_name_taken(&self, name: &str) -> bool {
+        self.name_taken.as_deref() == Some(name) || self.sessions.iter().any(|s| s.name == name)
     }
-}
-
-fn is_double_tap(last_press: Option<Instant>) -> bool {
-    last_press.is_some_and(|time| time.elapsed() <= DOUBLE_TAP_WINDOW)
-}
 
-impl Default for App {
-    fn default() -> Self {
-        Self::new()
+    /// Offer a new session sharing the selected session's windows, and,
+    /// when it already shares them, leaving the group by killing it.
+    fn show_group(&mut self) {
+        let Some(session) = self.selected_session() else {
+            self.status_message = "No session selected".to_string();
+            return;
+        };
+        let target = session.target();
+        let name = target.name.clone();
+        let members: Vec<&str> = session
+            .group
+            .as_ref()
+            .map(|group| {
+                self.sessions
+                    .iter()
+                    .filter(|s| s.server == session.server && s.group.as_ref() == Some(group))
+                    .filter(|s| s.name != name)
+                    .map(|s| s.name.as_str())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut items = vec![match &session.group {
+            Some(group) => format!("New session in group `{group}`"),
+            None => format!("Start a group: new session sharing `{name}`'s windows"),
+        }];
+        self.status_message = if members.is_empty() {
+            format!("`{name}` is not grouped")
+        } else {
+            items.push(format!("Leave the group (kill `{name}`, windows stay)"));
+            format!("`{name}` shares its windows with {}", members.join(", "))
+        };
+        self.pick_items = items;
+        self.pick_selected = 0;
+        self.mode = AppMode::Pick(PickPurpose::Group { session: target });
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crossterm::event::{Event, KeyEventState};
+    fn toggle_tag_filter(&mut self) {
+        if let Some(ref current) = self.tag_filter {
+            self.status_message = format!("Tag filter `{current}` cleared");
+            self.tag_filter = None;
+            self.selected = 0;
+            return;
+        }
 
-    fn make_key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
-        KeyEvent {
-            code,
-            modifiers,
-            kind: KeyEventKind::Press,
-            state: KeyEventState::NONE,
+        let all_tags: Vec<String> = self
+            .config
+            .tags
+            .values()
+            .flatten()
+            .cloned()
+            .collect::<std::collections::HashSet<_>>()
+            .into_iter()
+            .collect();
+        if all_tags.is_empty() {
+            self.status_message = "No tags defined".to_string();
+        } else {
+            self.mode = AppMode::Input(InputPurpose::FilterByTag);
+            self.input_buffer.clear();
+            self.status_message = format!("Filter by tag (available: {})", all_tags.join(", "));
         }
     }
 
-    fn make_key_with_kind(code: KeyCode, modifiers: KeyModifiers, kind: KeyEventKind) -> KeyEvent {
-        KeyEvent {
-            code,
-            modifiers,
-            kind,
-            state: KeyEventState::NONE,
+    /// Enter/`y`/`a` in the current mode.
+    fn submit(&mut self) -> Effects {
+        match self.mode.clone() {
+            AppMode::Normal
+            | AppMode::Timeline(_)
+            | AppMode::Visual
+            | AppMode::Changes
+            | AppMode::Stats => Effects::new(),
+            AppMode::Servers => match self.servers.get(self.selected_server) {
+                Some(server) if server.alive => {
+                    self.mode = AppMode::Normal;
+                    vec![Effect::ConnectServer(server.path.clone())]
+                }
+                Some(server) => {
+                    self.status_message =
+                        format!("`{}` has no server; x removes its socket", server.name());
+                    Effects::new()
+                }
+                None => Effects::new(),
+            },
+            AppMode::Pick(purpose) => {
+                self.mode = AppMode::Normal;
+                let selected = self.pick_selected;
+                let panes = std::mem::take(&mut self.panes);
+                let Some(choice) = self.pick_items.get(selected).cloned() else {
+                    return Effects::new();
+                };
+                self.pick_items.clear();
+                match purpose {
+                    PickPurpose::MoveWindow { window_id, .. } => vec![Effect::MoveWindow {
+                        window_id,
+                        to: choice,
+                    }],
+                    PickPurpose::KillPane { .. } => {
+                        if let Some(pane) = panes.get(selected) {
+                            self.confirm_kill_pane(pane);
+                        }
+                        Effects::new()
+                    }
+                    PickPurpose::BreakPane { .. } => panes
+                        .get(selected)
+                        .map(|pane| Effect::BreakPane(pane.id.clone()))
+                        .into_iter()
+                        .collect(),
+                    PickPurpose::RespawnPane { .. } => panes
+                        .get(selected)
+                        .map(|pane| Effect::RespawnPane(pane.id.clone()))
+                        .into_iter()
+                        .collect(),
+                    PickPurpose::JoinPane { .. } => {
+                        if let Some(pane) = panes.get(selected) {
+                            self.prompt_join_target(pane);
+                        }
+                        Effects::new()
+                    }
+                    PickPurpose::Group { session } if selected == 0 => {
+                        self.input_buffer.clear();
+                        self.mode =
+                            AppMode::Input(InputPurpose::GroupedSession { target: session });
+                        Effects::new()
+                    }
+                    PickPurpose::Group { session } => vec![Effect::KillSession(session)],
+                    PickPurpose::Host => {
+                        let remote = selected
+                            .checked_sub(1)
+                            .and_then(|i| self.config.remotes.get(i).cloned());
+                        vec![Effect::ConnectRemote(remote)]
+                    }
+                    PickPurpose::Layout { target } => vec![Effect::SelectLayout {
+                        target,
+                        layout: choice,
+                    }],
+                    PickPurpose::Options { target, window } => {
+                        let options = std::mem::take(&mut self.options);
+                        let Some(option) = options.get(selected) else {
+                            return Effects::new();
+                        };
+                        if let Some(value) = option.toggled() {
+                            self.pick_selected = selected;
+                            return vec![Effect::SetOption {
+                                target,
+                                window,
+                                name: option.name.clone(),
+                                value: value.to_string(),
+                            }];
+                        }
+                        self.input_buffer = option.value.clone();
+                        self.status_message = format!("Set `{}` on `{target}`", option.name);
+                        self.mode = AppMode::Input(InputPurpose::SetOption {
+                            target,
+                            window,
+                            name: option.name.clone(),
+                        });
+                        Effects::new()
+                    }
+                    PickPurpose::JoinPaneTo {
+                        pane_id,
+                        window_ids,
+                    } => window_ids
+                        .get(selected)
+                        .map(|target| Effect::JoinPane {
+                            pane_id,
+                            target: target.clone(),
+                        })
+                        .into_iter()
+                        .collect(),
+                }
+            }
+            AppMode::Buffers(target) => {
+                let Some(buffer) = self.buffers.get(self.selected_buffer) else {
+                    return Effects::new();
+                };
+                let name = buffer.name.clone();
+                self.cancel();
+                vec![Effect::PasteBuffer { name, target }]
+            }
+            AppMode::Clients(session) => match self.clients.get(self.selected_client) {
+                Some(client) => vec![Effect::DetachClient {
+                    session,
+                    tty: client.tty.clone(),
+                }],
+                None => Effects::new(),
+            },
+            AppMode::Search if self.window_search_active => {
+                let target = self.attach_target();
+                let target = target.map(|target| self.on_selected_server(target));
+                self.end_window_search();
+                match target {
+                    Some(target) => vec![Effect::Attach(target)],
+                    None => {
+                        self.status_message = "No match to attach".to_string();
+                        Effects::new()
+                    }
+                }
+            }
+            AppMode::Search => {
+                let target = if self.search_active && !self.filtered_results.is_empty() {
+                    let idx = self.selected.min(self.filtered_results.len() - 1);
+                    let session_idx = self.filtered_results[idx].session_index;
+                    self.sessions.get(session_idx).map(Session::target)
+                } else {
+                    None
+                };
+                self.end_session_search();
+
+                match target {
+                    Some(target) => vec![Effect::Attach(target)],
+                    None => {
+                        self.status_message = "No match to attach".to_string();
+                        Effects::new()
+                    }
+                }
+            }
+            AppMode::Input(purpose) => self.submit_input(purpose),
+            AppMode::Form(purpose) => self.submit_form(purpose),
+            AppMode::Confirm(action) => {
+                let typed = std::mem::take(&mut self.input_buffer);
+                if self.config.confirm_style(&action) == ConfirmStyle::Type
+                    && typed.trim() != action.phrase()
+                {
+                    self.status_message = format!("Type `{}` to confirm", action.phrase());
+                    self.input_buffer = typed;
+                    return Effects::new();
+                }
+                self.mode = AppMode::Normal;
+                match action {
+                    ConfirmAction::KillSession(name) => vec![Effect::KillSession(name)],
+                    ConfirmAction::ReplaceSession(name) => vec![Effect::ReplaceSession(name)],
+                    ConfirmAction::KillServer => vec![Effect::KillServer],
+                    ConfirmAction::KillPane { id, .. } => vec![Effect::KillPane(id)],
+                    ConfirmAction::CleanupSessions(targets) => {
+                        vec![Effect::CleanupSessions(targets)]
+                    }
+                    ConfirmAction::KillSessions(targets) => vec![Effect::KillSessions(targets)],
+                    ConfirmAction::DetachSessions(targets) => {
+                        vec![Effect::DetachSessions(targets)]
+                    }
+                }
+            }
+            AppMode::Conflict(name) => {
+                self.mode = AppMode::Normal;
+                vec![Effect::Attach(self.on_selected_server(name))]
+            }
+            AppMode::Palette => {
+                let chosen = self
+                    .palette_commands()
+                    .into_iter()
+                    .nth(self.palette_selected);
+                self.mode = AppMode::Normal;
+                self.input_buffer.clear();
+                match chosen {
+                    Some(command) => self.update(command.action),
+                    None => {
+                        self.status_message = "No matching command".to_string();
+                        Effects::new()
+                    }
+                }
+            }
+        }
+    }
+
+    /// Enter in a form: move to the next field, or validate and act on the
+    /// last one.
+    fn submit_form(&mut self, purpose: FormPurpose) -> Effects {
+        if !self.form.is_last_field() {
+            self.form.focus_next();
+            return Effects::new();
+        }
+        if !self.form.validate() {
+            return Effects::new();
+        }
+
+        match purpose {
+            FormPurpose::NewSession => {
+                let name = match self.config.naming.apply(self.form.value(0)) {
+                    Ok(name) => name,
+                    Err(reason) => {
+                        self.form.focus = 0;
+                        self.form.error = Some(format!("Name: {reason}"));
+                        return Effects::new();
+                    }
+                };
+                if self.sessions.iter().any(|s| s.name == name) {
+                    self.form.focus = 0;
+                    self.form.error = Some(format!("Name: `{name}` already exists"));
+                    return Effects::new();
+                }
+                let preset = match self.form.optional(3) {
+                    Some(preset) => match self.config.split_preset(&preset) {
+                        Some(preset) => Some(preset.clone()),
+                        None => {
+                            self.form.focus = 3;
+                            self.form.error = Some(format!("Splits: no preset `{preset}`"));
+                            return Effects::new();
+                        }
+                    },
+                    None => None,
+                };
+                let form = std::mem::take(&mut self.form);
+                self.mode = AppMode::Normal;
+                let (preset_command, splits) =
+                    preset.map_or((None, Vec::new()), |p| (p.command, p.panes));
+                vec![Effect::CreateSessionWith {
+                    name,
+                    path: form.optional(1).map(|path| expand_home(&path)),
+                    command: form.optional(2).or(preset_command),
+                    splits,
+                }]
+            }
+        }
+    }
+
+    fn submit_input(&mut self, purpose: InputPurpose) -> Effects {
+        let value = self.input_buffer.trim().to_string();
+        // Renames take the typed name as is, so only the pattern applies.
+        let naming = match &purpose {
+            InputPurpose::NewSession | InputPurpose::GroupedSession { .. } => {
+                self.config.naming.apply(&value).map(drop)
+            }
+            InputPurpose::RenameSession => self.config.naming.check(&value),
+            _ => Ok(()),
+        };
+        if let (Err(reason), false) = (naming, value.is_empty()) {
+            self.status_message = reason;
+            return Effects::new();
+        }
+        self.mode = AppMode::Normal;
+        let raw = std::mem::take(&mut self.input_buffer);
+
+        let mut effects = Effects::new();
+        match purpose {
+            InputPurpose::NewSession => {
+                if value.is_empty() {
+                    self.status_message = "Session name required".to_string();
+                } else {
+                    let name = self.config.naming.apply(&value).unwrap_or(value);
+                    if self.is_name_taken(&name) {
+                        self.status_message = format!("Session `{name}` already exists");
+                        self.mode = AppMode::Conflict(name);
+                    } else {
+                        effects.push(Effect::CreateSession(name));
+                    }
+                }
+            }
+            InputPurpose::CaptureToFile { target } => {
+                if value.is_empty() {
+                    self.status_message = "No file given".to_string();
+                } else {
+                    let path = PathBuf::from(expand_home(&value));
+                    effects.push(Effect::CaptureToFile { target, path });
+                }
+            }
+            InputPurpose::GroupedSession { target } => {
+                let name = self.config.naming.apply(&value).unwrap_or(value);
+                if name.is_empty() {
+                    self.status_message = "Session name required".to_string();
+                } else if self.is_name_taken(&name) {
+                    self.status_message = format!("Session `{name}` already exists");
+                    self.input_buffer = raw;
+                    self.mode = AppMode::Input(InputPurpose::GroupedSession { target });
+                } else {
+                    effects.push(Effect::CreateGroupedSession { name, target });
+                }
+            }
+            InputPurpose::RenameSession => {
+                if value.is_empty() {
+                    self.status_message = "Session name required".to_string();
+                } else if self.is_name_taken(&value) {
+                    self.status_message = format!("Session `{value}` already exists");
+                    self.input_buffer = raw;
+                    self.mode = AppMode::Input(InputPurpose::RenameSession);
+                } else if let Some(from) = self.selected_target() {
+                    effects.push(Effect::RenameSession { from, to: value });
+                } else {
+                    self.status_message = "No session selected".to_string();
+                }
+            }
+            InputPurpose::RenameWindow => {
+                if value.is_empty() {
+                    self.status_message = "Window name required".to_string();
+                } else if let (Some(session), Some(window)) =
+                    (self.selected_session(), self.target_window())
+                {
+                    let target = format!("{}:{}", session.name, window.index);
+                    effects.push(Effect::RenameWindow { target, to: value });
+                } else {
+                    self.status_message = "No window selected".to_string();
+                }
+            }
+            InputPurpose::AddTag => {
+                if value.is_empty() {
+                    self.status_message = "Tag name required".to_string();
+                } else if let Some(session_name) = self.selected_session_name() {
+                    self.config.add_tag(&session_name, &value);
+                    self.status_message = format!("Tagged `{session_name}` with `{value}`");
+                    effects.push(Effect::SaveConfig);
+                } else {
+                    self.status_message = "No session selected".to_string();
+                }
+            }
+            InputPurpose::TagSessions(names) => {
+                if value.is_empty() {
+                    self.status_message = "Tag name required".to_string();
+                } else {
+                    for name in &names {
+                        self.config.add_tag(name, &value);
+                    }
+                    self.status_message = format!("Tagged {} sessions with `{value}`", names.len());
+                    effects.push(Effect::SaveConfig);
+                }
+            }
+            InputPurpose::RunCommand => {
+                if value.is_empty() {
+                    self.status_message = "Command required".to_string();
+                } else {
+                    effects.push(Effect::RunCommand {
+                        name: next_runner_name(&self.sessions),
+                        command: value,
+                    });
+                }
+            }
+            InputPurpose::NewWindowCommand => {
+                if value.is_empty() {
+                    self.status_message = "Command required".to_string();
+                } else if let Some(session) = self.selected_target() {
+                    match self.config.split_preset(&value) {
+                        Some(preset) => effects.push(Effect::NewSplitWindow {
+                            session,
+                            preset: preset.clone(),
+                        }),
+                        None => effects.push(Effect::NewWindow {
+                            session,
+                            name: command_window_name(&value),
+                            command: value,
+                        }),
+                    }
+                } else {
+                    self.status_message = "No session selected".to_string();
+                }
+            }
+            InputPurpose::SetOption {
+                target,
+                window,
+                name,
+            } => effects.push(Effect::SetOption {
+                target,
+                window,
+                name,
+                value: raw,
+            }),
+            InputPurpose::SendKeys => {
+                // Untrimmed, so leading spaces reach the pane as typed.
+                if raw.is_empty() {
+                    self.status_message = "Nothing to send".to_string();
+                } else if let Some(target) = self.attach_target() {
+                    effects.push(Effect::SendKeys { target, text: raw });
+                } else {
+                    self.status_message = "No session selected".to_string();
+                }
+            }
+            InputPurpose::SendText => {
+                if raw.is_empty() {
+                    self.status_message = "Nothing to send".to_string();
+                } else if let Some(target) = self.attach_target() {
+                    effects.push(Effect::SendText { target, input: raw });
+                } else {
+                    self.status_message = "No session selected".to_string();
+                }
+            }
+            InputPurpose::FilterByTag => {
+                if value.is_empty() {
+                    self.tag_filter = None;
+                    self.status_message = "Tag filter cleared".to_string();
+                } else {
+                    self.status_message = format!("Filtering by tag `{value}`");
+                    self.tag_filter = Some(value);
+                    self.selected = 0;
+                }
+            }
+        }
+        effects
+    }
+
+    /// Esc/`n` in the current mode.
+    fn cancel(&mut self) {
+        match self.mode {
+            AppMode::Normal => self.status_message.clear(),
+            AppMode::Search if self.window_search_active => {
+                // Keep the cursor on the highlighted window once unfiltered.
+                let position = self
+                    .selected_window_entry()
+                    .map(|w| w.id.clone())
+                    .and_then(|id| {
+                        self.selected_windows()
+                            .and_then(|wins| wins.iter().position(|w| w.id == id))
+                    });
+                self.end_window_search();
+                self.selected_window = position.unwrap_or(0);
+                self.status_message = "Search cancelled".to_string();
+            }
+            AppMode::Search => {
+                self.end_session_search();
+                self.status_message = "Search cancelled".to_string();
+            }
+            AppMode::Input(_) => {
+                self.mode = AppMode::Normal;
+                self.input_buffer.clear();
+                self.status_message = "Input cancelled".to_string();
+            }
+            AppMode::Form(_) => {
+                self.mode = AppMode::Normal;
+                self.form = Form::default();
+                self.status_message = "Input cancelled".to_string();
+            }
+            AppMode::Confirm(_) | AppMode::Conflict(_) => {
+                self.mode = AppMode::Normal;
+                self.input_buffer.clear();
+                self.status_message = "Cancelled".to_string();
+            }
+            AppMode::Clients(_) => {
+                self.mode = AppMode::Normal;
+                self.clients.clear();
+            }
+            AppMode::Timeline(_) | AppMode::Changes | AppMode::Stats => self.mode = AppMode::Normal,
+            AppMode::Visual => {
+                self.mode = AppMode::Normal;
+                self.visual_marked.clear();
+                self.status_message.clear();
+            }
+            AppMode::Palette => {
+                self.mode = AppMode::Normal;
+                self.input_buffer.clear();
+            }
+            AppMode::Buffers(_) => {
+                self.mode = AppMode::Normal;
+                self.buffers.clear();
+                self.buffer_preview.clear();
+            }
+            AppMode::Servers => {
+                self.mode = AppMode::Normal;
+                self.servers.clear();
+            }
+            AppMode::Pick(_) => {
+                self.mode = AppMode::Normal;
+                self.pick_items.clear();
+                self.panes.clear();
+                self.options.clear();
+                self.status_message = "Cancelled".to_string();
+            }
+        }
+    }
+
+    fn end_session_search(&mut self) {
+        self.mode = AppMode::Normal;
+        self.input_buffer.clear();
+        self.search_active = false;
+        self.filtered_results.clear();
+    }
+
+    fn end_window_search(&mut self) {
+        self.mode = AppMode::Normal;
+        self.input_buffer.clear();
+        self.window_search_active = false;
+        self.filtered_windows.clear();
+    }
+
+    /// Run the effects returned by [`App::update`], reporting each outcome
+    /// in the status line or as an error. Each runs on the server of the
+    /// selected session unless it concerns servers as a whole or names the
+    /// server of the sessions it touches.
+    pub async fn run_effects(&mut self, effects: Effects) {
+        let last_error = self.error_time;
+        let server = self.session_server();
+        for effect in effects {
+            if let Some(skipped) = self.dry_run.as_mut() {
+                if !effect.is_read_only() {
+                    self.status_message = format!("Replay: skipped {effect:?}");
+                    skipped.push(effect);
+                    continue;
+                }
+            }
+            if effect.is_server_wide() {
+                self.run_effect(effect).await;
+            } else if let Some(named) = effect.session_server() {
+                let socket = named.map(tmux::Socket::from_setting);
+                tmux::on_server(socket, self.run_effect(effect)).await;
+            } else {
+                tmux::on_server(server.clone(), self.run_effect(effect)).await;
+            }
+        }
+        if self.config.sticky_errors.unwrap_or(false) && self.error_time != last_error {
+            self.error_sticky = self.error_message.is_some();
+        }
+    }
+
+    async fn run_effect(&mut self, effect: Effect) {
+        match effect {
+            Effect::CreateSession(name) => {
+                self.status_message = self.create_session(&name, None, None).await;
+            }
+            Effect::CreateSessionWith {
+                name,
+                path,
+                command,
+                splits,
+            } => {
+                self.status_message = self
+                    .create_session(&name, path.as_deref(), command.as_deref())
+                    .await;
+                let created = self.sessions.iter().any(|s| s.name == name);
+                if created && !splits.is_empty() {
+                    let target = format!("{name}:");
+                    if let Err(e) = split_panes(&target, &splits, path.as_deref()).await {
+                        self.set_error(format!("Failed to split `{name}`: {e}"));
+                    }
+                }
+            }
+            Effect::CreateGroupedSession { name, target } => {
+                let target = target.name;
+                match tmux::create_grouped_session(&name, &target).await {
+                    Ok(_) => {
+                        let _ = self.refresh_sessions().await;
+                        self.status_message = format!("Created `{name}` grouped with `{target}`");
+                    }
+                    Err(e) => self.set_error(format!("Failed to create: {e}")),
+                }
+            }
+            Effect::ReplaceSession(target) => {
+                if !self.kill_with_undo(&target).await {
+                    return;
+                }
+                let name = target.name;
+                // The new session takes the name, so undo brings the old
+                // one back next to it.
+                let sibling = next_free_name(&self.sessions, &name);
+                if let Some((snapshot, _)) = self.last_killed.as_mut() {
+                    snapshot.name = sibling.clone();
+                }
+                self.status_message = match self.spawn_session(&name, None, None).await {
+                    Ok(_) => {
+                        let _ = self.refresh_sessions().await;
+                        format!("Replaced session `{name}` (u to bring it back as `{sibling}`)")
+                    }
+                    Err(e) => {
+                        self.set_error(format!("Failed to recreate: {e}"));
+                        String::new()
+                    }
+                };
+            }
+            Effect::RenameSession { from, to } => {
+                let from = from.name;
+                self.status_message = match tmux::rename_session(&from, &to).await {
+                    Ok(_) => {
+                        let _ = self.refresh_sessions().await;
+                        format!("Renamed `{from}` → `{to}`")
+                    }
+                    Err(e) => {
+                        self.set_error(format!("Failed to rename: {e}"));
+                        String::new()
+                    }
+                };
+            }
+            Effect::RenameWindow { target, to } => match tmux::rename_window(&target, &to).await {
+                Ok(_) => {
+                    self.refresh_windows().await;
+                    self.status_message = format!("Renamed window `{target}` → `{to}`");
+                }
+                Err(e) => self.set_error(format!("Failed to rename window: {e}")),
+            },
+            Effect::KillSession(target) => {
+                self.kill_with_undo(&target).await;
+            }
+            Effect::KillServer => {
+                self.status_message = match tmux::kill_server().await {
+                    Ok(_) => {
+                        let count = self.sessions.len();
+                        let _ = self.refresh_sessions().await;
+                        self.session_windows.clear();
+                        format!("Killed tmux server ({count} sessions)")
+                    }
+                    Err(e) => {
+                        self.set_error(format!("Failed to kill server: {e}"));
+                        String::new()
+                    }
+                };
+            }
+            Effect::CleanupSessions(targets) => {
+                let killed = self.kill_sessions(&targets).await;
+                self.status_message = format!("Cleaned up {killed} of {} sessions", targets.len());
+            }
+            Effect::KillSessions(targets) => {
+                self.leave_doomed_session(&targets).await;
+                let killed = self.kill_sessions(&targets).await;
+                self.status_message = format!("Killed {killed} of {} sessions", targets.len());
+            }
+            Effect::DetachSessions(targets) => {
+                let mut detached = 0;
+                for target in &targets {
+                    let name = &target.name;
+                    let socket = target.server.as_deref().map(tmux::Socket::from_setting);
+                    match tmux::on_server(socket, tmux::detach_client(name)).await {
+                        Ok(_) => detached += 1,
+                        Err(e) => self.set_error(format!("Failed to detach `{name}`: {e}")),
+                    }
+                }
+                let _ = self.refresh_sessions().await;
+                self.status_message = format!(
+                    "Detached clients of {detached} of {} sessions",
+                    targets.len()
+                );
+            }
+            Effect::DetachClients(target) => {
+                let name = target.name;
+                // Listed first so the status line says who was disconnected.
+                let ttys: Vec<String> = tmux::list_clients(&name)
+                    .await
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|client| client.tty)
+                    .collect();
+                match tmux::detach_client(&name).await {
+                    Ok(_) => {
+                        self.status_message = if ttys.is_empty() {
+                            format!("Detached clients from `{name}`")
+                        } else {
+                            format!("Detached {} from `{name}`", ttys.join(", "))
+                        };
+                        let _ = self.refresh_sessions().await;
+                    }
+                    Err(e) => {
+                        self.set_error(format!("Failed to detach: {e}"));
+                    }
+                }
+            }
+            Effect::CheckSessionName(name) => {
+                // Keystrokes run ahead of tmux; only the latest name counts.
+                if self.typed_session_name().as_ref() == Some(&name) {
+                    let taken = tmux::has_session(&name).await.unwrap_or(false);
+                    self.name_taken = taken.then_some(name);
+                }
+            }
+            Effect::ListBuffers(target) => self.load_buffers(target).await,
+            Effect::PreviewBuffer(name) => match tmux::show_buffer(&name).await {
+                Ok(contents) => self.buffer_preview = contents,
+                Err(e) => self.set_error(format!("Failed to read {name}: {e}")),
+            },
+            Effect::CopyBuffer(name) => match tmux::show_buffer(&name).await {
+                Ok(contents) => self.copy_to_clipboard(contents, &name).await,
+                Err(e) => self.set_error(format!("Failed to read {name}: {e}")),
+            },
+            Effect::CopyPane(target) => match tmux::capture_pane(&target, false, None, false).await
+            {
+                Ok(contents) => {
+                    let text = contents.trim_end().to_string();
+                    self.copy_to_clipboard(text, &format!("`{target}`")).await;
+                }
+                Err(e) => self.set_error(format!("Failed to capture {target}: {e}")),
+            },
+            Effect::CaptureToFile { target, path } => {
+                let saved = match tmux::capture_scrollback(&target).await {
+                    Ok(text) => std::fs::create_dir_all(path.parent().unwrap_or(&path))
+                        .and_then(|_| std::fs::write(&path, &text))
+                        .map(|_| text.lines().count())
+                        .map_err(Into::into),
+                    Err(e) => Err(e),
+                };
+                match saved {
+                    Ok(lines) => {
+                        self.status_message =
+                            format!("Saved {lines} lines of `{target}` to {}", path.display())
+                    }
+                    Err(e) => self.set_error(format!("Failed to save {target}: {e}")),
+                }
+            }
+            Effect::StartPaneLog { target, path } => {
+                let started = match std::fs::create_dir_all(path.parent().unwrap_or(&path)) {
+                    Ok(_) => tmux::start_pipe(&target, &path.to_string_lossy()).await,
+                    Err(e) => Err(e.into()),
+                };
+                match started {
+                    Ok(_) => {
+                        self.refresh_windows().await;
+                        self.status_message = format!("Logging `{target}` to {}", path.display());
+                    }
+                    Err(e) => self.set_error(format!("Failed to log {target}: {e}")),
+                }
+            }
+            Effect::StopPaneLog(target) => match tmux::stop_pipe(&target).await {
+                Ok(_) => {
+                    self.refresh_windows().await;
+                    self.status_message = format!("Stopped logging `{target}`");
+                }
+                Err(e) => self.set_error(format!("Failed to stop logging {target}: {e}")),
+            },
+            Effect::PasteBuffer { name, target } => {
+                match tmux::paste_buffer(&name, &target).await {
+                    Ok(_) => self.status_message = format!("Pasted {name} into `{target}`"),
+                    Err(e) => self.set_error(format!("Failed to paste {name}: {e}")),
+                }
+            }
+            Effect::DeleteBuffer { name, target } => match tmux::delete_buffer(&name).await {
+                Ok(_) => {
+                    self.load_buffers(target).await;
+                    self.status_message = format!("Deleted {name}");
+                }
+                Err(e) => self.set_error(format!("Failed to delete {name}: {e}")),
+            },
+            Effect::DetachClient { session, tty } => match tmux::detach_tty(&tty).await {
+                Ok(_) => {
+                    self.status_message = format!("Detached {tty}");
+                    let _ = self.refresh_sessions().await;
+                    self.load_clients(session).await;
+                }
+                Err(e) => {
+                    self.set_error(format!("Failed to detach {tty}: {e}"));
+                }
+            },
+            Effect::ListClients(name) => self.load_clients(name).await,
+            Effect::DetachIdleClients(session) => {
+                self.detach_idle_clients(session.as_ref().map(|s| s.name.as_str()))
+                    .await;
+                if let Some(session) = session {
+                    self.load_clients(session).await;
+                }
+            }
+            Effect::ListPanes { window_id, purpose } => self.load_panes(&window_id, purpose).await,
+            Effect::ListOptions { target, window } => self.load_options(target, window).await,
+            Effect::SetOption {
+                target,
+                window,
+                name,
+                value,
+            } => match tmux::set_option(&target, window, &name, &value).await {
+                Ok(_) => {
+                    self.load_options(target, window).await;
+                    self.status_message = format!("{name} = {value}");
+                }
+                Err(e) => self.set_error(format!("Failed to set {name}: {e}")),
+            },
+            Effect::BreakPane(id) => match tmux::break_pane(&id).await {
+                Ok(_) => {
+                    let _ = self.refresh_sessions().await;
+                    self.refresh_windows().await;
+                    self.status_message = format!("Broke pane {id} out into a new window");
+                }
+                Err(e) => self.set_error(format!("Failed to break pane: {e}")),
+            },
+            Effect::JoinPane { pane_id, target } => {
+                match tmux::join_pane(&pane_id, &target).await {
+                    Ok(_) => {
+                        let _ = self.refresh_sessions().await;
+                        self.refresh_windows().await;
+                        self.status_message = format!("Joined pane {pane_id} into {target}");
+                    }
+                    Err(e) => self.set_error(format!("Failed to join pane: {e}")),
+                }
+            }
+            Effect::RespawnPane(id) => self.respawn_pane(&id).await,
+            Effect::KillPane(id) => match tmux::kill_pane(&id).await {
+                Ok(_) => {
+                    let _ = self.refresh_sessions().await;
+                    self.refresh_windows().await;
+                    self.status_message = format!("Killed pane {id}");
+                }
+                Err(e) => self.set_error(format!("Failed to kill pane: {e}")),
+            },
+            Effect::ListServers => self.load_servers().await,
+            Effect::LoadChanges => self.load_changes().await,
+            Effect::LoadStats => {
+                self.record_usage().await;
+                self.mode = AppMode::Stats;
+            }
+            Effect::ConnectServer(path) => self.connect_server(path).await,
+            Effect::ConnectRemote(remote) => self.connect_remote(remote).await,
+            Effect::RemoveSocket(path) => {
+                match std::fs::remove_file(&path) {
+                    Ok(_) => self.status_message = format!("Removed {}", path.display()),
+                    Err(e) => self.set_error(format!("Failed to remove socket: {e}")),
+                }
+                self.load_servers().await;
+            }
+            Effect::ToggleZoom(target) => match tmux::toggle_zoom(&target).await {
+                Ok(_) => {
+                    self.refresh_windows().await;
+                    self.status_message = format!("Toggled zoom in `{target}`");
+                }
+                Err(e) => self.set_error(format!("Failed to toggle zoom: {e}")),
+            },
+            Effect::SwapWindows { source, target } => {
+                match tmux::swap_window(&source, &target).await {
+                    Ok(_) => self.refresh_windows().await,
+                    Err(e) => self.set_error(format!("Failed to swap windows: {e}")),
+                }
+            }
+            Effect::SelectLayout { target, layout } => {
+                match tmux::select_layout(&target, &layout).await {
+                    Ok(_) => self.status_message = format!("Applied {layout} to `{target}`"),
+                    Err(e) => self.set_error(format!("Failed to apply layout: {e}")),
+                }
+            }
+            Effect::MoveWindow { window_id, to } => {
+                match tmux::move_window(&window_id, &to).await {
+                    Ok(_) => {
+                        let _ = self.refresh_sessions().await;
+                        self.refresh_windows().await;
+                        self.status_message = format!("Moved window to `{to}`");
+                    }
+                    Err(e) => {
+                        self.set_error(format!("Failed to move window: {e}"));
+                    }
+                }
+            }
+            Effect::RunCommand { name, command } => {
+                self.status_message = match tmux::create_runner_session(&name, &command).await {
+                    Ok(_) => {
+                        let _ = self.refresh_sessions().await;
+                        format!("Started `{name}`: {command}")
+                    }
+                    Err(e) => {
+                        self.set_error(format!("Failed to run: {e}"));
+                        String::new()
+                    }
+                };
+            }
+            Effect::NewWindow {
+                session: SessionTarget { name: session, .. },
+                name,
+                command,
+            } => match tmux::new_window(&session, &name, None, Some(&command)).await {
+                Ok(_) => {
+                    let _ = self.refresh_sessions().await;
+                    self.refresh_windows().await;
+                    self.status_message = format!("Started `{command}` in `{session}`");
+                }
+                Err(e) => self.set_error(format!("Failed to open window: {e}")),
+            },
+            Effect::RestoreSession(snapshot) => {
+                self.status_message = match self.restore_session(&snapshot).await {
+                    Ok(_) => {
+                        let _ = self.refresh_sessions().await;
+                        format!("Brought back `{}` (shells only)", snapshot.name)
+                    }
+                    Err(e) => {
+                        self.set_error(format!("Failed to bring back `{}`: {e}", snapshot.name));
+                        String::new()
+                    }
+                };
+            }
+            Effect::NewSplitWindow { session, preset } => {
+                let session = session.name;
+                let opened =
+                    tmux::new_window(&session, &preset.name, None, preset.command.as_deref());
+                match opened.await {
+                    Ok(window) => {
+                        if let Err(e) = split_panes(&window, &preset.panes, None).await {
+                            self.set_error(format!("Failed to split `{}`: {e}", preset.name));
+                        }
+                        let _ = self.refresh_sessions().await;
+                        self.refresh_windows().await;
+                        self.status_message = format!("Opened `{}` in `{session}`", preset.name);
+                    }
+                    Err(e) => self.set_error(format!("Failed to open window: {e}")),
+                }
+            }
+            Effect::SendKeys { target, text } => match tmux::send_line(&target, &text).await {
+                Ok(_) => self.status_message = format!("Sent `{text}` to `{target}`"),
+                Err(e) => self.set_error(format!("Failed to send keys: {e}")),
+            },
+            Effect::SendText { target, input } => self.send_text(target, input).await,
+            Effect::Attach(target) => self.attach(target.name).await,
+            Effect::AttachReadOnly(target) => self.attach_read_only(target.name).await,
+            Effect::SwitchLastClient => match tmux::switch_client_last().await {
+                Ok(_) => self.should_quit = true,
+                Err(e) => self.set_error(format!("No previous session to switch to: {e}")),
+            },
+            Effect::SaveConfig => {
+                let _ = self.config.save();
+            }
+        }
+    }
+
+    /// Open (or refresh) the clients popup for `session`, keeping the
+    /// cursor in range.
+    /// Copy `text` with OSC 52, which also reaches the clipboard over SSH,
+    /// or with a local clipboard command when configured (falling back to
+    /// OSC 52 when there is none) or when the text is too long for OSC 52.
+    async fn copy_to_clipboard(&mut self, text: String, what: &str) {
+        let too_long = text.len() > clipboard::OSC52_LIMIT;
+        if too_long || self.config.clipboard == Some(ClipboardMode::Local) {
+            match clipboard::copy_local(&text).await {
+                Ok(program) => {
+                    self.status_message = format!("Copied {what} with {program}");
+                    return;
+                }
+                Err(e) if too_long => {
+                    self.set_error(format!("Failed to copy {what}: {e}"));
+                    return;
+                }
+                Err(_) => {}
+            }
+        }
+        let in_tmux = std::env::var_os("TMUX").is_some();
+        self.pending_osc52 = Some(clipboard::osc52(&text, in_tmux));
+        self.status_message = format!("Copied {what} ({} bytes)", text.len());
+    }
+
+    /// Open (or refresh) the buffers popup with the highlighted buffer's
+    /// contents loaded.
+    async fn load_buffers(&mut self, target: String) {
+        match tmux::list_buffers().await {
+            Ok(buffers) if buffers.is_empty() => {
+                self.mode = AppMode::Normal;
+                self.buffers.clear();
+                self.status_message = "No paste buffers".to_string();
+            }
+            Ok(buffers) => {
+                self.selected_buffer = self.selected_buffer.min(buffers.len() - 1);
+                let name = buffers[self.selected_buffer].name.clone();
+                self.buffers = buffers;
+                self.mode = AppMode::Buffers(target);
+                self.buffer_preview = tmux::show_buffer(&name).await.unwrap_or_default();
+            }
+            Err(e) => self.set_error(format!("Failed to list buffers: {e}")),
+        }
+    }
+
+    async fn load_clients(&mut self, session: SessionTarget) {
+        match tmux::list_clients(&session.name).await {
+            Ok(clients) => {
+                self.clients = clients;
+                self.selected_client = self
+                    .selected_client
+                    .min(self.clients.len().saturating_sub(1));
+                self.mode = AppMode::Clients(session);
+            }
+            Err(e) => {
+                self.set_error(format!("Failed to list clients: {e}"));
+            }
+        }
+    }
+
+    /// Open the pane picker for `purpose` over the panes of `window_id`. A
+    /// window with a single pane skips the picker.
+    async fn load_panes(&mut self, window_id: &str, purpose: PickPurpose) {
+        let mut panes = match tmux::list_panes(window_id).await {
+            Ok(panes) => panes,
+            Err(e) => {
+                self.set_error(format!("Failed to list panes: {e}"));
+                return;
+            }
+        };
+        if let PickPurpose::RespawnPane { window } = &purpose {
+            panes.retain(|pane| pane.dead);
+            if panes.is_empty() {
+                self.status_message = format!("No dead pane in `{window}`");
+                return;
+            }
+        }
+        match panes.as_slice() {
+            [] => self.status_message = "Window has no panes".to_string(),
+            [pane] => match purpose {
+                PickPurpose::RespawnPane { .. } => self.respawn_pane(&pane.id).await,
+                PickPurpose::KillPane { .. } => self.confirm_kill_pane(pane),
+                PickPurpose::JoinPane { .. } => self.prompt_join_target(pane),
+                PickPurpose::BreakPane { window } => {
+                    self.status_message = format!("`{window}` has a single pane");
+                }
+                _ => {}
+            },
+            _ => {
+                self.pick_items = panes.iter().map(pane_label).collect();
+                self.pick_selected = 0;
+                self.panes = panes;
+                self.mode = AppMode::Pick(purpose);
+                self.status_message = "Choose a pane".to_string();
+            }
+        }
+    }
+
+    /// Open (or refresh) the options picker, keeping the highlighted row.
+    async fn load_options(&mut self, target: String, window: bool) {
+        let inherited = self.capabilities.inherited_options;
+        match tmux::list_options(&target, window, inherited).await {
+            Ok(options) => {
+                self.pick_items = options
+                    .iter()
+                    .map(|option| format!("{} {}", option.name, option.value))
+                    .collect();
+                self.pick_selected = self.pick_selected.min(options.len().saturating_sub(1));
+                self.options = options;
+                self.mode = AppMode::Pick(PickPurpose::Options { target, window });
+                self.status_message = "Enter flips on/off options and edits others".to_string();
+            }
+            Err(e) => self.set_error(format!("Failed to list options: {e}")),
+        }
+    }
+
+    async fn respawn_pane(&mut self, id: &str) {
+        match tmux::respawn_pane(id).await {
+            Ok(_) => {
+                self.refresh_windows().await;
+                self.status_message = format!("Respawned pane {id}");
+            }
+            Err(e) => self.set_error(format!("Failed to respawn pane: {e}")),
+        }
+    }
+
+    /// Open (or refresh) the servers popup from the socket directory.
+    async fn load_servers(&mut self) {
+        let Some(dir) = servers::socket_dir().await else {
+            self.set_error("Could not locate the tmux socket directory".to_string());
+            return;
+        };
+        self.servers = servers::scan(&dir);
+        self.selected_server = self
+            .selected_server
+            .min(self.servers.len().saturating_sub(1));
+        self.mode = AppMode::Servers;
+        self.status_message = format!("Sockets in {}", dir.display());
+    }
+
+    /// Point every tmux call at the server on `path` and reload from it.
+    async fn connect_server(&mut self, path: PathBuf) {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        tmux::set_socket(Some(tmux::Socket::Path(path)));
+        tmux::set_remote(None);
+        self.remote_host = None;
+        self.servers.clear();
+        self.reload_server().await;
+        self.status_message = format!("Connected to server `{name}`");
+    }
+
+    /// Manage `remote`'s server over ssh, or the local one again with
+    /// `None`, and reload from it.
+    async fn connect_remote(&mut self, remote: Option<Remote>) {
+        let label = remote
+            .as_ref()
+            .map_or_else(|| "local".to_string(), |r| r.host.clone());
+        self.remote_host = remote.as_ref().map(|r| r.host.clone());
+        tmux::set_remote(remote);
+        self.reload_server().await;
+        if self.error_message.is_none() {
+            self.status_message = format!("Connected to `{label}`");
+        }
+    }
+
+    /// Start over on the server tmux commands now go to.
+    async fn reload_server(&mut self) {
+        self.session_windows.clear();
+        self.session_ports.clear();
+        self.last_port_scan = None;
+        self.heat = HeatTracker::default();
+        self.last_heat_sample = None;
+        self.selected = 0;
+        self.selected_window = 0;
+        self.inside_tmux = self.remote_host.is_none() && tmux::is_inside_tmux();
+        self.nested_client = false;
+        self.current_session = None;
+        self.detect_capabilities().await;
+        self.detect_client().await;
+        if let Err(e) = self.refresh_sessions().await {
+            self.set_error(format!("Refresh failed: {e}"));
+        }
+        self.refresh_windows().await;
+        self.server_changed = true;
+    }
+
+    /// Offer the local server and every configured remote host.
+    fn show_hosts(&mut self) {
+        if self.config.remotes.is_empty() {
+            self.status_message = "No remotes configured ([[remotes]] in config.toml)".to_string();
+            return;
+        }
+        let current = self.remote_host.as_deref();
+        self.pick_items = std::iter::once("local".to_string())
+            .chain(self.config.remotes.iter().map(|r| match &r.socket {
+                Some(socket) => format!("{} ({socket})", r.host),
+                None => r.host.clone(),
+            }))
+            .collect();
+        self.pick_selected = current
+            .and_then(|host| self.config.remotes.iter().position(|r| r.host == host))
+            .map_or(0, |i| i + 1);
+        self.mode = AppMode::Pick(PickPurpose::Host);
+    }
+
+    /// `new-session` with the variables of the matching `environments`
+    /// templates. Servers before 3.2 get them through `set-environment`,
+    /// which only reaches windows opened afterwards.
+    async fn spawn_session(
+        &self,
+        name: &str,
+        path: Option<&str>,
+        command: Option<&str>,
+    ) -> AppResult<()> {
+        let env = self.config.session_environment(name);
+        if self.capabilities.session_environment {
+            return tmux::create_session(name, path, command, &env).await;
+        }
+        tmux::create_session(name, path, command, &[]).await?;
+        for (key, value) in &env {
+            tmux::set_environment(name, key, value).await?;
+        }
+        Ok(())
+    }
+
+    /// Snapshot file of the managed server, when snapshots are kept.
+    fn snapshot_path(&self) -> Option<PathBuf> {
+        if tmux::remote().is_some() {
+            return None;
+        }
+        let dir = self.snapshot_dir.as_deref()?;
+        Some(snapshot::path_in(dir, tmux::socket().as_ref()))
+    }
+
+    /// The managed server's sessions and windows as of now.
+    async fn take_snapshot(&mut self) -> AppResult<Snapshot> {
+        self.refresh_sessions().await?;
+        let windows = tmux::list_all_windows().await?;
+        Ok(Snapshot::capture(&self.sessions, &windows, unix_now()))
+    }
+
+    /// Save the server as it is now, for `E` in the next run.
+    pub async fn save_snapshot(&mut self) {
+        let Some(path) = self.snapshot_path() else {
+            return;
+        };
+        if let Ok(snapshot) = self.take_snapshot().await {
+            let _ = snapshot.save(&path);
+        }
+    }
+
+    async fn load_changes(&mut self) {
+        let Some(previous) = self.snapshot_path().and_then(|p| Snapshot::load(&p)) else {
+            self.status_message = "No snapshot yet: one is saved when tmui exits".to_string();
+            return;
+        };
+        match self.take_snapshot().await {
+            Ok(now) => {
+                self.changes = previous.changes_to(&now);
+                self.changes_since = previous.taken;
+                self.mode = AppMode::Changes;
+            }
+            Err(e) => self.set_error(format!("Failed to snapshot the server: {e}")),
+        }
+    }
+
+    /// Recreate `snapshot`'s session: its first window is the session's
+    /// own, renamed; the others are opened after it.
+    async fn restore_session(&self, snapshot: &SessionSnapshot) -> AppResult<()> {
+        let name = &snapshot.name;
+        let (first_path, first_name) = match snapshot.windows.first() {
+            Some((window, path)) => (path.as_str(), Some(window)),
+            None => (snapshot.path.as_str(), None),
+        };
+        let first_path = Some(first_path).filter(|p| !p.is_empty());
+        self.spawn_session(name, first_path, None).await?;
+        if let Some(window) = first_name {
+            tmux::rename_window(&format!("{name}:"), window).await?;
+        }
+        for (window, path) in snapshot.windows.iter().skip(1) {
+            tmux::new_window(name, window, Some(path), None).await?;
+        }
+        Ok(())
+    }
+
+    /// Kill session `target` after moving tmui's client off it, keeping a
+    /// snapshot for `u`; returns whether it went.
+    async fn kill_with_undo(&mut self, target: &SessionTarget) -> bool {
+        self.leave_doomed_session(std::slice::from_ref(target))
+            .await;
+        let name = &target.name;
+        let path = self
+            .sessions
+            .iter()
+            .find(|s| s.key() == target.key())
+            .map_or(String::new(), |s| s.path.clone());
+        let snapshot = tmux::snapshot_session(name, &path)
+            .await
+            .ok()
+            .map(|snapshot| SessionSnapshot {
+                server: target.server.clone(),
+                ..snapshot
+            });
+        match tmux::kill_session(name).await {
+            Ok(_) => {
+                let _ = self.refresh_sessions().await;
+                self.status_message = match snapshot {
+                    Some(snapshot) => {
+                        self.last_killed = Some((snapshot, Instant::now()));
+                        format!("Killed session `{name}` (u to undo)")
+                    }
+                    None => format!("Killed session `{name}`"),
+                };
+                true
+            }
+            Err(e) => {
+                self.set_error(format!("Failed to kill: {e}"));
+                self.status_message.clear();
+                false
+            }
+        }
+    }
+
+    /// Kill each of `targets` on its own server, reporting failures;
+    /// returns how many went.
+    async fn kill_sessions(&mut self, targets: &[SessionTarget]) -> usize {
+        let mut killed = 0;
+        for target in targets {
+            let name = &target.name;
+            let socket = target.server.as_deref().map(tmux::Socket::from_setting);
+            match tmux::on_server(socket, tmux::kill_session(name)).await {
+                Ok(_) => killed += 1,
+                Err(e) => self.set_error(format!("Failed to kill `{name}`: {e}")),
+            }
+        }
+        let _ = self.refresh_sessions().await;
+        killed
+    }
+
+    /// Create a detached session and return the status line to show.
+    async fn create_session(
+        &mut self,
+        name: &str,
+        path: Option<&str>,
+        command: Option<&str>,
+    ) -> String {
+        match self.spawn_session(name, path, command).await {
+            Ok(_) => {
+                let _ = self.refresh_sessions().await;
+                format!("Created session `{name}`")
+            }
+            Err(e) => {
+                self.set_error(format!("Failed to create: {e}"));
+                String::new()
+            }
+        }
+    }
+
+    /// Switch the current client when inside tmux; otherwise hand the
+    /// target to the event loop, which leaves the TUI and execs
+    /// `tmux attach-session`.
+    async fn attach(&mut self, target: String) {
+        if self.nested_client {
+            self.set_error(format!(
+                "Attaching `{target}` would nest tmux; run tmui outside tmux for this server"
+            ));
+            return;
+        }
+        let server = tmux::scoped_server();
+        if !self.inside_tmux {
+            self.remember_use(&target);
+            self.pending_attach = Some(PendingAttach {
+                target,
+                read_only: false,
+                server,
+            });
+            return;
+        }
+        if server.is_some() {
+            self.set_error(format!(
+                "`{target}` is on another server; attaching would nest tmux"
+            ));
+            return;
+        }
+        if self.current_session.as_ref() == Some(&target) {
+            self.status_message = format!("Already attached to `{target}` in this client");
+            return;
+        }
+        match tmux::switch_client(&target).await {
+            Ok(_) => {
+                self.remember_use(&target);
+                self.should_quit = true;
+            }
+            Err(e) => {
+                self.set_error(format!("Failed to switch: {e}"));
+            }
+        }
+    }
+
+    /// Paste the file named by `input` into `target`, or `input` itself
+    /// when no such file exists.
+    async fn send_text(&mut self, target: String, input: String) {
+        let path = PathBuf::from(expand_home(input.trim()));
+        let (text, what) = if path.is_file() {
+            match std::fs::read_to_string(&path) {
+                Ok(text) => (text, format!("{}", path.display())),
+                Err(e) => {
+                    self.set_error(format!("Failed to read {}: {e}", path.display()));
+                    return;
+                }
+            }
+        } else {
+            (input, "snippet".to_string())
+        };
+        match tmux::paste_text(&target, &text).await {
+            Ok(_) => self.status_message = format!("Pasted {what} into `{target}`"),
+            Err(e) => self.set_error(format!("Failed to paste into {target}: {e}")),
+        }
+    }
+
+    /// Put the session of `target` (`session` or `session:window`) first
+    /// in `mru` and count the visit.
+    fn remember_use(&mut self, target: &str) {
+        let session = target.split(':').next().unwrap_or(target);
+        self.config.touch_mru(session);
+        self.config.record_visit(session, unix_now());
+        if self.save_usage {
+            let _ = self.config.save();
+        }
+    }
+
+    /// Credit every session with attached clients for the time since its
+    /// earliest client attached, or since it was last credited.
+    async fn record_usage(&mut self) {
+        let Ok(clients) = tmux::list_all_clients().await else {
+            return;
+        };
+        let mut since: HashMap<&str, i64> = HashMap::new();
+        for client in &clients {
+            let start = since.entry(&client.session).or_insert(client.created);
+            *start = (*start).min(client.created);
+        }
+        let now = unix_now();
+        for (session, start) in since {
+            self.config.credit_attached(session, start, now);
+        }
+    }
+
+    /// Record attach time so far and save it, when usage is saved at all.
+    pub async fn save_usage(&mut self) {
+        if self.save_usage {
+            self.record_usage().await;
+            let _ = self.config.save();
+        }
+    }
+
+    /// Like [`Self::attach`], but the client cannot type into `target`.
+    /// Inside tmux a read-only client opens in a popup, leaving the user's
+    /// own client untouched.
+    async fn attach_read_only(&mut self, target: String) {
+        if self.nested_client {
+            self.set_error(format!(
+                "Attaching `{target}` would nest tmux; run tmui outside tmux for this server"
+            ));
+            return;
+        }
+        let server = tmux::scoped_server();
+        if !self.inside_tmux {
+            self.pending_attach = Some(PendingAttach {
+                target,
+                read_only: true,
+                server,
+            });
+            return;
+        }
+        if !self.capabilities.popups {
+            self.set_error(
+                "Read-only attach inside tmux needs tmux 3.2+ (display-popup)".to_string(),
+            );
+            return;
+        }
+        match tmux::popup_attach_read_only(&target).await {
+            Ok(_) => {
+                self.status_message =
+                    format!("Opened `{target}` read-only; detach to close the popup");
+            }
+            Err(e) => self.set_error(format!("Failed to open read-only popup: {e}")),
+        }
+    }
+
+    /// Open the cleanup review popup for sessions matched by the configured
+    /// policies. Leaves the mode untouched when nothing matches.
+    pub fn review_cleanup(&mut self) {
+        let mut candidates = self.config.cleanup_candidates(&self.sessions, unix_now());
+        // Never offer to kill the session tmui itself runs in.
+        candidates.retain(|target| !self.is_own_session(target));
+        if !candidates.is_empty() {
+            let question = format!("{} sessions match cleanup policies", candidates.len());
+            self.request_confirm(ConfirmAction::CleanupSessions(candidates), question);
+        }
+    }
+
+    /// Set a transient error message that auto-clears after
+    /// `error_seconds` (3 by default).
+    pub fn set_error(&mut self, msg: String) {
+        self.error_message = Some(msg);
+        self.error_time = Some(Instant::now());
+        self.error_sticky = false;
+    }
+
+    /// Clear expired error messages (called on tick). Sticky errors wait
+    /// for Esc.
+    pub fn tick_clear_errors(&mut self) {
+        if self.error_sticky {
+            return;
+        }
+        if let Some(time) = self.error_time {
+            if time.elapsed() >= self.config.error_duration() {
+                self.error_message = None;
+                self.error_time = None;
+            }
+        }
+    }
+
+    /// Ask for one tag to add to all of `names`, or say `none` if empty.
+    fn prompt_tag_sessions(&mut self, names: Vec<String>, none: &str) {
+        if names.is_empty() {
+            self.status_message = none.to_string();
+            return;
+        }
+        self.status_message = format!("Add tag to {} sessions", names.len());
+        self.input_buffer.clear();
+        self.mode = AppMode::Input(InputPurpose::TagSessions(names));
+    }
+
+    /// Sessions marked in visual mode, in list order, leaving the mode.
+    fn take_visual_marked(&mut self) -> Vec<SessionTarget> {
+        let marked = std::mem::take(&mut self.visual_marked);
+        self.mode = AppMode::Normal;
+        self.tag_filtered_sessions()
+            .into_iter()
+            .map(|i| &self.sessions[i])
+            .filter(|session| marked.contains(&session.key()))
+            .map(Session::target)
+            .collect()
+    }
+
+    /// Count a j/k press toward a held key and return how many rows it
+    /// moves.
+    fn repeat_step(&mut self, down: bool) -> usize {
+        let count = match self.nav_repeat {
+            Some((held, last, count)) if held == down && last.elapsed() <= KEY_REPEAT_WINDOW => {
+                count + 1
+            }
+            _ => 1,
+        };
+        self.nav_repeat = Some((down, Instant::now(), count));
+        self.nav_step()
+    }
+
+    /// Rows per j/k press while the key is held: 2 after 10 repeats, 5
+    /// after 25.
+    pub fn nav_step(&self) -> usize {
+        match self.nav_repeat {
+            Some((_, last, count)) if last.elapsed() <= KEY_REPEAT_WINDOW => match count {
+                0..=10 => 1,
+                11..=25 => 2,
+                _ => 5,
+            },
+            _ => 1,
+        }
+    }
+
+    fn clear_multi_key_state(&mut self) {
+        self.last_g_press = None;
+        self.last_d_press = None;
+    }
+
+    /// Owned name of the selected session, for effects and prompts; render
+    /// and navigation code borrows through [`App::selected_session`].
+    pub fn selected_session_name(&self) -> Option<String> {
+        self.selected_session().map(|session| session.name.clone())
+    }
+
+    /// The selected session with its server, for effects that must reach
+    /// it.
+    pub fn selected_target(&self) -> Option<SessionTarget> {
+        self.selected_session().map(Session::target)
+    }
+
+    /// `name` (a session or `session:index`) on the server of the selected
+    /// session, where new sessions are created too.
+    fn on_selected_server(&self, name: String) -> SessionTarget {
+        SessionTarget {
+            server: self.selected_session().and_then(|s| s.server.clone()),
+            name,
+        }
+    }
+
+    /// Whether keys currently go into a text field.
+    pub fn is_typing(&self) -> bool {
+        match &self.mode {
+            AppMode::Search | AppMode::Input(_) | AppMode::Form(_) => true,
+            AppMode::Confirm(action) => self.config.confirm_style(action) == ConfirmStyle::Type,
+            _ => false,
+        }
+    }
+
+    /// The selected session in the current snapshot, through the search or
+    /// tag filter when one is active.
+    pub fn selected_session(&self) -> Option<&Session> {
+        if self.search_active {
+            let idx = self
+                .selected
+                .min(self.filtered_results.len().saturating_sub(1));
+            self.filtered_results
+                .get(idx)
+                .and_then(|r| self.sessions.get(r.session_index))
+        } else if self.tag_filter.is_some()
+            || self.hides_ignored()
+            || self.config.group_by_path.unwrap_or(false)
+            || self.config.sort.is_some()
+            || !self.config.mru.is_empty()
+        {
+            let indices = self.tag_filtered_sessions();
+            let idx = self.selected.min(indices.len().saturating_sub(1));
+            indices.get(idx).and_then(|&i| self.sessions.get(i))
+        } else {
+            self.sessions.get(self.selected)
+        }
+    }
+
+    /// Put the cursor on `name` if it is in the (tag-filtered) list.
+    pub fn select_session_named(&mut self, name: &str) -> bool {
+        let position = self
+            .tag_filtered_sessions()
+            .iter()
+            .position(|&i| self.sessions[i].name == name);
+        if let Some(position) = position {
+            self.selected = position;
+            self.selected_window = 0;
+        }
+        position.is_some()
+    }
+
+    fn select_next(&mut self) {
+        let count = self.visible_session_count();
+        if count == 0 {
+            self.selected = 0;
+            return;
+        }
+        let prev = self.selected;
+        self.selected = (self.selected + 1).min(count - 1);
+        if self.selected != prev {
+            self.selected_window = 0;
+        }
+    }
+
+    fn select_previous(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+            self.selected_window = 0;
+        }
+    }
+
+    fn select_first(&mut self) {
+        if self.selected != 0 {
+            self.selected_window = 0;
+        }
+        self.selected = 0;
+    }
+
+    fn select_last(&mut self) {
+        let count = self.visible_session_count();
+        if count == 0 {
+            self.selected = 0;
+            return;
+        }
+
+        self.selected = count - 1;
+    }
+
+    /// Rows of the focused list as last drawn; one before the first draw.
+    fn page_rows(&self) -> usize {
+        let (sessions, windows) = self.list_rows;
+        match self.focus {
+            FocusPanel::Sessions => sessions,
+            FocusPanel::Windows => windows,
+        }
+        .max(1)
+    }
+
+    fn selected_windows(&self) -> Option<&Vec<Window>> {
+        self.session_windows.get(&self.selected_session()?.key())
+    }
+
+    fn select_next_window(&mut self) {
+        let count = self.visible_windows().len();
+        if count > 0 {
+            self.selected_window = (self.selected_window + 1).min(count - 1);
+        }
+    }
+
+    fn select_previous_window(&mut self) {
+        if self.selected_window > 0 {
+            self.selected_window -= 1;
+        }
+    }
+
+    fn select_last_window(&mut self) {
+        let count = self.visible_windows().len();
+        if count > 0 {
+            self.selected_window = count - 1;
+        }
+    }
+
+    /// Servers listed alongside the managed one; none while a remote host
+    /// is managed, as they are local sockets.
+    fn extra_servers(&self) -> Vec<String> {
+        if self.remote_host.is_some() {
+            return Vec::new();
+        }
+        self.config.servers.clone()
+    }
+
+    /// Socket of the server the session with [`Session::key`] `key` lives
+    /// on, when not the managed one.
+    fn server_of(&self, key: &str) -> Option<tmux::Socket> {
+        let session = self.sessions.iter().find(|s| s.key() == key)?;
+        session.server.as_deref().map(tmux::Socket::from_setting)
+    }
+
+    /// Socket of the selected session's server, when not the managed one.
+    fn session_server(&self) -> Option<tmux::Socket> {
+        let session = self.selected_session()?;
+        session.server.as_deref().map(tmux::Socket::from_setting)
+    }
+
+    /// Session to move tmui's own client to before killing `targets`, when
+    /// the one it runs in is among them: the most recently attached
+    /// survivor on the managed server.
+    fn refuge_session(&self, targets: &[SessionTarget]) -> Option<String> {
+        self.sessions
+            .iter()
+            .filter(|s| s.server.is_none())
+            .filter(|s| {
+                !targets
+                    .iter()
+                    .any(|t| t.server.is_none() && t.name == s.name)
+            })
+            .max_by_key(|s| s.last_attached)
+            .map(|s| s.name.clone())
+    }
+
+    /// Whether killing `targets` takes the session tmui runs in with it.
+    fn kills_own_session(&self, targets: &[SessionTarget]) -> bool {
+        self.inside_tmux && targets.iter().any(|target| self.is_own_session(target))
+    }
+
+    /// Whether `target` is the session tmui's client is on, which lives on
+    /// the managed server.
+    fn is_own_session(&self, target: &SessionTarget) -> bool {
+        target.server.is_none() && self.current_session.as_ref() == Some(&target.name)
+    }
+
+    /// Warning for a confirmation that would kill tmui's own session.
+    pub fn own_session_note(&self, targets: &[SessionTarget]) -> Option<String> {
+        if !self.kills_own_session(targets) {
+            return None;
+        }
+        Some(match self.refuge_session(targets) {
+            Some(refuge) => format!("switching this client to `{refuge}` first"),
+            None => "which tmui runs in and exits with".to_string(),
+        })
+    }
+
+    /// Switch tmui's client away from its session before that is killed,
+    /// so the kill does not take tmui down too.
+    async fn leave_doomed_session(&mut self, targets: &[SessionTarget]) {
+        if !self.kills_own_session(targets) {
+            return;
+        }
+        let Some(refuge) = self.refuge_session(targets) else {
+            return;
+        };
+        match tmux::on_server(None, tmux::switch_client(&refuge)).await {
+            Ok(_) => self.current_session = Some(refuge),
+            Err(e) => self.set_error(format!("Failed to switch to `{refuge}`: {e}")),
+        }
+    }
+
+    /// The most recently attached session other than the one tmui runs in,
+    /// for going back without tmux's own per-client history.
+    fn last_used_session(&self) -> Option<SessionTarget> {
+        self.sessions
+            .iter()
+            .filter(|s| s.last_attached > 0 && !self.is_own_session(&s.target()))
+            .max_by_key(|s| s.last_attached)
+            .map(Session::target)
+    }
+
+    fn attach_target(&self) -> Option<String> {
+        let session_name = &self.selected_session()?.name;
+        match self.focus {
+            FocusPanel::Sessions => Some(session_name.clone()),
+            FocusPanel::Windows => {
+                let win = self.selected_window_entry()?;
+                Some(format!("{}:{}", session_name, win.index))
+            }
+        }
+    }
+}
+
+/// File for `session:window` made at `now`, e.g.
+/// `work-1-20261015-093000.log`.
+fn target_file_name(target: &str, now: i64, extension: &str) -> String {
+    format!(
+        "{}-{}.{extension}",
+        target.replace([':', '/'], "-"),
+        crate::report::file_stamp(now)
+    )
+}
+
+/// Lowest free `base-N` (N >= 2) name for a sibling of an existing session.
+fn next_free_name(sessions: &[Session], base: &str) -> String {
+    (2..)
+        .map(|n| format!("{base}-{n}"))
+        .find(|name| !sessions.iter().any(|s| &s.name == name))
+        .expect("unbounded range always yields a free name")
+}
+
+/// Lowest free `run-N` session name for a new runner session.
+fn next_runner_name(sessions: &[Session]) -> String {
+    (1..)
+        .map(|n| format!("run-{n}"))
+        .find(|name| !sessions.iter().any(|s| &s.name == name))
+        .expect("unbounded range always yields a free name")
+}
+
+/// Window name for a command: its program without the path, skipping
+/// leading `VAR=value` assignments.
+fn command_window_name(command: &str) -> String {
+    command
+        .split_whitespace()
+        .find(|word| !word.contains('='))
+        .and_then(|program| program.rsplit('/').next())
+        .filter(|name| !name.is_empty())
+        .unwrap_or("cmd")
+        .to_string()
+}
+
+/// Picker and confirmation text for a pane: `1: vim ~/src (active)`.
+fn pane_label(pane: &Pane) -> String {
+    let mut label = format!(
+        "{}: {} {}",
+        pane.index, pane.current_command, pane.current_path
+    );
+    if pane.active {
+        label.push_str(" (active)");
+    }
+    if pane.dead {
+        label.push_str(" (dead)");
+    }
+    label
+}
+
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// Bracketed-paste text as a single-line insert; `None` when nothing
+/// printable is left.
+fn paste_action(text: &str) -> Option<Action> {
+    let text: String = text
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    let text = text.trim();
+    (!text.is_empty()).then(|| Action::InsertText(text.to_string()))
+}
+
+/// Fields of the `N` form; indices are read back in `submit_form`.
+/// The new-session wizard; `splits` adds a field naming a split preset.
+fn session_form(splits: bool) -> Form {
+    let mut fields = vec![
+        Field::new("Name").required(),
+        Field::new("Directory")
+            .placeholder("current directory")
+            .validate(|value| {
+                if value.is_empty() || std::path::Path::new(&expand_home(value)).is_dir() {
+                    Ok(())
+                } else {
+                    Err("no such directory".to_string())
+                }
+            }),
+        Field::new("Command").placeholder("default shell"),
+    ];
+    if splits {
+        fields.push(Field::new("Splits").placeholder("none"));
+    }
+    Form::new(fields)
+}
+
+/// Split `target`'s window pane by pane, stopping at the first failure.
+async fn split_panes(target: &str, panes: &[SplitPane], path: Option<&str>) -> AppResult<()> {
+    for pane in panes {
+        tmux::split_window(target, pane, path).await?;
+    }
+    Ok(())
+}
+
+/// Top-level directory of `path` for grouping: `~/work` for anything under
+/// `$HOME/work`, `/srv` for anything under `/srv`.
+fn path_group(path: &str, home: &str) -> String {
+    let (root, rest) = match path.strip_prefix(home) {
+        Some(rest) if !home.is_empty() && (rest.is_empty() || rest.starts_with('/')) => ("~", rest),
+        _ => ("", path),
+    };
+    match rest.split('/').find(|part| !part.is_empty()) {
+        Some(top) => format!("{root}/{top}"),
+        None if root.is_empty() => "/".to_string(),
+        None => root.to_string(),
+    }
+}
+
+/// `path` with a leading `~` replaced by `$HOME`.
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix('~'), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) if rest.is_empty() || rest.starts_with('/') => home + rest,
+        _ => path.to_string(),
+    }
+}
+
+/// Text-prompt keys shared by search and input modes.
+fn prompt_key_action(key: KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Esc => Some(Action::Cancel),
+        KeyCode::Enter => Some(Action::Submit),
+        KeyCode::Backspace => Some(Action::DeleteChar),
+        KeyCode::Char(c) => Some(Action::InsertText(c.to_string())),
+        _ => None,
+    }
+}
+
+fn is_double_tap(last_press: Option<Instant>) -> bool {
+    last_press.is_some_and(|time| time.elapsed() <= DOUBLE_TAP_WINDOW)
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::naming::NamingRule;
+    use crossterm::event::{Event, KeyEventState};
+
+    fn make_key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }
+    }
+
+    fn make_key_with_kind(code: KeyCode, modifiers: KeyModifiers, kind: KeyEventKind) -> KeyEvent {
+        KeyEvent {
+            code,
+            modifiers,
+            kind,
+            state: KeyEventState::NONE,
+        }
+    }
+
+    /// Session `name` on the managed server.
+    fn local(name: &str) -> SessionTarget {
+        SessionTarget {
+            server: None,
+            name: name.to_string(),
+        }
+    }
+
+    fn make_session(name: &str) -> Session {
+        Session {
+            id: format!("${name}"),
+            name: name.to_string(),
+            windows: 1,
+            attached: 0,
+            created: 0,
+            last_attached: 0,
+            group: None,
+            path: "/tmp".to_string(),
+            runner: false,
+            server: None,
+        }
+    }
+
+    #[test]
+    fn test_app_initial_state() {
+        let app = App::new();
+        assert!(!app.should_quit);
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.selected, 0);
+        assert!(app.sessions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ignore_key_release_events() {
+        let mut app = App::new();
+        let release = make_key_with_kind(
+            KeyCode::Char('q'),
+            KeyModifiers::NONE,
+            KeyEventKind::Release,
+        );
+
+        app.handle_event(Event::Key(release))
+            .await
+            .expect("release events should be ignored");
+
+        assert!(!app.should_quit);
+    }
+
+    #[tokio::test]
+    async fn test_app_quit_on_q() {
+        let mut app = App::new();
+        app.handle_event(Event::Key(make_key(KeyCode::Char('q'), KeyModifiers::NONE)))
+            .await
+            .expect("q should be handled");
+        assert!(app.should_quit);
+    }
+
+    #[tokio::test]
+    async fn test_app_quit_on_ctrl_c() {
+        let mut app = App::new();
+        app.handle_event(Event::Key(make_key(
+            KeyCode::Char('c'),
+            KeyModifiers::CONTROL,
+        )))
+        .await
+        .expect("ctrl-c should be handled");
+        assert!(app.should_quit);
+    }
+
+    #[tokio::test]
+    async fn test_vim_navigation() {
+        let mut app = App::new();
+        app.sessions = vec![
+            make_session("alpha"),
+            make_session("beta"),
+            make_session("gamma"),
+        ];
+
+        app.handle_event(Event::Key(make_key(KeyCode::Char('j'), KeyModifiers::NONE)))
+            .await
+            .expect("j should move selection down");
+        assert_eq!(app.selected, 1);
+
+        app.handle_event(Event::Key(make_key(KeyCode::Char('k'), KeyModifiers::NONE)))
+            .await
+            .expect("k should move selection up");
+        assert_eq!(app.selected, 0);
+
+        app.handle_event(Event::Key(make_key(
+            KeyCode::Char('G'),
+            KeyModifiers::SHIFT,
+        )))
+        .await
+        .expect("G should jump to last");
+        assert_eq!(app.selected, 2);
+
+        app.handle_event(Event::Key(make_key(KeyCode::Char('g'), KeyModifiers::NONE)))
+            .await
+            .expect("first g should arm gg");
+        app.handle_event(Event::Key(make_key(KeyCode::Char('g'), KeyModifiers::NONE)))
+            .await
+            .expect("second g should jump to first");
+        assert_eq!(app.selected, 0);
+    }
+
+    #[tokio::test]
+    async fn test_enter_no_session_selected() {
+        let mut app = App::new();
+        app.handle_event(Event::Key(make_key(KeyCode::Enter, KeyModifiers::NONE)))
+            .await
+            .expect("enter with no sessions should be handled");
+        assert_eq!(app.status_message, "No session selected");
+        assert!(!app.should_quit);
+    }
+
+    #[tokio::test]
+    async fn test_enter_inside_tmux_switch_fails_gracefully() {
+        let mut app = App::new();
+        app.sessions = vec![make_session("target")];
+        app.inside_tmux = true;
+
+        app.handle_event(Event::Key(make_key(KeyCode::Enter, KeyModifiers::NONE)))
+            .await
+            .expect("enter inside tmux should be handled");
+
+        let has_error = app
+            .error_message
+            .as_ref()
+            .is_some_and(|m| m.contains("Failed to switch"));
+        assert!(
+            has_error || app.should_quit,
+            "should either fail gracefully or quit after switch: error={:?}, status='{}'",
+            app.error_message,
+            app.status_message
+        );
+    }
+
+    #[tokio::test]
+    async fn test_attach_current_session_stays_open() {
+        let mut app = App::new();
+        app.sessions = vec![make_session("here")];
+        app.inside_tmux = true;
+        app.current_session = Some("here".to_string());
+
+        app.run_effects(vec![Effect::Attach(local("here"))]).await;
+
+        assert!(!app.should_quit);
+        assert_eq!(
+            app.status_message,
+            "Already attached to `here` in this client"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_attach_refuses_to_nest() {
+        let mut app = App::new();
+        app.inside_tmux = false;
+        app.nested_client = true;
+
+        app.run_effects(vec![Effect::Attach(local("work"))]).await;
+
+        assert_eq!(app.pending_attach, None);
+        assert!(app
+            .error_message
+            .as_ref()
+            .is_some_and(|m| m.contains("would nest tmux")));
+    }
+
+    #[tokio::test]
+    async fn test_preview_of_full_screen_app_can_show_shell_underneath() {
+        let tmux = crate::testing::FakeTmux::new();
+        let mut app = App::new();
+        app.sessions = vec![make_session("alpha")];
+        let mut top = make_window(0, "top", "htop");
+        top.alternate = true;
+        app.session_windows.insert("alpha".to_string(), vec![top]);
+
+        app.refresh_preview().await.expect("preview should refresh");
+        app.config.alternate_screen = Some(AlternateScreen::Shell);
+        app.refresh_preview().await.expect("preview should refresh");
+
+        let captures: Vec<String> = tmux
+            .calls()
+            .into_iter()
+            .filter(|c| c.starts_with("capture-pane"))
+            .collect();
+        assert_eq!(
+            captures,
+            [
+                "capture-pane -peJ -t alpha:0",
+                "capture-pane -peJ -a -t alpha:0"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_new_sessions_get_template_environment() {
+        let tmux = crate::testing::FakeTmux::new();
+        let mut app = App::new();
+        app.config.environments = vec![crate::config::EnvTemplate {
+            pattern: "infra-*".to_string(),
+            vars: [("AWS_PROFILE".to_string(), "infra".to_string())].into(),
+        }];
+
+        app.run_effects(vec![Effect::CreateSession("infra-eu".to_string())])
+            .await;
+        app.capabilities = Capabilities::for_version(Some(tmux::TmuxVersion::new(3, 1)));
+        app.run_effects(vec![Effect::CreateSession("infra-us".to_string())])
+            .await;
+        app.run_effects(vec![Effect::CreateSession("web".to_string())])
+            .await;
+
+        let calls: Vec<String> = tmux
+            .calls()
+            .into_iter()
+            .filter(|c| c.starts_with("new-session") || c.starts_with("set-environment"))
+            .collect();
+        assert_eq!(
+            calls,
+            [
+                "new-session -d -s infra-eu -e AWS_PROFILE=infra",
+                "new-session -d -s infra-us",
+                "set-environment -t infra-us AWS_PROFILE infra",
+                "new-session -d -s web",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_killing_own_session_switches_away_first() {
+        let tmux = crate::testing::FakeTmux::new();
+        let mut app = App::new();
+        app.sessions = vec![
+            Session {
+                last_attached: 5,
+                ..make_session("old")
+            },
+            Session {
+                last_attached: 9,
+                ..make_session("recent")
+            },
+            make_session("here"),
+        ];
+        app.inside_tmux = true;
+        app.current_session = Some("here".to_string());
+        app.selected = 2;
+
+        app.update(Action::RequestKill);
+        assert!(app
+            .status_message
+            .starts_with("Kill `here`, switching this client to `recent` first?"));
+        app.run_effects(vec![Effect::KillSession(local("here"))])
+            .await;
+        let calls = tmux.calls();
+        let switch = calls.iter().position(|c| c == "switch-client -t recent");
+        let kill = calls.iter().position(|c| c == "kill-session -t here");
+        assert!(switch.is_some() && switch < kill, "{calls:?}");
+        assert_eq!(app.current_session.as_deref(), Some("recent"));
+
+        app.sessions = vec![make_session("recent")];
+        app.selected = 0;
+        app.update(Action::RequestKill);
+        assert!(app
+            .status_message
+            .starts_with("Kill `recent`, which tmui runs in and exits with?"));
+    }
+
+    #[tokio::test]
+    async fn test_changes_since_saved_snapshot() {
+        let _tmux = crate::testing::FakeTmux::new()
+            .with_session("work")
+            .with_session("api");
+        let dir = std::env::temp_dir().join(format!("tmui-changes-{}", std::process::id()));
+        let mut app = App::new();
+        app.update(Action::ShowChanges);
+        app.run_effects(vec![Effect::LoadChanges]).await;
+        assert_eq!(app.mode, AppMode::Normal, "snapshots are off without a dir");
+
+        app.snapshot_dir = Some(dir.clone());
+        app.run_effects(vec![Effect::LoadChanges]).await;
+        assert_eq!(
+            app.status_message,
+            "No snapshot yet: one is saved when tmui exits"
+        );
+
+        let previous = Snapshot {
+            taken: 1_700_000_100,
+            sessions: vec![
+                snapshot::SnapshotSession {
+                    id: "$0".to_string(),
+                    created: 1_700_000_000,
+                    name: "work".to_string(),
+                    windows: vec![snapshot::SnapshotWindow {
+                        id: "@0".to_string(),
+                        name: "vim".to_string(),
+                    }],
+                },
+                snapshot::SnapshotSession {
+                    id: "$7".to_string(),
+                    created: 1_700_000_000,
+                    name: "gone".to_string(),
+                    windows: Vec::new(),
+                },
+            ],
+        };
+        previous
+            .save(&snapshot::path_in(&dir, None))
+            .expect("snapshot should save");
+        let e = KeyEvent::new(KeyCode::Char('E'), KeyModifiers::SHIFT);
+        let action = app.key_to_action(e).expect("E is bound");
+        let effects = app.update(action);
+        app.run_effects(effects).await;
+        assert_eq!(app.mode, AppMode::Changes);
+        assert_eq!(app.changes_since, 1_700_000_100);
+        assert_eq!(
+            app.changes,
+            [
+                Change::SessionCreated("api".to_string()),
+                Change::SessionKilled("gone".to_string()),
+                Change::WindowRenamed {
+                    session: "work".to_string(),
+                    from: "vim".to_string(),
+                    to: "zsh".to_string(),
+                },
+            ]
+        );
+
+        app.save_snapshot().await;
+        app.run_effects(vec![Effect::LoadChanges]).await;
+        assert!(app.changes.is_empty(), "saved snapshot matches the server");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_replay_skips_effects_that_change_anything() {
+        let tmux = crate::testing::FakeTmux::new().with_session("work");
+        let mut app = App::new();
+        app.sessions = vec![make_session("work")];
+        app.dry_run = Some(Vec::new());
+
+        app.run_effects(vec![
+            Effect::KillSession(local("work")),
+            Effect::ListClients(local("work")),
+        ])
+        .await;
+
+        let calls = tmux.calls();
+        assert!(
+            !calls.iter().any(|c| c.starts_with("kill-session")),
+            "{calls:?}"
+        );
+        assert!(calls.iter().any(|c| c.starts_with("list-clients")));
+        assert_eq!(app.dry_run, Some(vec![Effect::KillSession(local("work"))]));
+    }
+
+    #[tokio::test]
+    async fn test_undo_kill_recreates_session() {
+        let tmux = crate::testing::FakeTmux::new();
+        let mut app = App::new();
+        app.sessions = vec![make_session("work")];
+        app.run_effects(vec![Effect::KillSession(local("work"))])
+            .await;
+        assert_eq!(app.status_message, "Killed session `work` (u to undo)");
+
+        let u = KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE);
+        let action = app.key_to_action(u).expect("u is bound");
+        let snapshot = SessionSnapshot {
+            name: "work".to_string(),
+            server: None,
+            path: "/tmp".to_string(),
+            windows: Vec::new(),
+        };
+        assert_eq!(app.update(action), vec![Effect::RestoreSession(snapshot)]);
+        app.update(Action::UndoKill);
+        assert_eq!(app.status_message, "Nothing to undo");
+
+        app.run_effects(vec![Effect::RestoreSession(SessionSnapshot {
+            name: "work".to_string(),
+            server: None,
+            path: "/tmp".to_string(),
+            windows: vec![
+                ("editor".to_string(), "/srv/app".to_string()),
+                ("logs".to_string(), "/var/log".to_string()),
+            ],
+        })])
+        .await;
+        let calls = tmux.calls();
+        let restored: Vec<&str> = calls
+            .iter()
+            .map(String::as_str)
+            .skip_while(|c| !c.starts_with("new-session"))
+            .filter(|c| !c.starts_with("list-"))
+            .collect();
+        assert_eq!(
+            restored,
+            [
+                "new-session -d -s work -c /srv/app",
+                "rename-window -t work: -- editor",
+                "new-window -d -P -F #{window_id} -t work: -n logs -c /var/log",
+            ]
+        );
+        assert_eq!(app.status_message, "Brought back `work` (shells only)");
+
+        app.last_killed = Some((
+            SessionSnapshot {
+                name: "old".to_string(),
+                server: None,
+                path: String::new(),
+                windows: Vec::new(),
+            },
+            Instant::now() - UNDO_KILL_WINDOW * 2,
+        ));
+        app.update(Action::UndoKill);
+        assert_eq!(app.status_message, "Too late to bring back `old`");
+    }
+
+    #[tokio::test]
+    async fn test_split_preset_opens_window_with_panes() {
+        use crate::config::{SplitDirection, SplitPreset};
+        let tmux = crate::testing::FakeTmux::new();
+        let mut app = App::new();
+        app.sessions = vec![make_session("alpha")];
+        app.config.splits = vec![SplitPreset {
+            name: "dev".to_string(),
+            command: Some("nvim".to_string()),
+            panes: vec![SplitPane {
+                split: SplitDirection::Right,
+                size: Some(30),
+                command: Some("cargo watch".to_string()),
+            }],
+        }];
+
+        app.update(Action::PromptNewWindowCommand);
+        app.update(Action::InsertText("dev".to_string()));
+        let effects = app.update(Action::Submit);
+        assert!(matches!(&effects[..], [Effect::NewSplitWindow { .. }]));
+        app.run_effects(effects).await;
+
+        let calls: Vec<String> = tmux
+            .calls()
+            .into_iter()
+            .filter(|c| c.starts_with("new-window") || c.starts_with("split-window"))
+            .collect();
+        assert_eq!(
+            calls,
+            [
+                "new-window -d -P -F #{window_id} -t alpha: -n dev nvim",
+                "split-window -t @9 -h -l 30% cargo watch",
+            ]
+        );
+        assert_eq!(app.status_message, "Opened `dev` in `alpha`");
+
+        app.update(Action::PromptSessionWizard);
+        assert_eq!(app.form.fields.len(), 4, "presets add a Splits field");
+        app.update(Action::InsertText("beta".to_string()));
+        app.form.focus = 3;
+        app.update(Action::InsertText("ops".to_string()));
+        assert!(app.update(Action::Submit).is_empty());
+        assert_eq!(app.form.error.as_deref(), Some("Splits: no preset `ops`"));
+    }
+
+    #[tokio::test]
+    async fn test_tick_reuses_fresh_preview_of_same_target() {
+        let tmux = crate::testing::FakeTmux::new();
+        let mut app = App::new();
+        app.sessions = vec![make_session("alpha"), make_session("beta")];
+        let captures = || {
+            tmux.calls()
+                .into_iter()
+                .filter(|c| c.starts_with("capture-pane"))
+                .count()
+        };
+
+        app.refresh_stale_preview()
+            .await
+            .expect("preview should refresh");
+        app.refresh_stale_preview()
+            .await
+            .expect("preview should refresh");
+        assert_eq!(captures(), 1, "unchanged target within the interval");
+
+        app.selected = 1;
+        app.refresh_stale_preview()
+            .await
+            .expect("preview should refresh");
+        assert_eq!(captures(), 2, "new target is captured right away");
+
+        app.config.preview_interval_ms = Some(0);
+        app.refresh_stale_preview()
+            .await
+            .expect("preview should refresh");
+        assert_eq!(captures(), 3, "stale capture is redone");
+    }
+
+    #[tokio::test]
+    async fn test_extra_servers_are_listed_and_routed() {
+        let tmux = crate::testing::FakeTmux::new()
+            .with_session("main")
+            .with_server_session("work", "api")
+            .with_server_session("work", "main");
+        let mut app = App::new();
+        app.config.servers = vec!["work".to_string()];
+
+        app.refresh_sessions()
+            .await
+            .expect("refresh should succeed");
+        app.refresh_windows().await;
+
+        let names: Vec<&str> = app.sessions.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["main", "api", "main"], "same names on two servers");
+        assert_eq!(app.sessions[2].server.as_deref(), Some("work"));
+        assert_ne!(app.sessions[0].id, app.sessions[2].id);
+        assert!(app.session_windows.contains_key("main"));
+        assert!(app.session_windows.contains_key("work:api"));
+        assert!(app.session_windows.contains_key("work:main"));
+
+        // Effects carry their server, whatever is selected when they run.
+        app.selected = 0;
+        let work_main = app.sessions[2].target();
+        app.run_effects(vec![Effect::KillSession(work_main.clone())])
+            .await;
+        let calls = tmux.calls();
+        assert!(
+            calls.contains(&"-L work kill-session -t main".to_string()),
+            "{calls:?}"
+        );
+        assert!(!calls.contains(&"kill-session -t main".to_string()));
+        let (snapshot, _) = app
+            .last_killed
+            .clone()
+            .expect("kill should leave a snapshot");
+        assert_eq!(snapshot.server.as_deref(), Some("work"));
+        app.run_effects(vec![Effect::RestoreSession(snapshot)])
+            .await;
+        assert!(tmux
+            .calls()
+            .iter()
+            .any(|c| c.starts_with("-L work new-session -d -s main")));
+
+        let targets = vec![app.sessions[0].target(), app.sessions[1].target()];
+        app.run_effects(vec![Effect::CleanupSessions(targets)])
+            .await;
+        let calls = tmux.calls();
+        assert!(
+            calls.contains(&"kill-session -t main".to_string()),
+            "{calls:?}"
+        );
+        assert!(calls.contains(&"-L work kill-session -t api".to_string()));
+
+        app.inside_tmux = false;
+        app.run_effects(vec![Effect::Attach(work_main)]).await;
+        assert_eq!(
+            app.pending_attach.map(|p| p.server),
+            Some(Some(tmux::Socket::Name("work".to_string())))
+        );
+    }
+
+    #[test]
+    fn test_host_picker_offers_local_and_remotes() {
+        let mut app = App::new();
+        app.update(Action::ShowHosts);
+        assert_eq!(app.mode, AppMode::Normal, "nothing to pick without remotes");
+
+        let dev = Remote {
+            host: "dev".to_string(),
+            ssh_args: Vec::new(),
+            socket: Some("work".to_string()),
+        };
+        app.config.remotes = vec![dev.clone()];
+        app.update(Action::ShowHosts);
+        assert_eq!(app.mode, AppMode::Pick(PickPurpose::Host));
+        assert_eq!(app.pick_items, ["local", "dev (work)"]);
+
+        app.update(Action::SelectNext);
+        assert_eq!(
+            app.update(Action::Submit),
+            vec![Effect::ConnectRemote(Some(dev))]
+        );
+
+        app.remote_host = Some("dev".to_string());
+        app.update(Action::ShowHosts);
+        assert_eq!(app.pick_selected, 1, "the current host is preselected");
+        app.update(Action::SelectPrevious);
+        assert_eq!(
+            app.update(Action::Submit),
+            vec![Effect::ConnectRemote(None)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_switch_last_session() {
+        let tmux = crate::testing::FakeTmux::new();
+        let mut app = App::new();
+        let mut old = make_session("old");
+        old.last_attached = 100;
+        let mut recent = make_session("recent");
+        recent.last_attached = 200;
+        app.sessions = vec![old, recent, make_session("never")];
+
+        app.inside_tmux = false;
+        assert_eq!(
+            app.update(Action::SwitchLastSession),
+            vec![Effect::Attach(local("recent"))]
+        );
+
+        app.inside_tmux = true;
+        let effects = app.update(Action::SwitchLastSession);
+        assert_eq!(effects, vec![Effect::SwitchLastClient]);
+        app.run_effects(effects).await;
+        assert!(tmux.calls().contains(&"switch-client -l".to_string()));
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_path_groups() {
+        assert_eq!(path_group("/home/me/work/api", "/home/me"), "~/work");
+        assert_eq!(path_group("/home/me", "/home/me"), "~");
+        assert_eq!(path_group("/home/meg/x", "/home/me"), "/home");
+        assert_eq!(path_group("/srv/app/current", "/home/me"), "/srv");
+        assert_eq!(path_group("/", "/home/me"), "/");
+
+        let mut app = App::new();
+        app.sessions = ["/srv/b", "/opt/a", "/srv/c"]
+            .iter()
+            .enumerate()
+            .map(|(i, path)| Session {
+                path: path.to_string(),
+                ..make_session(&format!("s{i}"))
+            })
+            .collect();
+        app.selected = 2;
+        assert_eq!(
+            app.update(Action::TogglePathGroups),
+            vec![Effect::SaveConfig]
+        );
+        assert_eq!(app.tag_filtered_sessions(), vec![1, 0, 2]);
+        assert_eq!(
+            app.selected_session_name().as_deref(),
+            Some("s2"),
+            "cursor stays on the session"
+        );
+    }
+
+    #[test]
+    fn test_cycle_sort_keeps_selection() {
+        let mut app = App::new();
+        app.sessions = vec![
+            Session {
+                created: 3,
+                last_attached: 1,
+                windows: 2,
+                ..make_session("a")
+            },
+            Session {
+                created: 1,
+                last_attached: 3,
+                windows: 1,
+                ..make_session("b")
+            },
+            Session {
+                created: 2,
+                last_attached: 2,
+                windows: 5,
+                ..make_session("c")
+            },
+        ];
+        let s = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE);
+        let mut order = || {
+            let action = app.key_to_action(s).expect("s is bound");
+            assert_eq!(app.update(action), vec![Effect::SaveConfig]);
+            let names: Vec<&str> = app
+                .tag_filtered_sessions()
+                .into_iter()
+                .map(|i| app.sessions[i].name.as_str())
+                .collect();
+            (names.join(""), app.selected_session_name())
+        };
+        assert_eq!(order(), ("abc".to_string(), Some("a".to_string())));
+        assert_eq!(order(), ("bca".to_string(), Some("a".to_string())));
+        assert_eq!(order(), ("acb".to_string(), Some("a".to_string())));
+        assert_eq!(order(), ("cab".to_string(), Some("a".to_string())));
+        assert_eq!(order(), ("abc".to_string(), Some("a".to_string())));
+        assert_eq!(app.config.sort, None, "last-use order is the default");
+        assert_eq!(app.status_message, "Sessions sorted by last use");
+    }
+
+    #[tokio::test]
+    async fn test_stats_credit_attached_clients() {
+        let _tmux = crate::testing::FakeTmux::new()
+            .with_client("work", "/dev/pts/1")
+            .with_client("work", "/dev/pts/2");
+        let mut app = App::new();
+        let key = KeyEvent::new(KeyCode::Char('U'), KeyModifiers::NONE);
+        let action = app.key_to_action(key).expect("U is bound");
+        let effects = app.update(action);
+        assert_eq!(effects, vec![Effect::LoadStats]);
+        app.run_effects(effects).await;
+
+        assert_eq!(app.mode, AppMode::Stats);
+        let work = app.config.visits["work"];
+        assert!(work.seen_until > 1_700_000_000);
+        assert_eq!(work.attached_secs as i64, work.seen_until - 1_700_000_000);
+        assert_eq!(work.count, 0);
+
+        app.update(Action::Cancel);
+        assert_eq!(app.mode, AppMode::Normal);
+    }
+
+    #[tokio::test]
+    async fn test_send_text_pastes_file_or_snippet() {
+        let tmux = crate::testing::FakeTmux::new();
+        let mut app = App::new();
+        let path = std::env::temp_dir().join(format!("tmui-send-{}.conf", std::process::id()));
+        std::fs::write(&path, "set -g mouse on").expect("write temp file");
+
+        app.run_effects(vec![
+            Effect::SendText {
+                target: "work:1".to_string(),
+                input: path.display().to_string(),
+            },
+            Effect::SendText {
+                target: "work".to_string(),
+                input: "echo hi".to_string(),
+            },
+        ])
+        .await;
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(
+            tmux.calls(),
+            [
+                "set-buffer -b tmui-send -- set -g mouse on",
+                "paste-buffer -d -p -b tmui-send -t work:1",
+                "set-buffer -b tmui-send -- echo hi",
+                "paste-buffer -d -p -b tmui-send -t work",
+            ]
+        );
+        assert_eq!(app.status_message, "Pasted snippet into `work`");
+    }
+
+    #[tokio::test]
+    async fn test_attach_puts_session_first() {
+        let _tmux = crate::testing::FakeTmux::new();
+        let mut app = App::new();
+        app.sessions = vec![make_session("a"), make_session("b"), make_session("c")];
+        app.config.mru = vec!["a".to_string()];
+        app.inside_tmux = true;
+        app.run_effects(vec![Effect::Attach(local("c:1"))]).await;
+        assert_eq!(app.config.mru, ["c", "a"]);
+
+        let names: Vec<&str> = app
+            .tag_filtered_sessions()
+            .into_iter()
+            .map(|i| app.sessions[i].name.as_str())
+            .collect();
+        assert_eq!(names, ["c", "a", "b"], "used sessions first, then by name");
+        app.selected = 1;
+        assert_eq!(app.selected_session_name().as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn test_search_ranks_frequent_sessions_first() {
+        let mut app = App::new();
+        app.sessions = vec![make_session("wa"), make_session("wb"), make_session("wc")];
+        app.input_buffer = "w".to_string();
+        app.update_search_filter();
+        let order = |app: &App| -> Vec<String> {
+            app.filtered_results
+                .iter()
+                .map(|m| app.sessions[m.session_index].name.clone())
+                .collect()
+        };
+        assert_eq!(order(&app), ["wa", "wb", "wc"]);
+
+        let now = unix_now();
+        app.config.record_visit("wc", now);
+        app.config.record_visit("wc", now);
+        app.config.record_visit("wb", now - 2 * 86400);
+        app.update_search_filter();
+        assert_eq!(order(&app), ["wc", "wb", "wa"]);
+    }
+
+    #[test]
+    fn test_toggle_ignored_sessions() {
+        let mut app = App::new();
+        app.sessions = vec![
+            make_session("_popup-1"),
+            make_session("scratch"),
+            make_session("work"),
+        ];
+        assert_eq!(app.visible_session_count(), 3);
+        app.update(Action::ToggleIgnored);
+        assert_eq!(app.status_message, "No ignore patterns in the config");
+
+        app.config.ignore = vec!["_popup*".to_string(), "scratch".to_string()];
+        assert_eq!(app.visible_session_count(), 1);
+        assert_eq!(app.selected_session_name().as_deref(), Some("work"));
+
+        app.selected = 0;
+        let h = KeyEvent::new(KeyCode::Char('H'), KeyModifiers::SHIFT);
+        assert_eq!(app.key_to_action(h), Some(Action::ToggleIgnored));
+        assert!(app.update(Action::ToggleIgnored).is_empty());
+        assert_eq!(app.status_message, "Showing 2 ignored sessions");
+        assert_eq!(app.visible_session_count(), 3);
+        assert_eq!(
+            app.selected_session_name().as_deref(),
+            Some("work"),
+            "cursor stays on the session"
+        );
+
+        app.update(Action::ToggleIgnored);
+        assert_eq!(app.status_message, "Hiding 2 ignored sessions");
+        app.input_buffer = "o".to_string();
+        app.update_search_filter();
+        let found: Vec<&str> = app
+            .filtered_results
+            .iter()
+            .map(|m| app.sessions[m.session_index].name.as_str())
+            .collect();
+        assert_eq!(found, ["work"], "search skips hidden sessions");
+    }
+
+    #[test]
+    fn test_number_keys_select_or_attach() {
+        let mut app = App::new();
+        app.sessions = vec![make_session("a"), make_session("b"), make_session("c")];
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        assert_eq!(app.key_to_action(key('2')), None, "off by default");
+
+        app.config.session_numbers = Some(SessionNumbers::Select);
+        let action = app.key_to_action(key('2')).expect("digit is bound");
+        assert!(app.update(action).is_empty());
+        assert_eq!(app.selected_session_name().as_deref(), Some("b"));
+        app.update(Action::SelectNumber(9));
+        assert_eq!(app.status_message, "No session 9");
+        assert_eq!(app.selected, 1);
+
+        app.config.session_numbers = Some(SessionNumbers::Attach);
+        app.inside_tmux = false;
+        assert_eq!(
+            app.update(Action::SelectNumber(3)),
+            vec![Effect::Attach(local("c"))]
+        );
+    }
+
+    #[test]
+    fn test_marks_jump_back_to_session() {
+        let mut app = App::new();
+        app.sessions = vec![make_session("a"), make_session("b"), make_session("c")];
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        let press = |app: &mut App, c| {
+            let action = app.key_to_action(key(c)).expect("key is bound");
+            app.update(action)
+        };
+
+        app.selected = 1;
+        press(&mut app, 'm');
+        assert_eq!(app.status_message, "Mark session: press a key");
+        assert_eq!(press(&mut app, 'x'), vec![Effect::SaveConfig]);
+        assert_eq!(app.config.mark('x'), Some("b"));
+
+        app.selected = 2;
+        app.focus = FocusPanel::Windows;
+        press(&mut app, '\'');
+        press(&mut app, 'x');
+        assert_eq!(app.selected_session_name().as_deref(), Some("b"));
+        assert_eq!(app.focus, FocusPanel::Sessions);
+
+        press(&mut app, '\'');
+        press(&mut app, 'y');
+        assert_eq!(app.status_message, "No mark 'y");
+        app.sessions.remove(1);
+        press(&mut app, '\'');
+        press(&mut app, 'x');
+        assert_eq!(app.status_message, "'x marks `b`, not listed");
+    }
+
+    #[test]
+    fn test_drag_border_resizes_lists() {
+        let mut app = App::new();
+        app.screen = Rect::new(0, 0, 100, 30);
+        let mouse = |kind, column| MouseEvent {
+            kind,
+            column,
+            row: 5,
+            modifiers: KeyModifiers::NONE,
+        };
+        let left = MouseButton::Left;
+        assert_eq!(
+            app.mouse_action(mouse(MouseEventKind::Down(left), 50)),
+            None
+        );
+        assert_eq!(
+            app.mouse_action(mouse(MouseEventKind::Drag(left), 50)),
+            None,
+            "dragging elsewhere does nothing"
+        );
+
+        let action = app.mouse_action(mouse(MouseEventKind::Down(left), 29));
+        assert_eq!(action, Some(Action::StartResize));
+        app.update(Action::StartResize);
+        let action = app.mouse_action(mouse(MouseEventKind::Drag(left), 45));
+        assert_eq!(action, Some(Action::ResizeLists(45)));
+        app.update(Action::ResizeLists(45));
+        assert_eq!(app.config.list_width(), 45);
+        app.update(Action::ResizeLists(99));
+        assert_eq!(app.config.list_width(), 90);
+
+        let action = app.mouse_action(mouse(MouseEventKind::Up(left), 99));
+        assert_eq!(
+            app.update(action.expect("release ends the drag")),
+            vec![Effect::SaveConfig]
+        );
+        assert_eq!(app.status_message, "Lists at 90% width");
+        assert_eq!(
+            app.mouse_action(mouse(MouseEventKind::Drag(left), 40)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_held_j_speeds_up() {
+        let mut app = App::new();
+        app.sessions = (0..100).map(|i| make_session(&format!("s{i}"))).collect();
+        let j = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE);
+        let mut steps = Vec::new();
+        for _ in 0..30 {
+            let before = app.selected;
+            let action = app.key_to_action(j).expect("j is bound");
+            app.update(action);
+            steps.push(app.selected - before);
+        }
+        assert_eq!(&steps[..10], [1; 10]);
+        assert_eq!(&steps[10..25], [2; 15]);
+        assert_eq!(&steps[25..], [5; 5]);
+        assert_eq!(app.nav_step(), 5);
+
+        let k = KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE);
+        assert_eq!(app.key_to_action(k), Some(Action::SelectPrevious));
+        app.nav_repeat = Some((false, Instant::now() - KEY_REPEAT_WINDOW * 2, 40));
+        assert_eq!(app.key_to_action(k), Some(Action::SelectPrevious));
+    }
+
+    #[test]
+    fn test_search_tags_every_match_at_once() {
+        let mut app = App::new();
+        app.sessions = vec![
+            make_session("api"),
+            make_session("web"),
+            make_session("api-2"),
+        ];
+        app.update(Action::StartSearch);
+        app.update(Action::InsertText("api".to_string()));
+        let ctrl_t = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL);
+        let action = app.key_to_action(ctrl_t).expect("Ctrl-t tags matches");
+        app.update(action);
+        assert_eq!(app.status_message, "Add tag to 2 sessions");
+        assert!(!app.search_active);
+
+        app.update(Action::InsertText("backend".to_string()));
+        assert_eq!(app.update(Action::Submit), vec![Effect::SaveConfig]);
+        assert!(app.config.has_tag("api", "backend"));
+        assert!(app.config.has_tag("api-2", "backend"));
+        assert!(!app.config.has_tag("web", "backend"));
+    }
+
+    #[test]
+    fn test_visual_mode_acts_on_marked_sessions() {
+        let mut app = App::new();
+        app.sessions = vec![make_session("a"), make_session("b"), make_session("c")];
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        let press = |app: &mut App, c| {
+            let action = app.key_to_action(key(c)).expect("key is bound");
+            app.update(action)
+        };
+
+        app.selected = 2;
+        press(&mut app, 'V');
+        assert_eq!(app.mode, AppMode::Visual);
+        press(&mut app, 'k');
+        press(&mut app, 'k');
+        press(&mut app, ' ');
+        assert_eq!(app.visual_marked.len(), 2);
+        press(&mut app, 'd');
+        assert_eq!(
+            app.mode,
+            AppMode::Confirm(ConfirmAction::KillSessions(vec![local("a"), local("c")]))
+        );
+        assert!(app.visual_marked.is_empty());
+
+        app.mode = AppMode::Normal;
+        press(&mut app, 'V');
+        press(&mut app, 'j');
+        press(&mut app, ' ');
+        press(&mut app, 't');
+        for c in "wip".chars() {
+            app.update(Action::InsertText(c.to_string()));
+        }
+        assert_eq!(app.update(Action::Submit), vec![Effect::SaveConfig]);
+        assert_eq!(app.config.get_tags("a"), ["wip".to_string()]);
+        assert_eq!(app.config.get_tags("b"), ["wip".to_string()]);
+        assert_eq!(app.status_message, "Tagged 2 sessions with `wip`");
+
+        press(&mut app, 'V');
+        press(&mut app, 'D');
+        assert_eq!(app.status_message, "No marked session is attached");
+        assert_eq!(app.mode, AppMode::Normal);
+    }
+
+    #[test]
+    fn test_palette_runs_chosen_command() {
+        let mut app = App::new();
+        app.sessions = vec![make_session("work")];
+        let ctrl_p = KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL);
+        let action = app.key_to_action(ctrl_p).expect("Ctrl-p opens the palette");
+        app.update(action);
+        assert_eq!(app.mode, AppMode::Palette);
+
+        for c in "read only".chars() {
+            app.update(Action::InsertText(c.to_string()));
+        }
+        assert_eq!(app.palette_commands()[0].action, Action::AttachReadOnly);
+        app.inside_tmux = false;
+        assert_eq!(
+            app.update(Action::Submit),
+            vec![Effect::AttachReadOnly(local("work"))]
+        );
+        assert_eq!(app.mode, AppMode::Normal);
+        assert!(app.input_buffer.is_empty());
+
+        app.update(Action::OpenPalette);
+        app.update(Action::InsertText("zzzz".to_string()));
+        assert!(app.update(Action::Submit).is_empty());
+        assert_eq!(app.status_message, "No matching command");
+    }
+
+    #[tokio::test]
+    async fn test_read_only_attach_uses_popup_inside_tmux() {
+        let tmux = crate::testing::FakeTmux::new().with_session("work");
+        let mut app = App::new();
+        app.sessions = vec![make_session("work")];
+        app.inside_tmux = true;
+
+        let effects = app.update(Action::AttachReadOnly);
+        assert_eq!(effects, vec![Effect::AttachReadOnly(local("work"))]);
+        app.run_effects(effects).await;
+
+        let calls = tmux.calls();
+        let popup = calls
+            .iter()
+            .find(|c| c.starts_with("display-popup -E"))
+            .expect("a popup should open");
+        assert!(popup.contains("TMUX= '"));
+        assert!(popup.ends_with("'attach-session' '-r' '-t' 'work'"));
+        assert!(!calls.iter().any(|c| c.starts_with("switch-client")));
+        assert!(!app.should_quit, "tmui stays open behind the popup");
+
+        app.inside_tmux = false;
+        app.run_effects(vec![Effect::AttachReadOnly(local("work"))])
+            .await;
+        assert_eq!(
+            app.pending_attach,
+            Some(PendingAttach {
+                target: "work".to_string(),
+                read_only: true,
+                server: None,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_detach_no_session() {
+        let mut app = App::new();
+        app.handle_event(Event::Key(make_key(
+            KeyCode::Char('D'),
+            KeyModifiers::SHIFT,
+        )))
+        .await
+        .expect("D with no sessions should be handled");
+        assert_eq!(app.status_message, "No session selected");
+    }
+
+    #[tokio::test]
+    async fn test_tab_switches_focus_panel() {
+        let mut app = App::new();
+        app.sessions = vec![make_session("alpha"), make_session("beta")];
+        app.selected = 0;
+
+        assert_eq!(app.focus, crate::types::FocusPanel::Sessions);
+
+        app.handle_event(Event::Key(make_key(KeyCode::Tab, KeyModifiers::NONE)))
+            .await
+            .expect("Tab should switch to windows panel");
+        assert_eq!(app.focus, crate::types::FocusPanel::Windows);
+
+        app.handle_event(Event::Key(make_key(KeyCode::Tab, KeyModifiers::NONE)))
+            .await
+            .expect("Tab should switch back to sessions panel");
+        assert_eq!(app.focus, crate::types::FocusPanel::Sessions);
+    }
+
+    #[tokio::test]
+    async fn test_tab_on_empty_sessions() {
+        let mut app = App::new();
+        app.handle_event(Event::Key(make_key(KeyCode::Tab, KeyModifiers::NONE)))
+            .await
+            .expect("Tab on empty sessions should be safe");
+        assert!(app.expanded_sessions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dd_enters_confirm_mode() {
+        let mut app = App::new();
+        app.sessions = vec![make_session("alpha")];
+
+        app.handle_event(Event::Key(make_key(KeyCode::Char('d'), KeyModifiers::NONE)))
+            .await
+            .expect("first d should arm dd");
+        assert_eq!(app.mode, AppMode::Normal);
+
+        app.handle_event(Event::Key(make_key(KeyCode::Char('d'), KeyModifiers::NONE)))
+            .await
+            .expect("second d should enter confirm mode");
+
+        assert_eq!(
+            app.mode,
+            AppMode::Confirm(ConfirmAction::KillSession(local("alpha")))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_help_overlay_toggle() {
+        let mut app = App::new();
+        assert!(!app.show_help);
+
+        app.handle_event(Event::Key(make_key(KeyCode::Char('?'), KeyModifiers::NONE)))
+            .await
+            .expect("? should toggle help");
+        assert!(app.show_help);
+
+        app.handle_event(Event::Key(make_key(KeyCode::Char('?'), KeyModifiers::NONE)))
+            .await
+            .expect("? should toggle help off");
+        assert!(!app.show_help);
+    }
+
+    #[tokio::test]
+    async fn test_help_overlay_dismiss_on_any_key() {
+        let mut app = App::new();
+        app.show_help = true;
+
+        app.handle_event(Event::Key(make_key(KeyCode::Char('j'), KeyModifiers::NONE)))
+            .await
+            .expect("any key should dismiss help");
+        assert!(!app.show_help);
+        assert!(!app.should_quit, "dismissing help should not quit");
+    }
+
+    #[tokio::test]
+    async fn test_resize_event_handled() {
+        let mut app = App::new();
+        app.handle_event(Event::Resize(80, 24))
+            .await
+            .expect("resize event should be handled");
+        assert!(!app.should_quit);
+    }
+
+    #[tokio::test]
+    async fn test_bang_enters_run_command_input() {
+        let mut app = App::new();
+        app.handle_event(Event::Key(make_key(
+            KeyCode::Char('!'),
+            KeyModifiers::SHIFT,
+        )))
+        .await
+        .expect("! should open the command prompt");
+        assert_eq!(app.mode, AppMode::Input(InputPurpose::RunCommand));
+    }
+
+    #[test]
+    fn test_next_runner_name_skips_taken() {
+        let sessions = vec![make_session("run-1"), make_session("run-3")];
+        assert_eq!(next_runner_name(&sessions), "run-2");
+        assert_eq!(next_runner_name(&[]), "run-1");
+    }
+
+    #[test]
+    fn test_command_window_name() {
+        assert_eq!(command_window_name("cargo build --release"), "cargo");
+        assert_eq!(
+            command_window_name("RUST_LOG=debug ./target/debug/server"),
+            "server"
+        );
+        assert_eq!(command_window_name("A=1"), "cmd");
+    }
+
+    #[test]
+    fn test_select_session_named() {
+        let mut app = App::new();
+        app.sessions = vec![make_session("alpha"), make_session("beta")];
+
+        assert!(app.select_session_named("beta"));
+        assert_eq!(app.selected, 1);
+        assert!(!app.select_session_named("gone"));
+        assert_eq!(app.selected, 1);
+    }
+
+    #[test]
+    fn test_cleanup_review_skips_current_session() {
+        let mut app = App::new();
+        app.config.cleanup = vec![crate::config::CleanupPolicy {
+            pattern: "tmp-*".to_string(),
+            detached_for: "1h".to_string(),
+        }];
+        app.sessions = vec![make_session("tmp-a"), make_session("tmp-b")];
+        app.current_session = Some("tmp-a".to_string());
+
+        app.review_cleanup();
+
+        assert_eq!(
+            app.mode,
+            AppMode::Confirm(ConfirmAction::CleanupSessions(vec![local("tmp-b")]))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_review_lists_matching_sessions() {
+        let mut app = App::new();
+        app.config.cleanup = vec![crate::config::CleanupPolicy {
+            pattern: "tmp-*".to_string(),
+            detached_for: "1h".to_string(),
+        }];
+        app.sessions = vec![make_session("tmp-a"), make_session("work")];
+
+        app.handle_event(Event::Key(make_key(
+            KeyCode::Char('C'),
+            KeyModifiers::SHIFT,
+        )))
+        .await
+        .expect("C should open the cleanup review");
+
+        assert_eq!(
+            app.mode,
+            AppMode::Confirm(ConfirmAction::CleanupSessions(vec![local("tmp-a")]))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_review_without_matches() {
+        let mut app = App::new();
+        app.config.cleanup.clear();
+        app.sessions = vec![make_session("work")];
+
+        app.handle_event(Event::Key(make_key(
+            KeyCode::Char('C'),
+            KeyModifiers::SHIFT,
+        )))
+        .await
+        .expect("C without matches should be handled");
+
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.status_message, "No sessions match cleanup policies");
+    }
+
+    #[tokio::test]
+    async fn test_new_session_rejects_naming_violation() {
+        let mut app = App::new();
+        app.config.naming = crate::naming::NamingRule::new(Some("^[a-z]+$"), None);
+        app.mode = AppMode::Input(InputPurpose::NewSession);
+        app.input_buffer = "Bad Name".to_string();
+
+        app.handle_event(Event::Key(make_key(KeyCode::Enter, KeyModifiers::NONE)))
+            .await
+            .expect("enter should be handled");
+
+        assert_eq!(app.mode, AppMode::Input(InputPurpose::NewSession));
+        assert_eq!(app.input_buffer, "Bad Name", "input is kept for fixing");
+        assert!(app.status_message.contains("must match"));
+
+        app.sessions = vec![make_session("work")];
+        app.mode = AppMode::Input(InputPurpose::RenameSession);
+        let effects = app.update(Action::Submit);
+        assert!(effects.is_empty(), "renames follow the rule too");
+        assert_eq!(app.mode, AppMode::Input(InputPurpose::RenameSession));
+    }
+
+    #[tokio::test]
+    async fn test_new_session_name_conflict_opens_choices() {
+        let mut app = App::new();
+        app.config.naming = crate::naming::NamingRule::default();
+        app.sessions = vec![make_session("work")];
+        app.mode = AppMode::Input(InputPurpose::NewSession);
+        app.input_buffer = "work".to_string();
+
+        app.handle_event(Event::Key(make_key(KeyCode::Enter, KeyModifiers::NONE)))
+            .await
+            .expect("enter should be handled");
+        assert_eq!(app.mode, AppMode::Conflict("work".to_string()));
+
+        app.handle_event(Event::Key(make_key(KeyCode::Esc, KeyModifiers::NONE)))
+            .await
+            .expect("esc should cancel");
+        assert_eq!(app.mode, AppMode::Normal);
+    }
+
+    #[test]
+    fn test_next_free_name() {
+        let sessions = vec![make_session("work"), make_session("work-2")];
+        assert_eq!(next_free_name(&sessions, "work"), "work-3");
+        assert_eq!(next_free_name(&sessions, "dev"), "dev-2");
+    }
+
+    fn make_window(index: usize, name: &str, command: &str) -> Window {
+        Window {
+            id: format!("@{index}"),
+            session_id: "$alpha".to_string(),
+            index,
+            name: name.to_string(),
+            active: index == 0,
+            active_command: command.to_string(),
+            pane_title: String::new(),
+            remote_host: None,
+            flags: String::new(),
+            dead: false,
+            piped: false,
+            alternate: false,
+        }
+    }
+
+    #[test]
+    fn test_heat_targets_every_window_once_per_interval() {
+        let mut app = App::new();
+        assert!(app.heat_targets().is_none(), "heat column is off");
+
+        app.config.heat_column = Some(true);
+        let mut beta = make_session("beta");
+        beta.server = Some("work".to_string());
+        app.sessions = vec![make_session("alpha"), beta];
+        app.session_windows.insert(
+            "alpha".to_string(),
+            vec![
+                make_window(0, "editor", "nvim"),
+                make_window(1, "logs", "tail"),
+            ],
+        );
+        app.session_windows.insert(
+            "work:beta".to_string(),
+            vec![make_window(2, "shell", "zsh")],
+        );
+
+        let mut targets = app.heat_targets().expect("first sample is due");
+        targets.sort_by(|a, b| a.window_id.cmp(&b.window_id));
+        let windows: Vec<(&str, &str)> = targets
+            .iter()
+            .map(|t| (t.session.as_str(), t.window_id.as_str()))
+            .collect();
+        assert_eq!(
+            windows,
+            [("alpha", "@0"), ("alpha", "@1"), ("work:beta", "@2")]
+        );
+        assert_eq!(targets[0].server, None);
+        assert_eq!(
+            targets[2].server,
+            Some(tmux::Socket::Name("work".to_string()))
+        );
+
+        assert!(app.heat_targets().is_none(), "next sample waits");
+    }
+
+    #[test]
+    fn test_swap_window_follows_selection() {
+        let mut app = App::new();
+        app.sessions = vec![make_session("alpha")];
+        app.session_windows.insert(
+            "alpha".to_string(),
+            vec![
+                make_window(0, "editor", "nvim"),
+                make_window(1, "logs", "tail"),
+            ],
+        );
+        app.focus = FocusPanel::Windows;
+
+        assert!(app.update(Action::SwapWindowUp).is_empty());
+        assert_eq!(
+            app.update(Action::SwapWindowDown),
+            vec![Effect::SwapWindows {
+                source: "@0".to_string(),
+                target: "@1".to_string(),
+            }]
+        );
+        assert_eq!(app.selected_window, 1);
+        assert!(app.update(Action::SwapWindowDown).is_empty());
+        assert_eq!(
+            app.key_to_action(make_key(KeyCode::Char('K'), KeyModifiers::SHIFT)),
+            Some(Action::SwapWindowUp)
+        );
+    }
+
+    #[test]
+    fn test_servers_popup_connects_live_and_removes_dead() {
+        let mut app = App::new();
+        app.mode = AppMode::Servers;
+        app.servers = vec![
+            ServerSocket {
+                path: PathBuf::from("/tmp/tmux-1000/dead"),
+                alive: false,
+            },
+            ServerSocket {
+                path: PathBuf::from("/tmp/tmux-1000/work"),
+                alive: true,
+            },
+        ];
+
+        assert!(app.update(Action::Submit).is_empty());
+        assert_eq!(
+            app.update(Action::RemoveStraySocket),
+            vec![Effect::RemoveSocket(PathBuf::from("/tmp/tmux-1000/dead"))]
+        );
+
+        app.update(Action::SelectNext);
+        assert!(app.update(Action::RemoveStraySocket).is_empty());
+        assert_eq!(
+            app.update(Action::Submit),
+            vec![Effect::ConnectServer(PathBuf::from("/tmp/tmux-1000/work"))]
+        );
+        assert_eq!(app.mode, AppMode::Normal);
+    }
+
+    #[test]
+    fn test_rename_window_prefills_and_targets_index() {
+        let mut app = App::new();
+        app.sessions = vec![make_session("alpha")];
+        app.session_windows.insert(
+            "alpha".to_string(),
+            vec![
+                make_window(0, "editor", "nvim"),
+                make_window(1, "logs", "tail"),
+            ],
+        );
+        app.focus = FocusPanel::Windows;
+        app.selected_window = 1;
+
+        app.update(Action::PromptRename);
+        assert_eq!(app.mode, AppMode::Input(InputPurpose::RenameWindow));
+        assert_eq!(app.input_buffer, "logs");
+
+        app.update(Action::InsertText("-old".to_string()));
+        assert_eq!(
+            app.update(Action::Submit),
+            vec![Effect::RenameWindow {
+                target: "alpha:1".to_string(),
+                to: "logs-old".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_rename_active_window_from_sessions_focus() {
+        let mut app = App::new();
+        app.sessions = vec![make_session("alpha")];
+        let mut editor = make_window(0, "editor", "nvim");
+        editor.active = false;
+        let mut active = make_window(2, "zsh", "zsh");
+        active.active = true;
+        app.session_windows
+            .insert("alpha".to_string(), vec![editor, active]);
+
+        app.update(Action::PromptRenameWindow);
+        assert_eq!(app.mode, AppMode::Input(InputPurpose::RenameWindow));
+        assert_eq!(app.focus, FocusPanel::Sessions);
+        assert_eq!(app.input_buffer, "zsh");
+
+        app.input_buffer = "server".to_string();
+        assert_eq!(
+            app.update(Action::Submit),
+            vec![Effect::RenameWindow {
+                target: "alpha:2".to_string(),
+                to: "server".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_move_window_picks_other_session() {
+        let mut app = App::new();
+        app.sessions = vec![
+            make_session("alpha"),
+            make_session("beta"),
+            make_session("gamma"),
+        ];
+        app.session_windows.insert(
+            "alpha".to_string(),
+            vec![
+                make_window(0, "editor", "nvim"),
+                make_window(1, "logs", "tail"),
+            ],
+        );
+        app.focus = FocusPanel::Windows;
+        app.selected_window = 1;
+
+        app.update(Action::PromptMoveWindow);
+        assert_eq!(app.pick_items, ["beta", "gamma"]);
+        assert!(matches!(
+            app.mode,
+            AppMode::Pick(PickPurpose::MoveWindow { .. })
+        ));
+
+        app.update(Action::SelectNext);
+        assert_eq!(
+            app.update(Action::Submit),
+            vec![Effect::MoveWindow {
+                window_id: "@1".to_string(),
+                to: "gamma".to_string(),
+            }]
+        );
+        assert_eq!(app.mode, AppMode::Normal);
+    }
+
+    #[test]
+    fn test_layout_picker_targets_selected_window() {
+        let mut app = App::new();
+        app.sessions = vec![make_session("alpha")];
+        app.session_windows.insert(
+            "alpha".to_string(),
+            vec![
+                make_window(0, "editor", "nvim"),
+                make_window(3, "logs", "tail"),
+            ],
+        );
+
+        app.update(Action::PromptLayout);
+        assert_eq!(app.mode, AppMode::Normal, "needs the Windows panel");
+
+        app.focus = FocusPanel::Windows;
+        app.selected_window = 1;
+        app.update(Action::PromptLayout);
+        assert_eq!(app.pick_items.len(), LAYOUTS.len());
+        for _ in 0..LAYOUTS.len() {
+            app.update(Action::SelectNext);
+        }
+        assert_eq!(
+            app.update(Action::Submit),
+            vec![Effect::SelectLayout {
+                target: "alpha:3".to_string(),
+                layout: "tiled".to_string(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_kill_pane_picks_then_confirms() {
+        let tmux = crate::testing::FakeTmux::new()
+            .with_pane("@0", 0, "zsh")
+            .with_pane("@0", 1, "htop");
+        let mut app = App::new();
+        app.sessions = vec![make_session("alpha")];
+        app.session_windows
+            .insert("alpha".to_string(), vec![make_window(0, "shell", "zsh")]);
+        app.focus = FocusPanel::Windows;
+
+        app.handle_event(Event::Key(make_key(KeyCode::Char('x'), KeyModifiers::NONE)))
+            .await
+            .expect("key should be handled");
+        assert_eq!(app.pick_items, ["0: zsh /tmp (active)", "1: htop /tmp"]);
+        assert!(matches!(
+            app.mode,
+            AppMode::Pick(PickPurpose::KillPane { .. })
+        ));
+
+        app.handle_event(Event::Key(make_key(KeyCode::Char('j'), KeyModifiers::NONE)))
+            .await
+            .expect("key should be handled");
+        app.handle_event(Event::Key(make_key(KeyCode::Enter, KeyModifiers::NONE)))
+            .await
+            .expect("key should be handled");
+        assert_eq!(
+            app.mode,
+            AppMode::Confirm(ConfirmAction::KillPane {
+                id: "%1".to_string(),
+                label: "1: htop /tmp".to_string(),
+            })
+        );
+
+        app.handle_event(Event::Key(make_key(KeyCode::Char('y'), KeyModifiers::NONE)))
+            .await
+            .expect("key should be handled");
+        assert!(tmux.calls().contains(&"kill-pane -t %1".to_string()));
+        assert_eq!(app.status_message, "Killed pane %1");
+    }
+
+    #[tokio::test]
+    async fn test_detach_idle_skips_own_client() {
+        let tmux = crate::testing::FakeTmux::new()
+            .with_session("alpha")
+            .with_client("alpha", "/dev/pts/1")
+            .with_client("alpha", "/dev/pts/2");
+        let mut app = App::new();
+        app.config.idle_detach_hours = Some(1);
+        app.own_tty = Some("/dev/pts/2".to_string());
+
+        app.enforce_idle_detach().await;
+        app.enforce_idle_detach().await;
+        let detaches: Vec<String> = tmux
+            .calls()
+            .into_iter()
+            .filter(|c| c.starts_with("detach-client"))
+            .collect();
+        assert_eq!(detaches, ["detach-client -t /dev/pts/1"], "checked once");
+        assert_eq!(app.status_message, "Detached /dev/pts/1 idle over 1h");
+
+        app.mode = AppMode::Clients(local("alpha"));
+        let effects = app.update(Action::DetachIdle);
+        assert_eq!(
+            effects,
+            vec![Effect::DetachIdleClients(Some(local("alpha")))]
+        );
+        app.run_effects(effects).await;
+        assert_eq!(app.status_message, "No clients idle over 1h");
+        assert_eq!(app.mode, AppMode::Clients(local("alpha")));
+    }
+
+    #[tokio::test]
+    async fn test_rename_flags_taken_name_while_typing() {
+        let tmux = crate::testing::FakeTmux::new()
+            .with_session("alpha")
+            .with_session("alphabet");
+        let mut app = App::new();
+        app.sessions = vec![make_session("alphabet")];
+        app.mode = AppMode::Input(InputPurpose::RenameSession);
+
+        let effects = app.update(Action::InsertText("alpha".to_string()));
+        assert_eq!(effects, vec![Effect::CheckSessionName("alpha".to_string())]);
+        app.run_effects(effects).await;
+        assert!(tmux.calls().contains(&"has-session -t =alpha".to_string()));
+        assert_eq!(app.name_taken.as_deref(), Some("alpha"));
+
+        assert!(app.update(Action::Submit).is_empty());
+        assert_eq!(app.status_message, "Session `alpha` already exists");
+        assert_eq!(app.mode, AppMode::Input(InputPurpose::RenameSession));
+
+        let effects = app.update(Action::DeleteChar);
+        app.run_effects(effects).await;
+        assert_eq!(app.name_taken, None, "`alph` is free despite `alphabet`");
+        assert_eq!(
+            app.update(Action::Submit),
+            vec![Effect::RenameSession {
+                from: local("alphabet"),
+                to: "alph".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_group_picker_creates_member_or_leaves() {
+        let mut app = App::new();
+        let mut work = make_session("work");
+        work.group = Some("work".to_string());
+        let mut mirror = make_session("mirror");
+        mirror.group = Some("work".to_string());
+        app.sessions = vec![work, mirror, make_session("solo")];
+
+        app.update(Action::ShowGroup);
+        assert_eq!(
+            app.mode,
+            AppMode::Pick(PickPurpose::Group {
+                session: local("work")
+            })
+        );
+        assert_eq!(app.pick_items.len(), 2);
+        assert_eq!(app.status_message, "`work` shares its windows with mirror");
+
+        app.update(Action::Submit);
+        app.update(Action::InsertText("mirror".to_string()));
+        assert!(app.update(Action::Submit).is_empty());
+        assert_eq!(app.status_message, "Session `mirror` already exists");
+        app.update(Action::DeleteChar);
+        assert_eq!(
+            app.update(Action::Submit),
+            vec![Effect::CreateGroupedSession {
+                name: "mirro".to_string(),
+                target: local("work"),
+            }]
+        );
+
+        app.update(Action::ShowGroup);
+        app.update(Action::SelectNext);
+        assert_eq!(
+            app.update(Action::Submit),
+            vec![Effect::KillSession(local("work"))]
+        );
+
+        app.selected = 2;
+        app.update(Action::ShowGroup);
+        assert_eq!(app.pick_items.len(), 1, "nothing to leave");
+    }
+
+    #[tokio::test]
+    async fn test_capture_to_file_saves_scrollback() {
+        let tmux = crate::testing::FakeTmux::new().with_capture("build ok  \ndone\n");
+        let dir = std::env::temp_dir().join(format!("tmui-capture-{}", std::process::id()));
+        let mut app = App::new();
+        app.config.log_dir = Some(dir.to_string_lossy().into_owned());
+        app.preview_target = "job:0".to_string();
+
+        assert!(app.update(Action::PromptCaptureToFile).is_empty());
+        assert_eq!(
+            app.mode,
+            AppMode::Input(InputPurpose::CaptureToFile {
+                target: "job:0".to_string()
+            })
+        );
+        assert!(
+            app.input_buffer
+                .starts_with(&dir.join("job-0-").display().to_string())
+                && app.input_buffer.ends_with(".txt"),
+            "default path: {}",
+            app.input_buffer
+        );
+
+        let path = dir.join("out").join("job.txt");
+        app.input_buffer = path.display().to_string();
+        let effects = app.update(Action::Submit);
+        assert_eq!(
+            effects,
+            vec![Effect::CaptureToFile {
+                target: "job:0".to_string(),
+                path: path.clone(),
+            }]
+        );
+        app.run_effects(effects).await;
+        assert!(tmux
+            .calls()
+            .contains(&"capture-pane -pJ -S - -t job:0".to_string()));
+        assert_eq!(
+            std::fs::read_to_string(&path).ok().as_deref(),
+            Some("build ok\ndone\n")
+        );
+        assert_eq!(
+            app.status_message,
+            format!("Saved 2 lines of `job:0` to {}", path.display())
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_pane_log_toggles_pipe_of_active_window() {
+        let tmux = crate::testing::FakeTmux::new();
+        let log_dir = std::env::temp_dir().join(format!("tmui-logs-{}", std::process::id()));
+        let mut app = App::new();
+        app.config.log_dir = Some(log_dir.to_string_lossy().into_owned());
+        app.sessions = vec![make_session("alpha")];
+        let mut logged = make_window(1, "logs", "tail");
+        logged.piped = true;
+        app.session_windows.insert(
+            "alpha".to_string(),
+            vec![make_window(0, "shell", "zsh"), logged],
+        );
+
+        let effects = app.update(Action::TogglePaneLog);
+        let [Effect::StartPaneLog { target, path }] = effects.as_slice() else {
+            panic!("expected a log start, got {effects:?}");
+        };
+        assert_eq!(target, "alpha:0");
+        assert_eq!(path.parent(), Some(log_dir.as_path()));
+        let file = path.file_name().unwrap_or_default().to_string_lossy();
+        assert!(
+            file.starts_with("alpha-0-") && file.ends_with(".log"),
+            "timestamped file: {file}"
+        );
+        let path = path.clone();
+        app.run_effects(effects).await;
+        assert!(log_dir.is_dir());
+        assert!(tmux.calls().contains(&format!(
+            "pipe-pane -o -t alpha:0 cat >> '{}'",
+            path.display()
+        )));
+
+        app.focus = FocusPanel::Windows;
+        app.selected_window = 1;
+        assert_eq!(
+            app.update(Action::TogglePaneLog),
+            vec![Effect::StopPaneLog("alpha:1".to_string())]
+        );
+        let _ = std::fs::remove_dir_all(log_dir);
+    }
+
+    #[tokio::test]
+    async fn test_yank_copies_pane_and_buffer_over_osc52() {
+        let _tmux = crate::testing::FakeTmux::new()
+            .with_capture("hi\n\n")
+            .with_buffer("buffer0", "ls");
+        let mut app = App::new();
+        app.sessions = vec![make_session("alpha")];
+        app.preview_target = "alpha:0".to_string();
+
+        app.handle_event(Event::Key(make_key(KeyCode::Char('y'), KeyModifiers::NONE)))
+            .await
+            .expect("key should be handled");
+        let sequence = app.pending_osc52.take().expect("pane should be copied");
+        assert!(sequence.contains("]52;c;aGk=\x07"), "{sequence:?}");
+        assert_eq!(app.status_message, "Copied `alpha:0` (2 bytes)");
+
+        for code in [KeyCode::Char('='), KeyCode::Char('y')] {
+            app.handle_event(Event::Key(make_key(code, KeyModifiers::NONE)))
+                .await
+                .expect("key should be handled");
         }
+        let sequence = app.pending_osc52.take().expect("buffer should be copied");
+        assert!(sequence.contains("]52;c;bHM=\x07"), "{sequence:?}");
+        assert!(matches!(app.mode, AppMode::Buffers(_)));
     }
 
-    fn make_session(name: &str) -> Session {
-        Session {
-            id: format!("${name}"),
-            name: name.to_string(),
-            windows: 1,
-            attached: 0,
-            created: 0,
-            last_attached: 0,
-            group: None,
-            path: "/tmp".to_string(),
+    #[tokio::test]
+    async fn test_join_pane_picks_pane_then_window() {
+        let tmux = crate::testing::FakeTmux::new()
+            .with_pane("@0", 0, "zsh")
+            .with_pane("@0", 1, "htop");
+        let mut app = App::new();
+        app.sessions = vec![make_session("alpha"), make_session("beta")];
+        app.session_windows.insert(
+            "alpha".to_string(),
+            vec![
+                make_window(0, "shell", "zsh"),
+                make_window(1, "logs", "tail"),
+            ],
+        );
+        let mut beta = make_window(5, "build", "make");
+        beta.session_id = "$beta".to_string();
+        app.session_windows.insert("beta".to_string(), vec![beta]);
+        app.focus = FocusPanel::Windows;
+
+        for code in [KeyCode::Char('M'), KeyCode::Char('j'), KeyCode::Enter] {
+            app.handle_event(Event::Key(make_key(code, KeyModifiers::NONE)))
+                .await
+                .expect("key should be handled");
         }
-    }
+        assert_eq!(app.pick_items, ["alpha:1 logs", "beta:5 build"]);
 
-    #[test]
-    fn test_app_initial_state() {
-        let app = App::new();
-        assert!(!app.should_quit);
+        app.update(Action::SelectNext);
+        assert_eq!(
+            app.update(Action::Submit),
+            vec![Effect::JoinPane {
+                pane_id: "%1".to_string(),
+                target: "@5".to_string(),
+            }]
+        );
         assert_eq!(app.mode, AppMode::Normal);
-        assert_eq!(app.selected, 0);
-        assert!(app.sessions.is_empty());
+        assert!(app.panes.is_empty());
+
+        let effects = app.update(Action::RequestBreakPane);
+        app.run_effects(effects).await;
+        app.update(Action::SelectNext);
+        assert_eq!(
+            app.update(Action::Submit),
+            vec![Effect::BreakPane("%1".to_string())]
+        );
+        assert!(
+            tmux.calls()
+                .iter()
+                .filter(|c| c.starts_with("list-panes"))
+                .count()
+                >= 2
+        );
     }
 
     #[tokio::test]
-    async fn test_ignore_key_release_events() {
+    async fn test_respawn_restarts_the_only_dead_pane() {
+        let tmux = crate::testing::FakeTmux::new()
+            .with_pane("@0", 0, "zsh")
+            .with_dead_pane("@0", 1, "server");
         let mut app = App::new();
-        let release = make_key_with_kind(
-            KeyCode::Char('q'),
-            KeyModifiers::NONE,
-            KeyEventKind::Release,
-        );
+        app.sessions = vec![make_session("alpha")];
+        app.session_windows
+            .insert("alpha".to_string(), vec![make_window(0, "shell", "zsh")]);
+        app.focus = FocusPanel::Windows;
 
-        app.handle_event(Event::Key(release))
+        app.handle_event(Event::Key(make_key(KeyCode::Char('R'), KeyModifiers::NONE)))
             .await
-            .expect("release events should be ignored");
+            .expect("R should be handled");
 
-        assert!(!app.should_quit);
+        assert!(tmux.calls().contains(&"respawn-pane -k -t %1".to_string()));
+        assert_eq!(app.status_message, "Respawned pane %1");
+        assert_eq!(
+            app.mode,
+            AppMode::Normal,
+            "a lone dead pane needs no picker"
+        );
     }
 
     #[tokio::test]
-    async fn test_app_quit_on_q() {
+    async fn test_window_search_filters_selected_session() {
         let mut app = App::new();
-        app.handle_event(Event::Key(make_key(KeyCode::Char('q'), KeyModifiers::NONE)))
+        app.sessions = vec![make_session("alpha")];
+        app.session_windows.insert(
+            "alpha".to_string(),
+            vec![
+                make_window(0, "editor", "nvim"),
+                make_window(1, "server", "cargo"),
+                make_window(2, "logs", "tail"),
+            ],
+        );
+        app.focus = FocusPanel::Windows;
+
+        app.handle_event(Event::Key(make_key(KeyCode::Char('/'), KeyModifiers::NONE)))
             .await
-            .expect("q should be handled");
-        assert!(app.should_quit);
+            .expect("/ should start window search");
+        assert_eq!(app.mode, AppMode::Search);
+        assert!(app.window_search_active);
+        assert_eq!(app.visible_windows().len(), 3);
+
+        for c in "cargo".chars() {
+            app.handle_event(Event::Key(make_key(KeyCode::Char(c), KeyModifiers::NONE)))
+                .await
+                .expect("typing should filter");
+        }
+        let visible: Vec<&str> = app
+            .visible_windows()
+            .iter()
+            .map(|w| w.name.as_str())
+            .collect();
+        assert_eq!(visible, vec!["server"]);
+
+        app.handle_event(Event::Key(make_key(KeyCode::Esc, KeyModifiers::NONE)))
+            .await
+            .expect("esc should end window search");
+        assert!(!app.window_search_active);
+        assert_eq!(
+            app.selected_window_entry().map(|w| w.name.as_str()),
+            Some("server"),
+            "cursor should stay on the matched window"
+        );
     }
 
     #[tokio::test]
-    async fn test_app_quit_on_ctrl_c() {
+    async fn test_preview_scroll_keys() {
         let mut app = App::new();
+
+        app.handle_event(Event::Key(make_key(KeyCode::PageUp, KeyModifiers::SHIFT)))
+            .await
+            .expect("shift-page up should be handled");
+        assert_eq!(app.preview_scroll, 2 * PREVIEW_HALF_PAGE);
+
         app.handle_event(Event::Key(make_key(
-            KeyCode::Char('c'),
+            KeyCode::Char('e'),
             KeyModifiers::CONTROL,
         )))
         .await
-        .expect("ctrl-c should be handled");
-        assert!(app.should_quit);
+        .expect("ctrl-e should be handled");
+        assert_eq!(app.preview_scroll, PREVIEW_HALF_PAGE);
+
+        app.handle_event(Event::Key(make_key(KeyCode::PageDown, KeyModifiers::SHIFT)))
+            .await
+            .expect("shift-page down should be handled");
+        assert_eq!(app.preview_scroll, 0);
     }
 
     #[tokio::test]
-    async fn test_vim_navigation() {
+    async fn test_page_keys_move_by_list_height() {
         let mut app = App::new();
-        app.sessions = vec![
-            make_session("alpha"),
-            make_session("beta"),
-            make_session("gamma"),
-        ];
+        app.sessions = (0..30).map(|i| make_session(&format!("s{i:02}"))).collect();
+        app.list_rows = (10, 4);
 
-        app.handle_event(Event::Key(make_key(KeyCode::Char('j'), KeyModifiers::NONE)))
-            .await
-            .expect("j should move selection down");
-        assert_eq!(app.selected, 1);
-
-        app.handle_event(Event::Key(make_key(KeyCode::Char('k'), KeyModifiers::NONE)))
+        app.handle_event(Event::Key(make_key(KeyCode::PageDown, KeyModifiers::NONE)))
             .await
-            .expect("k should move selection up");
-        assert_eq!(app.selected, 0);
-
+            .expect("page down should be handled");
+        assert_eq!(app.selected, 10);
         app.handle_event(Event::Key(make_key(
-            KeyCode::Char('G'),
-            KeyModifiers::SHIFT,
+            KeyCode::Char('d'),
+            KeyModifiers::CONTROL,
         )))
         .await
-        .expect("G should jump to last");
-        assert_eq!(app.selected, 2);
+        .expect("ctrl-d should be handled");
+        assert_eq!(app.selected, 15);
+        assert_eq!(app.mode, AppMode::Normal, "ctrl-d must not start dd");
+        app.handle_event(Event::Key(make_key(
+            KeyCode::Char('u'),
+            KeyModifiers::CONTROL,
+        )))
+        .await
+        .expect("ctrl-u should be handled");
+        assert_eq!(app.selected, 10);
 
-        app.handle_event(Event::Key(make_key(KeyCode::Char('g'), KeyModifiers::NONE)))
+        app.handle_event(Event::Key(make_key(KeyCode::End, KeyModifiers::NONE)))
             .await
-            .expect("first g should arm gg");
-        app.handle_event(Event::Key(make_key(KeyCode::Char('g'), KeyModifiers::NONE)))
+            .expect("end should be handled");
+        assert_eq!(app.selected, 29);
+        app.handle_event(Event::Key(make_key(KeyCode::PageDown, KeyModifiers::NONE)))
             .await
-            .expect("second g should jump to first");
+            .expect("page down should be handled");
+        assert_eq!(app.selected, 29, "stops at the last session");
+        app.handle_event(Event::Key(make_key(KeyCode::Home, KeyModifiers::NONE)))
+            .await
+            .expect("home should be handled");
         assert_eq!(app.selected, 0);
+        assert_eq!(
+            app.preview_scroll, 0,
+            "list paging leaves the preview alone"
+        );
     }
 
     #[tokio::test]
-    async fn test_enter_no_session_selected() {
+    async fn test_kill_server_needs_capital_y() {
         let mut app = App::new();
-        app.handle_event(Event::Key(make_key(KeyCode::Enter, KeyModifiers::NONE)))
+        app.sessions = vec![make_session("alpha")];
+
+        app.handle_event(Event::Key(make_key(KeyCode::Char('K'), KeyModifiers::NONE)))
             .await
-            .expect("enter with no sessions should be handled");
-        assert_eq!(app.status_message, "No session selected");
-        assert!(!app.should_quit);
+            .expect("K should be handled");
+        assert_eq!(app.mode, AppMode::Confirm(ConfirmAction::KillServer));
+
+        assert_eq!(
+            app.key_to_action(make_key(KeyCode::Char('y'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(
+            app.key_to_action(make_key(KeyCode::Enter, KeyModifiers::NONE)),
+            Some(Action::Cancel),
+            "No is preselected"
+        );
+        assert_eq!(
+            app.key_to_action(make_key(KeyCode::Char('Y'), KeyModifiers::NONE)),
+            Some(Action::Submit)
+        );
+        assert_eq!(app.update(Action::Submit), vec![Effect::KillServer]);
+        assert_eq!(app.mode, AppMode::Normal);
     }
 
-    #[tokio::test]
-    async fn test_enter_inside_tmux_switch_fails_gracefully() {
+    #[test]
+    fn test_confirm_styles_from_config() {
         let mut app = App::new();
-        app.sessions = vec![make_session("target")];
-
-        let original = std::env::var("TMUX").ok();
-        unsafe { std::env::set_var("TMUX", "/tmp/tmux-fake,99999,0") };
-
-        app.handle_event(Event::Key(make_key(KeyCode::Enter, KeyModifiers::NONE)))
-            .await
-            .expect("enter inside tmux should be handled");
+        app.sessions = vec![make_session("alpha")];
+        app.config
+            .confirm
+            .insert("kill_session".to_string(), ConfirmStyle::Type);
 
-        let has_error = app
-            .error_message
-            .as_ref()
-            .is_some_and(|m| m.contains("Failed to switch"));
-        assert!(
-            has_error || app.should_quit,
-            "should either fail gracefully or quit after switch: error={:?}, status='{}'",
-            app.error_message,
-            app.status_message
+        app.update(Action::RequestKill);
+        assert_eq!(
+            app.status_message,
+            "Kill `alpha`? (type `alpha` to confirm)"
+        );
+        assert_eq!(
+            app.key_to_action(make_key(KeyCode::Char('y'), KeyModifiers::NONE)),
+            Some(Action::InsertText("y".to_string()))
+        );
+        app.update(Action::InsertText("alph".to_string()));
+        assert!(app.update(Action::Submit).is_empty());
+        assert!(matches!(app.mode, AppMode::Confirm(_)), "stays open");
+        app.update(Action::InsertText("a".to_string()));
+        assert_eq!(
+            app.update(Action::Submit),
+            vec![Effect::KillSession(local("alpha"))]
         );
+        assert!(app.input_buffer.is_empty());
 
-        match original {
-            Some(val) => unsafe { std::env::set_var("TMUX", val) },
-            None => unsafe { std::env::remove_var("TMUX") },
-        }
+        app.config
+            .confirm
+            .insert("kill_session".to_string(), ConfirmStyle::No);
+        app.update(Action::RequestKill);
+        assert_eq!(
+            app.key_to_action(make_key(KeyCode::Enter, KeyModifiers::NONE)),
+            Some(Action::Cancel)
+        );
+        assert_eq!(
+            app.key_to_action(make_key(KeyCode::Char('y'), KeyModifiers::NONE)),
+            Some(Action::Submit)
+        );
     }
 
-    #[tokio::test]
-    async fn test_detach_no_session() {
+    #[test]
+    fn test_update_kill_flow_yields_effect() {
         let mut app = App::new();
-        app.handle_event(Event::Key(make_key(
-            KeyCode::Char('D'),
-            KeyModifiers::SHIFT,
-        )))
-        .await
-        .expect("D with no sessions should be handled");
-        assert_eq!(app.status_message, "No session selected");
+        app.sessions = vec![make_session("alpha")];
+
+        assert!(app.update(Action::RequestKill).is_empty());
+        assert_eq!(
+            app.mode,
+            AppMode::Confirm(ConfirmAction::KillSession(local("alpha")))
+        );
+
+        let effects = app.update(Action::Submit);
+        assert_eq!(effects, vec![Effect::KillSession(local("alpha"))]);
+        assert_eq!(app.mode, AppMode::Normal);
     }
 
-    #[tokio::test]
-    async fn test_tab_switches_focus_panel() {
+    #[test]
+    fn test_update_new_session_create_or_conflict() {
         let mut app = App::new();
-        app.sessions = vec![make_session("alpha"), make_session("beta")];
-        app.selected = 0;
+        app.config.naming = NamingRule::default();
+        app.sessions = vec![make_session("alpha")];
 
-        assert_eq!(app.focus, crate::types::FocusPanel::Sessions);
+        app.update(Action::PromptNewSession);
+        app.update(Action::InsertText("beta".to_string()));
+        assert_eq!(
+            app.update(Action::Submit),
+            vec![Effect::CreateSession("beta".to_string())]
+        );
 
-        app.handle_event(Event::Key(make_key(KeyCode::Tab, KeyModifiers::NONE)))
-            .await
-            .expect("Tab should switch to windows panel");
-        assert_eq!(app.focus, crate::types::FocusPanel::Windows);
+        app.update(Action::PromptNewSession);
+        app.update(Action::InsertText("alpha".to_string()));
+        assert!(app.update(Action::Submit).is_empty());
+        assert_eq!(app.mode, AppMode::Conflict("alpha".to_string()));
+        assert_eq!(
+            app.update(Action::ConflictCreateSibling),
+            vec![Effect::CreateSession("alpha-2".to_string())]
+        );
 
-        app.handle_event(Event::Key(make_key(KeyCode::Tab, KeyModifiers::NONE)))
-            .await
-            .expect("Tab should switch back to sessions panel");
-        assert_eq!(app.focus, crate::types::FocusPanel::Sessions);
+        app.config.naming = NamingRule::new(Some("^[a-z]+$"), None);
+        app.mode = AppMode::Conflict("alpha".to_string());
+        assert!(app.update(Action::ConflictCreateSibling).is_empty());
+        assert_eq!(app.mode, AppMode::Conflict("alpha".to_string()));
+        assert!(app.status_message.contains("must match"));
     }
 
     #[tokio::test]
-    async fn test_tab_on_empty_sessions() {
+    async fn test_conflict_replace_confirms_and_keeps_undo() {
+        let tmux = crate::testing::FakeTmux::new().with_session("alpha");
         let mut app = App::new();
-        app.handle_event(Event::Key(make_key(KeyCode::Tab, KeyModifiers::NONE)))
-            .await
-            .expect("Tab on empty sessions should be safe");
-        assert!(app.expanded_sessions.is_empty());
+        app.sessions = vec![make_session("alpha")];
+        app.mode = AppMode::Conflict("alpha".to_string());
+
+        assert!(app.update(Action::ConflictReplace).is_empty());
+        assert_eq!(
+            app.mode,
+            AppMode::Confirm(ConfirmAction::ReplaceSession(local("alpha")))
+        );
+        let effects = app.update(Action::Submit);
+        assert_eq!(effects, vec![Effect::ReplaceSession(local("alpha"))]);
+
+        app.run_effects(effects).await;
+        let calls = tmux.calls();
+        let kill = calls.iter().position(|c| c == "kill-session -t alpha");
+        let create = calls.iter().position(|c| c.starts_with("new-session"));
+        assert!(kill.is_some() && kill < create, "{calls:?}");
+        assert_eq!(
+            app.last_killed.as_ref().map(|(s, _)| s.name.as_str()),
+            Some("alpha-2"),
+            "undo brings the old session back beside the new one"
+        );
     }
 
-    #[tokio::test]
-    async fn test_dd_enters_confirm_mode() {
+    #[test]
+    fn test_session_form_walks_fields_and_validates() {
         let mut app = App::new();
+        app.config.naming = NamingRule::default();
         app.sessions = vec![make_session("alpha")];
 
-        app.handle_event(Event::Key(make_key(KeyCode::Char('d'), KeyModifiers::NONE)))
-            .await
-            .expect("first d should arm dd");
-        assert_eq!(app.mode, AppMode::Normal);
+        app.update(Action::PromptSessionWizard);
+        app.update(Action::InsertText("alpha".to_string()));
+        assert!(app.update(Action::Submit).is_empty(), "Enter moves on");
+        assert_eq!(app.form.focus, 1);
+        app.update(Action::InsertText("/nonexistent/tmui".to_string()));
+        app.update(Action::FocusNextField);
+        assert!(app.update(Action::Submit).is_empty());
+        assert_eq!(app.form.focus, 1, "bad directory takes the focus");
 
-        app.handle_event(Event::Key(make_key(KeyCode::Char('d'), KeyModifiers::NONE)))
-            .await
-            .expect("second d should enter confirm mode");
+        for _ in 0.."/nonexistent/tmui".len() {
+            app.update(Action::DeleteChar);
+        }
+        app.update(Action::InsertText("/tmp".to_string()));
+        app.update(Action::FocusPreviousField);
+        assert_eq!(app.form.focus, 0);
+        app.update(Action::FocusPreviousField);
+        app.update(Action::InsertText("htop".to_string()));
+        assert!(app.update(Action::Submit).is_empty());
+        assert_eq!(
+            app.form.error.as_deref(),
+            Some("Name: `alpha` already exists")
+        );
 
+        app.update(Action::InsertText("2".to_string()));
+        app.form.focus = 2;
         assert_eq!(
-            app.mode,
-            AppMode::Confirm(ConfirmAction::KillSession("alpha".to_string()))
+            app.update(Action::Submit),
+            vec![Effect::CreateSessionWith {
+                name: "alpha2".to_string(),
+                path: Some("/tmp".to_string()),
+                command: Some("htop".to_string()),
+                splits: Vec::new(),
+            }]
         );
+        assert_eq!(app.mode, AppMode::Normal);
+    }
+
+    #[test]
+    fn test_update_without_selection_reports_instead_of_effects() {
+        let mut app = App::new();
+        assert!(app.update(Action::DetachSelected).is_empty());
+        assert!(app.update(Action::AttachSelected).is_empty());
+        assert_eq!(app.status_message, "No session selected");
     }
 
     #[tokio::test]
-    async fn test_help_overlay_toggle() {
+    async fn test_paste_into_prompts() {
         let mut app = App::new();
-        assert!(!app.show_help);
+        app.sessions = vec![make_session("alpha"), make_session("beta")];
 
-        app.handle_event(Event::Key(make_key(KeyCode::Char('?'), KeyModifiers::NONE)))
+        app.handle_event(Event::Paste("ignored".to_string()))
             .await
-            .expect("? should toggle help");
-        assert!(app.show_help);
+            .expect("paste in normal mode should be handled");
+        assert!(app.input_buffer.is_empty());
 
-        app.handle_event(Event::Key(make_key(KeyCode::Char('?'), KeyModifiers::NONE)))
+        app.mode = AppMode::Input(InputPurpose::NewSession);
+        app.handle_event(Event::Paste("my\nproject\n".to_string()))
             .await
-            .expect("? should toggle help off");
-        assert!(!app.show_help);
+            .expect("paste in input mode should be handled");
+        assert_eq!(app.input_buffer, "my project");
+
+        app.input_buffer.clear();
+        app.mode = AppMode::Search;
+        app.handle_event(Event::Paste("bet".to_string()))
+            .await
+            .expect("paste in search mode should be handled");
+        assert_eq!(app.input_buffer, "bet");
+        assert_eq!(app.filtered_results.len(), 1);
     }
 
     #[tokio::test]
-    async fn test_help_overlay_dismiss_on_any_key() {
+    async fn test_focus_events_toggle_focused() {
         let mut app = App::new();
-        app.show_help = true;
+        assert!(app.focused);
 
-        app.handle_event(Event::Key(make_key(KeyCode::Char('j'), KeyModifiers::NONE)))
+        app.handle_event(Event::FocusLost)
             .await
-            .expect("any key should dismiss help");
-        assert!(!app.show_help);
-        assert!(!app.should_quit, "dismissing help should not quit");
+            .expect("focus lost should be handled");
+        assert!(!app.focused);
+
+        app.handle_event(Event::FocusGained)
+            .await
+            .expect("focus gained should be handled");
+        assert!(app.focused);
     }
 
     #[tokio::test]
-    async fn test_resize_event_handled() {
+    async fn test_refresh_windows_drops_vanished_sessions() {
         let mut app = App::new();
-        app.handle_event(Event::Resize(80, 24))
-            .await
-            .expect("resize event should be handled");
-        assert!(!app.should_quit);
+        app.sessions = vec![make_session("alpha")];
+        app.session_windows
+            .insert("alpha".to_string(), vec![make_window(0, "editor", "nvim")]);
+        app.session_windows
+            .insert("gone".to_string(), vec![make_window(0, "shell", "zsh")]);
+
+        app.refresh_windows().await;
+
+        assert!(!app.session_windows.contains_key("gone"));
+        assert!(app.session_windows.contains_key("alpha"));
     }
 
     #[test]
@@ -929,5 +6011,31 @@ mod tests {
         app.error_time = Some(Instant::now() - Duration::from_secs(4));
         app.tick_clear_errors();
         assert!(app.error_message.is_none(), "error should clear after 3s");
+
+        app.config.error_seconds = Some(10);
+        app.set_error("slow link".to_string());
+        app.error_time = Some(Instant::now() - Duration::from_secs(4));
+        app.tick_clear_errors();
+        assert!(app.error_message.is_some(), "configured duration applies");
+    }
+
+    #[tokio::test]
+    async fn test_failed_action_error_sticks_until_esc() {
+        let _tmux = crate::testing::FakeTmux::new().failing("kill-pane", "pane gone");
+        let mut app = App::new();
+        app.config.sticky_errors = Some(true);
+
+        app.run_effects(vec![Effect::KillPane("%3".to_string())])
+            .await;
+        assert!(app.error_sticky);
+        app.error_time = Some(Instant::now() - Duration::from_secs(60));
+        app.tick_clear_errors();
+        assert!(app.error_message.is_some(), "sticky errors do not expire");
+
+        app.handle_event(Event::Key(make_key(KeyCode::Esc, KeyModifiers::NONE)))
+            .await
+            .expect("esc should be handled");
+        assert!(app.error_message.is_none());
+        assert!(!app.error_sticky);
     }
 }
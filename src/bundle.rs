@@ -0,0 +1,167 @@
+//! Metadata bundles: every tmui file from the config directory packed into a
+//! single TOML document, for moving to a new machine or sharing defaults.
+
+use std::collections::BTreeMap;
+use std::path::{Component, Path};
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+
+use crate::types::AppResult;
+
+const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bundle {
+    pub version: u32,
+    /// File contents keyed by path relative to the tmui config directory.
+    pub files: BTreeMap<String, String>,
+}
+
+impl Bundle {
+    /// Collect all text files under `dir`, skipping `.bak` leftovers.
+    pub fn collect(dir: &Path) -> AppResult<Self> {
+        let mut bundle = Bundle {
+            version: BUNDLE_VERSION,
+            files: BTreeMap::new(),
+        };
+        if dir.exists() {
+            collect_dir(dir, dir, &mut bundle.files)?;
+        }
+        Ok(bundle)
+    }
+
+    pub fn load(path: &Path) -> AppResult<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read bundle {}", path.display()))?;
+        let bundle: Bundle = toml::from_str(&content)
+            .with_context(|| format!("invalid bundle {}", path.display()))?;
+        if bundle.version > BUNDLE_VERSION {
+            bail!(
+                "bundle version {} is newer than supported version {BUNDLE_VERSION}",
+                bundle.version
+            );
+        }
+        Ok(bundle)
+    }
+
+    pub fn save(&self, path: &Path) -> AppResult<()> {
+        std::fs::write(path, toml::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write bundle {}", path.display()))
+    }
+
+    /// Write every bundled file into `dir`. Existing files with different
+    /// content are kept as `<name>.bak`. Returns the restored relative paths.
+    pub fn restore(&self, dir: &Path) -> AppResult<Vec<String>> {
+        for name in self.files.keys() {
+            if !is_safe_relative(name) {
+                bail!("refusing to restore unsafe path `{name}`");
+            }
+        }
+
+        let mut restored = Vec::new();
+        for (name, content) in &self.files {
+            let target = dir.join(name);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            if let Ok(existing) = std::fs::read_to_string(&target) {
+                if &existing == content {
+                    continue;
+                }
+                let mut bak = target.clone().into_os_string();
+                bak.push(".bak");
+                std::fs::rename(&target, bak)?;
+            }
+            std::fs::write(&target, content)?;
+            restored.push(name.clone());
+        }
+        Ok(restored)
+    }
+}
+
+fn collect_dir(root: &Path, dir: &Path, files: &mut BTreeMap<String, String>) -> AppResult<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_dir(root, &path, files)?;
+            continue;
+        }
+        if path.extension().is_some_and(|ext| ext == "bak") {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Ok(relative) = path.strip_prefix(root) {
+            files.insert(relative.to_string_lossy().into_owned(), content);
+        }
+    }
+    Ok(())
+}
+
+fn is_safe_relative(name: &str) -> bool {
+    let path = Path::new(name);
+    !name.is_empty() && path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("tmui-bundle-test").join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("temp dir should be creatable");
+        dir
+    }
+
+    #[test]
+    fn test_bundle_roundtrip() {
+        let source = temp_dir("source");
+        fs::write(source.join("config.toml"), "[tags]\n").unwrap();
+        fs::create_dir_all(source.join("workspaces")).unwrap();
+        fs::write(source.join("workspaces/api.toml"), "name = \"api\"\n").unwrap();
+        fs::write(source.join("config.toml.bak"), "stale").unwrap();
+
+        let bundle = Bundle::collect(&source).expect("collect should succeed");
+        assert_eq!(bundle.files.len(), 2, "bak files should be skipped");
+
+        let archive = source.join("bundle.toml");
+        bundle.save(&archive).expect("save should succeed");
+        let loaded = Bundle::load(&archive).expect("load should succeed");
+        assert_eq!(loaded, bundle);
+
+        let target = temp_dir("target");
+        fs::write(target.join("config.toml"), "old = true\n").unwrap();
+        let restored = loaded.restore(&target).expect("restore should succeed");
+
+        assert_eq!(restored.len(), 2);
+        assert_eq!(
+            fs::read_to_string(target.join("workspaces/api.toml")).unwrap(),
+            "name = \"api\"\n"
+        );
+        assert_eq!(
+            fs::read_to_string(target.join("config.toml.bak")).unwrap(),
+            "old = true\n",
+            "overwritten files should be backed up"
+        );
+
+        let _ = fs::remove_dir_all(source);
+        let _ = fs::remove_dir_all(target);
+    }
+
+    #[test]
+    fn test_restore_rejects_path_traversal() {
+        let target = temp_dir("traversal");
+        let mut bundle = Bundle::default();
+        bundle
+            .files
+            .insert("../escape.toml".to_string(), String::new());
+
+        assert!(bundle.restore(&target).is_err());
+        let _ = fs::remove_dir_all(target);
+    }
+}
@@ -0,0 +1,230 @@
+//! Command-line argument parsing for tmui.
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail};
+
+use crate::tmux::Socket;
+use crate::types::AppResult;
+
+pub const USAGE: &str = "\
+Usage: tmui [OPTIONS] [COMMAND]
+
+Commands:
+  (none)                   Launch the session manager
+  backup [FILE]            Write config and metadata to a bundle (default: tmui-backup.toml)
+  restore-meta FILE        Restore config and metadata from a bundle
+  report                   Print sessions, windows, tags and uptime as Markdown
+  help                     Show this message
+
+Options:
+  -L, --socket-name NAME   Manage the tmux server on socket NAME
+  -S, --socket-path PATH   Manage the tmux server on socket PATH
+      --record FILE        Write input and state changes to FILE
+      --replay FILE        Play back a recording without changing anything, then keep running
+      --headless           With --replay: play back instantly off-screen and print the final screen";
+
+const DEFAULT_BACKUP_FILE: &str = "tmui-backup.toml";
+
+/// How to run the session manager.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TuiOptions {
+    pub socket: Option<Socket>,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub headless: bool,
+}
+
+/// What the binary was asked to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Tui(TuiOptions),
+    Backup { output: PathBuf },
+    RestoreMeta { input: PathBuf },
+    Report { socket: Option<Socket> },
+    Help,
+}
+
+/// Parse arguments (excluding the program name) into a command.
+pub fn parse_args<I>(args: I) -> AppResult<Command>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut tui = TuiOptions::default();
+    let mut positional = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-L" | "--socket-name" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| anyhow!("{arg} requires a socket name"))?;
+                tui.socket = Some(Socket::Name(name));
+            }
+            "-S" | "--socket-path" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| anyhow!("{arg} requires a socket path"))?;
+                tui.socket = Some(Socket::Path(PathBuf::from(path)));
+            }
+            "--record" | "--replay" => {
+                let file = args
+                    .next()
+                    .map(PathBuf::from)
+                    .ok_or_else(|| anyhow!("{arg} requires a file"))?;
+                if arg == "--record" {
+                    tui.record = Some(file);
+                } else {
+                    tui.replay = Some(file);
+                }
+            }
+            "--headless" => tui.headless = true,
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut args = positional.into_iter();
+    let command = match args.next().as_deref() {
+        None => Command::Tui(tui.clone()),
+        Some("backup") => Command::Backup {
+            output: args
+                .next()
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_BACKUP_FILE)),
+        },
+        Some("restore-meta") => Command::RestoreMeta {
+            input: args
+                .next()
+                .map(PathBuf::from)
+                .ok_or_else(|| anyhow!("restore-meta requires a bundle file"))?,
+        },
+        Some("report") => Command::Report {
+            socket: tui.socket.take(),
+        },
+        Some("help" | "-h" | "--help") => Command::Help,
+        Some(other) => bail!("unknown command `{other}`\n\n{USAGE}"),
+    };
+
+    if let Some(extra) = args.next() {
+        bail!("unexpected argument `{extra}`\n\n{USAGE}");
+    }
+    if tui != TuiOptions::default() && !matches!(command, Command::Tui(_) | Command::Help) {
+        bail!("socket and recording options only apply to the session manager\n\n{USAGE}");
+    }
+    if tui.record.is_some() && tui.replay.is_some() {
+        bail!("--record and --replay cannot be combined");
+    }
+    if tui.headless && tui.replay.is_none() {
+        bail!("--headless needs --replay FILE");
+    }
+
+    Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_no_args_launches_tui() {
+        assert_eq!(
+            parse_args(args(&[])).unwrap(),
+            Command::Tui(TuiOptions::default())
+        );
+    }
+
+    #[test]
+    fn test_socket_flags() {
+        assert_eq!(
+            parse_args(args(&["--socket-name", "work"])).unwrap(),
+            Command::Tui(TuiOptions {
+                socket: Some(Socket::Name("work".to_string())),
+                ..TuiOptions::default()
+            })
+        );
+        assert_eq!(
+            parse_args(args(&["-S", "/tmp/tmux-1000/personal"])).unwrap(),
+            Command::Tui(TuiOptions {
+                socket: Some(Socket::Path(PathBuf::from("/tmp/tmux-1000/personal"))),
+                ..TuiOptions::default()
+            })
+        );
+        assert!(parse_args(args(&["--socket-name"])).is_err());
+        assert!(parse_args(args(&["-L", "work", "backup"])).is_err());
+    }
+
+    #[test]
+    fn test_record_and_replay_flags() {
+        assert_eq!(
+            parse_args(args(&["--replay", "bug.rec", "--headless"])).unwrap(),
+            Command::Tui(TuiOptions {
+                replay: Some(PathBuf::from("bug.rec")),
+                headless: true,
+                ..TuiOptions::default()
+            })
+        );
+        assert_eq!(
+            parse_args(args(&["--record", "demo.rec"])).unwrap(),
+            Command::Tui(TuiOptions {
+                record: Some(PathBuf::from("demo.rec")),
+                ..TuiOptions::default()
+            })
+        );
+        assert!(parse_args(args(&["--record"])).is_err());
+        assert!(parse_args(args(&["--headless"])).is_err());
+        assert!(parse_args(args(&["--record", "a", "--replay", "b"])).is_err());
+        assert!(parse_args(args(&["--record", "a", "backup"])).is_err());
+    }
+
+    #[test]
+    fn test_backup_default_and_explicit_path() {
+        assert_eq!(
+            parse_args(args(&["backup"])).unwrap(),
+            Command::Backup {
+                output: PathBuf::from("tmui-backup.toml")
+            }
+        );
+        assert_eq!(
+            parse_args(args(&["backup", "/tmp/meta.toml"])).unwrap(),
+            Command::Backup {
+                output: PathBuf::from("/tmp/meta.toml")
+            }
+        );
+    }
+
+    #[test]
+    fn test_restore_requires_file() {
+        assert!(parse_args(args(&["restore-meta"])).is_err());
+        assert_eq!(
+            parse_args(args(&["restore-meta", "meta.toml"])).unwrap(),
+            Command::RestoreMeta {
+                input: PathBuf::from("meta.toml")
+            }
+        );
+    }
+
+    #[test]
+    fn test_unknown_command_rejected() {
+        assert!(parse_args(args(&["frobnicate"])).is_err());
+        assert!(parse_args(args(&["backup", "a", "b"])).is_err());
+    }
+
+    #[test]
+    fn test_report_takes_socket() {
+        assert_eq!(
+            parse_args(args(&["report"])).unwrap(),
+            Command::Report { socket: None }
+        );
+        assert_eq!(
+            parse_args(args(&["-L", "work", "report"])).unwrap(),
+            Command::Report {
+                socket: Some(Socket::Name("work".to_string()))
+            }
+        );
+        assert!(parse_args(args(&["report", "--record", "a"])).is_err());
+    }
+}
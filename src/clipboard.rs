@@ -0,0 +1,108 @@
+//! Copying to the system clipboard. OSC 52 asks the terminal itself to set
+//! its clipboard, so it works over SSH where this host has no clipboard
+//! daemon; a local clipboard command is the fallback.
+
+use std::process::Stdio;
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::types::AppResult;
+
+/// Largest text sent over OSC 52; xterm drops sequences with more than
+/// 100000 bytes of base64.
+pub const OSC52_LIMIT: usize = 74_994;
+
+/// Local clipboard commands, tried in order.
+const LOCAL_COMMANDS: &[(&str, &[&str])] = &[
+    ("pbcopy", &[]),
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+];
+
+/// Which mechanism is tried first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardMode {
+    #[default]
+    Osc52,
+    Local,
+}
+
+/// The OSC 52 sequence setting the clipboard to `text`. Inside tmux it is
+/// wrapped for passthrough to the outer terminal.
+pub fn osc52(text: &str, in_tmux: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", base64(text.as_bytes()));
+    if in_tmux {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+/// Pipe `text` into the first local clipboard command found; returns its
+/// name.
+pub async fn copy_local(text: &str) -> AppResult<&'static str> {
+    for (program, args) in LOCAL_COMMANDS {
+        let Ok(mut child) = Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes()).await?;
+        }
+        if child.wait().await?.success() {
+            return Ok(program);
+        }
+    }
+    Err(anyhow!(
+        "no clipboard command found (pbcopy, wl-copy, xclip, xsel)"
+    ))
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_pads() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64("tmux ✓".as_bytes()), "dG11eCDinJM=");
+    }
+
+    #[test]
+    fn test_osc52_wraps_inside_tmux() {
+        assert_eq!(osc52("hi", false), "\x1b]52;c;aGk=\x07");
+        assert_eq!(osc52("hi", true), "\x1bPtmux;\x1b\x1b]52;c;aGk=\x07\x1b\\");
+    }
+}
@@ -1,11 +1,21 @@
 /// Configuration management for tmui.
 /// Handles session tags and groups with XDG TOML persistence.
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
+use crate::clipboard::ClipboardMode;
+use crate::naming::NamingRule;
+use crate::types::{ConfirmAction, ConfirmStyle, Session, SessionTarget};
+
+/// Sessions remembered in `mru`.
+pub const MRU_LIMIT: usize = 30;
+
+/// Visits older than this are forgotten when another is recorded.
+const VISIT_TTL_SECS: i64 = 90 * 86400;
+
 /// Application configuration loaded from/saved to TOML file.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Config {
@@ -13,21 +23,612 @@ pub struct Config {
     pub tags: HashMap<String, Vec<String>>,
     #[serde(default)]
     pub groups: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub cleanup: Vec<CleanupPolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cleanup_on_startup: Option<bool>,
+    #[serde(default, skip_serializing_if = "NamingRule::is_empty")]
+    pub naming: NamingRule,
+    /// Confirmation style per action (`kill_session`, `replace_session`,
+    /// `kill_server`, `kill_pane`, `cleanup`, `kill_sessions`,
+    /// `detach_sessions`): `yes`, `no` or `type`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub confirm: HashMap<String, ConfirmStyle>,
+    /// Session marks: `m<key>` stores a session name, `'<key>` jumps to it.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub marks: HashMap<String, String>,
+    /// Show listening TCP ports of processes under each session (uses `ss`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port_hints: Option<bool>,
+    /// Follow server changes through a `tmux -C` control client instead of
+    /// polling every tick; polling remains the fallback.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub control_mode: Option<bool>,
+    /// Refresh when tmux hooks report a change (sessions created, windows
+    /// renamed, ...), for when control mode is off or unavailable.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<bool>,
+    /// Show an idle/low/busy output indicator per session, sampled from
+    /// periodic window captures.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heat_column: Option<bool>,
+    /// List sessions under headers for the top-level directory of their
+    /// path (`~/work`, `~/oss`, ...); toggled with `P`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_by_path: Option<bool>,
+    /// Order of the session list, cycled with `s`; by last use when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<SessionSort>,
+    /// Sessions kept out of the list (exact names or globs such as
+    /// `_popup*`) until shown with `H`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+    /// Sessions tmui attached or switched to, most recent first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mru: Vec<String>,
+    /// How often and when tmui last attached or switched to each session,
+    /// to rank search results.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub visits: HashMap<String, Visit>,
+    /// Capture the preview without colors (no `capture-pane -e`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plain_preview: Option<bool>,
+    /// Preview of a pane running a full-screen program: the program
+    /// (`app`, default) or the shell screen underneath it (`shell`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alternate_screen: Option<AlternateScreen>,
+    /// Number the first nine listed sessions; their digit selects
+    /// (`select`) or attaches to (`attach`) the session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_numbers: Option<SessionNumbers>,
+    /// Milliseconds between captures of an unchanged preview (default 1000).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_interval_ms: Option<u64>,
+    /// Capture the mouse, so the border between the lists and the preview
+    /// can be dragged. Terminal text selection then needs Shift.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mouse: Option<bool>,
+    /// Percentage of the screen width taken by the session and window
+    /// lists (default 30); set by dragging the border.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub list_width: Option<u16>,
+    /// Layout overrides by terminal size; every matching profile applies,
+    /// later ones winning.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<LayoutProfile>,
+    /// Seconds an error stays in the status bar (default 3).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_seconds: Option<u64>,
+    /// Keep errors from failed actions until dismissed with Esc.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sticky_errors: Option<bool>,
+    /// Detach clients idle for longer than this many hours, checked every
+    /// few minutes; `i` in the clients panel applies it on demand.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_detach_hours: Option<u64>,
+    /// Directory for pane logs (default ~/.local/state/tmui/logs).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_dir: Option<String>,
+    /// Copy with OSC 52 (default) or a local clipboard command first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clipboard: Option<ClipboardMode>,
+    /// Render without colors even when the terminal reports support.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monochrome: Option<bool>,
+    /// tmux server to manage: a socket name (`-L`) or, if it contains `/`,
+    /// a socket path (`-S`). Overridden by `--socket-name`/`--socket-path`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socket: Option<String>,
+    /// More servers listed alongside the managed one, as socket names or
+    /// paths like `socket`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub servers: Vec<String>,
+    /// tmux executable to run instead of `tmux` from `PATH`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmux_path: Option<String>,
+    /// Hosts whose tmux server can be managed over ssh (`@` switches).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remotes: Vec<Remote>,
+    /// Environment variables for new sessions, by session name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub environments: Vec<EnvTemplate>,
+    /// Named pane splits applied to new windows and wizard sessions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub splits: Vec<SplitPreset>,
+    /// Read-only layer merged beneath this config; never written back.
+    #[serde(skip)]
+    layers: Option<Box<Layers>>,
+}
+
+/// Where a shared layer was found, which decides what it may set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerSource {
+    /// `/etc/tmui/config.toml`, written by whoever administers the machine.
+    System,
+    /// `.tmui/shared.toml` in a repository, written by whoever committed it.
+    Repo,
+}
+
+/// Settings a repository layer may provide. Anything that runs a program
+/// or picks a server (`tmux_path`, `remotes`, `splits`, `environments`,
+/// `socket`, `hooks`, `cleanup`, ...) only comes from the system layer or
+/// the user's own file, so cloning a repository cannot make tmui run it.
+const REPO_LAYER_KEYS: &[&str] = &[
+    "tags",
+    "groups",
+    "naming",
+    "ignore",
+    "sort",
+    "profiles",
+    "list_width",
+];
+
+/// Locations of read-only shared config layers, lowest priority first:
+/// the system-wide file, then the nearest `.tmui/shared.toml` above the
+/// working directory.
+pub fn shared_config_paths() -> Vec<(PathBuf, LayerSource)> {
+    let mut paths = vec![(PathBuf::from("/etc/tmui/config.toml"), LayerSource::System)];
+    if let Ok(cwd) = std::env::current_dir() {
+        if let Some(path) = cwd
+            .ancestors()
+            .map(|dir| dir.join(".tmui").join("shared.toml"))
+            .find(|path| path.is_file())
+        {
+            paths.push((path, LayerSource::Repo));
+        }
+    }
+    paths
+}
+
+/// The shared layer beneath a config, and the user's file as loaded, to
+/// tell on save which entries are the user's own.
+#[derive(Debug, Clone, Default)]
+struct Layers {
+    shared: toml::Table,
+    own: toml::Table,
+}
+
+fn to_table(config: &Config) -> toml::Table {
+    match toml::Value::try_from(config) {
+        Ok(toml::Value::Table(table)) => table,
+        _ => toml::Table::new(),
+    }
+}
+
+fn from_table(table: toml::Table) -> Option<Config> {
+    toml::Value::Table(table).try_into().ok()
+}
+
+/// `upper` laid over `lower`: tables merge key by key, arrays are unioned
+/// with `lower` entries first, and any other value set in `upper` wins.
+/// Working on the serialized form keeps every config field covered.
+fn merge(lower: toml::Value, upper: toml::Value) -> toml::Value {
+    use toml::Value;
+    match (lower, upper) {
+        (Value::Table(lower), Value::Table(upper)) => Value::Table(merge_tables(lower, upper)),
+        (Value::Array(mut lower), Value::Array(upper)) => {
+            for item in upper {
+                if !lower.contains(&item) {
+                    lower.push(item);
+                }
+            }
+            Value::Array(lower)
+        }
+        (_, upper) => upper,
+    }
+}
+
+fn merge_tables(mut lower: toml::Table, upper: toml::Table) -> toml::Table {
+    for (key, value) in upper {
+        let merged = match lower.remove(&key) {
+            Some(below) => merge(below, value),
+            None => value,
+        };
+        lower.insert(key, merged);
+    }
+    lower
+}
+
+/// The part of `value` the user's file needs: whatever `shared` does not
+/// provide, plus whatever the file already had (`own`), so a setting equal
+/// to the shared one survives the shared file going away.
+fn strip(
+    value: toml::Value,
+    shared: Option<&toml::Value>,
+    own: Option<&toml::Value>,
+) -> Option<toml::Value> {
+    use toml::Value;
+    let Some(shared) = shared else {
+        return Some(value);
+    };
+    match (value, shared) {
+        (Value::Table(table), Value::Table(shared)) => {
+            let own = own.and_then(Value::as_table);
+            let table = strip_table(table, shared, own);
+            (!table.is_empty()).then_some(Value::Table(table))
+        }
+        (Value::Array(items), Value::Array(shared)) => {
+            let own = own.and_then(Value::as_array);
+            let items: Vec<Value> = items
+                .into_iter()
+                .filter(|item| !shared.contains(item) || own.is_some_and(|own| own.contains(item)))
+                .collect();
+            (!items.is_empty()).then_some(Value::Array(items))
+        }
+        (value, shared) => (value != *shared || own == Some(&value)).then_some(value),
+    }
+}
+
+fn strip_table(table: toml::Table, shared: &toml::Table, own: Option<&toml::Table>) -> toml::Table {
+    table
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let kept = strip(value, shared.get(&key), own.and_then(|own| own.get(&key)))?;
+            Some((key, kept))
+        })
+        .collect()
+}
+
+/// What the preview shows for a pane on its alternate screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlternateScreen {
+    #[default]
+    App,
+    Shell,
+}
+
+/// Order of the session list.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionSort {
+    /// Sessions tmui attached or switched to last first (`mru`), then the
+    /// rest by name.
+    #[default]
+    Used,
+    /// tmux's own order, which is by name.
+    Name,
+    /// Most recently attached first.
+    Recent,
+    /// Newest first.
+    Created,
+    /// Most windows first.
+    Windows,
+}
+
+impl SessionSort {
+    /// The order after this one for `s`.
+    pub fn next(self) -> Self {
+        match self {
+            SessionSort::Used => SessionSort::Name,
+            SessionSort::Name => SessionSort::Recent,
+            SessionSort::Recent => SessionSort::Created,
+            SessionSort::Created => SessionSort::Windows,
+            SessionSort::Windows => SessionSort::Used,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SessionSort::Used => "last use",
+            SessionSort::Name => "name",
+            SessionSort::Recent => "recent",
+            SessionSort::Created => "created",
+            SessionSort::Windows => "windows",
+        }
+    }
+}
+
+/// What pressing a session's number does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionNumbers {
+    Select,
+    Attach,
+}
+
+/// A host whose tmux server is reached through `ssh host tmux ...`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Remote {
+    /// ssh destination, e.g. `dev` from `~/.ssh/config` or `me@dev.example`.
+    pub host: String,
+    /// Extra ssh arguments, e.g. `["-p", "2222"]`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ssh_args: Vec<String>,
+    /// Server on the remote host, like `socket` for the local one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socket: Option<String>,
+}
+
+/// Environment variables set in new sessions whose name matches `pattern`,
+/// e.g. `AWS_PROFILE` for `infra-*`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvTemplate {
+    /// Glob over session names (`*` and `?` wildcards).
+    pub pattern: String,
+    pub vars: BTreeMap<String, String>,
+}
+
+/// Initial panes of a window, e.g. an editor with a test watcher taking
+/// the right 30%.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SplitPreset {
+    pub name: String,
+    /// Command of the first pane; the default shell when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Panes split off in order, each from the one before.
+    #[serde(default)]
+    pub panes: Vec<SplitPane>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SplitPane {
+    pub split: SplitDirection,
+    /// Percentage of the split pane to take; tmux halves it when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+/// Where a new pane goes relative to the pane it splits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SplitDirection {
+    Right,
+    Below,
+}
+
+/// Attaches to one session, for frecency ranking and usage stats.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Visit {
+    pub count: u32,
+    /// Unix time of the latest attach.
+    pub last: i64,
+    /// Time clients spent attached, as far as tmui saw.
+    pub attached_secs: u64,
+    /// Unix time `attached_secs` was last credited up to.
+    pub seen_until: i64,
+}
+
+impl Visit {
+    /// Search score bonus: the visit count (capped at 16) weighted by how
+    /// recent the latest one is, at most 64 so a clearly better match
+    /// still wins.
+    pub fn bonus(&self, now: i64) -> u32 {
+        let weight = match now - self.last {
+            ..=3600 => 4,
+            3601..=86400 => 2,
+            86401..=604800 => 1,
+            _ => 0,
+        };
+        self.count.min(16) * weight
+    }
+}
+
+/// Layout for terminals within a size range, e.g. no preview below 100
+/// columns. Unset bounds and settings leave the default alone.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LayoutProfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_width: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_width: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_height: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_height: Option<u16>,
+    /// Like the top-level `list_width`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub list_width: Option<u16>,
+    /// Show the preview; without it the lists take the whole width.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<bool>,
+    /// Show the windows panel under the sessions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub windows: Option<bool>,
+}
+
+impl LayoutProfile {
+    fn matches(&self, width: u16, height: u16) -> bool {
+        self.min_width.is_none_or(|min| width >= min)
+            && self.max_width.is_none_or(|max| width <= max)
+            && self.min_height.is_none_or(|min| height >= min)
+            && self.max_height.is_none_or(|max| height <= max)
+    }
+}
+
+/// Panels shown on a screen of a given size, after profiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreenLayout {
+    /// Percentage of the width taken by the lists.
+    pub list_width: u16,
+    pub preview: bool,
+    pub windows: bool,
+}
+
+/// Rule for killing stale scratch sessions, e.g. `tmp-*` detached for `2d`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CleanupPolicy {
+    /// Glob over session names (`*` and `?` wildcards).
+    pub pattern: String,
+    /// Minimum time detached before a session qualifies, e.g. `30m`, `12h`, `2d`.
+    pub detached_for: String,
+}
+
+impl CleanupPolicy {
+    /// Whether `session` is covered by this policy at unix time `now`.
+    /// Attached sessions and policies with an unparseable duration never match.
+    pub fn matches(&self, session: &Session, now: i64) -> bool {
+        if session.attached > 0 || !glob_match(&self.pattern, &session.name) {
+            return false;
+        }
+        let Some(threshold) = parse_duration_secs(&self.detached_for) else {
+            return false;
+        };
+        let detached_since = session.last_attached.max(session.created);
+        now - detached_since > threshold
+    }
 }
 
 impl Config {
+    /// Returns the XDG config directory: ~/.config/tmui
+    pub fn config_dir() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("~/.config"))
+            .join("tmui")
+    }
+
     /// Returns the XDG config file path: ~/.config/tmui/config.toml
     pub fn config_path() -> PathBuf {
-        let config_dir = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("~/.config"))
-            .join("tmui");
-        config_dir.join("config.toml")
+        Self::config_dir().join("config.toml")
     }
 
     /// Load config from XDG path. Falls back to defaults on parse error.
     /// If the config file is corrupted, renames it to .bak and returns defaults.
+    /// Shared layers are merged beneath the user's settings.
     pub fn load() -> Result<Self> {
-        Self::load_from(Self::config_path())
+        let user = Self::load_from(Self::config_path())?;
+        Ok(user.with_shared_layers(&shared_config_paths()))
+    }
+
+    /// Merge the readable layers at `paths` beneath this config; repository
+    /// layers only contribute [`REPO_LAYER_KEYS`]. Missing or unparseable
+    /// layers are skipped; shared files are never modified.
+    pub fn with_shared_layers(self, paths: &[(PathBuf, LayerSource)]) -> Self {
+        let shared = paths
+            .iter()
+            .filter_map(|(path, source)| {
+                let mut table = std::fs::read_to_string(path)
+                    .ok()?
+                    .parse::<toml::Table>()
+                    .ok()?;
+                if *source == LayerSource::Repo {
+                    table.retain(|key, _| REPO_LAYER_KEYS.contains(&key));
+                }
+                // Through `Config` and back, so defaults are filled in the
+                // same way as in the user's table.
+                Some(to_table(&from_table(table)?))
+            })
+            .reduce(merge_tables);
+        let Some(shared) = shared else {
+            return self;
+        };
+
+        let own = to_table(&self);
+        match from_table(merge_tables(shared.clone(), own.clone())) {
+            Some(mut config) => {
+                config.layers = Some(Box::new(Layers { shared, own }));
+                config
+            }
+            None => self,
+        }
+    }
+
+    /// Combine `self` on top of `lower` (see [`merge`]).
+    #[cfg(test)]
+    fn merged_over(self, lower: &Config) -> Config {
+        from_table(merge_tables(to_table(lower), to_table(&self))).unwrap_or(self)
+    }
+
+    /// The user's own entries, with everything provided by the shared
+    /// layer stripped so it is not copied into the user's file on save.
+    fn user_layer(&self) -> toml::Table {
+        let current = to_table(self);
+        match self.layers.as_deref() {
+            Some(layers) => strip_table(current, &layers.shared, Some(&layers.own)),
+            None => current,
+        }
+    }
+
+    /// Where `pipe-pane` logs go: `log_dir` with `~` expanded, else the
+    /// XDG state directory.
+    pub fn log_dir(&self) -> PathBuf {
+        match (&self.log_dir, dirs::home_dir()) {
+            (Some(dir), Some(home)) if dir.starts_with("~/") => home.join(&dir[2..]),
+            (Some(dir), _) => PathBuf::from(dir),
+            (None, _) => dirs::state_dir()
+                .or_else(dirs::data_local_dir)
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("tmui")
+                .join("logs"),
+        }
+    }
+
+    /// Idle time after which a client counts as forgotten; 8 hours for
+    /// on-demand use when unset.
+    pub fn idle_detach_after(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.idle_detach_hours.unwrap_or(8) * 3600)
+    }
+
+    /// How long a captured preview is reused while its target is unchanged.
+    pub fn preview_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.preview_interval_ms.unwrap_or(1000))
+    }
+
+    /// Width of the lists column in percent, kept between 10 and 90.
+    pub fn list_width(&self) -> u16 {
+        self.list_width.unwrap_or(30).clamp(10, 90)
+    }
+
+    /// Panels for a `width` x `height` screen: the defaults with every
+    /// matching profile applied in order.
+    pub fn screen_layout(&self, width: u16, height: u16) -> ScreenLayout {
+        let mut layout = ScreenLayout {
+            list_width: self.list_width(),
+            preview: true,
+            windows: true,
+        };
+        for profile in self.profiles.iter().filter(|p| p.matches(width, height)) {
+            if let Some(percent) = profile.list_width {
+                layout.list_width = percent.clamp(10, 90);
+            }
+            layout.preview = profile.preview.unwrap_or(layout.preview);
+            layout.windows = profile.windows.unwrap_or(layout.windows);
+        }
+        layout
+    }
+
+    /// How long a non-sticky error stays visible.
+    pub fn error_duration(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.error_seconds.unwrap_or(3))
+    }
+
+    /// Variables of every template matching session `name`; later
+    /// templates override earlier ones.
+    pub fn session_environment(&self, name: &str) -> Vec<(String, String)> {
+        let mut vars = BTreeMap::new();
+        for template in &self.environments {
+            if glob_match(&template.pattern, name) {
+                vars.extend(template.vars.clone());
+            }
+        }
+        vars.into_iter().collect()
+    }
+
+    /// Split preset called `name`; the user's wins over a shared one.
+    pub fn split_preset(&self, name: &str) -> Option<&SplitPreset> {
+        self.splits.iter().rev().find(|preset| preset.name == name)
+    }
+
+    /// Point mark `key` at session `name`.
+    pub fn set_mark(&mut self, key: char, name: &str) {
+        self.marks.insert(key.to_string(), name.to_string());
+    }
+
+    /// Session marked with `key`.
+    pub fn mark(&self, key: char) -> Option<&str> {
+        self.marks.get(&key.to_string()).map(String::as_str)
+    }
+
+    /// How `action` is confirmed: the configured style, else its default.
+    pub fn confirm_style(&self, action: &ConfirmAction) -> ConfirmStyle {
+        self.confirm
+            .get(action.config_key())
+            .copied()
+            .unwrap_or_else(|| action.default_style())
     }
 
     /// Load config from a specific path (for testing).
@@ -61,7 +662,7 @@ impl Config {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
         }
-        let content = toml::to_string_pretty(self)?;
+        let content = toml::to_string_pretty(&self.user_layer())?;
         std::fs::write(path, content)?;
         Ok(())
     }
@@ -74,32 +675,106 @@ impl Config {
         }
     }
 
-    /// Remove a tag from a session.
-    #[allow(dead_code)]
-    pub fn remove_tag(&mut self, session: &str, tag: &str) {
-        if let Some(tags) = self.tags.get_mut(session) {
-            tags.retain(|t| t != tag);
-            if tags.is_empty() {
-                self.tags.remove(session);
-            }
+    /// Get tags for a session.
+    pub fn get_tags(&self, session: &str) -> &[String] {
+        self.tags.get(session).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether `session` carries `tag`.
+    pub fn has_tag(&self, session: &str, tag: &str) -> bool {
+        self.get_tags(session).iter().any(|t| t == tag)
+    }
+
+    /// Move `session` to the front of `mru`, dropping the oldest entries
+    /// past [`MRU_LIMIT`].
+    pub fn touch_mru(&mut self, session: &str) {
+        self.mru.retain(|name| name != session);
+        self.mru.insert(0, session.to_string());
+        self.mru.truncate(MRU_LIMIT);
+    }
+
+    /// Count an attach to `session` at `now`, forgetting sessions not
+    /// visited for [`VISIT_TTL_SECS`].
+    pub fn record_visit(&mut self, session: &str, now: i64) {
+        self.visits
+            .retain(|_, visit| now - visit.last.max(visit.seen_until) <= VISIT_TTL_SECS);
+        let visit = self.visits.entry(session.to_string()).or_default();
+        visit.count = visit.count.saturating_add(1);
+        visit.last = now;
+    }
+
+    /// Add the time `session` had a client attached from `since` to `now`,
+    /// skipping what was already credited.
+    pub fn credit_attached(&mut self, session: &str, since: i64, now: i64) {
+        let visit = self.visits.entry(session.to_string()).or_default();
+        let start = since.max(visit.seen_until);
+        if now > start {
+            visit.attached_secs += (now - start) as u64;
+            visit.seen_until = now;
         }
     }
 
-    /// Get tags for a session.
-    pub fn get_tags(&self, session: &str) -> Vec<String> {
-        self.tags.get(session).cloned().unwrap_or_default()
+    /// Whether `session` matches an `ignore` entry.
+    pub fn is_ignored(&self, session: &str) -> bool {
+        self.ignore.iter().any(|p| glob_match(p, session))
     }
 
-    /// Get all session names that have a given tag.
-    pub fn sessions_with_tag(&self, tag: &str) -> Vec<String> {
-        self.tags
+    /// Sessions matched by any cleanup policy at unix time `now`.
+    pub fn cleanup_candidates(&self, sessions: &[Session], now: i64) -> Vec<SessionTarget> {
+        sessions
             .iter()
-            .filter(|(_, tags)| tags.contains(&tag.to_string()))
-            .map(|(session, _)| session.clone())
+            .filter(|s| self.cleanup.iter().any(|p| p.matches(s, now)))
+            .map(Session::target)
             .collect()
     }
 }
 
+/// Shell-style glob match supporting `*` (any run) and `?` (any one char).
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Parse durations like `45s`, `30m`, `12h`, `2d` or `1w` into seconds.
+/// A bare number is taken as seconds.
+pub fn parse_duration_secs(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (digits, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(pos) => value.split_at(pos),
+        None => (value, "s"),
+    };
+    let amount: i64 = digits.parse().ok()?;
+    let scale = match unit.trim() {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    amount.checked_mul(scale)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,7 +786,7 @@ mod tests {
         dir.join("config.toml")
     }
 
-    fn cleanup(path: &PathBuf) {
+    fn cleanup(path: &std::path::Path) {
         if let Some(parent) = path.parent() {
             let _ = fs::remove_dir_all(parent);
         }
@@ -207,24 +882,6 @@ mod tests {
         assert_eq!(config.get_tags("work"), vec!["important", "dev"]);
     }
 
-    #[test]
-    fn test_remove_tag() {
-        let mut config = Config::default();
-        config.add_tag("work", "important");
-        config.add_tag("work", "dev");
-
-        config.remove_tag("work", "important");
-        assert_eq!(config.get_tags("work"), vec!["dev"]);
-
-        // Remove last tag should remove the session entry
-        config.remove_tag("work", "dev");
-        assert!(config.get_tags("work").is_empty());
-        assert!(!config.tags.contains_key("work"));
-
-        // Removing from nonexistent session should not panic
-        config.remove_tag("nonexistent", "tag");
-    }
-
     #[test]
     fn test_filter_by_tag() {
         let mut config = Config::default();
@@ -232,17 +889,15 @@ mod tests {
         config.add_tag("personal", "important");
         config.add_tag("dev", "coding");
 
-        let important = config.sessions_with_tag("important");
-        assert_eq!(important.len(), 2);
-        assert!(important.contains(&"work".to_string()));
-        assert!(important.contains(&"personal".to_string()));
+        assert!(config.has_tag("work", "important"));
+        assert!(config.has_tag("personal", "important"));
+        assert!(!config.has_tag("dev", "important"));
 
-        let coding = config.sessions_with_tag("coding");
-        assert_eq!(coding.len(), 1);
-        assert!(coding.contains(&"dev".to_string()));
+        assert!(config.has_tag("dev", "coding"));
+        assert!(!config.has_tag("work", "coding"));
 
-        let none = config.sessions_with_tag("nonexistent");
-        assert!(none.is_empty());
+        assert!(!config.has_tag("work", "nonexistent"));
+        assert!(!config.has_tag("nonexistent", "important"));
     }
 
     #[test]
@@ -258,6 +913,454 @@ mod tests {
         );
     }
 
+    fn scratch_session(name: &str, attached: usize, last_attached: i64) -> Session {
+        Session {
+            id: format!("${name}"),
+            name: name.to_string(),
+            windows: 1,
+            attached,
+            created: 0,
+            last_attached,
+            group: None,
+            path: "/tmp".to_string(),
+            runner: false,
+            server: None,
+        }
+    }
+
+    #[test]
+    fn test_touch_mru_moves_to_front_and_caps() {
+        let mut config = Config::default();
+        config.touch_mru("a");
+        config.touch_mru("b");
+        config.touch_mru("a");
+        assert_eq!(config.mru, ["a", "b"]);
+        for i in 0..MRU_LIMIT {
+            config.touch_mru(&format!("s{i}"));
+        }
+        assert_eq!(config.mru.len(), MRU_LIMIT);
+        assert_eq!(config.mru[0], format!("s{}", MRU_LIMIT - 1));
+    }
+
+    #[test]
+    fn test_visits_weight_frequency_by_recency() {
+        let mut config = Config::default();
+        config.record_visit("old", 0);
+        let now = 100 * 86400;
+        config.record_visit("work", now - 7200);
+        config.record_visit("work", now - 60);
+        assert!(!config.visits.contains_key("old"), "stale visits expire");
+        let work = config.visits["work"];
+        assert_eq!(
+            work,
+            Visit {
+                count: 2,
+                last: now - 60,
+                ..Visit::default()
+            }
+        );
+        assert_eq!(work.bonus(now), 8);
+        assert_eq!(work.bonus(now + 2 * 86400), 2);
+        assert_eq!(work.bonus(now + 30 * 86400), 0);
+    }
+
+    #[test]
+    fn test_credit_attached_counts_time_once() {
+        let mut config = Config::default();
+        config.credit_attached("work", 1000, 1600);
+        config.credit_attached("work", 1000, 1900);
+        config.credit_attached("work", 1000, 1900);
+        let work = config.visits["work"];
+        assert_eq!(work.attached_secs, 900);
+        assert_eq!(work.seen_until, 1900);
+        assert_eq!(work.count, 0, "crediting time is not an attach");
+    }
+
+    #[test]
+    fn test_ignore_matches_names_and_globs() {
+        let config = Config {
+            ignore: vec!["scratch".to_string(), "_popup*".to_string()],
+            ..Config::default()
+        };
+        assert!(config.is_ignored("scratch"));
+        assert!(config.is_ignored("_popup-42"));
+        assert!(!config.is_ignored("scratchpad"));
+        assert!(!config.is_ignored("work"));
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("tmp-*", "tmp-build"));
+        assert!(glob_match("tmp-*", "tmp-"));
+        assert!(glob_match("*-scratch", "api-scratch"));
+        assert!(glob_match("w?rk", "work"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("tmp-*", "work"));
+        assert!(!glob_match("w?rk", "wrk"));
+    }
+
+    #[test]
+    fn test_parse_duration_secs() {
+        assert_eq!(parse_duration_secs("45s"), Some(45));
+        assert_eq!(parse_duration_secs("30m"), Some(1800));
+        assert_eq!(parse_duration_secs("12h"), Some(43200));
+        assert_eq!(parse_duration_secs("2d"), Some(172800));
+        assert_eq!(parse_duration_secs("90"), Some(90));
+        assert_eq!(parse_duration_secs("2 days"), None);
+        assert_eq!(parse_duration_secs(""), None);
+    }
+
+    #[test]
+    fn test_cleanup_candidates() {
+        let mut config = Config::default();
+        config.cleanup.push(CleanupPolicy {
+            pattern: "tmp-*".to_string(),
+            detached_for: "2d".to_string(),
+        });
+
+        let now = 10 * 24 * 60 * 60;
+        let sessions = vec![
+            scratch_session("tmp-old", 0, 0),
+            scratch_session("tmp-fresh", 0, now - 60),
+            scratch_session("tmp-attached", 1, 0),
+            scratch_session("work", 0, 0),
+        ];
+
+        let names: Vec<String> = config
+            .cleanup_candidates(&sessions, now)
+            .into_iter()
+            .map(|target| target.name)
+            .collect();
+        assert_eq!(names, vec!["tmp-old"]);
+    }
+
+    #[test]
+    fn test_cleanup_policy_toml() {
+        let config: Config = toml::from_str(
+            "cleanup_on_startup = true\n[[cleanup]]\npattern = \"tmp-*\"\ndetached_for = \"2d\"\n",
+        )
+        .expect("cleanup policy should parse");
+        assert_eq!(config.cleanup_on_startup, Some(true));
+        assert_eq!(config.cleanup[0].pattern, "tmp-*");
+    }
+
+    #[test]
+    fn test_screen_layout_profiles() {
+        let config: Config = toml::from_str(
+            "list_width = 40\n\
+             [[profiles]]\nmax_width = 99\npreview = false\n\
+             [[profiles]]\nmin_width = 200\nlist_width = 20\n\
+             [[profiles]]\nmax_height = 20\nwindows = false\n",
+        )
+        .expect("profiles should parse");
+        let layout = |list_width, preview, windows| ScreenLayout {
+            list_width,
+            preview,
+            windows,
+        };
+        assert_eq!(config.screen_layout(120, 40), layout(40, true, true));
+        assert_eq!(config.screen_layout(80, 40), layout(40, false, true));
+        assert_eq!(config.screen_layout(240, 18), layout(20, true, false));
+    }
+
+    #[test]
+    fn test_split_presets() {
+        let config: Config = toml::from_str(
+            "[[splits]]\nname = \"dev\"\ncommand = \"nvim\"\n\
+             panes = [{ split = \"right\", size = 30, command = \"cargo watch\" }]\n",
+        )
+        .expect("splits should parse");
+        let preset = config.split_preset("dev").expect("dev preset");
+        assert_eq!(preset.command.as_deref(), Some("nvim"));
+        assert_eq!(
+            preset.panes,
+            vec![SplitPane {
+                split: SplitDirection::Right,
+                size: Some(30),
+                command: Some("cargo watch".to_string()),
+            }]
+        );
+        assert!(config.split_preset("ops").is_none());
+    }
+
+    #[test]
+    fn test_session_environment_templates() {
+        let config: Config = toml::from_str(
+            "[[environments]]\npattern = \"*\"\nvars = { EDITOR = \"vim\", AWS_PROFILE = \"dev\" }\n\
+             [[environments]]\npattern = \"infra-*\"\nvars = { AWS_PROFILE = \"infra\" }\n",
+        )
+        .expect("environments should parse");
+        let pairs = |list: &[(&str, &str)]| -> Vec<(String, String)> {
+            list.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        assert_eq!(
+            config.session_environment("infra-eu"),
+            pairs(&[("AWS_PROFILE", "infra"), ("EDITOR", "vim")])
+        );
+        assert_eq!(
+            config.session_environment("web"),
+            pairs(&[("AWS_PROFILE", "dev"), ("EDITOR", "vim")])
+        );
+    }
+
+    #[test]
+    fn test_remotes_toml() {
+        let config: Config = toml::from_str(
+            "[[remotes]]\nhost = \"dev\"\nssh_args = [\"-p\", \"2222\"]\n[[remotes]]\nhost = \"ci\"\nsocket = \"builds\"\n",
+        )
+        .expect("remotes should parse");
+        assert_eq!(config.remotes.len(), 2);
+        assert_eq!(config.remotes[0].ssh_args, ["-p", "2222"]);
+        assert_eq!(config.remotes[1].socket.as_deref(), Some("builds"));
+    }
+
+    #[test]
+    fn test_shared_layer_merges_beneath_user() {
+        let user_path = temp_config_path("shared-user");
+        let _guard = scopeguard(user_path.clone());
+        let shared_path = user_path.with_file_name("shared.toml");
+        fs::write(
+            &shared_path,
+            "[tags]\nwork = [\"team\"]\n[[cleanup]]\npattern = \"ci-*\"\ndetached_for = \"1d\"\n",
+        )
+        .expect("write should succeed");
+
+        let mut user = Config::default();
+        user.add_tag("work", "mine");
+        let mut config = user.with_shared_layers(&[(shared_path, LayerSource::System)]);
+
+        assert_eq!(config.get_tags("work"), vec!["team", "mine"]);
+        assert_eq!(config.cleanup.len(), 1);
+
+        config.add_tag("home", "personal");
+        config.save_to(&user_path).expect("save should succeed");
+        let saved = Config::load_from(user_path.clone()).expect("load should succeed");
+        assert_eq!(saved.get_tags("work"), vec!["mine"]);
+        assert_eq!(saved.get_tags("home"), vec!["personal"]);
+        assert!(
+            saved.cleanup.is_empty(),
+            "shared policies must not be copied into the user's file"
+        );
+    }
+
+    #[test]
+    fn test_user_setting_overrides_shared_switch() {
+        let shared: Config =
+            toml::from_str("mouse = true\nhooks = true\n").expect("shared layer should parse");
+        let user: Config = toml::from_str("mouse = false\n").expect("user layer should parse");
+
+        let config = user.merged_over(&shared);
+        assert_eq!(config.mouse, Some(false));
+        assert_eq!(config.hooks, Some(true));
+    }
+
+    /// Every setting, each different from its default.
+    const EVERY_SETTING: &str = r#"
+        cleanup_on_startup = false
+        port_hints = false
+        control_mode = false
+        hooks = false
+        heat_column = false
+        group_by_path = false
+        sort = "name"
+        ignore = ["_popup*"]
+        mru = ["work"]
+        plain_preview = false
+        alternate_screen = "shell"
+        session_numbers = "attach"
+        preview_interval_ms = 500
+        mouse = false
+        list_width = 40
+        error_seconds = 5
+        sticky_errors = false
+        idle_detach_hours = 12
+        log_dir = "~/logs"
+        clipboard = "local"
+        monochrome = false
+        socket = "work"
+        servers = ["builds"]
+        tmux_path = "/opt/tmux"
+        [tags]
+        work = ["team"]
+        [groups]
+        dev = ["work"]
+        [[cleanup]]
+        pattern = "tmp-*"
+        detached_for = "2d"
+        [naming]
+        pattern = "^[a-z]+$"
+        [confirm]
+        kill_session = "type"
+        [marks]
+        w = "work"
+        [visits.work]
+        count = 3
+        [[profiles]]
+        max_width = 99
+        [[remotes]]
+        host = "dev"
+        [[environments]]
+        pattern = "infra-*"
+        vars = { AWS_PROFILE = "infra" }
+        [[splits]]
+        name = "editor"
+    "#;
+
+    /// Names of the fields `Config` deserializes, as serde sees them.
+    fn config_fields() -> &'static [&'static str] {
+        use serde::de::{self, Visitor};
+
+        struct Fields<'a>(&'a mut &'static [&'static str]);
+
+        impl<'de> de::Deserializer<'de> for Fields<'_> {
+            type Error = de::value::Error;
+
+            fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+                Err(de::Error::custom("not a struct"))
+            }
+
+            fn deserialize_struct<V: Visitor<'de>>(
+                self,
+                _: &'static str,
+                fields: &'static [&'static str],
+                _: V,
+            ) -> Result<V::Value, Self::Error> {
+                *self.0 = fields;
+                Err(de::Error::custom("fields recorded"))
+            }
+
+            serde::forward_to_deserialize_any! {
+                bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
+                byte_buf option unit unit_struct newtype_struct seq tuple
+                tuple_struct map enum identifier ignored_any
+            }
+        }
+
+        let mut fields: &'static [&'static str] = &[];
+        let _ = Config::deserialize(Fields(&mut fields));
+        fields
+    }
+
+    fn toml_value(config: &Config) -> toml::Value {
+        toml::Value::try_from(config).expect("serialize should succeed")
+    }
+
+    #[test]
+    fn test_merge_and_user_layer_cover_every_setting() {
+        let full: Config = toml::from_str(EVERY_SETTING).expect("every setting should parse");
+        let value = toml_value(&full);
+        let mut keys: Vec<&str> = value
+            .as_table()
+            .expect("config is a table")
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let mut fields = config_fields().to_vec();
+        keys.sort_unstable();
+        fields.sort_unstable();
+        assert_eq!(keys, fields, "EVERY_SETTING must set every field");
+
+        assert_eq!(
+            toml_value(&full.clone().merged_over(&Config::default())),
+            value,
+            "merged_over drops a user setting"
+        );
+        assert_eq!(
+            toml_value(&Config::default().merged_over(&full)),
+            value,
+            "merged_over drops a shared setting"
+        );
+
+        let shared_path = temp_config_path("every-setting").with_file_name("shared.toml");
+        let _guard = scopeguard(shared_path.clone());
+        fs::write(&shared_path, EVERY_SETTING).expect("write should succeed");
+        let layers = [(shared_path, LayerSource::System)];
+
+        let config = Config::default().with_shared_layers(&layers);
+        let saved = from_table(config.user_layer()).expect("user layer should parse");
+        assert_eq!(
+            toml_value(&saved),
+            toml_value(&Config::default()),
+            "user_layer copies a shared setting into the user's file"
+        );
+
+        let config = full.with_shared_layers(&layers);
+        let saved = from_table(config.user_layer()).expect("user layer should parse");
+        assert_eq!(
+            toml_value(&saved),
+            value,
+            "user_layer drops the user's own settings equal to shared ones"
+        );
+    }
+
+    #[test]
+    fn test_repo_layer_only_sets_data() {
+        let repo_path = temp_config_path("repo-layer").with_file_name("shared.toml");
+        let _guard = scopeguard(repo_path.clone());
+        fs::write(
+            &repo_path,
+            "tmux_path = \"./evil\"\nhooks = true\nignore = [\"ci-*\"]\n\
+             [tags]\nwork = [\"team\"]\n\
+             [[remotes]]\nhost = \"dev\"\nssh_args = [\"-oProxyCommand=sh\"]\n",
+        )
+        .expect("write should succeed");
+
+        let config = Config::default().with_shared_layers(&[(repo_path, LayerSource::Repo)]);
+        assert_eq!(config.get_tags("work"), ["team"]);
+        assert_eq!(config.ignore, ["ci-*"]);
+        assert_eq!(config.tmux_path, None);
+        assert_eq!(config.hooks, None);
+        assert!(config.remotes.is_empty());
+    }
+
+    #[test]
+    fn test_naming_rule_toml() {
+        let config: Config =
+            toml::from_str("[naming]\npattern = \"^[a-z-]+$\"\ntemplate = \"team-{name}\"\n")
+                .expect("naming rule should parse");
+        assert_eq!(config.naming.apply("api"), Ok("team-api".to_string()));
+
+        let empty = toml::to_string_pretty(&Config::default()).expect("serialize should succeed");
+        assert!(!empty.contains("naming"), "unset naming rule is omitted");
+    }
+
+    #[test]
+    fn test_confirm_styles_toml() {
+        let config: Config =
+            toml::from_str("[confirm]\nkill_session = \"type\"\nkill_server = \"yes\"\n")
+                .expect("confirm styles should parse");
+        assert_eq!(
+            config.confirm_style(&ConfirmAction::KillSession(SessionTarget {
+                server: None,
+                name: "a".to_string(),
+            })),
+            ConfirmStyle::Type
+        );
+        assert_eq!(
+            config.confirm_style(&ConfirmAction::KillServer),
+            ConfirmStyle::Yes
+        );
+        assert_eq!(
+            Config::default().confirm_style(&ConfirmAction::KillServer),
+            ConfirmStyle::No,
+            "the server defaults to No"
+        );
+        assert!(toml::from_str::<Config>("[confirm]\nkill_pane = \"maybe\"\n").is_err());
+    }
+
+    #[test]
+    fn test_missing_shared_layer_is_ignored() {
+        let mut user = Config::default();
+        user.add_tag("work", "mine");
+        let config = user.with_shared_layers(&[(
+            PathBuf::from("/nonexistent/tmui/shared.toml"),
+            LayerSource::System,
+        )]);
+        assert_eq!(config.get_tags("work"), vec!["mine"]);
+    }
+
     /// Cleanup helper that removes a temp dir when dropped.
     fn scopeguard(path: PathBuf) -> impl Drop {
         struct Guard(PathBuf);
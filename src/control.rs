@@ -0,0 +1,134 @@
+//! Optional tmux control-mode client (`tmux -C`).
+//!
+//! A hidden, read-only control client is attached to the server and its
+//! notifications are turned into refresh signals, so changes made outside
+//! tmui show up immediately instead of on the next poll.
+
+use std::process::Stdio;
+
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+use crate::tmux;
+use crate::types::AppResult;
+
+/// What a control-mode notification invalidates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notification {
+    /// Sessions were created, renamed or closed.
+    Sessions,
+    /// Windows or panes of some session changed.
+    Windows,
+}
+
+/// Start a control client and forward its notifications. The receiver
+/// closes when the client exits (server gone, no sessions to attach to,
+/// control mode unsupported), which callers treat as "fall back to polling".
+pub fn spawn() -> AppResult<mpsc::UnboundedReceiver<Notification>> {
+    let (program, argv) = tmux::invocation(
+        &[
+            "-C",
+            "attach-session",
+            "-f",
+            "no-output,ignore-size,read-only",
+        ],
+        false,
+    );
+    let mut child = Command::new(program)
+        .args(argv)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow::anyhow!("control client has no stdout"))?;
+    let (sender, receiver) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        // Control mode exits when stdin closes, so keep it open for the
+        // lifetime of the reader.
+        let _stdin = child.stdin.take();
+        let mut lines = BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line.starts_with("%exit") {
+                break;
+            }
+            if let Some(notification) = parse_notification(&line) {
+                if sender.send(notification).is_err() {
+                    break;
+                }
+            }
+        }
+        let _ = child.kill().await;
+    });
+
+    Ok(receiver)
+}
+
+/// Map a control-mode line to the data it invalidates. Command replies
+/// (`%begin`/`%end` blocks) and unrelated notifications yield `None`.
+pub fn parse_notification(line: &str) -> Option<Notification> {
+    let name = line.split_whitespace().next()?;
+    match name {
+        "%sessions-changed" | "%session-renamed" | "%client-session-changed" => {
+            Some(Notification::Sessions)
+        }
+        "%window-add"
+        | "%window-close"
+        | "%window-renamed"
+        | "%unlinked-window-add"
+        | "%unlinked-window-close"
+        | "%unlinked-window-renamed"
+        | "%session-window-changed"
+        | "%window-pane-changed"
+        | "%layout-change" => Some(Notification::Windows),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_session_notifications() {
+        assert_eq!(
+            parse_notification("%sessions-changed"),
+            Some(Notification::Sessions)
+        );
+        assert_eq!(
+            parse_notification("%session-renamed $1 work"),
+            Some(Notification::Sessions)
+        );
+    }
+
+    #[test]
+    fn test_parse_window_notifications() {
+        assert_eq!(
+            parse_notification("%window-add @3"),
+            Some(Notification::Windows)
+        );
+        assert_eq!(
+            parse_notification("%unlinked-window-renamed @7 logs"),
+            Some(Notification::Windows)
+        );
+        assert_eq!(
+            parse_notification("%layout-change @1 b25d,80x24,0,0,0 b25d,80x24,0,0,0 *"),
+            Some(Notification::Windows)
+        );
+    }
+
+    #[test]
+    fn test_parse_ignores_replies_and_output() {
+        assert_eq!(parse_notification("%begin 1700000000 1 0"), None);
+        assert_eq!(parse_notification("%end 1700000000 1 0"), None);
+        assert_eq!(parse_notification("%output %1 hello"), None);
+        assert_eq!(parse_notification("work: 1 windows"), None);
+        assert_eq!(parse_notification(""), None);
+    }
+}
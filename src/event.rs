@@ -1,82 +1,539 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::anyhow;
 use crossterm::event::{self, Event};
-use ratatui::DefaultTerminal;
-use tokio::sync::mpsc;
+use ratatui::backend::Backend;
+use ratatui::{DefaultTerminal, Terminal};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
 
 use crate::app::App;
+use crate::control::{self, Notification};
+use crate::heat;
+use crate::hooks;
+use crate::tmux;
 use crate::types::AppResult;
 
 pub const TICK_RATE: Duration = Duration::from_millis(250);
 
-pub async fn run_event_loop(app: &mut App, terminal: &mut DefaultTerminal) -> AppResult<()> {
-    let mut interval = tokio::time::interval(TICK_RATE);
-    let mut events = spawn_event_channel();
+/// Safety-net poll while control mode delivers notifications; catches what
+/// tmux does not announce (attach counts, running commands).
+const CONTROL_POLL_INTERVAL: Duration = Duration::from_secs(2);
 
+/// Refresh period while the terminal is unfocused (e.g. tmui parked in a
+/// side pane); focus regained triggers an immediate refresh.
+const UNFOCUSED_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often to retry a control client that could not start or exited.
+const CONTROL_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Everything the event loop reacts to, merged from all [`EventSource`]s.
+#[derive(Debug)]
+pub enum AppEvent {
+    /// Terminal input; a read error ends the loop.
+    Terminal(std::io::Result<Event>),
+    /// Periodic refresh tick.
+    Tick,
+    /// Control-mode notification (bursts are coalesced into one).
+    Tmux(Notification),
+    /// The control client exited; fall back to polling.
+    TmuxDisconnected,
+    /// Waiting for hooks failed (server gone); fall back to polling.
+    HooksDisconnected,
+    /// A round of heat captures, as `(session, window id, capture)`.
+    Heat(Vec<(String, String, String)>),
+    /// A finite source (headless replay) is done; end the loop.
+    Shutdown,
+}
+
+/// An asynchronous producer feeding the loop's single event stream. New
+/// inputs implement this instead of adding arms to a `select!`.
+pub trait EventSource: Send + 'static {
+    /// Start producing events on `sender` until it closes or the source ends.
+    fn spawn(self: Box<Self>, sender: mpsc::UnboundedSender<AppEvent>);
+}
+
+/// Crossterm input, read on a dedicated thread since `event::read` blocks.
+pub struct TerminalSource;
+
+impl EventSource for TerminalSource {
+    fn spawn(self: Box<Self>, sender: mpsc::UnboundedSender<AppEvent>) {
+        thread::spawn(move || loop {
+            let event = event::read();
+            let should_stop = event.is_err();
+            if sender.send(AppEvent::Terminal(event)).is_err() || should_stop {
+                break;
+            }
+        });
+    }
+}
+
+/// Fixed-rate ticks.
+pub struct TickSource(pub Duration);
+
+impl EventSource for TickSource {
+    fn spawn(self: Box<Self>, sender: mpsc::UnboundedSender<AppEvent>) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.0);
+            loop {
+                interval.tick().await;
+                if sender.send(AppEvent::Tick).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+/// Notifications of a running control client. Queued notifications are
+/// merged so a burst costs one refresh; [`AppEvent::TmuxDisconnected`] is
+/// sent when the client goes away.
+pub struct ControlSource(pub mpsc::UnboundedReceiver<Notification>);
+
+impl EventSource for ControlSource {
+    fn spawn(self: Box<Self>, sender: mpsc::UnboundedSender<AppEvent>) {
+        let mut receiver = self.0;
+        tokio::spawn(async move {
+            while let Some(mut notification) = receiver.recv().await {
+                while let Ok(next) = receiver.try_recv() {
+                    if next == Notification::Sessions {
+                        notification = next;
+                    }
+                }
+                if sender.send(AppEvent::Tmux(notification)).is_err() {
+                    return;
+                }
+            }
+            let _ = sender.send(AppEvent::TmuxDisconnected);
+        });
+    }
+}
+
+/// Wakes the loop each time a hook set by [`hooks::install`] fires, as a
+/// session notification since hooks do not say what changed. Stops quietly
+/// once the sender of `stop` is dropped; [`AppEvent::HooksDisconnected`] is
+/// sent if waiting fails first.
+pub struct HookSource {
+    pub waiter: (PathBuf, Vec<String>),
+    pub stop: oneshot::Receiver<()>,
+}
+
+impl EventSource for HookSource {
+    fn spawn(self: Box<Self>, sender: mpsc::UnboundedSender<AppEvent>) {
+        let HookSource { waiter, mut stop } = *self;
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = &mut stop => return,
+                    result = hooks::wait(&waiter) => {
+                        if result.is_err() {
+                            break;
+                        }
+                    }
+                }
+                if sender.send(AppEvent::Tmux(Notification::Sessions)).is_err() {
+                    return;
+                }
+            }
+            let _ = sender.send(AppEvent::HooksDisconnected);
+        });
+    }
+}
+
+/// Leaves the TUI to attach to a session from outside tmux. Returns only
+/// if attaching failed, with the TUI restored.
+pub type AttachFn = fn(&str, bool) -> std::io::Error;
+
+/// Whether [`init_terminal`] captures the mouse (`mouse` in config).
+static MOUSE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_mouse_capture(enabled: bool) {
+    MOUSE.store(enabled, Ordering::Relaxed);
+}
+
+/// Enter the TUI: raw mode, alternate screen, plus focus reports (to slow
+/// down in the background) and bracketed paste. Terminals without support
+/// for the latter simply never send those events.
+pub fn init_terminal() -> DefaultTerminal {
+    let terminal = ratatui::init();
+    enable_events();
+    terminal
+}
+
+/// Focus, paste and, when configured, mouse reporting, which ratatui's
+/// own setup leaves off.
+fn enable_events() {
+    let _ = crossterm::execute!(
+        std::io::stdout(),
+        crossterm::event::EnableFocusChange,
+        crossterm::event::EnableBracketedPaste
+    );
+    if MOUSE.load(Ordering::Relaxed) {
+        let _ = crossterm::execute!(std::io::stdout(), crossterm::event::EnableMouseCapture);
+    }
+}
+
+/// Undo [`init_terminal`]. Safe to call from the panic hook.
+pub fn restore_terminal() {
+    if MOUSE.load(Ordering::Relaxed) {
+        let _ = crossterm::execute!(std::io::stdout(), crossterm::event::DisableMouseCapture);
+    }
+    let _ = crossterm::execute!(
+        std::io::stdout(),
+        crossterm::cursor::SetCursorStyle::DefaultUserShape,
+        crossterm::event::DisableBracketedPaste,
+        crossterm::event::DisableFocusChange
+    );
+    ratatui::restore();
+}
+
+/// Restore the terminal and exec `tmux attach-session` (with `-r` when
+/// `read_only`); if exec fails, re-enter the TUI and report why.
+fn exec_attach(target: &str, read_only: bool) -> std::io::Error {
+    restore_terminal();
+    let error = tmux::attach_session_exec(target, read_only);
+    // The loop's terminal stays in use; `init_terminal` would build another
+    // and chain a second panic hook.
+    let _ = crossterm::terminal::enable_raw_mode();
+    let _ = crossterm::execute!(std::io::stdout(), crossterm::terminal::EnterAlternateScreen);
+    enable_events();
+    error
+}
+
+/// Error line after a failed attach; a missing binary points at
+/// `tmux_path` since that is the usual fix.
+fn attach_error_message(target: &str, error: &std::io::Error) -> String {
+    if error.kind() == std::io::ErrorKind::NotFound {
+        format!(
+            "Failed to attach to `{target}`: {} not found (set tmux_path in config)",
+            tmux::binary().display()
+        )
+    } else {
+        format!("Failed to attach to `{target}`: {error}")
+    }
+}
+
+/// The live loop; `extra` adds sources such as a replayed recording.
+pub async fn run_event_loop(
+    app: &mut App,
+    terminal: &mut DefaultTerminal,
+    extra: Vec<Box<dyn EventSource>>,
+) -> AppResult<()> {
+    let mut sources: Vec<Box<dyn EventSource>> =
+        vec![Box::new(TerminalSource), Box::new(TickSource(TICK_RATE))];
+    sources.extend(extra);
+    run_with_sources(app, terminal, sources, exec_attach).await
+}
+
+/// The event loop over an arbitrary backend, set of sources and attach
+/// strategy; tests drive it with a `TestBackend`, scripted input and an
+/// attach that never execs.
+pub async fn run_with_sources<B: Backend>(
+    app: &mut App,
+    terminal: &mut Terminal<B>,
+    sources: Vec<Box<dyn EventSource>>,
+    attach: AttachFn,
+) -> AppResult<()> {
+    let mut hooks = Hooks::default();
+    let result = run_loop(app, terminal, sources, attach, &mut hooks).await;
+    // Every way out, errors included, takes the hooks off the server.
+    hooks.disconnect().await;
+    result?;
+    app.save_snapshot().await;
+    app.save_usage().await;
+    Ok(())
+}
+
+async fn run_loop<B: Backend>(
+    app: &mut App,
+    terminal: &mut Terminal<B>,
+    sources: Vec<Box<dyn EventSource>>,
+    attach: AttachFn,
+    hooks: &mut Hooks,
+) -> AppResult<()> {
+    let (sender, mut events) = mpsc::unbounded_channel();
+    for source in sources {
+        source.spawn(sender.clone());
+    }
+
+    app.detect_capabilities().await;
+    app.detect_client().await;
     let _ = app.refresh_sessions().await;
+    if let Some(current) = app.current_session.clone() {
+        app.select_session_named(&current);
+    }
     let _ = app.refresh_preview().await;
+    if app.config.cleanup_on_startup.unwrap_or(false) {
+        app.review_cleanup();
+    }
     terminal.clear()?;
-    terminal.draw(|frame| crate::ui::render(frame, app))?;
+    let mut chrome = Chrome::default();
+    draw(terminal, app, &mut chrome)?;
+
+    let mut control_connected = connect_control(app, &sender);
+    *hooks = Hooks::connect(app, &sender).await;
+    let mut last_control_attempt = Instant::now();
+    let mut last_poll = Instant::now();
+    let mut heat_round: Option<JoinHandle<()>> = None;
 
     while !app.should_quit {
-        tokio::select! {
-            _ = interval.tick() => {
+        let Some(event) = events.recv().await else {
+            break;
+        };
+        match event {
+            AppEvent::Tick => {
                 app.tick_clear_errors();
-                if let Err(e) = app.refresh_sessions().await {
-                    app.set_error(format!("Refresh failed: {e}"));
+                if !app.focused && last_poll.elapsed() < UNFOCUSED_REFRESH_INTERVAL {
+                    continue;
                 }
-                let _ = app.refresh_preview().await;
-                terminal.draw(|frame| crate::ui::render(frame, app))?;
-            }
-            maybe_event = events.recv() => {
-                match maybe_event {
-                    Some(Ok(event)) => {
-                        let is_resize = matches!(event, Event::Resize(_, _));
-                        let previous_selected = app.selected;
-                        if let Err(e) = app.handle_event(event).await {
-                            app.set_error(format!("{e}"));
-                        }
-                        if app.selected != previous_selected || is_resize {
-                            let _ = app.refresh_preview().await;
-                        }
-                        terminal.draw(|frame| crate::ui::render(frame, app))?;
+                if !control_connected
+                    && app.config.control_mode.unwrap_or(false)
+                    && last_control_attempt.elapsed() >= CONTROL_RETRY_INTERVAL
+                {
+                    control_connected = connect_control(app, &sender);
+                    last_control_attempt = Instant::now();
+                }
+                if !(control_connected || hooks.is_listening())
+                    || !app.focused
+                    || last_poll.elapsed() >= CONTROL_POLL_INTERVAL
+                {
+                    if let Err(e) = app.refresh_sessions().await {
+                        app.set_error(format!("Refresh failed: {e}"));
                     }
-                    Some(Err(error)) => {
-                        return Err(anyhow!(error));
+                    app.refresh_windows().await;
+                    last_poll = Instant::now();
+                }
+                app.refresh_ports().await;
+                if heat_round.as_ref().is_none_or(JoinHandle::is_finished) {
+                    if let Some(targets) = app.heat_targets() {
+                        heat_round = Some(spawn_heat_round(targets, &sender));
+                    }
+                }
+                app.enforce_idle_detach().await;
+                let _ = app.refresh_stale_preview().await;
+            }
+            AppEvent::Tmux(notification) => {
+                if notification == Notification::Sessions {
+                    if let Err(e) = app.refresh_sessions().await {
+                        app.set_error(format!("Refresh failed: {e}"));
                     }
-                    None => {
-                        break;
+                }
+                app.refresh_windows().await;
+                let _ = app.refresh_preview().await;
+            }
+            AppEvent::TmuxDisconnected => {
+                // Poll until a retry succeeds.
+                control_connected = false;
+                continue;
+            }
+            AppEvent::HooksDisconnected => {
+                // The hooks may still be set; they are removed on exit.
+                hooks.waiter = None;
+                continue;
+            }
+            AppEvent::Heat(samples) => app.heat.record(samples),
+            AppEvent::Shutdown => break,
+            AppEvent::Terminal(Ok(event)) => {
+                if let Some(recorder) = app.recorder.as_mut() {
+                    recorder.record_event(&event);
+                }
+                let is_resize = matches!(event, Event::Resize(_, _));
+                let regained_focus = matches!(event, Event::FocusGained) && !app.focused;
+                let previous_selected = app.selected;
+                let previous_scroll = app.preview_scroll;
+                if let Err(e) = app.handle_event(event).await {
+                    app.set_error(format!("{e}"));
+                }
+                if let Some(mut recorder) = app.recorder.take() {
+                    recorder.record_state(app);
+                    app.recorder = Some(recorder);
+                }
+                if regained_focus {
+                    if let Err(e) = app.refresh_sessions().await {
+                        app.set_error(format!("Refresh failed: {e}"));
                     }
+                    app.refresh_windows().await;
+                    last_poll = Instant::now();
+                }
+                if app.selected != previous_selected
+                    || app.preview_scroll != previous_scroll
+                    || is_resize
+                    || regained_focus
+                {
+                    let _ = app.refresh_preview().await;
                 }
             }
+            AppEvent::Terminal(Err(error)) => {
+                return Err(anyhow!(error));
+            }
         }
+        if std::mem::take(&mut app.server_changed) {
+            // Poll the new server until a control client for it connects;
+            // the old one only adds harmless refreshes until it exits. Hooks
+            // come off the old server before going onto the new one.
+            control_connected = false;
+            if let Some(round) = heat_round.take() {
+                round.abort();
+            }
+            hooks.disconnect().await;
+            *hooks = Hooks::connect(app, &sender).await;
+            last_poll = Instant::now();
+        }
+        if let Some(sequence) = app.pending_osc52.take() {
+            if app.terminal_chrome {
+                let mut out = std::io::stdout();
+                out.write_all(sequence.as_bytes())?;
+                out.flush()?;
+            }
+        }
+        if let Some(pending) = app.pending_attach.take() {
+            app.save_snapshot().await;
+            app.save_usage().await;
+            // A successful attach replaces this process, so nothing would
+            // remove the hooks afterwards.
+            hooks.disconnect().await;
+            let target = pending.target;
+            let error = tmux::on_server_sync(pending.server, || attach(&target, pending.read_only));
+            *hooks = Hooks::connect(app, &sender).await;
+            terminal.clear()?;
+            app.set_error(attach_error_message(&target, &error));
+        }
+        draw(terminal, app, &mut chrome)?;
     }
+    Ok(())
+}
 
+/// Terminal title and cursor shape as last written, so they are only sent
+/// when they change.
+#[derive(Default)]
+struct Chrome {
+    title: String,
+    bar_cursor: bool,
+}
+
+impl Chrome {
+    /// Title the terminal after the selected session and use a bar cursor
+    /// while typing.
+    fn update(&mut self, app: &App) -> std::io::Result<()> {
+        if !app.terminal_chrome {
+            return Ok(());
+        }
+        let mut out = std::io::stdout();
+        let title = crate::ui::terminal_title(app);
+        if title != self.title {
+            crossterm::execute!(out, crossterm::terminal::SetTitle(&title))?;
+            self.title = title;
+        }
+        let bar_cursor = app.is_typing();
+        if bar_cursor != self.bar_cursor {
+            let style = if bar_cursor {
+                crossterm::cursor::SetCursorStyle::BlinkingBar
+            } else {
+                crossterm::cursor::SetCursorStyle::DefaultUserShape
+            };
+            crossterm::execute!(out, style)?;
+            self.bar_cursor = bar_cursor;
+        }
+        Ok(())
+    }
+}
+
+/// Render a frame and remember the geometry input handling depends on.
+fn draw<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    chrome: &mut Chrome,
+) -> AppResult<()> {
+    chrome.update(app)?;
+    let area = terminal.draw(|frame| crate::ui::render(frame, app))?.area;
+    app.list_rows = crate::ui::list_rows(area, &app.config);
+    app.screen = area;
     Ok(())
 }
 
-fn spawn_event_channel() -> mpsc::UnboundedReceiver<std::io::Result<Event>> {
-    let (sender, receiver) = mpsc::unbounded_channel();
+/// Capture `targets` off the loop, which keeps handling input meanwhile,
+/// and report the round as [`AppEvent::Heat`].
+fn spawn_heat_round(
+    targets: Vec<heat::Target>,
+    sender: &mpsc::UnboundedSender<AppEvent>,
+) -> JoinHandle<()> {
+    let sender = sender.clone();
+    tokio::spawn(async move {
+        let samples = heat::sample(targets).await;
+        let _ = sender.send(AppEvent::Heat(samples));
+    })
+}
 
-    thread::spawn(move || loop {
-        let event = event::read();
-        let should_stop = event.is_err();
-        if sender.send(event).is_err() {
-            break;
+/// Refresh hooks set on the server, and the handle that keeps their
+/// waiter running.
+#[derive(Default)]
+struct Hooks {
+    installed: Option<hooks::Installed>,
+    waiter: Option<oneshot::Sender<()>>,
+}
+
+impl Hooks {
+    /// Install the refresh hooks and wait on them, when enabled and the
+    /// server supports hook arrays.
+    async fn connect(app: &App, sender: &mpsc::UnboundedSender<AppEvent>) -> Self {
+        if !app.config.hooks.unwrap_or(false) || !app.capabilities.hook_arrays {
+            return Hooks::default();
         }
-        if should_stop {
-            break;
+        let Ok(installed) = hooks::install().await else {
+            return Hooks::default();
+        };
+        let (stop, stopped) = oneshot::channel();
+        Box::new(HookSource {
+            waiter: installed.waiter(),
+            stop: stopped,
+        })
+        .spawn(sender.clone());
+        Hooks {
+            installed: Some(installed),
+            waiter: Some(stop),
         }
-    });
+    }
+
+    /// Whether hook signals are still arriving.
+    fn is_listening(&self) -> bool {
+        self.waiter.is_some()
+    }
 
-    receiver
+    /// Stop waiting, then remove the hooks from the server they were
+    /// installed on.
+    async fn disconnect(&mut self) {
+        self.waiter = None;
+        if let Some(installed) = self.installed.take() {
+            let _ = installed.uninstall().await;
+        }
+    }
+}
+
+/// Start a control client when enabled in config and feed it into the
+/// event stream; `false` means polling.
+fn connect_control(app: &App, sender: &mpsc::UnboundedSender<AppEvent>) -> bool {
+    if !app.config.control_mode.unwrap_or(false) || !app.capabilities.control_client {
+        return false;
+    }
+    match control::spawn() {
+        Ok(receiver) => {
+            Box::new(ControlSource(receiver)).spawn(sender.clone());
+            true
+        }
+        Err(_) => false,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::{attempted_attaches, char_keys, key, run_scripted, FakeTmux};
+    use crate::types::AppMode;
+    use crossterm::event::KeyCode;
 
     #[test]
     fn test_event_loop_function_exists() {
@@ -87,4 +544,311 @@ mod tests {
     fn test_tick_rate_is_250ms() {
         assert_eq!(TICK_RATE, Duration::from_millis(250));
     }
+
+    #[tokio::test]
+    async fn test_e2e_startup_lists_sessions_and_preview() {
+        let _tmux = FakeTmux::new()
+            .with_session("alpha")
+            .with_session("beta")
+            .with_capture("hello from alpha\n");
+        let mut app = App::new();
+
+        let screen = run_scripted(&mut app, vec![key(KeyCode::Char('q'))]).await;
+
+        assert!(screen.contains("alpha"));
+        assert!(screen.contains("beta"));
+        assert!(screen.contains("hello from alpha"));
+    }
+
+    #[tokio::test]
+    async fn test_e2e_enter_switches_client_inside_tmux() {
+        let tmux = FakeTmux::new().with_session("alpha").with_session("beta");
+        let mut app = App::new();
+        app.inside_tmux = true;
+
+        run_scripted(&mut app, vec![key(KeyCode::Char('j')), key(KeyCode::Enter)]).await;
+
+        assert!(app.should_quit, "switching should close tmui");
+        assert!(tmux.calls().iter().any(|c| c == "switch-client -t beta"));
+    }
+
+    #[tokio::test]
+    async fn test_e2e_starts_on_current_session() {
+        let _tmux = FakeTmux::new()
+            .with_session("alpha")
+            .with_session("beta")
+            .with_current("beta");
+        let mut app = App::new();
+        app.inside_tmux = true;
+
+        run_scripted(&mut app, char_keys("q")).await;
+
+        assert_eq!(app.current_session.as_deref(), Some("beta"));
+        assert_eq!(app.selected_session_name().as_deref(), Some("beta"));
+    }
+
+    #[tokio::test]
+    async fn test_e2e_failed_attach_outside_tmux_recovers() {
+        let _tmux = FakeTmux::new().with_session("alpha");
+        let mut app = App::new();
+        app.inside_tmux = false;
+
+        let screen =
+            run_scripted(&mut app, vec![key(KeyCode::Enter), key(KeyCode::Char('q'))]).await;
+
+        assert_eq!(attempted_attaches(), ["alpha"]);
+        assert!(screen.contains("Failed to attach to `alpha`"));
+    }
+
+    #[tokio::test]
+    async fn test_e2e_hooks_come_off_before_attach_outside_tmux() {
+        let tmux = FakeTmux::new()
+            .with_session("alpha")
+            .failing("wait-for", "server exited");
+        let mut app = App::new();
+        app.inside_tmux = false;
+        app.config.hooks = Some(true);
+
+        run_scripted(&mut app, vec![key(KeyCode::Enter), key(KeyCode::Char('q'))]).await;
+
+        assert_eq!(attempted_attaches(), ["alpha"]);
+        let slot = format!("session-created[{}]", std::process::id());
+        let calls = tmux.calls();
+        let count = |prefix: &str| {
+            calls
+                .iter()
+                .filter(|call| call.starts_with(prefix) && call.contains(&slot))
+                .count()
+        };
+        assert_eq!(
+            count("set-hook -g "),
+            2,
+            "installed again after the attach failed"
+        );
+        assert_eq!(
+            count("set-hook -gu "),
+            2,
+            "removed before attaching and on exit"
+        );
+    }
+
+    #[test]
+    fn test_attach_error_message_hints_tmux_path() {
+        let missing = std::io::Error::new(std::io::ErrorKind::NotFound, "gone");
+        assert!(attach_error_message("work", &missing).contains("set tmux_path"));
+
+        let denied = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied");
+        assert_eq!(
+            attach_error_message("work", &denied),
+            "Failed to attach to `work`: denied"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_e2e_clients_popup_lists_ttys() {
+        let tmux = FakeTmux::new()
+            .with_session("alpha")
+            .with_client("alpha", "/dev/pts/7");
+        let mut app = App::new();
+
+        run_scripted(
+            &mut app,
+            vec![
+                key(KeyCode::Char('c')),
+                key(KeyCode::Esc),
+                key(KeyCode::Char('q')),
+            ],
+        )
+        .await;
+
+        assert!(tmux
+            .calls()
+            .iter()
+            .any(|call| call.starts_with("list-clients") && call.ends_with("-t alpha")));
+        assert_eq!(app.mode, crate::types::AppMode::Normal);
+        assert!(app.clients.is_empty(), "closing the popup drops the list");
+    }
+
+    #[tokio::test]
+    async fn test_e2e_detach_one_client_keeps_others() {
+        let tmux = FakeTmux::new()
+            .with_session("alpha")
+            .with_client("alpha", "/dev/pts/7")
+            .with_client("alpha", "/dev/pts/8");
+        let mut app = App::new();
+
+        let mut events = char_keys("Djd");
+        events.extend([key(KeyCode::Esc), key(KeyCode::Char('q'))]);
+        run_scripted(&mut app, events).await;
+
+        assert!(tmux
+            .calls()
+            .contains(&"detach-client -t /dev/pts/8".to_string()));
+        assert_eq!(app.status_message, "Detached /dev/pts/8");
+    }
+
+    #[tokio::test]
+    async fn test_e2e_zoom_targets_selected_session() {
+        let tmux = FakeTmux::new().with_session("alpha");
+        let mut app = App::new();
+
+        run_scripted(&mut app, char_keys("zq")).await;
+
+        assert!(tmux
+            .calls()
+            .contains(&"resize-pane -Z -t alpha".to_string()));
+        assert_eq!(app.status_message, "Toggled zoom in `alpha`");
+    }
+
+    #[tokio::test]
+    async fn test_e2e_send_keys_types_line_into_session() {
+        let tmux = FakeTmux::new().with_session("alpha");
+        let mut app = App::new();
+
+        let mut script = char_keys("igit pull");
+        script.extend([key(KeyCode::Enter), key(KeyCode::Char('q'))]);
+        run_scripted(&mut app, script).await;
+
+        let calls = tmux.calls();
+        assert!(calls.contains(&"send-keys -t alpha -l -- git pull".to_string()));
+        assert!(calls.contains(&"send-keys -t alpha Enter".to_string()));
+        assert_eq!(app.status_message, "Sent `git pull` to `alpha`");
+    }
+
+    #[tokio::test]
+    async fn test_e2e_command_runs_in_new_window() {
+        let tmux = FakeTmux::new().with_session("alpha");
+        let mut app = App::new();
+
+        let mut script = char_keys("wnpm run dev");
+        script.extend([key(KeyCode::Enter), key(KeyCode::Char('q'))]);
+        run_scripted(&mut app, script).await;
+
+        assert!(tmux.calls().contains(
+            &"new-window -d -P -F #{window_id} -t alpha: -n npm npm run dev".to_string()
+        ));
+        assert_eq!(app.status_message, "Started `npm run dev` in `alpha`");
+    }
+
+    #[tokio::test]
+    async fn test_e2e_options_flip_and_edit() {
+        let tmux = FakeTmux::new()
+            .with_session("alpha")
+            .with_option("automatic-rename off")
+            .with_option("monitor-activity* on")
+            .with_option("status-left \"[#S] \"");
+        let mut app = App::new();
+
+        let mut script = vec![key(KeyCode::Char('o')), key(KeyCode::Enter)];
+        script.extend(char_keys("jj"));
+        script.push(key(KeyCode::Enter));
+        script.extend(char_keys("x"));
+        script.push(key(KeyCode::Enter));
+        script.extend([key(KeyCode::Esc), key(KeyCode::Char('q'))]);
+        run_scripted(&mut app, script).await;
+
+        let calls = tmux.calls();
+        assert!(calls.contains(&"show-options -A -t alpha".to_string()));
+        assert!(calls.contains(&"set-option -t alpha automatic-rename on".to_string()));
+        assert!(calls.contains(&"set-option -t alpha status-left [#S] x".to_string()));
+        assert_eq!(app.mode, AppMode::Normal);
+    }
+
+    #[tokio::test]
+    async fn test_e2e_buffers_paste_and_delete() {
+        let tmux = FakeTmux::new()
+            .with_session("alpha")
+            .with_buffer("buffer1", "make test")
+            .with_buffer("buffer0", "git status");
+        let mut app = App::new();
+
+        let mut script = char_keys("=jd");
+        script.push(key(KeyCode::Enter));
+        script.push(key(KeyCode::Char('q')));
+        run_scripted(&mut app, script).await;
+
+        let calls = tmux.calls();
+        assert!(calls.contains(&"show-buffer -b buffer0".to_string()));
+        assert!(calls.contains(&"delete-buffer -b buffer0".to_string()));
+        assert!(calls.contains(&"paste-buffer -b buffer1 -t alpha".to_string()));
+        assert_eq!(app.mode, AppMode::Normal);
+    }
+
+    #[tokio::test]
+    async fn test_e2e_failed_kill_shows_error() {
+        let tmux = FakeTmux::new()
+            .with_session("alpha")
+            .failing("kill-session", "can't find session: alpha");
+        let mut app = App::new();
+
+        let mut script = char_keys("ddy");
+        script.push(key(KeyCode::Char('q')));
+        let screen = run_scripted(&mut app, script).await;
+
+        assert!(tmux.calls().iter().any(|c| c == "kill-session -t alpha"));
+        assert!(screen.contains("Failed to kill"));
+        assert!(screen.contains("alpha"), "session should still be listed");
+    }
+
+    #[tokio::test]
+    async fn test_tick_source_emits_ticks() {
+        let (sender, mut events) = mpsc::unbounded_channel();
+        Box::new(TickSource(Duration::from_millis(5))).spawn(sender);
+
+        for _ in 0..2 {
+            let event = tokio::time::timeout(Duration::from_secs(1), events.recv())
+                .await
+                .expect("tick should arrive");
+            assert!(matches!(event, Some(AppEvent::Tick)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_control_source_coalesces_and_reports_disconnect() {
+        let (notify, receiver) = mpsc::unbounded_channel();
+        notify.send(Notification::Windows).unwrap();
+        notify.send(Notification::Sessions).unwrap();
+        notify.send(Notification::Windows).unwrap();
+        drop(notify);
+
+        let (sender, mut events) = mpsc::unbounded_channel();
+        Box::new(ControlSource(receiver)).spawn(sender);
+
+        assert!(matches!(
+            events.recv().await,
+            Some(AppEvent::Tmux(Notification::Sessions))
+        ));
+        assert!(matches!(
+            events.recv().await,
+            Some(AppEvent::TmuxDisconnected)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_hook_source_stops_quietly_or_reports_failure() {
+        let (sender, mut events) = mpsc::unbounded_channel();
+        let (stop, stopped) = oneshot::channel();
+        Box::new(HookSource {
+            waiter: (PathBuf::from("sleep"), vec!["30".to_string()]),
+            stop: stopped,
+        })
+        .spawn(sender);
+        drop(stop);
+        assert!(
+            events.recv().await.is_none(),
+            "a stopped waiter sends nothing"
+        );
+
+        let (sender, mut events) = mpsc::unbounded_channel();
+        let (_stop, stopped) = oneshot::channel();
+        Box::new(HookSource {
+            waiter: (PathBuf::from("false"), Vec::new()),
+            stop: stopped,
+        })
+        .spawn(sender);
+        assert!(matches!(
+            events.recv().await,
+            Some(AppEvent::HooksDisconnected)
+        ));
+    }
 }
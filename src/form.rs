@@ -0,0 +1,184 @@
+//! Multi-field dialogs: labelled text fields walked with Tab/Shift-Tab,
+//! checked field by field on submit. Rendering lives in
+//! `ui::widgets::FormPopup`; what a form is for is its `FormPurpose`.
+
+use crossterm::event::{KeyCode, KeyEvent};
+
+use crate::action::Action;
+
+/// Checks a field's trimmed value; `Err` is shown under the form.
+pub type Validator = fn(&str) -> Result<(), String>;
+
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub label: &'static str,
+    pub value: String,
+    /// Dimmed text shown while the field is empty.
+    pub placeholder: &'static str,
+    pub validate: Option<Validator>,
+}
+
+impl Field {
+    pub fn new(label: &'static str) -> Self {
+        Self {
+            label,
+            value: String::new(),
+            placeholder: "",
+            validate: None,
+        }
+    }
+
+    pub fn placeholder(mut self, text: &'static str) -> Self {
+        self.placeholder = text;
+        self
+    }
+
+    pub fn validate(mut self, check: Validator) -> Self {
+        self.validate = Some(check);
+        self
+    }
+
+    pub fn required(self) -> Self {
+        self.validate(|value| {
+            if value.is_empty() {
+                Err("required".to_string())
+            } else {
+                Ok(())
+            }
+        })
+    }
+}
+
+/// Fields in focus order, the focused one, and the last validation error.
+#[derive(Debug, Clone, Default)]
+pub struct Form {
+    pub fields: Vec<Field>,
+    pub focus: usize,
+    pub error: Option<String>,
+}
+
+impl Form {
+    pub fn new(fields: Vec<Field>) -> Self {
+        Self {
+            fields,
+            focus: 0,
+            error: None,
+        }
+    }
+
+    /// Trimmed value of field `index`.
+    pub fn value(&self, index: usize) -> &str {
+        self.fields.get(index).map_or("", |f| f.value.trim())
+    }
+
+    /// Trimmed value of field `index`, `None` when left empty.
+    pub fn optional(&self, index: usize) -> Option<String> {
+        Some(self.value(index).to_string()).filter(|v| !v.is_empty())
+    }
+
+    pub fn is_last_field(&self) -> bool {
+        self.focus + 1 >= self.fields.len()
+    }
+
+    pub fn focus_next(&mut self) {
+        if !self.fields.is_empty() {
+            self.focus = (self.focus + 1) % self.fields.len();
+        }
+    }
+
+    pub fn focus_previous(&mut self) {
+        if !self.fields.is_empty() {
+            self.focus = (self.focus + self.fields.len() - 1) % self.fields.len();
+        }
+    }
+
+    pub fn insert(&mut self, text: &str) {
+        if let Some(field) = self.fields.get_mut(self.focus) {
+            field.value.push_str(text);
+            self.error = None;
+        }
+    }
+
+    pub fn delete_char(&mut self) {
+        if let Some(field) = self.fields.get_mut(self.focus) {
+            field.value.pop();
+            self.error = None;
+        }
+    }
+
+    /// Run every validator; the first failure takes the focus and is kept
+    /// in `error`.
+    pub fn validate(&mut self) -> bool {
+        let failure = self.fields.iter().enumerate().find_map(|(index, field)| {
+            let check = field.validate?;
+            check(field.value.trim())
+                .err()
+                .map(|reason| (index, format!("{}: {reason}", field.label)))
+        });
+        match failure {
+            Some((index, reason)) => {
+                self.focus = index;
+                self.error = Some(reason);
+                false
+            }
+            None => {
+                self.error = None;
+                true
+            }
+        }
+    }
+}
+
+/// Keys of an open form. Enter moves on until the last field, where it
+/// submits.
+pub fn key_action(key: KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Esc => Some(Action::Cancel),
+        KeyCode::Enter => Some(Action::Submit),
+        KeyCode::Tab | KeyCode::Down => Some(Action::FocusNextField),
+        KeyCode::BackTab | KeyCode::Up => Some(Action::FocusPreviousField),
+        KeyCode::Backspace => Some(Action::DeleteChar),
+        KeyCode::Char(c) => Some(Action::InsertText(c.to_string())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn form() -> Form {
+        Form::new(vec![
+            Field::new("Name").required(),
+            Field::new("Directory"),
+            Field::new("Port")
+                .validate(|v| v.parse::<u16>().map(|_| ()).map_err(|e| e.to_string())),
+        ])
+    }
+
+    #[test]
+    fn test_focus_wraps_both_ways() {
+        let mut form = form();
+        form.focus_previous();
+        assert_eq!(form.focus, 2);
+        assert!(form.is_last_field());
+        form.focus_next();
+        assert_eq!(form.focus, 0);
+    }
+
+    #[test]
+    fn test_validate_focuses_first_failure() {
+        let mut form = form();
+        form.focus = 2;
+        form.insert("80");
+        assert!(!form.validate());
+        assert_eq!(form.focus, 0);
+        assert_eq!(form.error.as_deref(), Some("Name: required"));
+
+        form.insert("  api ");
+        assert_eq!(form.error, None, "typing clears the error");
+        assert!(form.validate());
+        assert_eq!(form.value(0), "api");
+        assert_eq!(form.optional(1), None);
+    }
+}
@@ -0,0 +1,162 @@
+//! Per-session output heat, derived from how much periodic pane captures
+//! change between samples.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::tmux;
+
+/// How often window captures are sampled for heat.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Changed bytes per sample above which a session counts as busy.
+const BUSY_THRESHOLD: usize = 256;
+
+/// Coarse output rate of a session over the last sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Heat {
+    Idle,
+    Low,
+    Busy,
+}
+
+impl Heat {
+    pub fn from_delta(bytes: usize) -> Self {
+        match bytes {
+            0 => Heat::Idle,
+            b if b <= BUSY_THRESHOLD => Heat::Low,
+            _ => Heat::Busy,
+        }
+    }
+
+    pub fn glyph(self) -> &'static str {
+        match self {
+            Heat::Idle => "▁",
+            Heat::Low => "▄",
+            Heat::Busy => "█",
+        }
+    }
+}
+
+/// A window to capture for a sample, on the server of its session.
+#[derive(Debug, Clone)]
+pub struct Target {
+    /// [`crate::types::Session::key`] of the window's session.
+    pub session: String,
+    pub server: Option<tmux::Socket>,
+    pub window_id: String,
+}
+
+/// Capture every target in turn for [`HeatTracker::record`]; windows that
+/// fail to capture are left out of the round.
+pub async fn sample(targets: Vec<Target>) -> Vec<(String, String, String)> {
+    let mut samples = Vec::with_capacity(targets.len());
+    for target in targets {
+        let capture = tmux::capture_pane(&target.window_id, false, None, false);
+        if let Ok(content) = tmux::on_server(target.server, capture).await {
+            samples.push((target.session, target.window_id, content));
+        }
+    }
+    samples
+}
+
+/// Last capture of every sampled window and the resulting session heat.
+/// Windows are keyed with their session, as window ids repeat across
+/// servers.
+#[derive(Debug, Default)]
+pub struct HeatTracker {
+    captures: HashMap<(String, String), String>,
+    heat: HashMap<String, Heat>,
+}
+
+impl HeatTracker {
+    /// Record one round of `(session, window id, capture)` samples. Windows
+    /// seen for the first time contribute nothing; windows and sessions
+    /// missing from the round are forgotten.
+    pub fn record(&mut self, samples: Vec<(String, String, String)>) {
+        let mut deltas: HashMap<String, usize> = HashMap::new();
+        let mut captures = HashMap::with_capacity(samples.len());
+
+        for (session, window_id, content) in samples {
+            let window = (session, window_id);
+            let delta = self
+                .captures
+                .get(&window)
+                .map_or(0, |previous| capture_delta(previous, &content));
+            *deltas.entry(window.0.clone()).or_default() += delta;
+            captures.insert(window, content);
+        }
+
+        self.captures = captures;
+        self.heat = deltas
+            .into_iter()
+            .map(|(session, delta)| (session, Heat::from_delta(delta)))
+            .collect();
+    }
+
+    pub fn get(&self, session: &str) -> Option<Heat> {
+        self.heat.get(session).copied()
+    }
+}
+
+/// Bytes of `new` on lines that differ from the same line of `old`.
+/// Scrolling output shifts every line, so it reads as a large delta.
+pub fn capture_delta(old: &str, new: &str) -> usize {
+    let mut old_lines = old.lines();
+    new.lines()
+        .filter(|line| old_lines.next() != Some(*line))
+        .map(str::len)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(session: &str, window: &str, content: &str) -> (String, String, String) {
+        (session.to_string(), window.to_string(), content.to_string())
+    }
+
+    #[test]
+    fn test_capture_delta() {
+        assert_eq!(capture_delta("a\nb\n", "a\nb\n"), 0);
+        assert_eq!(capture_delta("a\nb\n", "a\nbc\n"), 2);
+        assert_eq!(capture_delta("a\n", "a\nnew line\n"), 8);
+        assert_eq!(capture_delta("", ""), 0);
+    }
+
+    #[test]
+    fn test_heat_from_delta() {
+        assert_eq!(Heat::from_delta(0), Heat::Idle);
+        assert_eq!(Heat::from_delta(10), Heat::Low);
+        assert_eq!(Heat::from_delta(BUSY_THRESHOLD + 1), Heat::Busy);
+    }
+
+    #[test]
+    fn test_tracker_sums_windows_per_session() {
+        let mut tracker = HeatTracker::default();
+        tracker.record(vec![
+            sample("work", "@0", "$ "),
+            sample("work", "@1", "idle"),
+            sample("ops", "@2", "tail"),
+        ]);
+        assert_eq!(
+            tracker.get("work"),
+            Some(Heat::Idle),
+            "first sample has no delta"
+        );
+
+        let burst = "log line\n".repeat(40);
+        tracker.record(vec![
+            sample("work", "@0", "$ ls"),
+            sample("work", "@1", "idle"),
+            sample("ops", "@2", &burst),
+        ]);
+        assert_eq!(tracker.get("work"), Some(Heat::Low));
+        assert_eq!(tracker.get("ops"), Some(Heat::Busy));
+
+        tracker.record(vec![sample("work", "@0", "$ ls")]);
+        assert_eq!(tracker.get("work"), Some(Heat::Idle));
+        assert_eq!(tracker.get("ops"), None, "vanished sessions are dropped");
+    }
+}
@@ -0,0 +1,139 @@
+//! Refresh signals from tmux hooks, for servers where control mode is off
+//! or unsupported.
+//!
+//! Each hook in [`HOOKS`] gets an entry at this process's [`index`] that
+//! signals its own `wait-for` [`channel`], and a waiter blocks on that
+//! channel. Signals sent while nobody waits are kept by tmux, so a burst of
+//! changes wakes the waiter once instead of being lost. Other tmui
+//! instances on the same server use other slots, so one exiting leaves the
+//! hooks of the rest alone.
+
+use std::path::PathBuf;
+use std::process::Stdio;
+
+use anyhow::anyhow;
+use tokio::process::Command;
+
+use crate::tmux;
+use crate::types::AppResult;
+
+/// `wait-for` channel this process's hooks signal.
+pub fn channel() -> String {
+    format!("tmui-refresh-{}", std::process::id())
+}
+
+/// Slot in each hook array: the process id, unique among running
+/// instances and clear of the low indexes users set by hand.
+fn index() -> u32 {
+    std::process::id()
+}
+
+/// Hooks that change what tmui lists.
+const HOOKS: &[&str] = &[
+    "session-created",
+    "session-closed",
+    "session-renamed",
+    "client-attached",
+    "client-detached",
+    "window-linked",
+    "window-unlinked",
+    "window-renamed",
+    "after-split-window",
+    "pane-exited",
+];
+
+/// A command line for the server the hooks were installed on.
+type Invocation = (PathBuf, Vec<String>);
+
+/// Hooks set on one server. Keeps the command lines for that server, so
+/// waiting and removing still reach it after tmui switched to another one.
+#[derive(Debug)]
+pub struct Installed {
+    wait: Invocation,
+    uninstall: Vec<Invocation>,
+}
+
+/// Point every hook at [`channel`]; setting them again is harmless.
+pub async fn install() -> AppResult<Installed> {
+    let channel = channel();
+    let index = index();
+    let command = format!("wait-for -S {channel}");
+    for hook in HOOKS {
+        tmux::run_tmux(&["set-hook", "-g", &format!("{hook}[{index}]"), &command]).await?;
+    }
+    Ok(Installed {
+        wait: tmux::invocation(&["wait-for", &channel], false),
+        uninstall: HOOKS
+            .iter()
+            .map(|hook| tmux::invocation(&["set-hook", "-gu", &format!("{hook}[{index}]")], false))
+            .collect(),
+    })
+}
+
+impl Installed {
+    /// Remove the hooks again, leaving the rest of each hook array alone.
+    pub async fn uninstall(self) -> AppResult<()> {
+        for (program, argv) in &self.uninstall {
+            tmux::run_invocation(program, argv).await?;
+        }
+        Ok(())
+    }
+
+    /// What [`wait`] runs, to hand to the waiting task.
+    pub fn waiter(&self) -> Invocation {
+        self.wait.clone()
+    }
+}
+
+/// Block until a hook fires; fails when the server goes away. Unlike other
+/// tmux commands this has no timeout, since quiet servers are the norm, and
+/// dropping the future kills the `wait-for` client.
+pub async fn wait((program, argv): &Invocation) -> AppResult<()> {
+    let status = Command::new(program)
+        .args(argv)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .status()
+        .await?;
+    if !status.success() {
+        return Err(anyhow!("wait-for {} failed ({status})", channel()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FakeTmux;
+
+    #[tokio::test]
+    async fn test_install_and_uninstall_use_own_slot() {
+        let tmux = FakeTmux::new();
+        let installed = install().await.expect("hooks should install");
+        installed.uninstall().await.expect("hooks should uninstall");
+
+        let calls = tmux.calls();
+        assert_eq!(calls.len(), 2 * HOOKS.len());
+        let pid = std::process::id();
+        assert!(calls.contains(&format!(
+            "set-hook -g session-created[{pid}] wait-for -S tmui-refresh-{pid}"
+        )));
+        assert!(calls.contains(&format!("set-hook -gu pane-exited[{pid}]")));
+    }
+
+    #[tokio::test]
+    async fn test_uninstall_reaches_server_installed_on() {
+        let tmux = FakeTmux::new();
+        let installed = tmux::on_server(Some(tmux::Socket::Name("old".to_string())), install())
+            .await
+            .expect("hooks should install");
+        installed.uninstall().await.expect("hooks should uninstall");
+
+        assert!(tmux.calls().contains(&format!(
+            "-L old set-hook -gu session-created[{}]",
+            std::process::id()
+        )));
+    }
+}
@@ -1,37 +1,176 @@
+mod action;
 mod app;
+mod bundle;
+mod cli;
+mod clipboard;
 mod config;
+mod control;
 mod event;
+mod form;
+mod heat;
+mod hooks;
+mod naming;
+mod palette;
+mod ports;
+mod replay;
+mod report;
 mod search;
+mod servers;
+mod snapshot;
+mod ssh;
+#[cfg(test)]
+mod testing;
+mod timeline;
 mod tmux;
 mod types;
 mod ui;
 
 use crate::app::App;
+use crate::bundle::Bundle;
+use crate::cli::{Command, TuiOptions};
+use crate::config::Config;
 use crate::types::AppResult;
 
+/// Screen size of a headless replay.
+const HEADLESS_WIDTH: u16 = 120;
+const HEADLESS_HEIGHT: u16 = 40;
+
 fn install_panic_hook() {
     let original_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |panic_info| {
-        ratatui::restore();
+        event::restore_terminal();
         original_hook(panic_info);
     }));
 }
 
-async fn run() -> AppResult<()> {
-    install_panic_hook();
+/// App with the tmux binary and server chosen from flags and config.
+fn configured_app(socket: Option<tmux::Socket>) -> App {
+    let app = App::new();
+    tmux::set_binary(
+        app.config
+            .tmux_path
+            .as_deref()
+            .map(std::path::PathBuf::from),
+    );
+    tmux::set_socket(
+        socket.or_else(|| app.config.socket.as_deref().map(tmux::Socket::from_setting)),
+    );
+    app
+}
+
+async fn run(options: TuiOptions) -> AppResult<()> {
+    if options.headless {
+        let recording = options
+            .replay
+            .as_deref()
+            .unwrap_or(std::path::Path::new(""));
+        return replay_headless(options.socket, recording).await;
+    }
+    let mut app = configured_app(options.socket);
+    app.color_mode = ui::ColorMode::from_env(app.config.monochrome.unwrap_or(false));
+    app.terminal_chrome = true;
+    app.snapshot_dir = Some(snapshot::default_dir());
+    app.save_usage = options.replay.is_none();
+    if let Some(path) = &options.record {
+        app.recorder = Some(replay::Recorder::create(path)?);
+    }
+    let mut extra: Vec<Box<dyn event::EventSource>> = Vec::new();
+    if let Some(path) = &options.replay {
+        app.dry_run = Some(Vec::new());
+        extra.push(Box::new(replay::ReplaySource {
+            events: replay::load(path)?,
+            realtime: true,
+        }));
+    }
 
-    let mut terminal = ratatui::init();
-    let mut app = App::new();
-    let result = event::run_event_loop(&mut app, &mut terminal).await;
+    install_panic_hook();
+    event::set_mouse_capture(app.config.mouse.unwrap_or(false));
+    let mut terminal = event::init_terminal();
+    let result = event::run_event_loop(&mut app, &mut terminal, extra).await;
 
-    ratatui::restore();
+    event::restore_terminal();
 
     result
 }
 
+/// Play a recording against an off-screen terminal and print the final
+/// screen, then the effects the replay did not run, for reproducing bug
+/// reports without a TTY.
+async fn replay_headless(
+    socket: Option<tmux::Socket>,
+    recording: &std::path::Path,
+) -> AppResult<()> {
+    let mut app = configured_app(socket);
+    app.dry_run = Some(Vec::new());
+    let source = replay::ReplaySource {
+        events: replay::load(recording)?,
+        realtime: false,
+    };
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(
+        HEADLESS_WIDTH,
+        HEADLESS_HEIGHT,
+    ))?;
+    event::run_with_sources(&mut app, &mut terminal, vec![Box::new(source)], no_attach).await?;
+    print!("{}", replay::screen_text(terminal.backend().buffer()));
+    for effect in app.dry_run.unwrap_or_default() {
+        println!("skipped: {effect:?}");
+    }
+    Ok(())
+}
+
+/// Headless replays never leave for a real tmux client.
+fn no_attach(target: &str, _read_only: bool) -> std::io::Error {
+    std::io::Error::other(format!(
+        "not attaching to `{target}` during a headless replay"
+    ))
+}
+
+fn backup(output: &std::path::Path) -> AppResult<()> {
+    let bundle = Bundle::collect(&Config::config_dir())?;
+    bundle.save(output)?;
+    println!("Wrote {} files to {}", bundle.files.len(), output.display());
+    Ok(())
+}
+
+fn restore_meta(input: &std::path::Path) -> AppResult<()> {
+    let bundle = Bundle::load(input)?;
+    let restored = bundle.restore(&Config::config_dir())?;
+    if restored.is_empty() {
+        println!("Nothing to restore; metadata already up to date");
+    }
+    for name in restored {
+        println!("Restored {name}");
+    }
+    Ok(())
+}
+
+async fn report(socket: Option<tmux::Socket>) -> AppResult<()> {
+    let app = configured_app(socket);
+    let sessions = tmux::list_sessions().await?;
+    let windows = tmux::list_all_windows().await?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+    print!(
+        "{}",
+        report::markdown(&sessions, &windows, &app.config, now)
+    );
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> AppResult<()> {
-    run().await
+    match cli::parse_args(std::env::args().skip(1))? {
+        Command::Tui(options) => run(options).await,
+        Command::Backup { output } => backup(&output),
+        Command::RestoreMeta { input } => restore_meta(&input),
+        Command::Report { socket } => report(socket).await,
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            Ok(())
+        }
+    }
 }
 
 #[cfg(test)]
@@ -60,6 +199,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_cargo_builds() {
         assert!(true, "If this test runs, cargo build succeeded");
     }
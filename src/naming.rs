@@ -0,0 +1,128 @@
+//! Session naming conventions: an optional template applied to typed names
+//! and a pattern the resulting name must match.
+//!
+//! Patterns are regular expressions in the syntax of the `regex` crate,
+//! matched anywhere in the name unless anchored with `^`/`$`.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// The `[naming]` table as written in the config file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct NamingFields {
+    /// Pattern new session names must match, e.g. `^[a-z0-9-]+$`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pattern: Option<String>,
+    /// Template wrapping the typed name, e.g. `team-{name}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    template: Option<String>,
+}
+
+/// A naming convention with its pattern compiled once, when the config is
+/// loaded, since the create prompt checks it on every frame.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "NamingFields", into = "NamingFields")]
+pub struct NamingRule {
+    fields: NamingFields,
+    /// `pattern` compiled, or why it does not compile.
+    regex: Option<Result<Regex, String>>,
+}
+
+impl From<NamingFields> for NamingRule {
+    fn from(fields: NamingFields) -> Self {
+        let regex = fields.pattern.as_deref().map(|pattern| {
+            Regex::new(pattern).map_err(|e| {
+                // Syntax errors span several lines; the last one says what is wrong.
+                let message = e.to_string();
+                let reason = message.lines().last().unwrap_or_default();
+                format!(
+                    "invalid naming pattern: {}",
+                    reason.trim_start_matches("error: ")
+                )
+            })
+        });
+        Self { fields, regex }
+    }
+}
+
+impl From<NamingRule> for NamingFields {
+    fn from(rule: NamingRule) -> Self {
+        rule.fields
+    }
+}
+
+impl NamingRule {
+    #[cfg(test)]
+    pub fn new(pattern: Option<&str>, template: Option<&str>) -> Self {
+        NamingFields {
+            pattern: pattern.map(str::to_string),
+            template: template.map(str::to_string),
+        }
+        .into()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.pattern.is_none() && self.fields.template.is_none()
+    }
+
+    /// Apply the template to `input` and validate the result.
+    /// Returns the final session name or a message explaining the violation.
+    pub fn apply(&self, input: &str) -> Result<String, String> {
+        let name = match &self.fields.template {
+            Some(template) => template.replace("{name}", input),
+            None => input.to_string(),
+        };
+        self.check(&name)?;
+        Ok(name)
+    }
+
+    /// Validate a final session name, such as a rename target or a name
+    /// tmui derived itself, against the pattern.
+    pub fn check(&self, name: &str) -> Result<(), String> {
+        match &self.regex {
+            Some(Ok(regex)) if !regex.is_match(name) => {
+                Err(format!("`{name}` must match {}", regex.as_str()))
+            }
+            Some(Err(reason)) => Err(reason.clone()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str) -> NamingRule {
+        NamingRule::new(Some(pattern), None)
+    }
+
+    #[test]
+    fn test_pattern_uses_full_regex_syntax() {
+        let teams = rule("^(team|ops)-[a-z]+$");
+        assert!(teams.check("ops-api").is_ok());
+        assert!(teams.check("dev-api").is_err());
+        assert!(rule("^[a-z]{2,4}$").check("abcde").is_err());
+        assert!(rule("dev").check("my-dev-box").is_ok());
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_reported() {
+        let reason = rule("[a-z").check("api").unwrap_err();
+        assert!(reason.starts_with("invalid naming pattern"), "{reason}");
+    }
+
+    #[test]
+    fn test_naming_rule_template_and_pattern() {
+        let rule = NamingRule::new(Some("^team-[a-z]+$"), Some("team-{name}"));
+        assert_eq!(rule.apply("api"), Ok("team-api".to_string()));
+        assert!(rule.apply("API").is_err());
+    }
+
+    #[test]
+    fn test_empty_rule_accepts_anything() {
+        let rule = NamingRule::default();
+        assert!(rule.is_empty());
+        assert_eq!(rule.apply("Any Name"), Ok("Any Name".to_string()));
+    }
+}
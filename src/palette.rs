@@ -0,0 +1,140 @@
+//! Command palette (Ctrl-p): normal-mode actions under searchable labels,
+//! for operations used too rarely to remember their key.
+
+use crate::action::Action;
+use crate::search;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Command {
+    pub label: &'static str,
+    /// Key running the same action in normal mode.
+    pub key: &'static str,
+    pub action: Action,
+    /// Acts on the selected window, so only offered with the Windows panel
+    /// focused.
+    pub windows: bool,
+}
+
+fn command(label: &'static str, key: &'static str, action: Action) -> Command {
+    Command {
+        label,
+        key,
+        action,
+        windows: false,
+    }
+}
+
+fn window_command(label: &'static str, key: &'static str, action: Action) -> Command {
+    Command {
+        windows: true,
+        ..command(label, key, action)
+    }
+}
+
+/// Every command, in the order of the help overlay.
+pub fn commands() -> Vec<Command> {
+    vec![
+        command(
+            "Attach / switch to session",
+            "Enter",
+            Action::AttachSelected,
+        ),
+        command("Attach read-only", "A", Action::AttachReadOnly),
+        command("Back to last session", "-", Action::SwitchLastSession),
+        command("New session", "n", Action::PromptNewSession),
+        command(
+            "New session with directory / command",
+            "N",
+            Action::PromptSessionWizard,
+        ),
+        command(
+            "Run command (throwaway session)",
+            "!",
+            Action::PromptRunCommand,
+        ),
+        command("Send keys to pane", "i", Action::PromptSendKeys),
+        command(
+            "Paste file or snippet into pane",
+            "I",
+            Action::PromptSendText,
+        ),
+        command(
+            "Run command in new window",
+            "w",
+            Action::PromptNewWindowCommand,
+        ),
+        command("Options", "o", Action::ShowOptions),
+        command("Rename", "r", Action::PromptRename),
+        command("Rename active window", "W", Action::PromptRenameWindow),
+        command("Kill session", "dd", Action::RequestKill),
+        command("Undo kill session", "u", Action::UndoKill),
+        command("Clients: detach one or all", "D", Action::ShowClients),
+        command("Kill tmux server", "K", Action::RequestKillServer),
+        command("tmux servers / stray sockets", "S", Action::ShowServers),
+        command("Switch host (local / ssh)", "@", Action::ShowHosts),
+        command("Paste buffers", "=", Action::ShowBuffers),
+        command("Session group", "+", Action::ShowGroup),
+        command("Activity timeline", "h", Action::ShowTimeline),
+        command("Changes since last run", "E", Action::ShowChanges),
+        command("Session usage stats", "U", Action::ShowStats),
+        command("Copy pane text to clipboard", "y", Action::YankPane),
+        command(
+            "Save pane scrollback to file",
+            ">",
+            Action::PromptCaptureToFile,
+        ),
+        command("Start / stop logging pane", "l", Action::TogglePaneLog),
+        command("Review cleanup policies", "C", Action::ReviewCleanup),
+        command("Search sessions", "/", Action::StartSearch),
+        command("Add tag", "t", Action::PromptTag),
+        command("Filter by tag / clear filter", "T", Action::ToggleTagFilter),
+        command("Group sessions by directory", "P", Action::TogglePathGroups),
+        command("Cycle session sort order", "s", Action::CycleSort),
+        command("Show / hide ignored sessions", "H", Action::ToggleIgnored),
+        command("Select several sessions", "V", Action::StartVisual),
+        command("Toggle windows panel focus", "Tab", Action::ToggleFocus),
+        command("Toggle pane zoom", "z", Action::ToggleZoom),
+        window_command("Search windows", "/", Action::StartWindowSearch),
+        window_command("Move window to session", "m", Action::PromptMoveWindow),
+        window_command("Apply layout preset", "L", Action::PromptLayout),
+        window_command("Move window down", "J", Action::SwapWindowDown),
+        window_command("Move window up", "K", Action::SwapWindowUp),
+        window_command("Kill pane", "x", Action::RequestKillPane),
+        window_command("Break pane out", "b", Action::RequestBreakPane),
+        window_command("Join pane into window", "M", Action::RequestJoinPane),
+        window_command("Respawn dead pane", "R", Action::RequestRespawnPane),
+        command("Help", "?", Action::ToggleHelp),
+        command("Quit", "q", Action::Quit),
+    ]
+}
+
+/// Commands available with the Windows panel focused or not, best match
+/// for `query` first; an empty query keeps the list order.
+pub fn matching(query: &str, windows_focused: bool) -> Vec<Command> {
+    let available: Vec<Command> = commands()
+        .into_iter()
+        .filter(|c| windows_focused || !c.windows)
+        .collect();
+    let labels: Vec<&str> = available.iter().map(|c| c.label).collect();
+    search::fuzzy_match_labels(&labels, query)
+        .into_iter()
+        .map(|i| available[i].clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matching_ranks_and_hides_window_commands() {
+        let all = matching("", false);
+        assert_eq!(all[0].action, Action::AttachSelected);
+        assert!(all.iter().all(|c| !c.windows));
+        assert!(matching("", true).len() > all.len());
+
+        let found = matching("layout", true);
+        assert_eq!(found[0].action, Action::PromptLayout);
+        assert!(matching("layout", false).is_empty());
+    }
+}
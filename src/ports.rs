@@ -0,0 +1,130 @@
+//! Listening-port hints: which TCP ports are served by processes running
+//! under each session's panes. Opt-in, since it shells out to `ss`.
+
+use std::collections::HashMap;
+use std::process::Stdio;
+
+use tokio::process::Command;
+
+use crate::types::AppResult;
+
+/// Scan listening sockets and attribute them to sessions by walking each
+/// listener's parent chain up to a pane process.
+pub async fn scan(pane_pids: &[(String, u32)]) -> AppResult<HashMap<String, Vec<u16>>> {
+    let output = Command::new("ss")
+        .args(["-ltnpH"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .await?;
+    let listeners = parse_ss_listeners(&String::from_utf8_lossy(&output.stdout));
+    Ok(attribute_ports(pane_pids, &listeners, parent_pid))
+}
+
+/// Parse `ss -ltnpH` output into `(port, pid)` pairs. Sockets whose owner
+/// is not visible to us carry no pid and are skipped.
+pub fn parse_ss_listeners(output: &str) -> Vec<(u16, u32)> {
+    let mut listeners = Vec::new();
+    for line in output.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let Some(local) = fields.get(3) else {
+            continue;
+        };
+        let Some(port) = local.rsplit(':').next().and_then(|p| p.parse().ok()) else {
+            continue;
+        };
+        let Some(users) = fields.iter().find(|f| f.starts_with("users:")) else {
+            continue;
+        };
+        for pid in users.split("pid=").skip(1) {
+            if let Ok(pid) = pid
+                .split(|c: char| !c.is_ascii_digit())
+                .next()
+                .unwrap_or("")
+                .parse()
+            {
+                listeners.push((port, pid));
+            }
+        }
+    }
+    listeners
+}
+
+/// Map listeners to the sessions whose pane processes are their ancestors.
+/// Ports are sorted and deduplicated per session.
+pub fn attribute_ports<F>(
+    pane_pids: &[(String, u32)],
+    listeners: &[(u16, u32)],
+    parent_of: F,
+) -> HashMap<String, Vec<u16>>
+where
+    F: Fn(u32) -> Option<u32>,
+{
+    let owners: HashMap<u32, &str> = pane_pids
+        .iter()
+        .map(|(session, pid)| (*pid, session.as_str()))
+        .collect();
+
+    let mut ports: HashMap<String, Vec<u16>> = HashMap::new();
+    for &(port, pid) in listeners {
+        let mut current = Some(pid);
+        // Bounded walk guards against pid reuse forming a cycle.
+        for _ in 0..64 {
+            let Some(pid) = current else {
+                break;
+            };
+            if let Some(session) = owners.get(&pid) {
+                ports.entry(session.to_string()).or_default().push(port);
+                break;
+            }
+            current = parent_of(pid).filter(|&ppid| ppid > 1);
+        }
+    }
+
+    for list in ports.values_mut() {
+        list.sort_unstable();
+        list.dedup();
+    }
+    ports
+}
+
+/// Parent pid from `/proc/<pid>/stat`; the command name may contain spaces,
+/// so fields are read after its closing parenthesis.
+fn parent_pid(pid: u32) -> Option<u32> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    let after_name = &stat[stat.rfind(')')? + 1..];
+    after_name.split_whitespace().nth(1)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ss_listeners() {
+        let output = "\
+LISTEN 0 511 0.0.0.0:3000 0.0.0.0:* users:((\"node\",pid=4242,fd=20))
+LISTEN 0 128 [::]:5432 [::]:* users:((\"postgres\",pid=77,fd=6),(\"postgres\",pid=78,fd=6))
+LISTEN 0 128 0.0.0.0:22 0.0.0.0:*
+";
+        assert_eq!(
+            parse_ss_listeners(output),
+            vec![(3000, 4242), (5432, 77), (5432, 78)]
+        );
+    }
+
+    #[test]
+    fn test_attribute_ports_walks_parents() {
+        let panes = vec![("web".to_string(), 100), ("db".to_string(), 200)];
+        let listeners = vec![(3000, 101), (5432, 201), (5432, 202), (8080, 999)];
+        let parents: HashMap<u32, u32> = [(101, 100), (201, 200), (202, 201), (999, 1)]
+            .into_iter()
+            .collect();
+
+        let ports = attribute_ports(&panes, &listeners, |pid| parents.get(&pid).copied());
+
+        assert_eq!(ports.get("web"), Some(&vec![3000]));
+        assert_eq!(ports.get("db"), Some(&vec![5432]));
+        assert_eq!(ports.len(), 2, "unrelated listeners are ignored");
+    }
+}
@@ -0,0 +1,373 @@
+//! Session recordings: `--record` writes terminal input and the resulting
+//! state changes to a text file, `--replay` feeds the input back through
+//! the event loop, live or headless.
+//!
+//! One entry per line, `<ms since start> <kind> <payload>`:
+//!
+//! ```text
+//! # tmui recording v1
+//! 0 key j
+//! 180 key C-d
+//! 900 paste some\ntext
+//! 950 state Normal focus=Sessions selected=alpha
+//! ```
+//!
+//! `state` lines are for humans reading a bug report; replay skips them.
+
+use std::fs::File;
+use std::io::{LineWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::buffer::Buffer;
+use tokio::sync::mpsc;
+
+use crate::app::App;
+use crate::event::{AppEvent, EventSource};
+use crate::types::AppResult;
+
+const HEADER: &str = "# tmui recording v1";
+
+/// Appends input and state changes to a recording file as they happen.
+pub struct Recorder {
+    out: LineWriter<File>,
+    start: Instant,
+    last_state: String,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> AppResult<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("cannot create recording {}", path.display()))?;
+        let mut out = LineWriter::new(file);
+        writeln!(out, "{HEADER}")?;
+        Ok(Self {
+            out,
+            start: Instant::now(),
+            last_state: String::new(),
+        })
+    }
+
+    /// Record `event` if it is input tmui reacts to.
+    pub fn record_event(&mut self, event: &Event) {
+        if let Some(line) = encode_event(event) {
+            self.write(&line);
+        }
+    }
+
+    /// Record the app's state if it changed since the last call.
+    pub fn record_state(&mut self, app: &App) {
+        let state = state_summary(app);
+        if state != self.last_state {
+            self.write(&format!("state {state}"));
+            self.last_state = state;
+        }
+    }
+
+    fn write(&mut self, entry: &str) {
+        // A failing disk must not take the session down with it.
+        let _ = writeln!(self.out, "{} {entry}", self.start.elapsed().as_millis());
+    }
+}
+
+/// What a reader of the recording needs to follow along.
+fn state_summary(app: &App) -> String {
+    format!(
+        "{:?} focus={:?} selected={}",
+        app.mode,
+        app.focus,
+        app.selected_session_name().unwrap_or_default()
+    )
+}
+
+/// Input entries of a recording with their offsets from the start.
+pub fn load(path: &Path) -> AppResult<Vec<(Duration, Event)>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("cannot read recording {}", path.display()))?;
+    Ok(parse(&text))
+}
+
+fn parse(text: &str) -> Vec<(Duration, Event)> {
+    text.lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let (ms, entry) = line.split_once(' ')?;
+            let offset = Duration::from_millis(ms.parse().ok()?);
+            Some((offset, decode_event(entry)?))
+        })
+        .collect()
+}
+
+/// Plays a recording into the event loop: with the recorded timing when
+/// `realtime`, otherwise as fast as possible and then asks the loop to
+/// stop, so headless runs always finish.
+pub struct ReplaySource {
+    pub events: Vec<(Duration, Event)>,
+    pub realtime: bool,
+}
+
+impl EventSource for ReplaySource {
+    fn spawn(self: Box<Self>, sender: mpsc::UnboundedSender<AppEvent>) {
+        tokio::spawn(async move {
+            let start = tokio::time::Instant::now();
+            for (offset, event) in self.events {
+                if self.realtime {
+                    tokio::time::sleep_until(start + offset).await;
+                } else {
+                    tokio::task::yield_now().await;
+                }
+                if sender.send(AppEvent::Terminal(Ok(event))).is_err() {
+                    return;
+                }
+            }
+            if !self.realtime {
+                let _ = sender.send(AppEvent::Shutdown);
+            }
+        });
+    }
+}
+
+/// A rendered buffer as plain text, one line per row.
+pub fn screen_text(buffer: &Buffer) -> String {
+    let mut text = String::new();
+    for y in 0..buffer.area.height {
+        for x in 0..buffer.area.width {
+            text.push_str(buffer[(x, y)].symbol());
+        }
+        text.push('\n');
+    }
+    text
+}
+
+fn encode_event(event: &Event) -> Option<String> {
+    match event {
+        Event::Key(key) if key.kind == KeyEventKind::Press => {
+            Some(format!("key {}", encode_key(key)?))
+        }
+        Event::Paste(text) => Some(format!(
+            "paste {}",
+            text.replace('\\', "\\\\").replace('\n', "\\n")
+        )),
+        Event::FocusGained => Some("focus in".to_string()),
+        Event::FocusLost => Some("focus out".to_string()),
+        Event::Resize(width, height) => Some(format!("resize {width} {height}")),
+        _ => None,
+    }
+}
+
+fn decode_event(entry: &str) -> Option<Event> {
+    let (kind, payload) = entry.split_once(' ')?;
+    match kind {
+        "key" => decode_key(payload).map(Event::Key),
+        "paste" => Some(Event::Paste(unescape(payload))),
+        "focus" => match payload {
+            "in" => Some(Event::FocusGained),
+            "out" => Some(Event::FocusLost),
+            _ => None,
+        },
+        "resize" => {
+            let (width, height) = payload.split_once(' ')?;
+            Some(Event::Resize(width.parse().ok()?, height.parse().ok()?))
+        }
+        _ => None,
+    }
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Emacs-style notation: `j`, `K`, `C-d`, `A-Enter`, `S-Tab`, `F5`.
+fn encode_key(key: &KeyEvent) -> Option<String> {
+    let code = match key.code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{n}"),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::BackTab => "BackTab".to_string(),
+        KeyCode::Backspace => "Backspace".to_string(),
+        KeyCode::Delete => "Delete".to_string(),
+        KeyCode::Up => "Up".to_string(),
+        KeyCode::Down => "Down".to_string(),
+        KeyCode::Left => "Left".to_string(),
+        KeyCode::Right => "Right".to_string(),
+        KeyCode::Home => "Home".to_string(),
+        KeyCode::End => "End".to_string(),
+        KeyCode::PageUp => "PageUp".to_string(),
+        KeyCode::PageDown => "PageDown".to_string(),
+        _ => return None,
+    };
+    let mut prefix = String::new();
+    for (modifier, tag) in [
+        (KeyModifiers::CONTROL, "C-"),
+        (KeyModifiers::ALT, "A-"),
+        (KeyModifiers::SHIFT, "S-"),
+    ] {
+        if key.modifiers.contains(modifier) {
+            prefix.push_str(tag);
+        }
+    }
+    Some(prefix + &code)
+}
+
+fn decode_key(text: &str) -> Option<KeyEvent> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = text;
+    loop {
+        let stripped = [
+            ("C-", KeyModifiers::CONTROL),
+            ("A-", KeyModifiers::ALT),
+            ("S-", KeyModifiers::SHIFT),
+        ]
+        .into_iter()
+        .find_map(|(tag, modifier)| {
+            let after = rest.strip_prefix(tag).filter(|after| !after.is_empty())?;
+            Some((after, modifier))
+        });
+        let Some((after, modifier)) = stripped else {
+            break;
+        };
+        modifiers |= modifier;
+        rest = after;
+    }
+
+    let code = match rest {
+        "Space" => KeyCode::Char(' '),
+        "Enter" => KeyCode::Enter,
+        "Esc" => KeyCode::Esc,
+        "Tab" => KeyCode::Tab,
+        "BackTab" => KeyCode::BackTab,
+        "Backspace" => KeyCode::Backspace,
+        "Delete" => KeyCode::Delete,
+        "Up" => KeyCode::Up,
+        "Down" => KeyCode::Down,
+        "Left" => KeyCode::Left,
+        "Right" => KeyCode::Right,
+        "Home" => KeyCode::Home,
+        "End" => KeyCode::End,
+        "PageUp" => KeyCode::PageUp,
+        "PageDown" => KeyCode::PageDown,
+        _ => {
+            let mut chars = rest.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                (Some('F'), Some(_)) => KeyCode::F(rest[1..].parse().ok()?),
+                _ => return None,
+            }
+        }
+    };
+    Some(KeyEvent::new(code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn test_events_round_trip() {
+        let events = [
+            key(KeyCode::Char('j'), KeyModifiers::NONE),
+            key(KeyCode::Char('K'), KeyModifiers::SHIFT),
+            key(KeyCode::Char('d'), KeyModifiers::CONTROL),
+            key(KeyCode::Char('-'), KeyModifiers::CONTROL),
+            key(KeyCode::Char(' '), KeyModifiers::NONE),
+            key(KeyCode::Enter, KeyModifiers::ALT),
+            key(KeyCode::F(12), KeyModifiers::NONE),
+            Event::Paste("a\\b\nc".to_string()),
+            Event::FocusLost,
+            Event::Resize(120, 40),
+        ];
+        for event in events {
+            let line = encode_event(&event).expect("event should encode");
+            assert_eq!(decode_event(&line), Some(event), "via `{line}`");
+        }
+    }
+
+    #[test]
+    fn test_parse_skips_comments_and_state() {
+        let text = "# tmui recording v1\n0 key j\n5 state Normal focus=Sessions selected=a\n\
+                    20 key C-c\ngarbage\n";
+        let events = parse(text);
+        assert_eq!(
+            events,
+            [
+                (Duration::ZERO, key(KeyCode::Char('j'), KeyModifiers::NONE)),
+                (
+                    Duration::from_millis(20),
+                    key(KeyCode::Char('c'), KeyModifiers::CONTROL)
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_headless_replay_ends_without_quit_key() {
+        let _tmux = crate::testing::FakeTmux::new()
+            .with_session("alpha")
+            .with_session("beta");
+        let mut app = App::new();
+        let source = ReplaySource {
+            events: parse("0 key j\n10 key ?\n"),
+            realtime: false,
+        };
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(100, 30))
+            .expect("test terminal should initialize");
+
+        tokio::time::timeout(
+            Duration::from_secs(10),
+            crate::event::run_with_sources(
+                &mut app,
+                &mut terminal,
+                vec![Box::new(source)],
+                |_, _| std::io::Error::other("no attach"),
+            ),
+        )
+        .await
+        .expect("replay should stop on its own")
+        .expect("event loop should finish");
+
+        assert_eq!(app.selected_session_name().as_deref(), Some("beta"));
+        assert!(screen_text(terminal.backend().buffer()).contains("Keybindings"));
+    }
+
+    #[test]
+    fn test_recorder_writes_input_and_state_changes() {
+        let path = std::env::temp_dir().join(format!("tmui-record-{}.txt", std::process::id()));
+        let mut recorder = Recorder::create(&path).expect("recording should be created");
+        let app = App::new();
+
+        recorder.record_event(&key(KeyCode::Char('j'), KeyModifiers::NONE));
+        recorder.record_state(&app);
+        recorder.record_state(&app);
+        drop(recorder);
+
+        let text = std::fs::read_to_string(&path).expect("recording should be readable");
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], HEADER);
+        assert!(lines[1].ends_with(" key j"));
+        assert!(lines[2].contains(" state Normal focus=Sessions"));
+        assert_eq!(lines.len(), 3, "unchanged state is not repeated");
+    }
+}
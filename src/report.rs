@@ -0,0 +1,242 @@
+//! `tmui report`: a Markdown summary of the server's sessions and windows,
+//! for pasting into handoff notes or incident timelines.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use crate::config::Config;
+use crate::types::{Session, Window};
+
+/// Report on `sessions` (by name) with their windows from `windows`, tags
+/// and groups from `config`, and times relative to `now`.
+pub fn markdown(
+    sessions: &[Session],
+    windows: &HashMap<String, Vec<Window>>,
+    config: &Config,
+    now: i64,
+) -> String {
+    let mut sessions: Vec<&Session> = sessions.iter().collect();
+    sessions.sort_by(|a, b| a.name.cmp(&b.name));
+    let window_count: usize = sessions
+        .iter()
+        .map(|s| windows.get(&s.name).map_or(s.windows, Vec::len))
+        .sum();
+    let attached = sessions.iter().filter(|s| s.attached > 0).count();
+
+    let mut out = String::from("# tmux report\n\n");
+    let _ = writeln!(
+        out,
+        "Generated {} · {} · {} · {attached} attached",
+        utc_timestamp(now),
+        plural(sessions.len(), "session"),
+        plural(window_count, "window"),
+    );
+    if sessions.is_empty() {
+        out.push_str("\nNo sessions running.\n");
+    }
+
+    for session in sessions {
+        let _ = writeln!(out, "\n## {}\n", session.name);
+        if !session.path.is_empty() {
+            let _ = writeln!(out, "- Path: `{}`", session.path);
+        }
+        if let Some(tags) = config.tags.get(&session.name).filter(|t| !t.is_empty()) {
+            let _ = writeln!(out, "- Tags: {}", tags.join(", "));
+        }
+        if let Some(group) = &session.group {
+            let _ = writeln!(out, "- Group: {group}");
+        }
+        let _ = writeln!(
+            out,
+            "- Uptime: {} (since {})",
+            format_duration(now - session.created),
+            utc_timestamp(session.created)
+        );
+        let last = if session.last_attached > 0 {
+            format!(
+                "last attached {} ago",
+                format_duration(now - session.last_attached)
+            )
+        } else {
+            "never attached".to_string()
+        };
+        let _ = writeln!(
+            out,
+            "- Attached: {}, {last}",
+            plural(session.attached, "client")
+        );
+
+        let Some(session_windows) = windows.get(&session.name).filter(|w| !w.is_empty()) else {
+            continue;
+        };
+        out.push_str("\n| # | Window | Command | Flags |\n|---|---|---|---|\n");
+        for window in session_windows {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                window.index,
+                cell(&window.name),
+                cell(&window.active_command),
+                cell(window.status_flags())
+            );
+        }
+    }
+    out
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+/// Table cell text with pipes escaped so they don't split the row.
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+/// Two largest units of a duration: `42s`, `5m 3s`, `3h 12m`, `2d 4h`.
+fn format_duration(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    let (days, hours) = (seconds / 86400, seconds % 86400 / 3600);
+    let (minutes, secs) = (seconds % 3600 / 60, seconds % 60);
+    match seconds {
+        0..=59 => format!("{secs}s"),
+        60..=3599 => format!("{minutes}m {secs}s"),
+        3600..=86399 => format!("{hours}h {minutes}m"),
+        _ => format!("{days}d {hours}h"),
+    }
+}
+
+/// `YYYY-MM-DD HH:MM UTC` for a Unix time.
+fn utc_timestamp(unix: i64) -> String {
+    let (year, month, day, secs) = civil(unix);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02} UTC",
+        secs / 3600,
+        secs % 3600 / 60
+    )
+}
+
+/// `YYYYMMDD-HHMMSS` in UTC, for file names that sort by time.
+pub fn file_stamp(unix: i64) -> String {
+    let (year, month, day, secs) = civil(unix);
+    format!(
+        "{year:04}{month:02}{day:02}-{:02}{:02}{:02}",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// UTC year, month, day and second of the day of a Unix time.
+fn civil(unix: i64) -> (i64, i64, i64, i64) {
+    let (days, secs) = (unix.div_euclid(86400), unix.rem_euclid(86400));
+    // Civil-from-days (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day, secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(name: &str, created: i64, attached: usize, last_attached: i64) -> Session {
+        Session {
+            id: "$0".to_string(),
+            name: name.to_string(),
+            windows: 1,
+            attached,
+            created,
+            last_attached,
+            group: None,
+            path: format!("/home/me/{name}"),
+            runner: false,
+            server: None,
+        }
+    }
+
+    fn window(index: usize, name: &str, command: &str, active: bool) -> Window {
+        Window {
+            id: format!("@{index}"),
+            session_id: "$0".to_string(),
+            index,
+            name: name.to_string(),
+            active,
+            active_command: command.to_string(),
+            pane_title: String::new(),
+            remote_host: None,
+            flags: if active { "*" } else { "" }.to_string(),
+            dead: false,
+            piped: false,
+            alternate: false,
+        }
+    }
+
+    #[test]
+    fn test_report_lists_sessions_windows_and_tags() {
+        let now = 1_700_000_000 + 3 * 3600 + 120;
+        let sessions = vec![
+            session("work", 1_700_000_000, 1, now - 300),
+            session("api", now - 42, 0, 0),
+        ];
+        let windows = HashMap::from([(
+            "work".to_string(),
+            vec![
+                window(0, "editor", "nvim", true),
+                window(1, "a|b", "cargo", false),
+            ],
+        )]);
+        let mut config = Config::default();
+        config.add_tag("work", "oncall");
+
+        let report = markdown(&sessions, &windows, &config, now);
+        assert_eq!(
+            report,
+            "# tmux report\n\
+             \n\
+             Generated 2023-11-15 01:15 UTC · 2 sessions · 3 windows · 1 attached\n\
+             \n\
+             ## api\n\
+             \n\
+             - Path: `/home/me/api`\n\
+             - Uptime: 42s (since 2023-11-15 01:14 UTC)\n\
+             - Attached: 0 clients, never attached\n\
+             \n\
+             ## work\n\
+             \n\
+             - Path: `/home/me/work`\n\
+             - Tags: oncall\n\
+             - Uptime: 3h 2m (since 2023-11-14 22:13 UTC)\n\
+             - Attached: 1 client, last attached 5m 0s ago\n\
+             \n\
+             | # | Window | Command | Flags |\n\
+             |---|---|---|---|\n\
+             | 0 | editor | nvim | * |\n\
+             | 1 | a\\|b | cargo |  |\n"
+        );
+    }
+
+    #[test]
+    fn test_report_without_sessions() {
+        let report = markdown(&[], &HashMap::new(), &Config::default(), 0);
+        assert!(report.contains("Generated 1970-01-01 00:00 UTC · 0 sessions · 0 windows"));
+        assert!(report.ends_with("No sessions running.\n"));
+    }
+
+    #[test]
+    fn test_file_stamp() {
+        assert_eq!(file_stamp(1_700_000_000), "20231114-221320");
+        assert_eq!(file_stamp(951_782_400), "20000229-000000");
+    }
+}
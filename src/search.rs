@@ -3,7 +3,7 @@
 use nucleo_matcher::pattern::{AtomKind, CaseMatching, Normalization, Pattern};
 use nucleo_matcher::{Config, Matcher, Utf32Str};
 
-use crate::types::Session;
+use crate::types::{Session, Window};
 
 /// Result of a fuzzy match: the session index, score, and matched char indices.
 #[derive(Debug, Clone)]
@@ -13,19 +13,94 @@ pub struct MatchResult {
     pub indices: Vec<u32>,
 }
 
+/// Result of a fuzzy match over a session's windows.
+#[derive(Debug, Clone)]
+pub struct WindowMatch {
+    pub window_index: usize,
+    pub score: u32,
+}
+
 /// Perform fuzzy matching of `query` against a list of sessions.
 /// Returns matched sessions sorted by score (highest first).
 /// Empty query returns all sessions with score 0.
-pub fn fuzzy_match_sessions(sessions: &[Session], query: &str) -> Vec<MatchResult> {
+/// Each session may contribute extra searchable text (e.g. ssh hosts of
+/// its windows) after its name. Match indices past the name are harmless
+/// for highlighting.
+pub fn fuzzy_match_sessions_with<F>(sessions: &[Session], query: &str, extra: F) -> Vec<MatchResult>
+where
+    F: Fn(&Session) -> Option<String>,
+{
+    let haystacks = sessions.iter().map(|s| match extra(s) {
+        Some(extra) => format!("{} {extra}", s.name),
+        None => s.name.clone(),
+    });
+    let mut results: Vec<MatchResult> = fuzzy_match(haystacks, query)
+        .into_iter()
+        .map(|(session_index, score, indices)| MatchResult {
+            session_index,
+            score,
+            indices,
+        })
+        .collect();
+    results.sort_by_key(|r| std::cmp::Reverse(r.score));
+    results
+}
+
+/// Add `bonus(session_index)` to each score and re-sort, highest first;
+/// ties keep their order.
+pub fn boost_sessions<F>(results: &mut [MatchResult], bonus: F)
+where
+    F: Fn(usize) -> u32,
+{
+    for result in results.iter_mut() {
+        result.score += bonus(result.session_index);
+    }
+    results.sort_by_key(|r| std::cmp::Reverse(r.score));
+}
+
+/// Fuzzy match `query` against window names, running commands, titles and
+/// ssh hosts.
+/// Same ordering and empty-query behavior as [`fuzzy_match_sessions_with`].
+pub fn fuzzy_match_windows(windows: &[Window], query: &str) -> Vec<WindowMatch> {
+    let haystacks = windows.iter().map(|w| {
+        format!(
+            "{} {} {} {}",
+            w.name,
+            w.active_command,
+            w.pane_title,
+            w.remote_host.as_deref().unwrap_or_default()
+        )
+    });
+    let mut results: Vec<WindowMatch> = fuzzy_match(haystacks, query)
+        .into_iter()
+        .map(|(window_index, score, _)| WindowMatch {
+            window_index,
+            score,
+        })
+        .collect();
+    results.sort_by_key(|r| std::cmp::Reverse(r.score));
+    results
+}
+
+/// Fuzzy match `query` against plain labels, returning label indices
+/// sorted by score (highest first); an empty query keeps the input order.
+pub fn fuzzy_match_labels(labels: &[&str], query: &str) -> Vec<usize> {
+    let mut results = fuzzy_match(labels.iter(), query);
+    results.sort_by_key(|(_, score, _)| std::cmp::Reverse(*score));
+    results.into_iter().map(|(index, _, _)| index).collect()
+}
+
+/// Score each haystack against `query`, returning `(index, score, indices)`
+/// for every match in input order.
+fn fuzzy_match<I, S>(haystacks: I, query: &str) -> Vec<(usize, u32, Vec<u32>)>
+where
+    I: Iterator<Item = S>,
+    S: AsRef<str>,
+{
     if query.is_empty() {
-        return sessions
-            .iter()
+        return haystacks
             .enumerate()
-            .map(|(i, _)| MatchResult {
-                session_index: i,
-                score: 0,
-                indices: Vec::new(),
-            })
+            .map(|(i, _)| (i, 0, Vec::new()))
             .collect();
     }
 
@@ -37,24 +112,19 @@ pub fn fuzzy_match_sessions(sessions: &[Session], query: &str) -> Vec<MatchResul
         AtomKind::Fuzzy,
     );
 
-    let mut results: Vec<MatchResult> = Vec::new();
+    let mut results = Vec::new();
     let mut buf = Vec::new();
 
-    for (i, session) in sessions.iter().enumerate() {
-        let haystack = Utf32Str::new(&session.name, &mut buf);
+    for (i, text) in haystacks.enumerate() {
+        let haystack = Utf32Str::new(text.as_ref(), &mut buf);
         let mut indices = Vec::new();
         if let Some(score) = pattern.indices(haystack, &mut matcher, &mut indices) {
             indices.sort_unstable();
             indices.dedup();
-            results.push(MatchResult {
-                session_index: i,
-                score,
-                indices,
-            });
+            results.push((i, score, indices));
         }
     }
 
-    results.sort_by(|a, b| b.score.cmp(&a.score));
     results
 }
 
@@ -74,6 +144,8 @@ mod tests {
             last_attached: 0,
             group: None,
             path: "/tmp".to_string(),
+            runner: false,
+            server: None,
         }
     }
 
@@ -85,7 +157,7 @@ mod tests {
             make_session("dev"),
         ];
 
-        let results = fuzzy_match_sessions(&sessions, "work");
+        let results = fuzzy_match_sessions_with(&sessions, "work", |_| None);
         assert!(!results.is_empty(), "exact match should return results");
         assert_eq!(
             sessions[results[0].session_index].name, "work",
@@ -105,7 +177,7 @@ mod tests {
             make_session("dev"),
         ];
 
-        let results = fuzzy_match_sessions(&sessions, "wrk");
+        let results = fuzzy_match_sessions_with(&sessions, "wrk", |_| None);
         assert!(
             !results.is_empty(),
             "partial match 'wrk' should match 'work'"
@@ -126,7 +198,7 @@ mod tests {
             make_session("gamma"),
         ];
 
-        let results = fuzzy_match_sessions(&sessions, "");
+        let results = fuzzy_match_sessions_with(&sessions, "", |_| None);
         assert_eq!(results.len(), 3, "empty query should return all sessions");
         for r in &results {
             assert_eq!(r.score, 0, "empty query score should be 0");
@@ -141,7 +213,7 @@ mod tests {
             make_session("dev"),
         ];
 
-        let results = fuzzy_match_sessions(&sessions, "xyz123");
+        let results = fuzzy_match_sessions_with(&sessions, "xyz123", |_| None);
         assert!(
             results.is_empty(),
             "query 'xyz123' should match nothing, got {} results",
@@ -157,7 +229,7 @@ mod tests {
             make_session("개발서버"),
         ];
 
-        let results = fuzzy_match_sessions(&sessions, "데모");
+        let results = fuzzy_match_sessions_with(&sessions, "데모", |_| None);
         assert!(
             !results.is_empty(),
             "Korean query '데모' should match '데모세션'"
@@ -175,7 +247,7 @@ mod tests {
             .collect();
 
         let start = Instant::now();
-        let _results = fuzzy_match_sessions(&sessions, "sess42");
+        let _results = fuzzy_match_sessions_with(&sessions, "sess42", |_| None);
         let elapsed = start.elapsed();
 
         assert!(
@@ -188,7 +260,7 @@ mod tests {
     #[test]
     fn test_fuzzy_match_indices_returned() {
         let sessions = vec![make_session("work")];
-        let results = fuzzy_match_sessions(&sessions, "wk");
+        let results = fuzzy_match_sessions_with(&sessions, "wk", |_| None);
         assert!(!results.is_empty());
         let indices = &results[0].indices;
         assert!(
@@ -205,10 +277,55 @@ mod tests {
         );
     }
 
+    fn make_window(index: usize, name: &str, command: &str) -> Window {
+        Window {
+            id: format!("@{index}"),
+            session_id: "$0".to_string(),
+            index,
+            name: name.to_string(),
+            active: false,
+            active_command: command.to_string(),
+            pane_title: String::new(),
+            remote_host: None,
+            flags: String::new(),
+            dead: false,
+            piped: false,
+            alternate: false,
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_match_windows_by_name_and_command() {
+        let windows = vec![
+            make_window(0, "editor", "nvim"),
+            make_window(1, "server", "cargo"),
+            make_window(2, "shell", "zsh"),
+        ];
+
+        let by_name = fuzzy_match_windows(&windows, "serv");
+        assert_eq!(by_name[0].window_index, 1);
+
+        let by_command = fuzzy_match_windows(&windows, "nvim");
+        assert_eq!(by_command.len(), 1);
+        assert_eq!(by_command[0].window_index, 0);
+
+        assert_eq!(fuzzy_match_windows(&windows, "").len(), 3);
+    }
+
+    #[test]
+    fn test_fuzzy_match_sessions_with_extra_text() {
+        let sessions = vec![make_session("work"), make_session("ops")];
+        let results = fuzzy_match_sessions_with(&sessions, "prod-db", |s| {
+            (s.name == "ops").then(|| "prod-db".to_string())
+        });
+        assert_eq!(results.len(), 1);
+        assert_eq!(sessions[results[0].session_index].name, "ops");
+    }
+
     #[test]
     fn test_fuzzy_case_insensitive() {
         let sessions = vec![make_session("WorkStation"), make_session("dev")];
-        let results = fuzzy_match_sessions(&sessions, "work");
+        let results = fuzzy_match_sessions_with(&sessions, "work", |_| None);
         assert!(
             !results.is_empty(),
             "case-insensitive match: 'work' should match 'WorkStation'"
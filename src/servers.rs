@@ -0,0 +1,98 @@
+//! Stray server discovery: tmux sockets in the per-user socket directory,
+//! including ones left behind by a server that died.
+
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use tokio::process::Command;
+
+/// A socket file found in the socket directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerSocket {
+    pub path: PathBuf,
+    /// Whether a server still accepts connections on it.
+    pub alive: bool,
+}
+
+impl ServerSocket {
+    /// The `-L` name of the socket.
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// `$TMUX_TMPDIR/tmux-<uid>` (or under `/tmp`), where tmux puts `-L`
+/// sockets.
+pub async fn socket_dir() -> Option<PathBuf> {
+    let output = Command::new("id")
+        .arg("-u")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .ok()?;
+    let uid = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if uid.is_empty() {
+        return None;
+    }
+    let base = std::env::var_os("TMUX_TMPDIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/tmp"));
+    Some(base.join(format!("tmux-{uid}")))
+}
+
+/// Sockets in `dir`, sorted by name; a socket is alive when connecting to
+/// it succeeds.
+pub fn scan(dir: &Path) -> Vec<ServerSocket> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut sockets: Vec<ServerSocket> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_socket()))
+        .map(|entry| {
+            let path = entry.path();
+            let alive = UnixStream::connect(&path).is_ok();
+            ServerSocket { path, alive }
+        })
+        .collect();
+    sockets.sort_by(|a, b| a.path.cmp(&b.path));
+    sockets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+
+    #[test]
+    fn test_scan_tells_live_from_dead_sockets() {
+        let dir = std::env::temp_dir().join(format!("tmui-servers-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("socket dir should be created");
+
+        let _live = UnixListener::bind(dir.join("work")).expect("live socket should bind");
+        drop(UnixListener::bind(dir.join("crashed")).expect("dead socket should bind"));
+        std::fs::write(dir.join("notes"), "not a socket").expect("file should be written");
+
+        let sockets = scan(&dir);
+        let summary: Vec<(String, bool)> = sockets.iter().map(|s| (s.name(), s.alive)).collect();
+        assert_eq!(
+            summary,
+            [("crashed".to_string(), false), ("work".to_string(), true)]
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_scan_missing_dir_is_empty() {
+        assert!(scan(Path::new("/nonexistent/tmui-sockets")).is_empty());
+    }
+}
@@ -0,0 +1,268 @@
+//! Server snapshots saved when tmui exits or attaches, compared with the
+//! live server on `E` to show what changed in between: sessions created,
+//! killed or renamed and windows opened, closed or renamed. Sessions are
+//! matched by id and creation time, so a restarted server reusing ids does
+//! not pass for renames.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::tmux::Socket;
+use crate::types::{AppResult, Session, Window};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Unix time the snapshot was taken.
+    pub taken: i64,
+    #[serde(default)]
+    pub sessions: Vec<SnapshotSession>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotSession {
+    pub id: String,
+    pub created: i64,
+    pub name: String,
+    #[serde(default)]
+    pub windows: Vec<SnapshotWindow>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotWindow {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    SessionCreated(String),
+    SessionKilled(String),
+    SessionRenamed {
+        from: String,
+        to: String,
+    },
+    WindowOpened {
+        session: String,
+        window: String,
+    },
+    WindowClosed {
+        session: String,
+        window: String,
+    },
+    WindowRenamed {
+        session: String,
+        from: String,
+        to: String,
+    },
+}
+
+impl Change {
+    pub fn describe(&self) -> String {
+        match self {
+            Change::SessionCreated(name) => format!("+ session `{name}` created"),
+            Change::SessionKilled(name) => format!("- session `{name}` killed"),
+            Change::SessionRenamed { from, to } => format!("~ session `{from}` renamed to `{to}`"),
+            Change::WindowOpened { session, window } => {
+                format!("+ window `{window}` opened in `{session}`")
+            }
+            Change::WindowClosed { session, window } => {
+                format!("- window `{window}` closed in `{session}`")
+            }
+            Change::WindowRenamed { session, from, to } => {
+                format!("~ window `{from}` in `{session}` renamed to `{to}`")
+            }
+        }
+    }
+}
+
+impl Snapshot {
+    /// Snapshot of `sessions` with their windows from `windows`, keyed by
+    /// session name.
+    pub fn capture(sessions: &[Session], windows: &HashMap<String, Vec<Window>>, now: i64) -> Self {
+        let sessions = sessions
+            .iter()
+            .filter(|session| session.server.is_none())
+            .map(|session| SnapshotSession {
+                id: session.id.clone(),
+                created: session.created,
+                name: session.name.clone(),
+                windows: windows
+                    .get(&session.name)
+                    .map_or(&[][..], Vec::as_slice)
+                    .iter()
+                    .map(|window| SnapshotWindow {
+                        id: window.id.clone(),
+                        name: window.name.clone(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        Snapshot {
+            taken: now,
+            sessions,
+        }
+    }
+
+    /// The snapshot saved at `path`; `None` when missing or unreadable.
+    pub fn load(path: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(path).ok()?;
+        toml::from_str(&content).ok()
+    }
+
+    pub fn save(&self, path: &Path) -> AppResult<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// What happened between `self` and the later snapshot `now`:
+    /// session changes first, then window changes, in list order.
+    pub fn changes_to(&self, now: &Snapshot) -> Vec<Change> {
+        let key = |s: &SnapshotSession| (s.id.clone(), s.created);
+        let before: HashMap<_, _> = self.sessions.iter().map(|s| (key(s), s)).collect();
+        let after: HashMap<_, _> = now.sessions.iter().map(|s| (key(s), s)).collect();
+
+        let mut changes = Vec::new();
+        for session in &now.sessions {
+            match before.get(&key(session)) {
+                None => changes.push(Change::SessionCreated(session.name.clone())),
+                Some(old) if old.name != session.name => changes.push(Change::SessionRenamed {
+                    from: old.name.clone(),
+                    to: session.name.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+        for session in &self.sessions {
+            if !after.contains_key(&key(session)) {
+                changes.push(Change::SessionKilled(session.name.clone()));
+            }
+        }
+
+        for session in &now.sessions {
+            let Some(old) = before.get(&key(session)) else {
+                continue;
+            };
+            let name = &session.name;
+            for window in &session.windows {
+                match old.windows.iter().find(|w| w.id == window.id) {
+                    None => changes.push(Change::WindowOpened {
+                        session: name.clone(),
+                        window: window.name.clone(),
+                    }),
+                    Some(was) if was.name != window.name => changes.push(Change::WindowRenamed {
+                        session: name.clone(),
+                        from: was.name.clone(),
+                        to: window.name.clone(),
+                    }),
+                    Some(_) => {}
+                }
+            }
+            for window in &old.windows {
+                if !session.windows.iter().any(|w| w.id == window.id) {
+                    changes.push(Change::WindowClosed {
+                        session: name.clone(),
+                        window: window.name.clone(),
+                    });
+                }
+            }
+        }
+        changes
+    }
+}
+
+/// Where snapshots are kept: the XDG state directory.
+pub fn default_dir() -> PathBuf {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("tmui")
+}
+
+/// Snapshot file in `dir` for the server on `socket`, one per socket.
+pub fn path_in(dir: &Path, socket: Option<&Socket>) -> PathBuf {
+    let file = match socket {
+        Some(Socket::Name(name)) => format!("snapshot-{name}.toml"),
+        Some(Socket::Path(path)) => format!(
+            "snapshot-{}.toml",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        ),
+        None => "snapshot.toml".to_string(),
+    };
+    dir.join(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, created: i64, name: &str, windows: &[(&str, &str)]) -> SnapshotSession {
+        SnapshotSession {
+            id: id.to_string(),
+            created,
+            name: name.to_string(),
+            windows: windows
+                .iter()
+                .map(|(id, name)| SnapshotWindow {
+                    id: id.to_string(),
+                    name: name.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_changes_between_snapshots() {
+        let before = Snapshot {
+            taken: 100,
+            sessions: vec![
+                session("$0", 1, "work", &[("@0", "vim"), ("@1", "logs")]),
+                session("$1", 2, "old", &[]),
+                session("$2", 3, "ci", &[]),
+            ],
+        };
+        let now = Snapshot {
+            taken: 200,
+            sessions: vec![
+                session("$0", 1, "work", &[("@0", "editor"), ("@5", "db")]),
+                session("$2", 3, "build", &[]),
+                session("$1", 150, "fresh", &[]),
+            ],
+        };
+        let changes: Vec<String> = before
+            .changes_to(&now)
+            .iter()
+            .map(Change::describe)
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                "~ session `ci` renamed to `build`",
+                "+ session `fresh` created",
+                "- session `old` killed",
+                "~ window `vim` in `work` renamed to `editor`",
+                "+ window `db` opened in `work`",
+                "- window `logs` closed in `work`",
+            ]
+        );
+        assert!(now.changes_to(&now).is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let dir = std::env::temp_dir().join(format!("tmui-snapshot-{}", std::process::id()));
+        let path = dir.join("snapshot.toml");
+        let snapshot = Snapshot {
+            taken: 7,
+            sessions: vec![session("$0", 1, "work", &[("@0", "vim")])],
+        };
+        snapshot.save(&path).expect("snapshot should save");
+        assert_eq!(Snapshot::load(&path), Some(snapshot));
+        let _ = std::fs::remove_dir_all(dir);
+        assert_eq!(Snapshot::load(&path), None);
+    }
+}
@@ -0,0 +1,129 @@
+//! Remote host detection for panes running `ssh`.
+
+use std::path::Path;
+
+/// ssh options that consume the following argument.
+const OPTIONS_WITH_VALUE: &str = "BbcDEeFIiJLlmOoPpQRSWw";
+
+/// Destination of the ssh process at `pane_pid` or among its children,
+/// read from the local /proc.
+pub fn host_from_pid(pane_pid: u32) -> Option<String> {
+    ssh_args_for(pane_pid).and_then(|args| host_from_args(&args))
+}
+
+/// Extract the destination host from ssh arguments (without `argv[0]`).
+pub fn host_from_args(args: &[String]) -> Option<String> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            return iter.next().map(|dest| strip_destination(dest));
+        }
+        if let Some(flags) = arg.strip_prefix('-') {
+            // `-p22` carries its value inline; `-p 22` consumes the next arg.
+            let takes_value = flags
+                .chars()
+                .position(|c| OPTIONS_WITH_VALUE.contains(c))
+                .is_some_and(|pos| pos == flags.len() - 1);
+            if takes_value {
+                iter.next();
+            }
+            continue;
+        }
+        return Some(strip_destination(arg));
+    }
+    None
+}
+
+/// Parse a host out of titles like `deploy@prod-db: ~/app` or `prod-db`,
+/// which many shells set over ssh. Only for titles a program set: tmux's
+/// default title is the local hostname.
+pub fn host_from_title(title: &str) -> Option<String> {
+    let title = title.trim();
+    let host = match title.split_once('@') {
+        Some((_, rest)) => rest.split([':', ' ']).next().unwrap_or_default(),
+        None if !title.contains(char::is_whitespace) => title,
+        None => return None,
+    };
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+/// `ssh://user@host:22` or `user@host` to `host`.
+fn strip_destination(dest: &str) -> String {
+    let dest = dest.strip_prefix("ssh://").unwrap_or(dest);
+    let host = dest.rsplit_once('@').map_or(dest, |(_, host)| host);
+    let host = host.split(':').next().unwrap_or(host);
+    host.to_string()
+}
+
+/// Arguments of the ssh process at `pid` or among its direct children.
+fn ssh_args_for(pid: u32) -> Option<Vec<String>> {
+    let proc_dir = Path::new("/proc");
+    let mut candidates = vec![pid];
+    if let Ok(children) =
+        std::fs::read_to_string(proc_dir.join(format!("{pid}/task/{pid}/children")))
+    {
+        candidates.extend(
+            children
+                .split_whitespace()
+                .filter_map(|c| c.parse::<u32>().ok()),
+        );
+    }
+
+    candidates.into_iter().find_map(|pid| {
+        let raw = std::fs::read(proc_dir.join(format!("{pid}/cmdline"))).ok()?;
+        let mut args = raw
+            .split(|&b| b == 0)
+            .filter(|a| !a.is_empty())
+            .map(|a| String::from_utf8_lossy(a).into_owned());
+        let program = args.next()?;
+        let is_ssh = Path::new(&program)
+            .file_name()
+            .is_some_and(|name| name == "ssh");
+        is_ssh.then(|| args.collect())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_host_from_args() {
+        assert_eq!(host_from_args(&args(&["prod-db"])), Some("prod-db".into()));
+        assert_eq!(
+            host_from_args(&args(&[
+                "-p",
+                "2222",
+                "-i",
+                "key",
+                "deploy@prod-db",
+                "uptime"
+            ])),
+            Some("prod-db".into())
+        );
+        assert_eq!(
+            host_from_args(&args(&["-p2222", "-A", "ssh://me@bastion:22"])),
+            Some("bastion".into())
+        );
+        assert_eq!(
+            host_from_args(&args(&["-tt", "-o", "ProxyJump=x", "web1"])),
+            Some("web1".into())
+        );
+        assert_eq!(host_from_args(&args(&["-v"])), None);
+    }
+
+    #[test]
+    fn test_host_from_title() {
+        assert_eq!(
+            host_from_title("deploy@prod-db: ~/app"),
+            Some("prod-db".into())
+        );
+        assert_eq!(host_from_title("prod-db"), Some("prod-db".into()));
+        assert_eq!(host_from_title("vim main.rs"), None);
+        assert_eq!(host_from_title(""), None);
+    }
+}
@@ -0,0 +1,282 @@
+//! Test-only harness: a scripted fake `tmux` binary and helpers to drive
+//! the full event loop against a `TestBackend`.
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+use tokio::sync::mpsc;
+
+use crate::app::App;
+use crate::event::{self, AppEvent, EventSource};
+use crate::tmux;
+
+/// Shell shim answering tmux commands from files in its directory and
+/// logging every invocation to `calls`.
+const FAKE_TMUX_SCRIPT: &str = r#"#!/bin/sh
+dir="$(dirname "$0")"
+server=""
+while [ $# -gt 0 ]; do
+  case "$1" in
+    -L|-S) server="$(basename "$2")"; shift 2 ;;
+    *) break ;;
+  esac
+done
+echo "${server:+-L $server }$*" >> "$dir/calls"
+cmd="$1"
+if [ -f "$dir/fail-$cmd" ]; then
+  cat "$dir/fail-$cmd" >&2
+  exit 1
+fi
+case "$cmd" in
+  list-sessions) cat "$dir/sessions${server:+-$server}" 2>/dev/null ;;
+  list-windows) cat "$dir/windows${server:+-$server}" 2>/dev/null ;;
+  list-clients) cat "$dir/clients" 2>/dev/null ;;
+  list-panes) cat "$dir/panes" 2>/dev/null ;;
+  detach-client) [ "$2" = "-t" ] && grep -v "^$3" "$dir/clients" > "$dir/clients.new"; mv "$dir/clients.new" "$dir/clients" 2>/dev/null ;;
+  capture-pane) cat "$dir/capture" 2>/dev/null ;;
+  show-options) cat "$dir/options" 2>/dev/null ;;
+  list-buffers) cat "$dir/buffers" 2>/dev/null ;;
+  new-window) echo @9 ;;
+  has-session)
+    name="${3#=}"
+    sep="$(printf '\001')"
+    grep -q "$sep$name$sep" "$dir/sessions" 2>/dev/null || {
+      echo "can't find session: $name" >&2
+      exit 1
+    } ;;
+  show-buffer) cat "$dir/buffer-$3" ;;
+  delete-buffer)
+    grep -v "^$3$(printf '\001')" "$dir/buffers" > "$dir/buffers.new"
+    mv "$dir/buffers.new" "$dir/buffers" ;;
+  set-option)
+    shift
+    [ "$1" = "-w" ] && shift
+    sed "s/^$3\*\{0,1\} .*/$3 $4/" "$dir/options" > "$dir/options.new"
+    mv "$dir/options.new" "$dir/options" ;;
+  display-message)
+    case "$*" in
+      *client_session*) cat "$dir/current" 2>/dev/null ;;
+      *) echo 3.4 ;;
+    esac ;;
+  -V) echo "tmux 3.4" ;;
+esac
+exit 0
+"#;
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static ATTACHES: std::cell::RefCell<Vec<String>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// Attach stand-in that records the target (prefixed with `-r ` when
+/// read-only) and fails like a missing exec.
+fn fake_attach(target: &str, read_only: bool) -> std::io::Error {
+    let target = if read_only {
+        format!("-r {target}")
+    } else {
+        target.to_string()
+    };
+    ATTACHES.with(|attaches| attaches.borrow_mut().push(target));
+    std::io::Error::new(std::io::ErrorKind::NotFound, "exec disabled in tests")
+}
+
+/// Targets passed to the outside-tmux attach on this thread so far.
+pub fn attempted_attaches() -> Vec<String> {
+    ATTACHES.with(|attaches| attaches.borrow().clone())
+}
+
+/// A fake tmux installed for the current test thread. Dropping it removes
+/// the override and its directory.
+pub struct FakeTmux {
+    dir: PathBuf,
+    sessions: Vec<String>,
+}
+
+impl FakeTmux {
+    pub fn new() -> Self {
+        let dir = std::env::temp_dir().join(format!(
+            "tmui-fake-tmux-{}-{}",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir).expect("fake tmux dir should be created");
+        let binary = dir.join("tmux");
+        fs::write(&binary, FAKE_TMUX_SCRIPT).expect("fake tmux should be written");
+        fs::set_permissions(&binary, fs::Permissions::from_mode(0o755))
+            .expect("fake tmux should be executable");
+        tmux::TEST_BINARY.with(|b| *b.borrow_mut() = Some(binary));
+        Self {
+            dir,
+            sessions: Vec::new(),
+        }
+    }
+
+    /// Add a detached session with a single `zsh` window.
+    pub fn with_session(self, name: &str) -> Self {
+        self.add_session("", name, 0)
+    }
+
+    /// Like [`FakeTmux::with_session`], with one client attached.
+    pub fn with_attached_session(self, name: &str) -> Self {
+        self.add_session("", name, 1)
+    }
+
+    /// Like [`FakeTmux::with_session`], on the server of socket `server`
+    /// (`-L`/`-S`) instead of the default one.
+    pub fn with_server_session(self, server: &str, name: &str) -> Self {
+        self.add_session(&format!("-{server}"), name, 0)
+    }
+
+    fn add_session(mut self, suffix: &str, name: &str, attached: u8) -> Self {
+        let id = self.sessions.len();
+        self.sessions.push(name.to_string());
+        self.append(
+            &format!("sessions{suffix}"),
+            &format!(
+                "${id}\x01{name}\x011\x01{attached}\x011700000000\x011700000000\x01\x01/tmp\x01"
+            ),
+        );
+        self.append(
+            &format!("windows{suffix}"),
+            &format!(
+                "{name}\x01@{id}\x01${id}\x010\x01zsh\x011\x01zsh\x01host\x01host\x010\x01*\x010\x010\x010"
+            ),
+        );
+        self
+    }
+
+    /// Add a 120x40 client on `tty` attached to `session`.
+    pub fn with_client(self, session: &str, tty: &str) -> Self {
+        self.append(
+            "clients",
+            &format!("{tty}\x01{session}\x01120\x0140\x011700000000\x011700000000\x010"),
+        );
+        self
+    }
+
+    /// Add pane `index` running `command` to window `window_id`; pane 0 is
+    /// the active one.
+    pub fn with_pane(self, window_id: &str, index: usize, command: &str) -> Self {
+        self.add_pane(window_id, index, command, false)
+    }
+
+    /// Like [`FakeTmux::with_pane`], for a pane whose command has exited.
+    pub fn with_dead_pane(self, window_id: &str, index: usize, command: &str) -> Self {
+        self.add_pane(window_id, index, command, true)
+    }
+
+    fn add_pane(self, window_id: &str, index: usize, command: &str, dead: bool) -> Self {
+        let active = u8::from(index == 0);
+        let dead = u8::from(dead);
+        self.append(
+            "panes",
+            &format!("%{index}\x01{window_id}\x01$0\x01{index}\x01{active}\x01{command}\x01/tmp\x01host\x01host\x01{dead}"),
+        );
+        self
+    }
+
+    /// Session reported as the one tmui's own client is on.
+    pub fn with_current(self, session: &str) -> Self {
+        fs::write(self.dir.join("current"), session).expect("current should be written");
+        self
+    }
+
+    /// Add a `show-options` line, e.g. `monitor-activity* on`; `set-option`
+    /// rewrites it in place.
+    pub fn with_option(self, line: &str) -> Self {
+        self.append("options", line);
+        self
+    }
+
+    /// Add paste buffer `name` holding `content`, listed after earlier ones.
+    pub fn with_buffer(self, name: &str, content: &str) -> Self {
+        self.append(
+            "buffers",
+            &format!("{name}\x01{}\x011700000000\x01{content}", content.len()),
+        );
+        fs::write(self.dir.join(format!("buffer-{name}")), content)
+            .expect("buffer should be written");
+        self
+    }
+
+    /// Contents returned by `capture-pane`.
+    pub fn with_capture(self, content: &str) -> Self {
+        fs::write(self.dir.join("capture"), content).expect("capture should be written");
+        self
+    }
+
+    /// Make every `command` invocation fail with `stderr`.
+    pub fn failing(self, command: &str, stderr: &str) -> Self {
+        fs::write(self.dir.join(format!("fail-{command}")), stderr)
+            .expect("failure should be written");
+        self
+    }
+
+    /// Arguments of every invocation so far, one line per call.
+    pub fn calls(&self) -> Vec<String> {
+        fs::read_to_string(self.dir.join("calls"))
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn append(&self, file: &str, line: &str) {
+        let path = self.dir.join(file);
+        let mut content = fs::read_to_string(&path).unwrap_or_default();
+        content.push_str(line);
+        content.push('\n');
+        fs::write(path, content).expect("fake state should be written");
+    }
+}
+
+impl Drop for FakeTmux {
+    fn drop(&mut self) {
+        tmux::TEST_BINARY.with(|b| *b.borrow_mut() = None);
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Terminal input replayed in order, then silence.
+pub struct ScriptedSource(pub Vec<Event>);
+
+impl EventSource for ScriptedSource {
+    fn spawn(self: Box<Self>, sender: mpsc::UnboundedSender<AppEvent>) {
+        for event in self.0 {
+            if sender.send(AppEvent::Terminal(Ok(event))).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+pub fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+pub fn char_keys(text: &str) -> Vec<Event> {
+    text.chars().map(|c| key(KeyCode::Char(c))).collect()
+}
+
+/// Run the real event loop over `events` (which should end by quitting)
+/// and return the final screen as text.
+pub async fn run_scripted(app: &mut App, events: Vec<Event>) -> String {
+    let mut terminal =
+        Terminal::new(TestBackend::new(100, 30)).expect("test terminal should initialize");
+    let sources: Vec<Box<dyn EventSource>> = vec![Box::new(ScriptedSource(events))];
+    tokio::time::timeout(
+        std::time::Duration::from_secs(10),
+        event::run_with_sources(app, &mut terminal, sources, fake_attach),
+    )
+    .await
+    .expect("script should end by quitting")
+    .expect("event loop should finish");
+
+    crate::replay::screen_text(terminal.backend().buffer())
+}
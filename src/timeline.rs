@@ -0,0 +1,261 @@
+//! Per-session activity timeline, assembled from what tmui sees change
+//! between refreshes: clients attaching and detaching, renames, windows
+//! opening and closing. Sessions are keyed by id so a rename keeps its
+//! history. Times tmux reports itself (creation, last attach) seed the
+//! timeline when a session is first seen.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::types::{Session, Window};
+
+/// Events kept per session; older ones are dropped.
+const MAX_EVENTS: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventKind {
+    Created,
+    /// When tmux last saw a client attach, as of the first refresh.
+    LastAttached,
+    /// A client attached; `clients` is the new count.
+    Attached {
+        clients: usize,
+    },
+    Detached {
+        clients: usize,
+    },
+    Renamed {
+        from: String,
+    },
+    WindowOpened {
+        index: usize,
+        name: String,
+    },
+    WindowClosed {
+        index: usize,
+        name: String,
+    },
+}
+
+impl EventKind {
+    pub fn describe(&self) -> String {
+        match self {
+            EventKind::Created => "created".to_string(),
+            EventKind::LastAttached => "last attached".to_string(),
+            EventKind::Attached { clients } => format!("client attached ({clients} attached)"),
+            EventKind::Detached { clients } => format!("client detached ({clients} attached)"),
+            EventKind::Renamed { from } => format!("renamed from `{from}`"),
+            EventKind::WindowOpened { index, name } => format!("window {index} `{name}` opened"),
+            EventKind::WindowClosed { index, name } => format!("window {index} `{name}` closed"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    /// Unix time.
+    pub time: i64,
+    pub kind: EventKind,
+}
+
+#[derive(Debug, Default)]
+pub struct Timeline {
+    events: HashMap<String, VecDeque<Event>>,
+    /// Last seen name and client count per session id.
+    sessions: HashMap<String, (String, usize)>,
+    /// Last seen windows per session id, by window id.
+    windows: HashMap<String, HashMap<String, (usize, String)>>,
+}
+
+impl Timeline {
+    /// Compare a fresh session list with the previous one. Sessions seen
+    /// for the first time are seeded from tmux's own timestamps.
+    pub fn observe_sessions(&mut self, sessions: &[Session], now: i64) {
+        let mut seen = HashMap::with_capacity(sessions.len());
+        for session in sessions {
+            match self.sessions.get(&session.id).cloned() {
+                None => {
+                    self.push(&session.id, session.created, EventKind::Created);
+                    if session.last_attached > session.created {
+                        self.push(&session.id, session.last_attached, EventKind::LastAttached);
+                    }
+                }
+                Some((name, clients)) => {
+                    if name != session.name {
+                        let kind = EventKind::Renamed { from: name };
+                        self.push(&session.id, now, kind);
+                    }
+                    let now_clients = session.attached;
+                    if now_clients > clients {
+                        self.push(
+                            &session.id,
+                            now,
+                            EventKind::Attached {
+                                clients: now_clients,
+                            },
+                        );
+                    } else if now_clients < clients {
+                        self.push(
+                            &session.id,
+                            now,
+                            EventKind::Detached {
+                                clients: now_clients,
+                            },
+                        );
+                    }
+                }
+            }
+            seen.insert(session.id.clone(), (session.name.clone(), session.attached));
+        }
+        self.events.retain(|id, _| seen.contains_key(id));
+        self.windows.retain(|id, _| seen.contains_key(id));
+        self.sessions = seen;
+    }
+
+    /// Compare the windows of session `session_id` with the previous list;
+    /// the first list of a session only sets the baseline.
+    pub fn observe_windows(&mut self, session_id: &str, windows: &[Window], now: i64) {
+        let current: HashMap<String, (usize, String)> = windows
+            .iter()
+            .map(|w| (w.id.clone(), (w.index, w.name.clone())))
+            .collect();
+        let Some(previous) = self.windows.insert(session_id.to_string(), current.clone()) else {
+            return;
+        };
+        let mut changes: Vec<EventKind> = current
+            .iter()
+            .filter(|(id, _)| !previous.contains_key(*id))
+            .map(|(_, (index, name))| EventKind::WindowOpened {
+                index: *index,
+                name: name.clone(),
+            })
+            .chain(
+                previous
+                    .iter()
+                    .filter(|(id, _)| !current.contains_key(*id))
+                    .map(|(_, (index, name))| EventKind::WindowClosed {
+                        index: *index,
+                        name: name.clone(),
+                    }),
+            )
+            .collect();
+        changes.sort_by_key(|kind| match kind {
+            EventKind::WindowOpened { index, .. } | EventKind::WindowClosed { index, .. } => *index,
+            _ => 0,
+        });
+        for kind in changes {
+            self.push(session_id, now, kind);
+        }
+    }
+
+    /// Events of session `session_id`, oldest first.
+    pub fn events(&self, session_id: &str) -> Vec<&Event> {
+        self.events
+            .get(session_id)
+            .map(|events| events.iter().collect())
+            .unwrap_or_default()
+    }
+
+    fn push(&mut self, session_id: &str, time: i64, kind: EventKind) {
+        let events = self.events.entry(session_id.to_string()).or_default();
+        let at = events.partition_point(|e| e.time <= time);
+        events.insert(at, Event { time, kind });
+        if events.len() > MAX_EVENTS {
+            events.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(name: &str, attached: usize) -> Session {
+        Session {
+            id: "$1".to_string(),
+            name: name.to_string(),
+            windows: 1,
+            attached,
+            created: 100,
+            last_attached: 150,
+            group: None,
+            path: String::new(),
+            runner: false,
+            server: None,
+        }
+    }
+
+    fn window(id: &str, index: usize, name: &str) -> Window {
+        Window {
+            id: id.to_string(),
+            session_id: "$1".to_string(),
+            index,
+            name: name.to_string(),
+            active: false,
+            active_command: "zsh".to_string(),
+            pane_title: String::new(),
+            remote_host: None,
+            flags: String::new(),
+            dead: false,
+            piped: false,
+            alternate: false,
+        }
+    }
+
+    fn kinds(timeline: &Timeline) -> Vec<EventKind> {
+        timeline
+            .events("$1")
+            .into_iter()
+            .map(|e| e.kind.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_sessions_seed_then_track_attach_and_rename() {
+        let mut timeline = Timeline::default();
+        timeline.observe_sessions(&[session("work", 0)], 200);
+        timeline.observe_sessions(&[session("work", 2)], 210);
+        timeline.observe_sessions(&[session("api", 1)], 220);
+
+        assert_eq!(
+            kinds(&timeline),
+            vec![
+                EventKind::Created,
+                EventKind::LastAttached,
+                EventKind::Attached { clients: 2 },
+                EventKind::Renamed {
+                    from: "work".to_string()
+                },
+                EventKind::Detached { clients: 1 },
+            ]
+        );
+        assert_eq!(timeline.events("$1")[0].time, 100);
+
+        timeline.observe_sessions(&[], 230);
+        assert!(
+            timeline.events("$1").is_empty(),
+            "gone sessions are forgotten"
+        );
+    }
+
+    #[test]
+    fn test_windows_opened_and_closed_after_baseline() {
+        let mut timeline = Timeline::default();
+        timeline.observe_windows("$1", &[window("@1", 0, "shell")], 100);
+        assert!(kinds(&timeline).is_empty());
+
+        timeline.observe_windows("$1", &[window("@2", 1, "logs")], 110);
+        assert_eq!(
+            kinds(&timeline),
+            vec![
+                EventKind::WindowClosed {
+                    index: 0,
+                    name: "shell".to_string()
+                },
+                EventKind::WindowOpened {
+                    index: 1,
+                    name: "logs".to_string()
+                },
+            ]
+        );
+    }
+}
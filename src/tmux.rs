@@ -1,85 +1,727 @@
+use std::collections::HashMap;
 use std::env;
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::sync::RwLock;
 use std::time::Duration;
 
 use anyhow::{anyhow, Context};
 use tokio::process::Command;
 use tokio::time::timeout;
 
-use crate::types::{AppResult, Pane, Session, Window};
+use crate::config::{Remote, SplitDirection, SplitPane};
+use crate::ssh;
+use crate::types::{
+    AppResult, Client, Pane, PasteBuffer, Session, SessionSnapshot, TmuxOption, Window,
+};
 
-const SESSION_FORMAT: &str = "#{session_id}\x01#{session_name}\x01#{session_windows}\x01#{session_attached}\x01#{session_created}\x01#{session_last_attached}\x01#{session_group}\x01#{session_path}";
+const SESSION_FORMAT: &str = "#{session_id}\x01#{session_name}\x01#{session_windows}\x01#{session_attached}\x01#{session_created}\x01#{session_last_attached}\x01#{session_group}\x01#{session_path}\x01#{@tmui-runner}";
 const WINDOW_FORMAT: &str =
-    "#{window_id}\x01#{session_id}\x01#{window_index}\x01#{window_name}\x01#{window_active}\x01#{pane_current_command}";
-#[allow(dead_code)]
-const PANE_FORMAT: &str = "#{pane_id}\x01#{window_id}\x01#{session_id}\x01#{pane_index}\x01#{pane_active}\x01#{pane_current_command}\x01#{pane_current_path}";
+    "#{window_id}\x01#{session_id}\x01#{window_index}\x01#{window_name}\x01#{window_active}\x01#{pane_current_command}\x01#{pane_title}\x01#{host}\x01#{pane_pid}\x01#{window_flags}\x01#{pane_dead}\x01#{pane_pipe}\x01#{alternate_on}";
+const PANE_FORMAT: &str = "#{pane_id}\x01#{window_id}\x01#{session_id}\x01#{pane_index}\x01#{pane_active}\x01#{pane_current_command}\x01#{pane_current_path}\x01#{pane_title}\x01#{host}\x01#{pane_dead}";
+/// [`WINDOW_FORMAT`] prefixed with the owning session's name, for `list-windows -a`.
+const ALL_WINDOWS_FORMAT: &str =
+    "#{session_name}\x01#{window_id}\x01#{session_id}\x01#{window_index}\x01#{window_name}\x01#{window_active}\x01#{pane_current_command}\x01#{pane_title}\x01#{host}\x01#{pane_pid}\x01#{window_flags}\x01#{pane_dead}\x01#{pane_pipe}\x01#{alternate_on}";
+const CLIENT_FORMAT: &str =
+    "#{client_tty}\x01#{client_session}\x01#{client_width}\x01#{client_height}\x01#{client_activity}\x01#{client_created}\x01#{client_control_mode}";
+const BUFFER_FORMAT: &str =
+    "#{buffer_name}\x01#{buffer_size}\x01#{buffer_created}\x01#{buffer_sample}";
+const PANE_PID_FORMAT: &str = "#{session_name}\x01#{pane_pid}";
+const RUNNER_PANE_FORMAT: &str = "#{session_name}\x01#{pane_dead}\x01#{pane_dead_status}";
 const DELIMITER: char = '\x01';
 
+/// Session option marking throwaway sessions spawned by the command runner.
+pub const RUNNER_OPTION: &str = "@tmui-runner";
+
+/// Scratch paste buffer for [`paste_text`], deleted once pasted.
+const SEND_BUFFER: &str = "tmui-send";
+
+/// Which tmux server to talk to when not the default one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Socket {
+    /// `-L name`: a named socket in tmux's socket directory.
+    Name(String),
+    /// `-S path`: an explicit socket path.
+    Path(PathBuf),
+}
+
+impl Socket {
+    /// Interpret a config value: anything containing `/` is a path,
+    /// otherwise a socket name.
+    pub fn from_setting(value: &str) -> Self {
+        if value.contains('/') {
+            Socket::Path(PathBuf::from(value))
+        } else {
+            Socket::Name(value.to_string())
+        }
+    }
+
+    fn args(&self) -> [String; 2] {
+        match self {
+            Socket::Name(name) => ["-L".to_string(), name.clone()],
+            Socket::Path(path) => ["-S".to_string(), path.display().to_string()],
+        }
+    }
+}
+
+/// A tmux release as `major.minor`; letter suffixes (`3.3a`) are dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TmuxVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl TmuxVersion {
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+
+    /// Parse `tmux -V` / `#{version}` output such as `tmux 3.3a`,
+    /// `next-3.5` or `2.9`. Builds without a number (`master`) yield `None`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let text = text.strip_prefix("tmux ").unwrap_or(text);
+        let text = text.strip_prefix("next-").unwrap_or(text);
+        let (major, rest) = text.split_once('.')?;
+        let minor: String = rest.chars().take_while(char::is_ascii_digit).collect();
+        Some(Self::new(major.parse().ok()?, minor.parse().ok()?))
+    }
+}
+
+impl std::fmt::Display for TmuxVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Oldest tmux whose formats and commands tmui relies on.
+pub const MIN_SUPPORTED_VERSION: TmuxVersion = TmuxVersion::new(2, 6);
+
+/// Optional tmux features, gated on the server version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Detected version; `None` for unnumbered builds, assumed current.
+    pub version: Option<TmuxVersion>,
+    /// `capture-pane -e` (escape sequences), tmux 1.8+.
+    pub capture_escapes: bool,
+    /// Read-only, output-free control clients (`attach -f`), tmux 3.2+.
+    pub control_client: bool,
+    /// Inherited values in `show-options -A`, tmux 3.0+.
+    pub inherited_options: bool,
+    /// Hook arrays (`set-hook name[index]`), tmux 3.0+.
+    pub hook_arrays: bool,
+    /// Floating popups (`display-popup`), tmux 3.2+.
+    pub popups: bool,
+    /// Environment for the first pane (`new-session -e`), tmux 3.2+.
+    pub session_environment: bool,
+}
+
+impl Capabilities {
+    pub fn for_version(version: Option<TmuxVersion>) -> Self {
+        let at_least =
+            |major, minor| !matches!(version, Some(v) if v < TmuxVersion::new(major, minor));
+        Self {
+            version,
+            capture_escapes: at_least(1, 8),
+            control_client: at_least(3, 2),
+            inherited_options: at_least(3, 0),
+            hook_arrays: at_least(3, 0),
+            popups: at_least(3, 2),
+            session_environment: at_least(3, 2),
+        }
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self::for_version(None)
+    }
+}
+
+static SOCKET: RwLock<Option<Socket>> = RwLock::new(None);
+static BINARY: RwLock<Option<PathBuf>> = RwLock::new(None);
+static REMOTE: RwLock<Option<Remote>> = RwLock::new(None);
+
+tokio::task_local! {
+    /// Server the tmux commands of the current task go to instead of the
+    /// configured one; see [`on_server`].
+    static SERVER: Option<Socket>;
+}
+
+/// Run `future` with its tmux commands sent to `socket`, or to the
+/// configured server with `None`.
+pub async fn on_server<F: Future>(socket: Option<Socket>, future: F) -> F::Output {
+    SERVER.scope(socket, future).await
+}
+
+/// [`on_server`] for synchronous code, such as exec'ing `attach-session`.
+pub fn on_server_sync<R>(socket: Option<Socket>, f: impl FnOnce() -> R) -> R {
+    SERVER.sync_scope(socket, f)
+}
+
+/// The server set by an enclosing [`on_server`], if any.
+pub fn scoped_server() -> Option<Socket> {
+    SERVER.try_with(Clone::clone).ok().flatten()
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Per-thread binary override so tests can point at a fake tmux without
+    /// affecting tests running in parallel.
+    pub static TEST_BINARY: std::cell::RefCell<Option<PathBuf>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// Use `path` instead of `tmux` from `PATH` (`None` = default).
+pub fn set_binary(path: Option<PathBuf>) {
+    if let Ok(mut current) = BINARY.write() {
+        *current = path;
+    }
+}
+
+/// The tmux executable to invoke.
+pub fn binary() -> PathBuf {
+    #[cfg(test)]
+    if let Some(path) = TEST_BINARY.with(|binary| binary.borrow().clone()) {
+        return path;
+    }
+    BINARY
+        .read()
+        .ok()
+        .and_then(|binary| binary.clone())
+        .unwrap_or_else(|| PathBuf::from("tmux"))
+}
+
+/// The server tmux commands go to, `None` for the default one.
+pub fn socket() -> Option<Socket> {
+    scoped_server().or_else(|| SOCKET.read().ok().and_then(|socket| socket.clone()))
+}
+
+/// Point every subsequent tmux invocation at `socket` (`None` = default server).
+pub fn set_socket(socket: Option<Socket>) {
+    if let Ok(mut current) = SOCKET.write() {
+        *current = socket;
+    }
+}
+
+/// Run every tmux command on `remote` over ssh, or locally again with
+/// `None`. The local socket and binary are kept for switching back.
+pub fn set_remote(remote: Option<Remote>) {
+    if let Ok(mut current) = REMOTE.write() {
+        *current = remote;
+    }
+}
+
+/// The remote host tmux commands currently go to.
+pub fn remote() -> Option<Remote> {
+    REMOTE.read().ok().and_then(|remote| remote.clone())
+}
+
+/// Program and arguments running tmux with `args` on the managed server:
+/// the local binary, or `ssh host -- tmux ...` for a remote host. `tty`
+/// asks ssh for a terminal, for attaching; without one ssh runs in batch
+/// mode so a password prompt fails instead of hanging the TUI.
+pub fn invocation(args: &[&str], tty: bool) -> (PathBuf, Vec<String>) {
+    let Some(remote) = remote() else {
+        let mut argv = server_args();
+        argv.extend(args.iter().map(|arg| arg.to_string()));
+        return (binary(), argv);
+    };
+    remote_invocation(remote, args, tty)
+}
+
+fn remote_invocation(remote: Remote, args: &[&str], tty: bool) -> (PathBuf, Vec<String>) {
+    let mut words = vec!["tmux".to_string()];
+    if let Some(socket) = remote.socket.as_deref() {
+        words.extend(Socket::from_setting(socket).args());
+    }
+    words.extend(args.iter().map(|arg| arg.to_string()));
+    let command: Vec<String> = words.iter().map(|word| shell_quote(word)).collect();
+
+    let mut argv = Vec::new();
+    if tty {
+        argv.push("-t".to_string());
+    } else {
+        argv.extend(["-o".to_string(), "BatchMode=yes".to_string()]);
+    }
+    argv.extend(remote.ssh_args);
+    argv.extend([remote.host, "--".to_string(), command.join(" ")]);
+    (PathBuf::from("ssh"), argv)
+}
+
+/// `word` in single quotes for `sh`.
+fn shell_quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', "'\\''"))
+}
+
+/// Global tmux flags selecting the configured server, to precede the command.
+pub fn server_args() -> Vec<String> {
+    if let Some(socket) = scoped_server() {
+        return socket.args().to_vec();
+    }
+    SOCKET
+        .read()
+        .ok()
+        .and_then(|socket| socket.as_ref().map(|s| s.args().to_vec()))
+        .unwrap_or_default()
+}
+
+/// Sessions of the server. Attach counts leave out control clients, such
+/// as tmui's own, so they count people.
 pub async fn list_sessions() -> AppResult<Vec<Session>> {
     let output = run_tmux(&["list-sessions", "-F", SESSION_FORMAT]).await?;
-    parse_sessions(&output)
+    let mut sessions = parse_sessions(&output)?;
+    if sessions.iter().any(|s| s.attached > 0) {
+        // Counts stay as listed when the clients cannot be read; a failed
+        // refinement must not lose the session list.
+        let clients = run_tmux(&["list-clients", "-F", CLIENT_FORMAT])
+            .await
+            .unwrap_or_default();
+        for (name, count) in control_clients(&clients) {
+            if let Some(session) = sessions.iter_mut().find(|s| s.name == name) {
+                session.attached = session.attached.saturating_sub(count);
+            }
+        }
+    }
+    Ok(sessions)
 }
 
 pub async fn list_windows(session_name: &str) -> AppResult<Vec<Window>> {
     let output = run_tmux(&["list-windows", "-F", WINDOW_FORMAT, "-t", session_name]).await?;
-    parse_windows(&output)
+    Ok(resolve_remote_hosts(parse_windows(&output)?))
+}
+
+/// Windows of every session in one `list-windows -a` call, keyed by session
+/// name.
+pub async fn list_all_windows() -> AppResult<HashMap<String, Vec<Window>>> {
+    let output = run_tmux(&["list-windows", "-a", "-F", ALL_WINDOWS_FORMAT]).await?;
+    Ok(parse_all_windows(&output)
+        .into_iter()
+        .map(|(session, windows)| (session, resolve_remote_hosts(windows)))
+        .collect())
+}
+
+/// Name and active pane directory of each window of `session_name`, to
+/// recreate the session after a kill.
+pub async fn snapshot_session(session_name: &str, path: &str) -> AppResult<SessionSnapshot> {
+    let format = "#{window_name}\x01#{pane_current_path}";
+    let output = run_tmux(&["list-windows", "-F", format, "-t", session_name]).await?;
+    Ok(SessionSnapshot {
+        name: session_name.to_string(),
+        server: None,
+        path: path.to_string(),
+        windows: parse_window_paths(&output),
+    })
+}
+
+/// Working directory of the active pane of `target`.
+pub async fn pane_current_path(target: &str) -> AppResult<String> {
+    let output = run_tmux(&[
+        "display-message",
+        "-p",
+        "-t",
+        target,
+        "#{pane_current_path}",
+    ])
+    .await?;
+    Ok(output.trim_end().to_string())
 }
 
-#[allow(dead_code)]
 pub async fn list_panes(target_window: &str) -> AppResult<Vec<Pane>> {
     let output = run_tmux(&["list-panes", "-F", PANE_FORMAT, "-t", target_window]).await?;
     parse_panes(&output)
 }
 
-#[allow(dead_code)]
-pub async fn create_session(name: &str, path: Option<&str>) -> AppResult<()> {
+/// Pipe the output of `target`'s active pane into `log_path`; with `-o`
+/// nothing changes when a pipe is already open. tmux expands formats in the
+/// command, so `#` in the path is doubled.
+pub async fn start_pipe(target: &str, log_path: &str) -> AppResult<()> {
+    let command = format!("cat >> {}", shell_quote(log_path).replace('#', "##"));
+    run_tmux(&["pipe-pane", "-o", "-t", target, &command]).await?;
+    Ok(())
+}
+
+/// Close the pipe of `target`'s active pane.
+pub async fn stop_pipe(target: &str) -> AppResult<()> {
+    run_tmux(&["pipe-pane", "-t", target]).await?;
+    Ok(())
+}
+
+/// Toggle zoom of the active pane in `target_window`.
+pub async fn toggle_zoom(target_window: &str) -> AppResult<()> {
+    run_tmux(&["resize-pane", "-Z", "-t", target_window]).await?;
+    Ok(())
+}
+
+/// Restart the command of `pane_id`, killing it first if still running.
+pub async fn respawn_pane(pane_id: &str) -> AppResult<()> {
+    run_tmux(&["respawn-pane", "-k", "-t", pane_id]).await?;
+    Ok(())
+}
+
+/// Paste buffers, most recent first.
+pub async fn list_buffers() -> AppResult<Vec<PasteBuffer>> {
+    let output = run_tmux(&["list-buffers", "-F", BUFFER_FORMAT]).await?;
+    Ok(parse_buffers(&output))
+}
+
+pub async fn show_buffer(name: &str) -> AppResult<String> {
+    run_tmux(&["show-buffer", "-b", name]).await
+}
+
+/// Paste buffer `name` into the active pane of `target`.
+pub async fn paste_buffer(name: &str, target: &str) -> AppResult<()> {
+    run_tmux(&["paste-buffer", "-b", name, "-t", target]).await?;
+    Ok(())
+}
+
+pub async fn delete_buffer(name: &str) -> AppResult<()> {
+    run_tmux(&["delete-buffer", "-b", name]).await?;
+    Ok(())
+}
+
+/// Session options of `target`, or window options when `window`. With
+/// `inherited`, options left at their global value are included.
+pub async fn list_options(
+    target: &str,
+    window: bool,
+    inherited: bool,
+) -> AppResult<Vec<TmuxOption>> {
+    let mut args = vec!["show-options"];
+    if window {
+        args.push("-w");
+    }
+    if inherited {
+        args.push("-A");
+    }
+    args.extend(["-t", target]);
+    let output = run_tmux(&args).await?;
+    Ok(parse_options(&output))
+}
+
+pub async fn set_option(target: &str, window: bool, name: &str, value: &str) -> AppResult<()> {
+    let mut args = vec!["set-option"];
+    if window {
+        args.push("-w");
+    }
+    args.extend(["-t", target, name, value]);
+    run_tmux(&args).await?;
+    Ok(())
+}
+
+pub async fn kill_pane(pane_id: &str) -> AppResult<()> {
+    run_tmux(&["kill-pane", "-t", pane_id]).await?;
+    Ok(())
+}
+
+pub async fn select_layout(target: &str, layout: &str) -> AppResult<()> {
+    run_tmux(&["select-layout", "-t", target, layout]).await?;
+    Ok(())
+}
+
+/// Move `pane_id` into a new window, leaving the current window active.
+pub async fn break_pane(pane_id: &str) -> AppResult<()> {
+    run_tmux(&["break-pane", "-d", "-s", pane_id]).await?;
+    Ok(())
+}
+
+/// Split `target_window` to make room for `pane_id`, moving it there.
+pub async fn join_pane(pane_id: &str, target_window: &str) -> AppResult<()> {
+    run_tmux(&["join-pane", "-d", "-s", pane_id, "-t", target_window]).await?;
+    Ok(())
+}
+
+/// Swap the positions of two windows without changing the active one.
+pub async fn swap_window(source: &str, target: &str) -> AppResult<()> {
+    run_tmux(&["swap-window", "-d", "-s", source, "-t", target]).await?;
+    Ok(())
+}
+
+/// Move `window` (any target, e.g. `@3`) to the first free index of
+/// `session_name`.
+pub async fn move_window(window: &str, session_name: &str) -> AppResult<()> {
+    let destination = format!("{session_name}:");
+    run_tmux(&["move-window", "-s", window, "-t", &destination]).await?;
+    Ok(())
+}
+
+/// Clients attached to `session_name`.
+pub async fn list_clients(session_name: &str) -> AppResult<Vec<Client>> {
+    let output = run_tmux(&["list-clients", "-F", CLIENT_FORMAT, "-t", session_name]).await?;
+    Ok(parse_clients(&output))
+}
+
+/// Clients of every session.
+pub async fn list_all_clients() -> AppResult<Vec<Client>> {
+    let output = run_tmux(&["list-clients", "-F", CLIENT_FORMAT]).await?;
+    Ok(parse_clients(&output))
+}
+
+/// Terminal of the client tmui runs in.
+pub async fn current_client_tty() -> AppResult<String> {
+    let output = run_tmux(&["display-message", "-p", "#{client_tty}"]).await?;
+    Ok(output.trim().to_string())
+}
+
+pub async fn create_session(
+    name: &str,
+    path: Option<&str>,
+    command: Option<&str>,
+    env: &[(String, String)],
+) -> AppResult<()> {
+    let env: Vec<String> = env
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect();
     let mut args = vec!["new-session", "-d", "-s", name];
     if let Some(path) = path {
         args.extend(["-c", path]);
     }
+    for var in &env {
+        args.extend(["-e", var]);
+    }
+    args.extend(command);
     run_tmux(&args).await?;
     Ok(())
 }
 
-#[allow(dead_code)]
+/// Set `key` in the environment of `session`, inherited by panes created
+/// from now on.
+pub async fn set_environment(session: &str, key: &str, value: &str) -> AppResult<()> {
+    run_tmux(&["set-environment", "-t", session, key, value]).await?;
+    Ok(())
+}
+
+/// Create session `name` in the group of `target`, starting a group when
+/// `target` has none.
+pub async fn create_grouped_session(name: &str, target: &str) -> AppResult<()> {
+    run_tmux(&["new-session", "-d", "-t", target, "-s", name]).await?;
+    Ok(())
+}
+
+/// Spawn a detached session running `command`, flagged as a runner session.
+/// `remain-on-exit` keeps the dead pane around so tmui can report the exit
+/// status before cleaning the session up; it is set on the session's shell
+/// before `respawn-pane` starts the command, so even an instant exit stays.
+pub async fn create_runner_session(name: &str, command: &str) -> AppResult<()> {
+    let pane = format!("{name}:");
+    run_tmux(&[
+        "new-session",
+        "-d",
+        "-s",
+        name,
+        ";",
+        "set-option",
+        "-t",
+        name,
+        RUNNER_OPTION,
+        "1",
+        ";",
+        "set-option",
+        "-t",
+        name,
+        "remain-on-exit",
+        "on",
+        ";",
+        "respawn-pane",
+        "-k",
+        "-t",
+        &pane,
+        command,
+    ])
+    .await?;
+    Ok(())
+}
+
+/// Exit state of runner sessions: session name to the exit status of its
+/// command, for sessions whose panes have all died.
+pub async fn finished_runners() -> AppResult<Vec<(String, i32)>> {
+    let output = run_tmux(&["list-panes", "-a", "-F", RUNNER_PANE_FORMAT]).await?;
+    Ok(parse_finished_runners(&output))
+}
+
+/// `(session name, pane pid)` for every pane on the server.
+pub async fn list_pane_pids() -> AppResult<Vec<(String, u32)>> {
+    let output = run_tmux(&["list-panes", "-a", "-F", PANE_PID_FORMAT]).await?;
+    Ok(parse_pane_pids(&output))
+}
+
 pub async fn kill_session(name: &str) -> AppResult<()> {
     run_tmux(&["kill-session", "-t", name]).await?;
     Ok(())
 }
 
-#[allow(dead_code)]
+pub async fn kill_server() -> AppResult<()> {
+    run_tmux(&["kill-server"]).await?;
+    Ok(())
+}
+
 pub async fn rename_session(current_name: &str, new_name: &str) -> AppResult<()> {
     run_tmux(&["rename-session", "-t", current_name, new_name]).await?;
     Ok(())
 }
 
+pub async fn rename_window(target_window: &str, new_name: &str) -> AppResult<()> {
+    run_tmux(&["rename-window", "-t", target_window, "--", new_name]).await?;
+    Ok(())
+}
+
+/// Open a window named `name` in `path` running `command` (the default
+/// shell when `None`) at the next free index of `session_name`, without making it the
+/// current window. Returns the new window's id.
+pub async fn new_window(
+    session_name: &str,
+    name: &str,
+    path: Option<&str>,
+    command: Option<&str>,
+) -> AppResult<String> {
+    let target = format!("{session_name}:");
+    let mut args = vec!["new-window", "-d", "-P", "-F", "#{window_id}"];
+    args.extend(["-t", &target, "-n", name]);
+    if let Some(path) = path {
+        args.extend(["-c", path]);
+    }
+    args.extend(command);
+    let output = run_tmux(&args).await?;
+    Ok(output.trim().to_string())
+}
+
+/// Split the active pane of `target` as `pane` describes; the new pane
+/// becomes active, so the next split divides it in turn.
+pub async fn split_window(target: &str, pane: &SplitPane, path: Option<&str>) -> AppResult<()> {
+    let direction = match pane.split {
+        SplitDirection::Right => "-h",
+        SplitDirection::Below => "-v",
+    };
+    let size = pane.size.map(|percent| format!("{percent}%"));
+    let mut args = vec!["split-window", "-t", target, direction];
+    if let Some(size) = &size {
+        args.extend(["-l", size]);
+    }
+    if let Some(path) = path {
+        args.extend(["-c", path]);
+    }
+    args.extend(pane.command.as_deref());
+    run_tmux(&args).await?;
+    Ok(())
+}
+
+/// Type `text` literally into `target`'s active pane and press Enter.
+pub async fn send_line(target: &str, text: &str) -> AppResult<()> {
+    run_tmux(&["send-keys", "-t", target, "-l", "--", text]).await?;
+    run_tmux(&["send-keys", "-t", target, "Enter"]).await?;
+    Ok(())
+}
+
+/// Paste `text` into the active pane of `target` through a scratch
+/// buffer, bracketed so shells don't run it line by line.
+pub async fn paste_text(target: &str, text: &str) -> AppResult<()> {
+    run_tmux(&["set-buffer", "-b", SEND_BUFFER, "--", text]).await?;
+    run_tmux(&["paste-buffer", "-d", "-p", "-b", SEND_BUFFER, "-t", target]).await?;
+    Ok(())
+}
+
 pub async fn switch_client(target_session: &str) -> AppResult<()> {
     run_tmux(&["switch-client", "-t", target_session]).await?;
     Ok(())
 }
 
-#[allow(dead_code)]
-pub async fn attach_session(target_session: &str) -> AppResult<()> {
-    run_tmux(&["attach-session", "-t", target_session]).await?;
+/// Switch the current client back to its previous session.
+pub async fn switch_client_last() -> AppResult<()> {
+    run_tmux(&["switch-client", "-l"]).await?;
     Ok(())
 }
 
+/// Detach every client of `session` one by one, so control clients (tmui's
+/// own refresh client among them) stay attached.
 pub async fn detach_client(session: &str) -> AppResult<()> {
-    run_tmux(&["detach-client", "-s", session]).await?;
+    for client in list_clients(session).await? {
+        detach_tty(&client.tty).await?;
+    }
     Ok(())
 }
 
-pub fn attach_session_exec(target: &str) -> ! {
+/// Detach the single client on `tty`.
+pub async fn detach_tty(tty: &str) -> AppResult<()> {
+    run_tmux(&["detach-client", "-t", tty]).await?;
+    Ok(())
+}
+
+/// Replace this process with `tmux attach-session`; only returns (with
+/// the reason) if the exec failed.
+pub fn attach_session_exec(target: &str, read_only: bool) -> std::io::Error {
     use std::os::unix::process::CommandExt;
-    let error = std::process::Command::new("tmux")
-        .args(["attach-session", "-t", target])
-        .exec();
-    eprintln!("Failed to attach: {error}");
-    std::process::exit(1);
+    let args: &[&str] = if read_only {
+        &["attach-session", "-r", "-t", target]
+    } else {
+        &["attach-session", "-t", target]
+    };
+    let (program, argv) = invocation(args, true);
+    std::process::Command::new(program).args(argv).exec()
+}
+
+/// Watch `target` read-only in a popup over the current client. Inside
+/// tmux, `switch-client -r` would leave the user's own client read-only,
+/// so a second, nested client attaches with `-r` instead; detaching it
+/// closes the popup.
+pub async fn popup_attach_read_only(target: &str) -> AppResult<()> {
+    let (program, argv) = invocation(&["attach-session", "-r", "-t", target], true);
+    let quoted: Vec<String> = std::iter::once(program.to_string_lossy().into_owned())
+        .chain(argv)
+        .map(|word| shell_quote(&word))
+        .collect();
+    let command = format!("TMUX= {}", quoted.join(" "));
+    run_tmux(&["display-popup", "-E", "-w", "90%", "-h", "90%", &command]).await?;
+    Ok(())
 }
 
-pub async fn capture_pane(target_pane: &str) -> AppResult<String> {
-    run_tmux(&["capture-pane", "-p", "-t", target_pane]).await
+/// Contents of `target_pane`; with `escapes`, colors and attributes are
+/// kept as ANSI sequences (`-e`). With `saved_screen`, a pane on its
+/// alternate screen yields the screen underneath instead (`-a`). `start`
+/// reaches into the scrollback (`-S -1000` = the last 1000 history lines);
+/// `None` captures the visible screen only.
+///
+/// Lines the pane wrapped at its own width come back joined (`-J`), so a
+/// narrower preview rewraps them once instead of breaking every row again.
+pub async fn capture_pane(
+    target_pane: &str,
+    escapes: bool,
+    start: Option<i64>,
+    saved_screen: bool,
+) -> AppResult<String> {
+    let flags = if escapes { "-peJ" } else { "-pJ" };
+    let start = start.map(|line| line.to_string());
+    let mut args = vec!["capture-pane", flags];
+    if saved_screen {
+        args.push("-a");
+    }
+    if let Some(start) = &start {
+        args.extend(["-S", start]);
+    }
+    args.extend(["-t", target_pane]);
+    run_tmux(&args).await.map(|output| trim_line_ends(&output))
+}
+
+/// Plain text of the whole history and screen of `target_pane`.
+pub async fn capture_scrollback(target_pane: &str) -> AppResult<String> {
+    run_tmux(&["capture-pane", "-pJ", "-S", "-", "-t", target_pane])
+        .await
+        .map(|output| trim_line_ends(&output))
+}
+
+/// `-J` keeps the trailing spaces of every line; drop them so padded rows
+/// don't wrap into blank ones.
+fn trim_line_ends(text: &str) -> String {
+    let mut trimmed: String = text
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n");
+    if text.ends_with('\n') {
+        trimmed.push('\n');
+    }
+    trimmed
 }
 
 pub fn is_inside_tmux() -> bool {
@@ -88,9 +730,44 @@ pub fn is_inside_tmux() -> bool {
         .is_some_and(|value| !value.trim().is_empty())
 }
 
-#[allow(dead_code)]
+/// Version of the running server, falling back to the client binary's
+/// `tmux -V` when no server is up.
+pub async fn server_version() -> AppResult<TmuxVersion> {
+    let output = match run_tmux(&["display-message", "-p", "#{version}"]).await {
+        Ok(output) if !output.trim().is_empty() => output,
+        _ => run_tmux(&["-V"]).await?,
+    };
+    TmuxVersion::parse(&output)
+        .ok_or_else(|| anyhow!("unrecognized tmux version: {}", output.trim()))
+}
+
+/// Session of the client tmui runs in; only meaningful inside tmux.
+pub async fn current_session() -> AppResult<String> {
+    let output = run_tmux(&["display-message", "-p", "#{client_session}"]).await?;
+    Ok(output.trim().to_string())
+}
+
+/// Whether tmui runs inside a client of the server it manages, so
+/// `switch-client` applies. With a custom socket, `$TMUX` may belong to a
+/// different server.
+pub async fn is_inside_target_server() -> bool {
+    if !is_inside_tmux() {
+        return false;
+    }
+    if server_args().is_empty() {
+        return true;
+    }
+    let own = env::var("TMUX").unwrap_or_default();
+    let own_socket = own.split(',').next().unwrap_or_default();
+    run_tmux(&["display-message", "-p", "#{socket_path}"])
+        .await
+        .is_ok_and(|path| path.trim() == own_socket)
+}
+
+/// Whether a session is named exactly `name`; plain `-t` would also match
+/// a longer name starting with it.
 pub async fn has_session(name: &str) -> AppResult<bool> {
-    match run_tmux(&["has-session", "-t", name]).await {
+    match run_tmux(&["has-session", "-t", &format!("={name}")]).await {
         Ok(_) => Ok(true),
         Err(error) => {
             let message = error.to_string();
@@ -104,10 +781,22 @@ pub async fn has_session(name: &str) -> AppResult<bool> {
 }
 
 pub async fn run_tmux(args: &[&str]) -> AppResult<String> {
-    let command_line = format!("tmux {}", args.join(" "));
+    let (program, argv) = invocation(args, false);
+    run_invocation(&program, &argv).await
+}
+
+/// Run a command line built by [`invocation`], possibly for a server tmux
+/// commands no longer go to.
+pub async fn run_invocation(program: &Path, argv: &[String]) -> AppResult<String> {
+    let shown = if program == Path::new("ssh") {
+        "ssh"
+    } else {
+        "tmux"
+    };
+    let command_line = format!("{shown} {}", argv.join(" "));
 
-    let mut command = Command::new("tmux");
-    command.args(args);
+    let mut command = Command::new(program);
+    command.args(argv);
     command.stdout(Stdio::piped());
     command.stderr(Stdio::piped());
 
@@ -145,7 +834,7 @@ fn parse_sessions(output: &str) -> AppResult<Vec<Session>> {
         }
 
         let fields = split_fields(line);
-        if fields.len() != 8 {
+        if fields.len() != 9 {
             continue;
         }
 
@@ -169,44 +858,90 @@ fn parse_sessions(output: &str) -> AppResult<Vec<Session>> {
             last_attached,
             group: optional_field(fields[6]),
             path: fields[7].to_string(),
+            runner: fields[8] == "1",
+            server: None,
         });
     }
 
     Ok(sessions)
 }
 
-fn parse_windows(output: &str) -> AppResult<Vec<Window>> {
-    let mut windows = Vec::new();
+/// A parsed window and the pid of its active pane, for
+/// [`resolve_remote_hosts`].
+type ParsedWindow = (Window, u32);
 
-    for line in output.lines() {
-        if line.trim().is_empty() {
-            continue;
-        }
+fn parse_windows(output: &str) -> AppResult<Vec<ParsedWindow>> {
+    Ok(output
+        .lines()
+        .filter_map(|line| parse_window_fields(&split_fields(line)))
+        .collect())
+}
 
+fn parse_all_windows(output: &str) -> HashMap<String, Vec<ParsedWindow>> {
+    let mut windows: HashMap<String, Vec<ParsedWindow>> = HashMap::new();
+    for line in output.lines() {
         let fields = split_fields(line);
-        if fields.len() != 6 {
-            continue;
-        }
-
-        let index = parse_usize(fields[2]);
-        let Some(index) = index else {
+        let Some((session_name, rest)) = fields.split_first() else {
             continue;
         };
+        if let Some(window) = parse_window_fields(rest) {
+            windows
+                .entry(session_name.to_string())
+                .or_default()
+                .push(window);
+        }
+    }
+    windows
+}
 
-        windows.push(Window {
-            id: fields[0].to_string(),
-            session_id: fields[1].to_string(),
-            index,
-            name: fields[3].to_string(),
-            active: fields[4] == "1",
-            active_command: fields[5].to_string(),
-        });
+/// Build a window from the fields of one [`WINDOW_FORMAT`] line. An ssh
+/// pane's host comes from its title here, if a program set one.
+fn parse_window_fields(fields: &[&str]) -> Option<ParsedWindow> {
+    if fields.len() != 13 {
+        return None;
     }
+    let index = parse_usize(fields[2])?;
+    let pane_title = custom_title(fields[6], fields[7]);
+    let remote_host = match fields[5] {
+        "ssh" => ssh::host_from_title(&pane_title),
+        _ => None,
+    };
+
+    let window = Window {
+        id: fields[0].to_string(),
+        session_id: fields[1].to_string(),
+        index,
+        name: fields[3].to_string(),
+        active: fields[4] == "1",
+        active_command: fields[5].to_string(),
+        pane_title,
+        remote_host,
+        flags: fields[9].to_string(),
+        dead: fields[10] == "1",
+        piped: fields[11] == "1",
+        alternate: fields[12] == "1",
+    };
+    Some((window, fields[8].parse().unwrap_or_default()))
+}
 
-    Ok(windows)
+/// Prefer the destination in the ssh process's arguments over the title.
+/// Pane pids of a remote server mean nothing in the local /proc, so those
+/// keep the title's host.
+fn resolve_remote_hosts(windows: Vec<ParsedWindow>) -> Vec<Window> {
+    let local = remote().is_none();
+    windows
+        .into_iter()
+        .map(|(mut window, pane_pid)| {
+            if local && window.active_command == "ssh" {
+                if let Some(host) = ssh::host_from_pid(pane_pid) {
+                    window.remote_host = Some(host);
+                }
+            }
+            window
+        })
+        .collect()
 }
 
-#[allow(dead_code)]
 fn parse_panes(output: &str) -> AppResult<Vec<Pane>> {
     let mut panes = Vec::new();
 
@@ -216,7 +951,7 @@ fn parse_panes(output: &str) -> AppResult<Vec<Pane>> {
         }
 
         let fields = split_fields(line);
-        if fields.len() != 7 {
+        if fields.len() != 10 {
             continue;
         }
 
@@ -233,12 +968,142 @@ fn parse_panes(output: &str) -> AppResult<Vec<Pane>> {
             active: fields[4] == "1",
             current_command: fields[5].to_string(),
             current_path: fields[6].to_string(),
+            title: custom_title(fields[7], fields[8]),
+            dead: fields[9] == "1",
         });
     }
 
     Ok(panes)
 }
 
+fn parse_window_paths(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| match split_fields(line)[..] {
+            [name, path] => Some((name.to_string(), path.to_string())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Clients other than control-mode ones, which no one sits at.
+fn parse_clients(output: &str) -> Vec<Client> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields = split_fields(line);
+            if fields.len() != 7 || fields[6] == "1" {
+                return None;
+            }
+            Some(Client {
+                tty: fields[0].to_string(),
+                session: fields[1].to_string(),
+                width: fields[2].parse().ok()?,
+                height: fields[3].parse().ok()?,
+                activity: parse_i64_with_empty_default(fields[4], 0)?,
+                created: parse_i64_with_empty_default(fields[5], 0)?,
+            })
+        })
+        .collect()
+}
+
+/// Number of control-mode clients on each session.
+fn control_clients(output: &str) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for line in output.lines() {
+        let fields = split_fields(line);
+        if fields.len() == 7 && fields[6] == "1" {
+            *counts.entry(fields[1].to_string()).or_default() += 1;
+        }
+    }
+    counts
+}
+
+fn parse_buffers(output: &str) -> Vec<PasteBuffer> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.splitn(4, DELIMITER).collect();
+            if fields.len() != 4 {
+                return None;
+            }
+            Some(PasteBuffer {
+                name: fields[0].to_string(),
+                size: fields[1].parse().ok()?,
+                created: parse_i64_with_empty_default(fields[2], 0)?,
+                sample: fields[3].to_string(),
+            })
+        })
+        .collect()
+}
+
+/// `name value` lines; `-A` marks inherited names with a trailing `*` and
+/// values with spaces come quoted.
+fn parse_options(output: &str) -> Vec<TmuxOption> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, value) = line.split_once(' ').unwrap_or((line, ""));
+            if name.is_empty() {
+                return None;
+            }
+            let (name, inherited) = match name.strip_suffix('*') {
+                Some(name) => (name, true),
+                None => (name, false),
+            };
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            Some(TmuxOption {
+                name: name.to_string(),
+                value: value.to_string(),
+                inherited,
+            })
+        })
+        .collect()
+}
+
+fn parse_pane_pids(output: &str) -> Vec<(String, u32)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields = split_fields(line);
+            if fields.len() != 2 {
+                return None;
+            }
+            Some((fields[0].to_string(), fields[1].parse().ok()?))
+        })
+        .collect()
+}
+
+fn parse_finished_runners(output: &str) -> Vec<(String, i32)> {
+    let mut states: Vec<(String, bool, i32)> = Vec::new();
+
+    for line in output.lines() {
+        let fields = split_fields(line);
+        if fields.len() != 3 {
+            continue;
+        }
+
+        let dead = fields[1] == "1";
+        let status = fields[2].parse().unwrap_or_default();
+        match states.iter_mut().find(|(name, _, _)| name == fields[0]) {
+            Some(entry) => {
+                entry.1 &= dead;
+                entry.2 = entry.2.max(status);
+            }
+            None => states.push((fields[0].to_string(), dead, status)),
+        }
+    }
+
+    states
+        .into_iter()
+        .filter(|(_, dead, _)| *dead)
+        .map(|(name, _, status)| (name, status))
+        .collect()
+}
+
 fn parse_usize(value: &str) -> Option<usize> {
     value.parse().ok()
 }
@@ -255,6 +1120,16 @@ fn parse_i64_with_empty_default(value: &str, default: i64) -> Option<i64> {
     }
 }
 
+/// tmux defaults a pane's title to the hostname; only keep titles that a
+/// program actually set.
+fn custom_title(title: &str, host: &str) -> String {
+    if title == host {
+        String::new()
+    } else {
+        title.to_string()
+    }
+}
+
 fn optional_field(value: &str) -> Option<String> {
     if value.is_empty() {
         None
@@ -278,38 +1153,279 @@ mod tests {
     #[test]
     fn test_parse_sessions() {
         let fixture =
-            "$0\x01work\x012\x011\x011770744224\x011770749593\x01\x01/home/aceworks/study\n";
+            "$0\x01work\x012\x011\x011770744224\x011770749593\x01\x01/home/aceworks/study\x01\n";
         let sessions = parse_sessions(fixture).expect("fixture should parse");
         assert_eq!(sessions.len(), 1);
         assert_eq!(sessions[0].id, "$0");
         assert_eq!(sessions[0].name, "work");
     }
 
+    #[test]
+    fn test_parse_runner_session() {
+        let fixture = "$3\x01run-1\x011\x010\x011770744224\x01\x01\x01/tmp\x011\n";
+        let sessions = parse_sessions(fixture).expect("fixture should parse");
+        assert_eq!(sessions.len(), 1);
+        assert!(sessions[0].runner);
+    }
+
+    #[test]
+    fn test_parse_pane_pids() {
+        let fixture = "work\x014242\nwork\x014300\nbroken\x01\n";
+        assert_eq!(
+            parse_pane_pids(fixture),
+            vec![("work".to_string(), 4242), ("work".to_string(), 4300)]
+        );
+    }
+
+    #[test]
+    fn test_parse_finished_runners() {
+        let fixture = "run-1\x011\x010\nrun-2\x011\x012\nrun-2\x010\x010\nrun-3\x011\x017\n";
+        let finished = parse_finished_runners(fixture);
+        assert_eq!(
+            finished,
+            vec![("run-1".to_string(), 0), ("run-3".to_string(), 7)]
+        );
+    }
+
     #[test]
     fn test_parse_windows() {
-        let fixture = "@0\x01$0\x010\x01editor\x011\x01vim\n";
-        let windows = parse_windows(fixture).expect("fixture should parse");
+        let fixture =
+            "@0\x01$0\x010\x01editor\x011\x01vim\x01myhost\x01myhost\x01100\x01*\x010\x010\x010\n";
+        let windows: Vec<Window> = parse_windows(fixture)
+            .expect("fixture should parse")
+            .into_iter()
+            .map(|(window, _)| window)
+            .collect();
         assert_eq!(windows.len(), 1);
         assert_eq!(windows[0].id, "@0");
         assert_eq!(windows[0].session_id, "$0");
         assert_eq!(windows[0].name, "editor");
+        assert_eq!(windows[0].pane_title, "", "hostname title counts as unset");
+
+        let ssh =
+            "@1\x01$0\x011\x01zsh\x011\x01ssh\x01myhost\x01myhost\x01100\x01*\x010\x010\x010\n";
+        let (window, pane_pid) = parse_windows(ssh).expect("fixture should parse").remove(0);
+        assert_eq!(window.remote_host, None, "the local hostname is no remote");
+        assert_eq!(pane_pid, 100);
+    }
+
+    #[test]
+    fn test_parse_windows_pane_title() {
+        let fixture =
+            "@1\x01$0\x011\x01zsh\x010\x01ssh\x01deploy@prod-db\x01myhost\x010\x01-Z\x011\x011\x011\n";
+        let windows: Vec<Window> = parse_windows(fixture)
+            .expect("fixture should parse")
+            .into_iter()
+            .map(|(window, _)| window)
+            .collect();
+        assert_eq!(windows[0].pane_title, "deploy@prod-db");
+        assert_eq!(windows[0].remote_host.as_deref(), Some("prod-db"));
+        assert_eq!(windows[0].flags, "-Z");
+        assert!(windows[0].dead);
+        assert!(windows[0].piped);
+        assert!(windows[0].alternate);
+    }
+
+    #[test]
+    fn test_parse_all_windows_groups_by_session() {
+        let fixture =
+            "work\x01@0\x01$0\x010\x01editor\x011\x01vim\x01h\x01h\x01100\x01*\x010\x010\x010\n\
+                       work\x01@1\x01$0\x011\x01shell\x010\x01zsh\x01h\x01h\x01101\x01\x010\x010\x010\n\
+                       ops\x01@2\x01$1\x010\x01logs\x011\x01tail\x01h\x01h\x01102\x01*\x010\x010\x010\n\
+                       broken line\n";
+        let windows = parse_all_windows(fixture);
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows["work"].len(), 2);
+        assert_eq!(windows["work"][1].0.name, "shell");
+        assert_eq!(windows["ops"][0].0.active_command, "tail");
+    }
+
+    #[test]
+    fn test_socket_from_setting() {
+        assert_eq!(
+            Socket::from_setting("work"),
+            Socket::Name("work".to_string())
+        );
+        assert_eq!(
+            Socket::from_setting("/tmp/tmux-1000/personal"),
+            Socket::Path(PathBuf::from("/tmp/tmux-1000/personal"))
+        );
+        assert_eq!(Socket::Name("work".to_string()).args(), ["-L", "work"]);
+        assert_eq!(Socket::Path(PathBuf::from("/s")).args(), ["-S", "/s"]);
+    }
+
+    #[test]
+    fn test_parse_tmux_version() {
+        assert_eq!(
+            TmuxVersion::parse("tmux 3.3a\n"),
+            Some(TmuxVersion::new(3, 3))
+        );
+        assert_eq!(TmuxVersion::parse("3.2"), Some(TmuxVersion::new(3, 2)));
+        assert_eq!(
+            TmuxVersion::parse("tmux next-3.5"),
+            Some(TmuxVersion::new(3, 5))
+        );
+        assert_eq!(
+            TmuxVersion::parse("tmux 2.10"),
+            Some(TmuxVersion::new(2, 10))
+        );
+        assert_eq!(TmuxVersion::parse("tmux master"), None);
+        assert!(TmuxVersion::new(2, 10) > TmuxVersion::new(2, 9));
+    }
+
+    #[test]
+    fn test_remote_invocation_quotes_command_for_ssh() {
+        let remote = Remote {
+            host: "dev".to_string(),
+            ssh_args: vec!["-p".to_string(), "2222".to_string()],
+            socket: Some("work".to_string()),
+        };
+
+        let (program, argv) =
+            remote_invocation(remote.clone(), &["has-session", "-t", "=it's"], false);
+        assert_eq!(program, PathBuf::from("ssh"));
+        assert_eq!(
+            argv,
+            [
+                "-o",
+                "BatchMode=yes",
+                "-p",
+                "2222",
+                "dev",
+                "--",
+                "'tmux' '-L' 'work' 'has-session' '-t' '=it'\\''s'",
+            ]
+        );
+
+        let (_, argv) = remote_invocation(remote, &["attach-session"], true);
+        assert_eq!(argv[0], "-t", "attaching needs a terminal");
+    }
+
+    #[test]
+    fn test_trim_line_ends() {
+        assert_eq!(
+            trim_line_ends("a long line   \n  $ \n"),
+            "a long line\n  $\n"
+        );
+        assert_eq!(trim_line_ends("no newline  "), "no newline");
+    }
+
+    #[test]
+    fn test_capabilities_for_version() {
+        let old = Capabilities::for_version(Some(TmuxVersion::new(3, 1)));
+        assert!(old.capture_escapes);
+        assert!(!old.control_client);
+        assert!(old.inherited_options);
+        assert!(old.hook_arrays);
+        let older = Capabilities::for_version(Some(TmuxVersion::new(2, 9)));
+        assert!(!older.inherited_options);
+        assert!(!older.hook_arrays);
+
+        let current = Capabilities::for_version(Some(TmuxVersion::new(3, 4)));
+        assert!(current.control_client);
+        assert!(current.popups);
+        assert!(!old.popups);
+        assert!(current.session_environment);
+        assert!(!old.session_environment);
+
+        assert!(
+            Capabilities::for_version(None).control_client,
+            "unnumbered builds are assumed current"
+        );
     }
 
     #[test]
     fn test_parse_panes() {
-        let fixture = "%0\x01@0\x01$0\x010\x010\x01bash\x01/home/aceworks/study\n";
+        let fixture =
+            "%0\x01@0\x01$0\x010\x010\x01bash\x01/home/aceworks/study\x01vim: main.rs\x01myhost\x010\n";
         let panes = parse_panes(fixture).expect("fixture should parse");
         assert_eq!(panes.len(), 1);
         assert_eq!(panes[0].id, "%0");
         assert_eq!(panes[0].window_id, "@0");
         assert_eq!(panes[0].session_id, "$0");
         assert_eq!(panes[0].current_command, "bash");
+        assert_eq!(panes[0].title, "vim: main.rs");
+    }
+
+    #[test]
+    fn test_parse_window_paths() {
+        let fixture = "editor\x01/home/me/app\nshell\x01/tmp\nbroken\n";
+        assert_eq!(
+            parse_window_paths(fixture),
+            vec![
+                ("editor".to_string(), "/home/me/app".to_string()),
+                ("shell".to_string(), "/tmp".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_clients() {
+        let fixture = "/dev/pts/3\x01work\x01200\x0150\x011770749593\x011770740000\x010\n\
+                       client-9\x01work\x0180\x0124\x011770749593\x011770740000\x011\n\
+                       broken\x01work\n";
+        let clients = parse_clients(fixture);
+        assert_eq!(
+            clients,
+            vec![Client {
+                tty: "/dev/pts/3".to_string(),
+                session: "work".to_string(),
+                width: 200,
+                height: 50,
+                activity: 1770749593,
+                created: 1770740000,
+            }]
+        );
+        assert_eq!(
+            control_clients(fixture),
+            HashMap::from([("work".to_string(), 1)])
+        );
+    }
+
+    #[test]
+    fn test_parse_buffers() {
+        let fixture =
+            "buffer1\x0111\x011770749593\x01echo hi\\012\nbuffer0\x01x\x010\x01bad size\n";
+        assert_eq!(
+            parse_buffers(fixture),
+            vec![PasteBuffer {
+                name: "buffer1".to_string(),
+                size: 11,
+                created: 1770749593,
+                sample: "echo hi\\012".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let fixture = "automatic-rename off\nmonitor-activity* on\nstatus-left \"[#S] \"\n\n";
+        assert_eq!(
+            parse_options(fixture),
+            vec![
+                TmuxOption {
+                    name: "automatic-rename".to_string(),
+                    value: "off".to_string(),
+                    inherited: false,
+                },
+                TmuxOption {
+                    name: "monitor-activity".to_string(),
+                    value: "on".to_string(),
+                    inherited: true,
+                },
+                TmuxOption {
+                    name: "status-left".to_string(),
+                    value: "[#S] ".to_string(),
+                    inherited: false,
+                },
+            ]
+        );
     }
 
     #[test]
     fn test_parse_special_chars() {
         let fixture =
-            "$1\x01테스트|파이프 with spaces\x011\x010\x011770744224\x011770749593\x01\x01/tmp\n";
+            "$1\x01테스트|파이프 with spaces\x011\x010\x011770744224\x011770749593\x01\x01/tmp\x01\n";
         let sessions = parse_sessions(fixture).expect("fixture should parse");
         assert_eq!(sessions.len(), 1);
         assert_eq!(sessions[0].name, "테스트|파이프 with spaces");
@@ -328,7 +1444,8 @@ mod tests {
 
     #[test]
     fn test_parse_malformed() {
-        let fixture = "malformed\n$2\x01valid\x011\x010\x011770744224\x011770749593\x01\x01/tmp\n";
+        let fixture =
+            "malformed\n$2\x01valid\x011\x010\x011770744224\x011770749593\x01\x01/tmp\x01\n";
         let sessions = parse_sessions(fixture).expect("fixture should parse");
 
         assert_eq!(sessions.len(), 1);
@@ -387,7 +1504,51 @@ mod tests {
     fn test_attach_session_exec_exists() {
         // Verify attach_session_exec is callable (type-level test).
         // We cannot actually call it since it replaces the process.
-        let _fn_ptr: fn(&str) -> ! = attach_session_exec;
+        let _fn_ptr: fn(&str, bool) -> std::io::Error = attach_session_exec;
+    }
+
+    #[tokio::test]
+    async fn test_pipe_quotes_path_and_escapes_formats() {
+        let tmux = crate::testing::FakeTmux::new();
+
+        start_pipe("work:1", "/tmp/it's #1.log")
+            .await
+            .expect("pipe should start");
+
+        assert_eq!(
+            tmux.calls(),
+            ["pipe-pane -o -t work:1 cat >> '/tmp/it'\\''s ##1.log'"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_runner_keeps_pane_before_command_starts() {
+        let tmux = crate::testing::FakeTmux::new();
+
+        create_runner_session("run-1", "exit 3")
+            .await
+            .expect("runner should start");
+
+        let calls = tmux.calls();
+        assert_eq!(calls.len(), 1, "one tmux invocation: {calls:?}");
+        let call = &calls[0];
+        assert!(call.starts_with("new-session -d -s run-1 ;"), "{call}");
+        let remain = call.find("remain-on-exit on").expect("remain-on-exit set");
+        let start = call
+            .find("respawn-pane -k -t run-1: exit 3")
+            .expect("command run");
+        assert!(remain < start, "{call}");
+    }
+
+    #[tokio::test]
+    async fn test_list_sessions_survives_failed_client_listing() {
+        let _tmux = crate::testing::FakeTmux::new()
+            .with_attached_session("work")
+            .failing("list-clients", "server exited unexpectedly");
+
+        let sessions = list_sessions().await.expect("sessions should still list");
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].attached, 1, "count kept as tmux reports it");
     }
 
     #[tokio::test]
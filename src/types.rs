@@ -1,5 +1,7 @@
 //! Core types for tmui application.
 
+use serde::{Deserialize, Serialize};
+
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub enum AppMode {
     #[default]
@@ -7,19 +9,176 @@ pub enum AppMode {
     Search,
     Input(InputPurpose),
     Confirm(ConfirmAction),
+    /// Create prompt hit an existing session name.
+    Conflict(String),
+    /// Popup listing the clients attached to this session.
+    Clients(SessionTarget),
+    /// Diagnostic list of tmux server sockets.
+    Servers,
+    /// Choosing one of `App::pick_items`.
+    Pick(PickPurpose),
+    /// Filling in `App::form`.
+    Form(FormPurpose),
+    /// Paste buffers, to paste into `target` or delete.
+    Buffers(String),
+    /// Activity timeline of the session with this id.
+    Timeline(String),
+    /// Command palette; the query is in `App::input_buffer`.
+    Palette,
+    /// Marking sessions (`App::visual_marked`) for a bulk action.
+    Visual,
+    /// What changed since the last saved snapshot (`App::changes`).
+    Changes,
+    /// Attach counts and time per session, least used first.
+    Stats,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormPurpose {
+    /// Name, start directory and command of a new session.
+    NewSession,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PickPurpose {
+    /// Destination session for `move-window`.
+    MoveWindow { window_id: String, name: String },
+    /// Pane of window `window` to kill; the choices are `App::panes`.
+    KillPane { window: String },
+    /// Pane of window `window` to move into a window of its own.
+    BreakPane { window: String },
+    /// Pane of window `window` to move into another window.
+    JoinPane { window: String },
+    /// Dead pane of window `window` to restart.
+    RespawnPane { window: String },
+    /// Destination of `join-pane` for `pane_id`; `window_ids` lines up
+    /// with `App::pick_items`.
+    JoinPaneTo {
+        pane_id: String,
+        window_ids: Vec<String>,
+    },
+    /// Layout preset for window `target` (`session:index`).
+    Layout { target: String },
+    /// Options of `target` (window options when `window`); the choices
+    /// are `App::options`.
+    Options { target: String, window: bool },
+    /// What to do with the session group of `session`.
+    Group { session: SessionTarget },
+    /// Server to manage: the local one, then `Config::remotes` in order.
+    Host,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InputPurpose {
     NewSession,
     RenameSession,
+    RenameWindow,
     AddTag,
     FilterByTag,
+    RunCommand,
+    /// Text typed into the active pane of the selected session or window.
+    SendKeys,
+    /// File path or snippet pasted into the active pane of the selected
+    /// session or window.
+    SendText,
+    /// Command to run in a new window of the selected session.
+    NewWindowCommand,
+    /// File to save the scrollback of `target`'s active pane to.
+    CaptureToFile {
+        target: String,
+    },
+    /// Name of a new session grouped with `target`.
+    GroupedSession {
+        target: SessionTarget,
+    },
+    /// New value of option `name` on `target`.
+    SetOption {
+        target: String,
+        window: bool,
+        name: String,
+    },
+    /// Tag for every one of these sessions.
+    TagSessions(Vec<String>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ConfirmAction {
-    KillSession(String),
+    KillSession(SessionTarget),
+    /// Kill the session and start an empty one under the same name, from
+    /// the name conflict prompt.
+    ReplaceSession(SessionTarget),
+    CleanupSessions(Vec<SessionTarget>),
+    /// Sessions marked in visual mode.
+    KillSessions(Vec<SessionTarget>),
+    /// Detach every client of the sessions marked in visual mode.
+    DetachSessions(Vec<SessionTarget>),
+    /// `tmux kill-server`; by default needs an explicit `Y` and Enter
+    /// cancels.
+    KillServer,
+    /// `kill-pane` on pane `id`, shown as `label`.
+    KillPane {
+        id: String,
+        label: String,
+    },
+}
+
+impl ConfirmAction {
+    /// Key of the action in the `[confirm]` config table.
+    pub fn config_key(&self) -> &'static str {
+        match self {
+            ConfirmAction::KillSession(_) => "kill_session",
+            ConfirmAction::ReplaceSession(_) => "replace_session",
+            ConfirmAction::CleanupSessions(_) => "cleanup",
+            ConfirmAction::KillSessions(_) => "kill_sessions",
+            ConfirmAction::DetachSessions(_) => "detach_sessions",
+            ConfirmAction::KillServer => "kill_server",
+            ConfirmAction::KillPane { .. } => "kill_pane",
+        }
+    }
+
+    pub fn default_style(&self) -> ConfirmStyle {
+        match self {
+            ConfirmAction::KillServer => ConfirmStyle::No,
+            _ => ConfirmStyle::Yes,
+        }
+    }
+
+    /// What [`ConfirmStyle::Type`] asks to be typed.
+    pub fn phrase(&self) -> &str {
+        match self {
+            ConfirmAction::KillSession(target) | ConfirmAction::ReplaceSession(target) => {
+                &target.name
+            }
+            ConfirmAction::CleanupSessions(_) => "cleanup",
+            ConfirmAction::KillSessions(_) => "kill",
+            ConfirmAction::DetachSessions(_) => "detach",
+            ConfirmAction::KillServer => "kill-server",
+            ConfirmAction::KillPane { id, .. } => id,
+        }
+    }
+
+    /// Key confirming under [`ConfirmStyle::No`]; the server takes a
+    /// capital `Y`.
+    pub fn confirm_key(&self) -> char {
+        match self {
+            ConfirmAction::KillServer => 'Y',
+            _ => 'y',
+        }
+    }
+}
+
+/// How a confirmation popup is answered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfirmStyle {
+    /// `y` or Enter confirms.
+    #[default]
+    Yes,
+    /// "No" is preselected: Enter cancels, only the confirm key goes ahead.
+    No,
+    /// The action's phrase (e.g. the session name) must be typed, then
+    /// Enter.
+    Type,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -41,6 +200,58 @@ pub struct Session {
     pub last_attached: i64,
     pub group: Option<String>,
     pub path: String,
+    pub runner: bool,
+    /// Extra server (`servers` in the config) the session lives on; `None`
+    /// for the managed one.
+    pub server: Option<String>,
+}
+
+/// A session named together with the server it lives on, for effects
+/// that must reach that server whatever is selected when they run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionTarget {
+    /// As [`Session::server`]: `None` for the managed server.
+    pub server: Option<String>,
+    pub name: String,
+}
+
+impl Session {
+    /// Identity of the session across every listed server (see
+    /// [`session_key`]).
+    pub fn key(&self) -> String {
+        session_key(self.server.as_deref(), &self.name)
+    }
+
+    pub fn target(&self) -> SessionTarget {
+        SessionTarget {
+            server: self.server.clone(),
+            name: self.name.clone(),
+        }
+    }
+}
+
+/// Key for caches of sessions from several servers: the name on the
+/// managed server, `server:name` on an extra one. tmux keeps `:` out of
+/// session names, so two sessions never share a key.
+pub fn session_key(server: Option<&str>, name: &str) -> String {
+    match server {
+        Some(server) => format!("{server}:{name}"),
+        None => name.to_string(),
+    }
+}
+
+impl SessionTarget {
+    pub fn key(&self) -> String {
+        session_key(self.server.as_deref(), &self.name)
+    }
+
+    /// Name as listed, with the server of sessions not on the managed one.
+    pub fn label(&self) -> String {
+        match &self.server {
+            Some(server) => format!("{} ({server})", self.name),
+            None => self.name.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -51,10 +262,118 @@ pub struct Window {
     pub name: String,
     pub active: bool,
     pub active_command: String,
+    /// Title set by the active pane's program; empty when unset.
+    pub pane_title: String,
+    /// Destination host when the active pane runs ssh.
+    pub remote_host: Option<String>,
+    /// `#{window_flags}` as in tmux's status line: `*` current, `-` last,
+    /// `Z` zoomed, `M` marked, `!` bell, `#` activity, `~` silence.
+    pub flags: String,
+    /// The active pane's command exited and `remain-on-exit` kept it.
+    pub dead: bool,
+    /// The active pane's output is piped to a command (`pipe-pane`).
+    pub piped: bool,
+    /// The active pane is on its alternate screen (`#{alternate_on}`), as
+    /// full-screen programs like vim or htop put it.
+    pub alternate: bool,
+}
+
+impl Window {
+    /// Flags to show after the index; falls back to the active mark when
+    /// tmux reported none.
+    pub fn status_flags(&self) -> &str {
+        match (self.flags.is_empty(), self.active) {
+            (false, _) => &self.flags,
+            (true, true) => "*",
+            (true, false) => "",
+        }
+    }
+
+    /// Whether the active pane is zoomed to fill the window.
+    pub fn zoomed(&self) -> bool {
+        self.flags.contains('Z')
+    }
+
+    /// What to show for the window's activity: a dead marker, the ssh
+    /// destination, the pane title when a program set one, otherwise the
+    /// running command.
+    pub fn activity(&self) -> String {
+        if self.dead {
+            format!("dead: {}", self.active_command)
+        } else if let Some(host) = &self.remote_host {
+            format!("{} → {host}", self.active_command)
+        } else if self.pane_title.is_empty() {
+            self.active_command.clone()
+        } else {
+            self.pane_title.clone()
+        }
+    }
+}
+
+/// A tmux paste buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasteBuffer {
+    pub name: String,
+    /// Size in bytes.
+    pub size: usize,
+    /// Unix time the buffer was created.
+    pub created: i64,
+    /// Start of the contents, with control characters escaped by tmux.
+    pub sample: String,
+}
+
+/// One line of `show-options`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TmuxOption {
+    pub name: String,
+    pub value: String,
+    /// Not set on the target itself; shown from the global value (`-A`).
+    pub inherited: bool,
+}
+
+impl TmuxOption {
+    /// `on`/`off` options flip in place; others are edited as text.
+    pub fn toggled(&self) -> Option<&'static str> {
+        match self.value.as_str() {
+            "on" => Some("off"),
+            "off" => Some("on"),
+            _ => None,
+        }
+    }
+}
+
+/// What `u` needs to bring back a killed session: its windows as empty
+/// shells, by name and working directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSnapshot {
+    pub name: String,
+    /// As [`Session::server`], so the session comes back where it was.
+    pub server: Option<String>,
+    /// Directory of the session itself, for when no window was captured.
+    pub path: String,
+    pub windows: Vec<(String, String)>,
+}
+
+impl SessionSnapshot {
+    pub fn key(&self) -> String {
+        session_key(self.server.as_deref(), &self.name)
+    }
+}
+
+/// A terminal attached to the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Client {
+    pub tty: String,
+    pub session: String,
+    pub width: u16,
+    pub height: u16,
+    /// Unix time of the client's last input.
+    pub activity: i64,
+    /// Unix time the client attached.
+    pub created: i64,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-#[allow(dead_code)]
 pub struct Pane {
     pub id: String,
     pub window_id: String,
@@ -63,6 +382,10 @@ pub struct Pane {
     pub active: bool,
     pub current_command: String,
     pub current_path: String,
+    /// Title set by the pane's program; empty when unset.
+    pub title: String,
+    /// The command exited and `remain-on-exit` kept the pane.
+    pub dead: bool,
 }
 
 #[cfg(test)]
@@ -78,14 +401,15 @@ mod tests {
     fn test_mode_variants_construct() {
         let search = AppMode::Search;
         let input = AppMode::Input(InputPurpose::NewSession);
-        let confirm = AppMode::Confirm(ConfirmAction::KillSession("demo".to_string()));
+        let demo = SessionTarget {
+            server: None,
+            name: "demo".to_string(),
+        };
+        let confirm = AppMode::Confirm(ConfirmAction::KillSession(demo.clone()));
 
         assert_eq!(search, AppMode::Search);
         assert_eq!(input, AppMode::Input(InputPurpose::NewSession));
-        assert_eq!(
-            confirm,
-            AppMode::Confirm(ConfirmAction::KillSession("demo".to_string()))
-        );
+        assert_eq!(confirm, AppMode::Confirm(ConfirmAction::KillSession(demo)));
     }
 
     #[test]
@@ -99,6 +423,8 @@ mod tests {
             last_attached: 1770749593,
             group: None,
             path: "/tmp".to_string(),
+            runner: false,
+            server: None,
         };
 
         assert_eq!(session.name, "work");
@@ -113,11 +439,39 @@ mod tests {
             name: "editor".to_string(),
             active: true,
             active_command: "vim".to_string(),
+            pane_title: String::new(),
+            remote_host: None,
+            flags: String::new(),
+            dead: false,
+            piped: false,
+            alternate: false,
         };
 
         assert_eq!(window.name, "editor");
     }
 
+    #[test]
+    fn test_window_activity_marks_dead_pane() {
+        let mut window = Window {
+            id: "@0".to_string(),
+            session_id: "$0".to_string(),
+            index: 0,
+            name: "server".to_string(),
+            active: true,
+            active_command: "node".to_string(),
+            pane_title: String::new(),
+            remote_host: None,
+            flags: String::new(),
+            dead: false,
+            piped: false,
+            alternate: false,
+        };
+        assert_eq!(window.activity(), "node");
+
+        window.dead = true;
+        assert_eq!(window.activity(), "dead: node");
+    }
+
     #[test]
     fn test_pane_struct_fields() {
         let pane = Pane {
@@ -128,6 +482,8 @@ mod tests {
             active: true,
             current_command: "bash".to_string(),
             current_path: "/tmp".to_string(),
+            title: String::new(),
+            dead: false,
         };
 
         assert_eq!(pane.current_command, "bash");
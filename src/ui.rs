@@ -1,5 +1,6 @@
 use ansi_to_tui::IntoText;
 use ratatui::{
+    buffer::Buffer,
     layout::{Alignment, Constraint, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
@@ -9,32 +10,172 @@ use ratatui::{
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::app::App;
-use crate::types::{AppMode, ConfirmAction, FocusPanel, InputPurpose, Session, Window};
+use crate::config::{AlternateScreen, Config, Visit};
+use crate::heat::Heat;
+use crate::snapshot::Change;
+
+mod widgets;
+
+use crate::types::{
+    AppMode, ConfirmAction, ConfirmStyle, FocusPanel, FormPurpose, InputPurpose, PickPurpose,
+    Session, SessionTarget, Window,
+};
+use widgets::{ConfirmPopup, FormPopup, InputPopup, PickerPopup, Popup};
+
+/// Rows built beyond either edge of the sessions viewport.
+const VIEWPORT_MARGIN: usize = 5;
+
+/// How much styling the terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    #[default]
+    Color,
+    /// No colors; bold/reverse still apply.
+    Monochrome,
+    /// No colors and ASCII in place of box-drawing and symbol glyphs.
+    Ascii,
+}
+
+impl ColorMode {
+    /// Detect from `TERM`, `NO_COLOR` and the terminal's color depth;
+    /// `forced` is the `monochrome` config setting.
+    pub fn from_env(forced: bool) -> Self {
+        let term = std::env::var("TERM").ok();
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        Self::detect(
+            term.as_deref(),
+            no_color || forced,
+            crossterm::style::available_color_count(),
+        )
+    }
+
+    fn detect(term: Option<&str>, no_color: bool, color_count: u16) -> Self {
+        if matches!(term, Some("dumb") | None) {
+            ColorMode::Ascii
+        } else if no_color || color_count < 8 {
+            ColorMode::Monochrome
+        } else {
+            ColorMode::Color
+        }
+    }
+}
+
+/// ASCII stand-ins for the glyphs tmui draws.
+const ASCII_GLYPHS: &[(&str, &str)] = &[
+    ("─", "-"),
+    ("│", "|"),
+    ("┌", "+"),
+    ("┐", "+"),
+    ("└", "+"),
+    ("┘", "+"),
+    ("├", "+"),
+    ("┤", "+"),
+    ("▶", ">"),
+    ("▼", "v"),
+    ("●", "*"),
+    ("○", "o"),
+    ("…", "~"),
+    ("→", ">"),
+    ("▁", "."),
+    ("▄", "o"),
+    ("█", "#"),
+];
+
+/// Strip what the terminal cannot show from a rendered frame.
+fn degrade(buffer: &mut Buffer, mode: ColorMode) {
+    if mode == ColorMode::Color {
+        return;
+    }
+    for cell in &mut buffer.content {
+        cell.set_fg(Color::Reset).set_bg(Color::Reset);
+        if mode == ColorMode::Ascii {
+            if let Some((_, ascii)) = ASCII_GLYPHS.iter().find(|(g, _)| *g == cell.symbol()) {
+                cell.set_symbol(ascii);
+            }
+        }
+    }
+}
 
 pub fn render(frame: &mut Frame, app: &App) {
+    draw(frame, app);
+    degrade(frame.buffer_mut(), app.color_mode);
+}
+
+/// Header, sessions, windows, preview and status bar areas of the screen,
+/// as arranged by `config` for its size; hidden panels get an empty area.
+fn layout(area: Rect, config: &Config) -> [Rect; 5] {
+    let screen = config.screen_layout(area.width, area.height);
+    let list_width = if screen.preview {
+        screen.list_width
+    } else {
+        100
+    };
+    let sessions_height = if screen.windows { 60 } else { 100 };
     let chunks = Layout::vertical([
         Constraint::Length(1),
         Constraint::Min(0),
         Constraint::Length(1),
     ])
-    .split(frame.area());
+    .split(area);
+
+    let main_chunks = Layout::horizontal([
+        Constraint::Percentage(list_width),
+        Constraint::Percentage(100 - list_width),
+    ])
+    .split(chunks[1]);
+
+    let left_chunks = Layout::vertical([
+        Constraint::Percentage(sessions_height),
+        Constraint::Percentage(100 - sessions_height),
+    ])
+    .split(main_chunks[0]);
+
+    [
+        chunks[0],
+        left_chunks[0],
+        left_chunks[1],
+        main_chunks[1],
+        chunks[2],
+    ]
+}
 
-    render_header(frame, app, chunks[0]);
+/// Screen column where the lists column ends and the preview begins;
+/// `None` while the preview is hidden.
+pub fn list_border(area: Rect, config: &Config) -> Option<u16> {
+    let preview = layout(area, config)[3];
+    (!preview.is_empty()).then_some(preview.x)
+}
 
-    let main_chunks = Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)])
-        .split(chunks[1]);
+/// Rows the sessions and windows lists show on a screen of size `area`,
+/// for paging through them.
+pub fn list_rows(area: Rect, config: &Config) -> (usize, usize) {
+    let [_, sessions, windows, _, _] = layout(area, config);
+    let rows = |panel| Block::default().borders(Borders::ALL).inner(panel).height as usize;
+    (rows(sessions), rows(windows))
+}
 
-    let left_chunks = Layout::vertical([Constraint::Percentage(60), Constraint::Percentage(40)])
-        .split(main_chunks[0]);
+fn draw(frame: &mut Frame, app: &App) {
+    let [header, sessions, windows, preview, status] = layout(frame.area(), &app.config);
 
-    render_session_list(frame, app, left_chunks[0]);
-    render_windows_panel(frame, app, left_chunks[1]);
-    render_preview(frame, app, main_chunks[1]);
-    render_status_bar(frame, app, chunks[2]);
+    render_header(frame, app, header);
+    render_session_list(frame, app, sessions);
+    render_windows_panel(frame, app, windows);
+    render_preview(frame, app, preview);
+    render_status_bar(frame, app, status);
 
     match &app.mode {
-        AppMode::Input(purpose) => render_input_popup(frame, app, purpose.clone()),
+        AppMode::Input(purpose) => render_input_popup(frame, app, purpose),
         AppMode::Confirm(action) => render_confirm_popup(frame, app, action.clone()),
+        AppMode::Conflict(name) => render_conflict_popup(frame, app, name),
+        AppMode::Clients(session) => render_clients_popup(frame, app, &session.label()),
+        AppMode::Buffers(target) => render_buffers_popup(frame, app, target),
+        AppMode::Timeline(session_id) => render_timeline_popup(frame, app, session_id),
+        AppMode::Changes => render_changes_popup(frame, app),
+        AppMode::Stats => render_stats_popup(frame, app),
+        AppMode::Pick(purpose) => render_pick_popup(frame, app, purpose),
+        AppMode::Servers => render_servers_popup(frame, app),
+        AppMode::Form(purpose) => render_form_popup(frame, app, purpose),
+        AppMode::Palette => render_palette_popup(frame, app),
         _ => {}
     }
 
@@ -47,16 +188,39 @@ fn render_header(frame: &mut Frame, app: &App, area: Rect) {
     let session_info = if app.sessions.is_empty() {
         String::new()
     } else {
-        format!(" ({} sessions)", app.sessions.len())
+        let sort = app.config.sort.unwrap_or_default();
+        let hidden = if app.hides_ignored() {
+            match app.ignored_count() {
+                0 => String::new(),
+                n => format!(", {n} hidden"),
+            }
+        } else {
+            String::new()
+        };
+        format!(
+            " ({} sessions{hidden}, by {})",
+            app.sessions.len(),
+            sort.label()
+        )
     };
-    let header = Paragraph::new(format!("tmui{session_info} | ? help | q quit"))
+    let host = app
+        .remote_host
+        .as_ref()
+        .map(|host| format!(" @{host}"))
+        .unwrap_or_default();
+    let header = Paragraph::new(format!("tmui{host}{session_info} | ? help | q quit"))
         .style(Style::default().bg(Color::DarkGray).fg(Color::White));
     frame.render_widget(header, area);
 }
 
 fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
     if let Some(ref err) = app.error_message {
-        let error_bar = Paragraph::new(err.as_str()).style(
+        let text = if app.error_sticky {
+            format!("{err} (Esc to dismiss)")
+        } else {
+            err.clone()
+        };
+        let error_bar = Paragraph::new(text).style(
             Style::default()
                 .bg(Color::Red)
                 .fg(Color::White)
@@ -73,8 +237,7 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
         .unwrap_or_default();
 
     let selected_info = app
-        .sessions
-        .get(app.selected)
+        .selected_session()
         .map(|s| {
             let status = if s.attached > 0 {
                 "attached"
@@ -84,30 +247,72 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
             format!(" | {} ({status})", s.name)
         })
         .unwrap_or_default();
+    let path_info = match (app.focus, &app.pane_path) {
+        (FocusPanel::Windows, Some(path)) => format!(" {}", contract_home(path)),
+        _ => String::new(),
+    };
+
+    let speed = match app.nav_step() {
+        1 => String::new(),
+        rows => format!(" ×{rows}"),
+    };
 
     let footer_text = match app.mode {
-        AppMode::Normal | AppMode::Input(_) | AppMode::Confirm(_) => format!(
-            "NORMAL{tag_indicator}{selected_info} | {}",
+        AppMode::Normal
+        | AppMode::Input(_)
+        | AppMode::Confirm(_)
+        | AppMode::Conflict(_)
+        | AppMode::Clients(_)
+        | AppMode::Buffers(_)
+        | AppMode::Timeline(_)
+        | AppMode::Changes
+        | AppMode::Stats
+        | AppMode::Pick(_)
+        | AppMode::Form(_)
+        | AppMode::Servers => {
+            format!(
+                "NORMAL{speed}{tag_indicator}{selected_info}{path_info} | {}",
+                app.status_message
+            )
+        }
+        AppMode::Search => format!("SEARCH /{}", app.input_buffer),
+        AppMode::Palette => format!("COMMAND > {}", app.input_buffer),
+        AppMode::Visual => format!(
+            "VISUAL {} marked | {}",
+            app.visual_marked.len(),
             app.status_message
         ),
-        AppMode::Search => format!("SEARCH /{}", app.input_buffer),
     };
+    if matches!(app.mode, AppMode::Search | AppMode::Palette) {
+        let end = area.x.saturating_add(footer_text.width() as u16);
+        frame.set_cursor_position((end.min(area.right().saturating_sub(1)), area.y));
+    }
     let footer =
         Paragraph::new(footer_text).style(Style::default().bg(Color::Blue).fg(Color::White));
     frame.render_widget(footer, area);
 }
 
-fn render_help_overlay(frame: &mut Frame) {
-    let area = frame.area();
-    let popup_width = 44u16.min(area.width.saturating_sub(4));
-    let popup_height = 20u16.min(area.height.saturating_sub(4));
-
-    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
-    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
-    let popup_area = Rect::new(x, y, popup_width, popup_height);
+/// `path` with `$HOME` shortened to `~`.
+fn contract_home(path: &str) -> String {
+    match std::env::var("HOME") {
+        Ok(home) if !home.is_empty() && path.starts_with(&home) => match &path[home.len()..] {
+            "" => "~".to_string(),
+            rest if rest.starts_with('/') => format!("~{rest}"),
+            _ => path.to_string(),
+        },
+        _ => path.to_string(),
+    }
+}
 
-    frame.render_widget(Clear, popup_area);
+/// Window title for terminal tab bars: `tmui – <selected session>`.
+pub fn terminal_title(app: &App) -> String {
+    match app.selected_session() {
+        Some(session) => format!("tmui – {}", session.name),
+        None => "tmui".to_string(),
+    }
+}
 
+fn render_help_overlay(frame: &mut Frame) {
     let key_style = Style::default()
         .fg(Color::Yellow)
         .add_modifier(Modifier::BOLD);
@@ -115,21 +320,57 @@ fn render_help_overlay(frame: &mut Frame) {
 
     let bindings: &[(&str, &str)] = &[
         ("j / k", "Move down / up"),
-        ("G", "Jump to last"),
-        ("g g", "Jump to first"),
-        ("Enter", "Attach / switch session"),
-        ("n", "New session"),
-        ("r", "Rename session"),
-        ("d d", "Kill session (confirm)"),
-        ("D", "Detach clients"),
-        ("/", "Fuzzy search"),
-        ("t", "Add tag to session"),
-        ("T", "Filter by tag / clear"),
+        ("G / End", "Jump to last"),
+        ("g g / Home", "Jump to first"),
+        ("Enter / A", "Attach (A: read-only)"),
+        ("- / C-^", "Back to last session"),
+        ("n / N", "New session (N: dir / command)"),
+        ("!", "Run command (throwaway)"),
+        ("i / I", "Send keys / paste file or snippet"),
+        ("w", "Run command in new window"),
+        ("o", "Session/window options"),
+        ("r / W", "Rename (W: active window)"),
+        ("d d / u", "Kill session / undo the kill"),
+        ("D / c", "Clients (detach one or all)"),
+        ("K", "Kill tmux server (sessions)"),
+        ("S", "tmux servers / stray sockets"),
+        ("@", "Local / remote (ssh) hosts"),
+        ("=", "Paste buffers (paste / copy / delete)"),
+        ("+", "Session group: new grouped session / leave"),
+        ("h / E / U", "Timeline / changes / usage"),
+        ("y / l / >", "Copy / log / save pane to file"),
+        ("C", "Review cleanup policies"),
+        ("/", "Fuzzy search (C-t: tag all matches)"),
+        ("t / T", "Add tag / filter by tag"),
+        ("mX / 'X", "Mark session X / jump to it"),
+        ("V", "Select several: kill / tag / detach"),
+        ("P / s / H", "Group by dir / sort / ignored"),
         ("Tab", "Expand / collapse windows"),
+        ("m", "Move window to session (windows)"),
+        ("L", "Apply layout preset (windows)"),
+        ("J / K", "Reorder window (windows)"),
+        ("z", "Toggle pane zoom"),
+        ("x / b / M", "Kill / break / join pane (windows)"),
+        ("R", "Respawn dead pane (windows)"),
+        ("PgUp / PgDn", "Page list (C-u / C-d: half)"),
+        ("S-PgUp / S-PgDn", "Scroll preview (C-y / C-e: half)"),
+        ("C-p", "Command palette"),
         ("?", "Toggle this help"),
         ("q", "Quit"),
     ];
 
+    // Spill into a second column once the list outgrows the screen.
+    let area = frame.area();
+    let max_rows = (area.height.saturating_sub(10) as usize).max(1);
+    let columns = if bindings.len() > max_rows { 2 } else { 1 };
+    let rows = bindings.len().div_ceil(columns);
+    let popup_width = (44 * columns as u16).min(area.width.saturating_sub(4));
+    let popup_height = (rows as u16 + 6).min(area.height.saturating_sub(4));
+
+    let popup_area = widgets::centered_rect(area, popup_width, popup_height);
+
+    frame.render_widget(Clear, popup_area);
+
     let mut lines: Vec<Line> = Vec::new();
     lines.push(Line::from(Span::styled(
         "  Keybindings",
@@ -137,13 +378,20 @@ fn render_help_overlay(frame: &mut Frame) {
     )));
     lines.push(Line::from(""));
 
-    for (key, desc) in bindings {
-        lines.push(Line::from(vec![
-            Span::raw("  "),
-            Span::styled(format!("{key:<8}"), key_style),
-            Span::styled(" │ ", sep_style),
-            Span::raw(*desc),
-        ]));
+    for row in 0..rows {
+        let mut spans = Vec::new();
+        for column in 0..columns {
+            let Some((key, desc)) = bindings.get(column * rows + row) else {
+                continue;
+            };
+            spans.extend([
+                Span::raw("  "),
+                Span::styled(format!("{key:<8}"), key_style),
+                Span::styled(" │ ", sep_style),
+                Span::raw(format!("{:<31}", truncate_with_ellipsis(desc, 31))),
+            ]);
+        }
+        lines.push(Line::from(spans));
     }
 
     lines.push(Line::from(""));
@@ -161,114 +409,580 @@ fn render_help_overlay(frame: &mut Frame) {
     frame.render_widget(help, popup_area);
 }
 
-fn render_input_popup(frame: &mut Frame, app: &App, purpose: InputPurpose) {
-    let area = frame.area();
-
+fn render_input_popup(frame: &mut Frame, app: &App, purpose: &InputPurpose) {
     let title = match purpose {
-        InputPurpose::NewSession => " New Session ",
-        InputPurpose::RenameSession => " Rename Session ",
-        InputPurpose::AddTag => " Add Tag ",
-        InputPurpose::FilterByTag => " Filter by Tag ",
+        InputPurpose::NewSession => "New Session",
+        InputPurpose::RenameSession => "Rename Session",
+        InputPurpose::RenameWindow => "Rename Window",
+        InputPurpose::AddTag => "Add Tag",
+        InputPurpose::TagSessions(_) => "Tag Sessions",
+        InputPurpose::FilterByTag => "Filter by Tag",
+        InputPurpose::RunCommand => "Run Command",
+        InputPurpose::SendKeys => "Send Keys",
+        InputPurpose::SendText => "Paste File or Snippet",
+        InputPurpose::NewWindowCommand => "Run in New Window",
+        InputPurpose::GroupedSession { .. } => "New Grouped Session",
+        InputPurpose::CaptureToFile { .. } => "Save Scrollback",
+        InputPurpose::SetOption { window: true, .. } => "Set Window Option",
+        InputPurpose::SetOption { .. } => "Set Session Option",
     };
 
     let label = match purpose {
-        InputPurpose::NewSession => "Session name",
-        InputPurpose::RenameSession => "New name",
-        InputPurpose::AddTag => "Tag name",
+        InputPurpose::NewSession | InputPurpose::GroupedSession { .. } => "Session name",
+        InputPurpose::RenameSession | InputPurpose::RenameWindow => "New name",
+        InputPurpose::AddTag | InputPurpose::TagSessions(_) => "Tag name",
         InputPurpose::FilterByTag => "Tag",
+        InputPurpose::RunCommand | InputPurpose::NewWindowCommand => "Command",
+        InputPurpose::SendKeys => "Text (Enter appended)",
+        InputPurpose::SendText => "File path or text",
+        InputPurpose::CaptureToFile { .. } => "File",
+        InputPurpose::SetOption { name, .. } => name,
     };
 
-    let popup_width = 40u16.min(area.width.saturating_sub(4));
-    let popup_height = 5u16;
-    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
-    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
-    let popup_area = Rect::new(x, y, popup_width, popup_height);
+    InputPopup {
+        title,
+        label,
+        value: &app.input_buffer,
+        feedback: input_feedback(app, purpose),
+    }
+    .render(frame);
+}
+
+/// Inline validation line shown under the input while typing.
+fn input_feedback<'a>(app: &App, purpose: &InputPurpose) -> Option<Line<'a>> {
+    if let Some(name) = app.typed_session_name().filter(|n| app.is_name_taken(n)) {
+        return Some(Line::from(Span::styled(
+            format!("  ✗ `{name}` already exists"),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    let value = app.input_buffer.trim();
+    if *purpose != InputPurpose::NewSession || app.config.naming.is_empty() || value.is_empty() {
+        return None;
+    }
 
-    frame.render_widget(Clear, popup_area);
+    Some(match app.config.naming.apply(value) {
+        Ok(name) => Line::from(Span::styled(
+            format!("  → {name}"),
+            Style::default().fg(Color::Green),
+        )),
+        Err(reason) => Line::from(Span::styled(
+            format!("  ✗ {reason}"),
+            Style::default().fg(Color::Red),
+        )),
+    })
+}
+
+fn render_form_popup(frame: &mut Frame, app: &App, purpose: &FormPurpose) {
+    let title = match purpose {
+        FormPurpose::NewSession => "New Session",
+    };
+    FormPopup {
+        title,
+        form: &app.form,
+    }
+    .render(frame);
+}
+
+fn render_confirm_popup(frame: &mut Frame, app: &App, action: ConfirmAction) {
+    let (message, targets): (String, &[SessionTarget]) = match &action {
+        ConfirmAction::KillSession(target) => {
+            let name = target.label();
+            let message = match app.own_session_note(std::slice::from_ref(target)) {
+                Some(note) => format!("Kill session `{name}`, {note}?"),
+                None => format!("Kill session `{name}`?"),
+            };
+            (message, &[])
+        }
+        ConfirmAction::ReplaceSession(target) => {
+            let name = target.label();
+            let message = match app.own_session_note(std::slice::from_ref(target)) {
+                Some(note) => format!("Replace `{name}` with a new session, {note}?"),
+                None => format!("Replace `{name}` with a new session?"),
+            };
+            (message, &[])
+        }
+        ConfirmAction::CleanupSessions(targets) => {
+            (format!("Kill {} stale sessions?", targets.len()), targets)
+        }
+        ConfirmAction::KillSessions(targets) => {
+            let message = match app.own_session_note(targets) {
+                Some(note) => format!("Kill {} sessions, {note}?", targets.len()),
+                None => format!("Kill {} sessions?", targets.len()),
+            };
+            (message, targets)
+        }
+        ConfirmAction::DetachSessions(targets) => (
+            format!("Detach all clients of {} sessions?", targets.len()),
+            targets,
+        ),
+        ConfirmAction::KillPane { label, .. } => (format!("Kill pane `{label}`?"), &[]),
+        ConfirmAction::KillServer => (
+            format!(
+                "Kill the tmux server and all {} sessions?",
+                app.sessions.len()
+            ),
+            &[],
+        ),
+    };
+    let style = app.config.confirm_style(&action);
+    let hint = match style {
+        ConfirmStyle::Yes => "y: confirm  n/Esc: cancel",
+        ConfirmStyle::No if action == ConfirmAction::KillServer => {
+            "Y: kill server  Enter/n/Esc: cancel"
+        }
+        ConfirmStyle::No => "y: confirm  Enter/n/Esc: cancel",
+        ConfirmStyle::Type => "Enter: confirm  Esc: cancel",
+    };
+
+    let targets: Vec<String> = targets.iter().map(SessionTarget::label).collect();
+    ConfirmPopup {
+        message,
+        targets: &targets,
+        hint,
+        default_no: style == ConfirmStyle::No,
+        typed: (style == ConfirmStyle::Type).then(|| (action.phrase(), app.input_buffer.as_str())),
+    }
+    .render(frame);
+}
 
-    let input_display = format!("{}▌", app.input_buffer);
+fn render_conflict_popup(frame: &mut Frame, _app: &App, name: &str) {
+    let key_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
     let lines = vec![
         Line::from(""),
         Line::from(vec![
             Span::raw("  "),
-            Span::styled(format!("{label}: "), Style::default().fg(Color::DarkGray)),
-            Span::styled(input_display, Style::default().fg(Color::White)),
+            Span::styled(
+                format!("Session `{name}` already exists"),
+                Style::default().fg(Color::Yellow),
+            ),
         ]),
-        Line::from(Span::styled(
-            "  Enter: confirm  Esc: cancel",
-            Style::default().fg(Color::DarkGray),
-        )),
+        Line::from(vec![
+            Span::styled("  a", key_style),
+            Span::raw(": attach  "),
+            Span::styled("c", key_style),
+            Span::raw(": create numbered copy"),
+        ]),
+        Line::from(vec![
+            Span::styled("  x", key_style),
+            Span::raw(": replace (kill + recreate)"),
+        ]),
+        widgets::hint_line("Esc: cancel"),
     ];
 
-    let popup = Paragraph::new(lines).block(
-        Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Cyan))
-            .title(title)
-            .title_style(
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD),
-            )
-            .style(Style::default().bg(Color::Black)),
-    );
-    frame.render_widget(popup, popup_area);
+    Popup::new("Name Taken", 44, lines)
+        .accent(Color::Yellow)
+        .render(frame);
 }
 
-fn render_confirm_popup(frame: &mut Frame, _app: &App, action: ConfirmAction) {
-    let area = frame.area();
+fn render_clients_popup(frame: &mut Frame, app: &App, session: &str) {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
 
-    let message = match &action {
-        ConfirmAction::KillSession(name) => format!("Kill session `{name}`?"),
-    };
+    let rows = app
+        .clients
+        .iter()
+        .map(|client| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:<14}", client.tty),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(format!(
+                    " {:>9}  idle {}",
+                    format!("{}x{}", client.width, client.height),
+                    format_idle(now - client.activity)
+                )),
+            ])
+        })
+        .collect();
+
+    PickerPopup {
+        title: format!("Clients of {session}"),
+        rows,
+        selected: app.selected_client,
+        empty: "No clients attached",
+        hint: "d: detach  D: detach all  i: detach idle  Esc: close",
+        width: 50,
+        accent: None,
+        detail: Vec::new(),
+    }
+    .render(frame);
+}
 
-    let popup_width = 40u16.min(area.width.saturating_sub(4));
-    let popup_height = 5u16;
-    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
-    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
-    let popup_area = Rect::new(x, y, popup_width, popup_height);
+/// Newest events of a session, oldest at the top, as many as fit.
+fn render_timeline_popup(frame: &mut Frame, app: &App, session_id: &str) {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+    let name = app
+        .sessions
+        .iter()
+        .find(|s| s.id == session_id)
+        .map_or(session_id, |s| s.name.as_str());
+
+    let events = app.timeline.events(session_id);
+    // Margin, borders, the blank lines and the hint.
+    let capacity = (frame.area().height as usize).saturating_sub(7).max(1);
+    let mut lines = vec![Line::from("")];
+    if events.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  Nothing recorded yet",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    for event in events.iter().skip(events.len().saturating_sub(capacity)) {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("  {:>4} ago  ", format_idle(now - event.time)),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::raw(event.kind.describe()),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(widgets::hint_line("Esc: close"));
 
-    frame.render_widget(Clear, popup_area);
+    Popup::new(format!("Timeline of `{name}`"), 60, lines).render(frame);
+}
 
-    let lines = vec![
+fn render_changes_popup(frame: &mut Frame, app: &App) {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+    // Margin, borders, the blank lines and the hint.
+    let capacity = (frame.area().height as usize).saturating_sub(7).max(1);
+    let mut lines = vec![Line::from("")];
+    if app.changes.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  Nothing changed",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    for change in app.changes.iter().take(capacity) {
+        let color = match change {
+            Change::SessionCreated(_) | Change::WindowOpened { .. } => Color::Green,
+            Change::SessionKilled(_) | Change::WindowClosed { .. } => Color::Red,
+            _ => Color::Yellow,
+        };
+        lines.push(Line::from(Span::styled(
+            format!("  {}", change.describe()),
+            Style::default().fg(color),
+        )));
+    }
+    if app.changes.len() > capacity {
+        lines.push(Line::from(Span::styled(
+            format!("  … and {} more", app.changes.len() - capacity),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(widgets::hint_line("Esc: close"));
+
+    let title = format!("Changes since {} ago", format_idle(now - app.changes_since));
+    Popup::new(title, 60, lines).render(frame);
+}
+
+/// Attach counts and time of the listed sessions, least used first, to
+/// spot sessions safe to kill.
+fn render_stats_popup(frame: &mut Frame, app: &App) {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+    let mut sessions: Vec<(&Session, Visit)> = app
+        .sessions
+        .iter()
+        .map(|s| {
+            (
+                s,
+                app.config.visits.get(&s.name).copied().unwrap_or_default(),
+            )
+        })
+        .collect();
+    sessions.sort_by_key(|(_, visit)| (visit.attached_secs, visit.count));
+
+    // Margin, borders, the header, the blank lines and the hint.
+    let capacity = (frame.area().height as usize).saturating_sub(8).max(1);
+    let mut lines = vec![
         Line::from(""),
-        Line::from(vec![
-            Span::raw("  "),
-            Span::styled(message, Style::default().fg(Color::Yellow)),
-        ]),
         Line::from(Span::styled(
-            "  y: confirm  n/Esc: cancel",
+            format!(
+                "  {:<20} {:>8} {:>9}  {}",
+                "Session", "attaches", "attached", "last attached"
+            ),
             Style::default().fg(Color::DarkGray),
         )),
     ];
+    if sessions.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  No sessions",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    for (session, visit) in sessions.iter().take(capacity) {
+        let last = if session.last_attached > 0 {
+            format!("{} ago", format_idle(now - session.last_attached))
+        } else {
+            "never".to_string()
+        };
+        let style = if visit.count == 0 && visit.attached_secs == 0 {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::from(Span::styled(
+            format!(
+                "  {:<20} {:>8} {:>9}  {last}",
+                truncate_with_ellipsis(&session.name, 20),
+                visit.count,
+                format_idle(visit.attached_secs as i64)
+            ),
+            style,
+        )));
+    }
+    if sessions.len() > capacity {
+        lines.push(Line::from(Span::styled(
+            format!("  … and {} more", sessions.len() - capacity),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(widgets::hint_line("Esc: close"));
 
-    let popup = Paragraph::new(lines).block(
-        Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Red))
-            .title(" Confirm ")
-            .title_style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
-            .style(Style::default().bg(Color::Black)),
+    Popup::new("Usage, least used first", 64, lines).render(frame);
+}
+
+/// Lines of the highlighted buffer shown under the list.
+const BUFFER_PREVIEW_LINES: usize = 8;
+
+fn render_buffers_popup(frame: &mut Frame, app: &App, target: &str) {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+
+    let rows = app
+        .buffers
+        .iter()
+        .map(|buffer| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:<10}", buffer.name),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(format!(
+                    " {:>7}  {:>4} ago  ",
+                    format!("{}B", buffer.size),
+                    format_idle(now - buffer.created)
+                )),
+                Span::styled(
+                    buffer.sample.chars().take(40).collect::<String>(),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ])
+        })
+        .collect();
+
+    let mut detail = vec![Line::from(Span::styled(
+        "  ─── contents ───",
+        Style::default().fg(Color::DarkGray),
+    ))];
+    let mut preview = app.buffer_preview.lines();
+    detail.extend(
+        preview
+            .by_ref()
+            .take(BUFFER_PREVIEW_LINES)
+            .map(|line| Line::from(format!("  {line}"))),
     );
-    frame.render_widget(popup, popup_area);
+    if preview.next().is_some() {
+        detail.push(Line::from(Span::styled(
+            "  …",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    PickerPopup {
+        title: format!("Paste Buffers → {target}"),
+        rows,
+        selected: app.selected_buffer,
+        empty: "No paste buffers",
+        hint: "Enter/p: paste  y: copy  d: delete  Esc: close",
+        width: 80,
+        accent: None,
+        detail,
+    }
+    .render(frame);
 }
 
-fn render_windows_panel(frame: &mut Frame, app: &App, area: Rect) {
-    let session_name = if app.search_active {
-        app.filtered_results
-            .get(app.selected)
-            .and_then(|r| app.sessions.get(r.session_index))
-            .map(|s| s.name.clone())
-    } else {
-        app.sessions.get(app.selected).map(|s| s.name.clone())
+fn render_servers_popup(frame: &mut Frame, app: &App) {
+    let rows = app
+        .servers
+        .iter()
+        .map(|server| {
+            let (state, color) = if server.alive {
+                ("running", Color::Green)
+            } else {
+                ("dead", Color::Red)
+            };
+            let mut spans = vec![
+                Span::styled(
+                    format!("{:<20}", server.name()),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::styled(state, Style::default().fg(color)),
+            ];
+            if app.config.servers.contains(&server.name()) {
+                spans.push(Span::styled(" listed", Style::default().fg(Color::Cyan)));
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    PickerPopup {
+        title: "tmux Servers".to_string(),
+        rows,
+        selected: app.selected_server,
+        empty: "No sockets found",
+        hint: "Enter: connect  v: list alongside  x: remove dead  Esc: close",
+        width: 50,
+        accent: None,
+        detail: Vec::new(),
+    }
+    .render(frame);
+}
+
+fn render_pick_popup(frame: &mut Frame, app: &App, purpose: &PickPurpose) {
+    let title = match purpose {
+        PickPurpose::MoveWindow { name, .. } => format!("Move `{name}` to"),
+        PickPurpose::KillPane { window } => format!("Kill a pane of `{window}`"),
+        PickPurpose::BreakPane { window } => format!("Break a pane out of `{window}`"),
+        PickPurpose::JoinPane { window } => format!("Move a pane of `{window}`"),
+        PickPurpose::RespawnPane { window } => format!("Respawn a pane of `{window}`"),
+        PickPurpose::JoinPaneTo { pane_id, .. } => format!("Join {pane_id} into"),
+        PickPurpose::Layout { target } => format!("Layout of `{target}`"),
+        PickPurpose::Group { session } => format!("Session group of `{}`", session.label()),
+        PickPurpose::Host => "tmux server".to_string(),
+        PickPurpose::Options { target, .. } => {
+            return render_options_popup(frame, app, purpose, target);
+        }
+    };
+
+    PickerPopup {
+        title,
+        rows: app
+            .pick_items
+            .iter()
+            .map(|item| Line::from(item.as_str()))
+            .collect(),
+        selected: app.pick_selected,
+        empty: "Nothing to choose",
+        hint: "Enter: choose  Esc: cancel",
+        width: 40,
+        accent: Some(Color::Cyan),
+        detail: Vec::new(),
+    }
+    .render(frame);
+}
+
+fn render_palette_popup(frame: &mut Frame, app: &App) {
+    let commands = app.palette_commands();
+    let label_width = commands.iter().map(|c| c.label.len()).max().unwrap_or(0);
+    let rows = commands
+        .iter()
+        .map(|command| {
+            Line::from(vec![
+                Span::raw(format!("{:<label_width$} ", command.label)),
+                Span::styled(command.key, Style::default().fg(Color::DarkGray)),
+            ])
+        })
+        .collect();
+
+    PickerPopup {
+        title: "Commands".to_string(),
+        rows,
+        selected: app.palette_selected,
+        empty: "No matching command",
+        hint: "type to filter  Enter: run  Esc: close",
+        width: 56,
+        accent: Some(Color::Cyan),
+        detail: Vec::new(),
+    }
+    .render(frame);
+}
+
+fn render_options_popup(frame: &mut Frame, app: &App, purpose: &PickPurpose, target: &str) {
+    let kind = match purpose {
+        PickPurpose::Options { window: true, .. } => "Window",
+        _ => "Session",
     };
+    let name_width = app
+        .options
+        .iter()
+        .map(|option| option.name.len())
+        .max()
+        .unwrap_or(0);
+    let rows = app
+        .options
+        .iter()
+        .map(|option| {
+            let name_style = if option.inherited {
+                Style::default().fg(Color::DarkGray)
+            } else {
+                Style::default().fg(Color::Yellow)
+            };
+            let value_style = match option.value.as_str() {
+                "on" => Style::default().fg(Color::Green),
+                "off" => Style::default().fg(Color::Red),
+                _ => Style::default(),
+            };
+            Line::from(vec![
+                Span::styled(format!("{:<name_width$} ", option.name), name_style),
+                Span::styled(option.value.as_str(), value_style),
+            ])
+        })
+        .collect();
+
+    PickerPopup {
+        title: format!("{kind} Options of `{target}`"),
+        rows,
+        selected: app.pick_selected,
+        empty: "No options set",
+        hint: "Enter: flip on/off or edit  Esc: close",
+        width: 70,
+        accent: Some(Color::Cyan),
+        detail: Vec::new(),
+    }
+    .render(frame);
+}
+
+/// Compact idle time: `42s`, `5m`, `3h`, `2d`.
+fn format_idle(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    match seconds {
+        0..=59 => format!("{seconds}s"),
+        60..=3599 => format!("{}m", seconds / 60),
+        3600..=86399 => format!("{}h", seconds / 3600),
+        _ => format!("{}d", seconds / 86400),
+    }
+}
+
+fn render_windows_panel(frame: &mut Frame, app: &App, area: Rect) {
+    let session_name = app.selected_session().map(|s| s.name.as_str());
 
     let is_focused = app.focus == FocusPanel::Windows;
 
-    let title = session_name
-        .as_deref()
-        .map(|n| format!("Windows [{n}]"))
-        .unwrap_or_else(|| "Windows".to_string());
+    let title = match (session_name, app.window_search_active) {
+        (Some(n), true) => format!("Windows [{n}] /{}", app.input_buffer),
+        (Some(n), false) => format!("Windows [{n}]"),
+        (None, _) => "Windows".to_string(),
+    };
 
     let border_style = if is_focused {
         Style::default().fg(Color::Cyan)
@@ -281,63 +995,80 @@ fn render_windows_panel(frame: &mut Frame, app: &App, area: Rect) {
         .border_style(border_style)
         .title(title);
 
-    let windows = session_name
-        .as_deref()
-        .and_then(|n| app.session_windows.get(n));
-
-    match windows {
-        Some(wins) if !wins.is_empty() => {
-            let items: Vec<ListItem> = wins
-                .iter()
-                .map(|w| {
-                    let active = if w.active { "*" } else { " " };
-                    let text = format!(" {}{} {} ({})", w.index, active, w.name, w.active_command);
-                    let style = if w.active {
-                        Style::default()
-                            .fg(Color::Cyan)
-                            .add_modifier(Modifier::BOLD)
-                    } else {
-                        Style::default().fg(Color::White)
-                    };
-                    ListItem::new(text).style(style)
-                })
-                .collect();
-
-            let mut state = ListState::default();
-            if is_focused {
-                state.select(Some(app.selected_window.min(wins.len().saturating_sub(1))));
-            }
+    let wins = app.visible_windows();
 
-            let list = List::new(items)
-                .block(block)
-                .highlight_symbol(">> ")
-                .highlight_style(
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD),
+    if !wins.is_empty() {
+        let items: Vec<ListItem> = wins
+            .iter()
+            .map(|w| {
+                let text = format!(
+                    " {}{:<1} {} ({})",
+                    w.index,
+                    w.status_flags(),
+                    w.name,
+                    w.activity()
                 );
-            frame.render_stateful_widget(list, area, &mut state);
-        }
-        _ => {
-            let inner = block.inner(area);
-            frame.render_widget(block, area);
-            if inner.width > 0 && inner.height > 0 {
-                let msg = if session_name.is_some() {
-                    "No windows"
+                let style = if w.active {
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD)
                 } else {
-                    "No session selected"
+                    Style::default().fg(Color::White)
                 };
-                let p = Paragraph::new(msg)
-                    .alignment(Alignment::Center)
-                    .style(Style::default().fg(Color::DarkGray));
-                let centered = Layout::vertical([
-                    Constraint::Fill(1),
-                    Constraint::Length(1),
-                    Constraint::Fill(1),
-                ])
-                .split(inner);
-                frame.render_widget(p, centered[1]);
-            }
+                let mut spans = vec![Span::raw(text)];
+                if w.zoomed() {
+                    spans.push(Span::styled(
+                        " [zoomed]",
+                        Style::default()
+                            .fg(Color::Magenta)
+                            .add_modifier(Modifier::BOLD),
+                    ));
+                }
+                if w.piped {
+                    spans.push(Span::styled(
+                        " [logging]",
+                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    ));
+                }
+                ListItem::new(Line::from(spans)).style(style)
+            })
+            .collect();
+
+        let mut state = ListState::default();
+        if is_focused {
+            state.select(Some(app.selected_window.min(wins.len().saturating_sub(1))));
+        }
+
+        let list = List::new(items)
+            .block(block)
+            .highlight_symbol(">> ")
+            .highlight_style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            );
+        frame.render_stateful_widget(list, area, &mut state);
+    } else {
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        if inner.width > 0 && inner.height > 0 {
+            let msg = if session_name.is_none() {
+                "No session selected"
+            } else if app.window_search_active {
+                "No matching windows"
+            } else {
+                "No windows"
+            };
+            let p = Paragraph::new(msg)
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(Color::DarkGray));
+            let centered = Layout::vertical([
+                Constraint::Fill(1),
+                Constraint::Length(1),
+                Constraint::Fill(1),
+            ])
+            .split(inner);
+            frame.render_widget(p, centered[1]);
         }
     }
 }
@@ -404,82 +1135,27 @@ fn render_session_list(frame: &mut Frame, app: &App, area: Rect) {
     }
 
     let available_width = area.width.saturating_sub(5) as usize;
-    let mut items: Vec<ListItem> = Vec::new();
-    let mut selected_item_index: Option<usize> = None;
-
-    if app.search_active {
-        for (vis_idx, match_result) in app.filtered_results.iter().enumerate() {
-            if let Some(session) = app.sessions.get(match_result.session_index) {
-                let is_expanded = app.expanded_sessions.contains(&session.name);
-                let arrow = if is_expanded { "▼" } else { "▶" };
-                let tags = app.config.get_tags(&session.name);
-
-                let line = build_highlighted_session_line(
-                    session,
-                    arrow,
-                    &match_result.indices,
-                    &tags,
-                    available_width,
-                );
-
-                if vis_idx == app.selected {
-                    selected_item_index = Some(items.len());
-                }
-                items.push(ListItem::new(line));
-
-                if is_expanded {
-                    if let Some(windows) = app.session_windows.get(&session.name) {
-                        for window in windows {
-                            let window_line =
-                                format_window_line(window, available_width.saturating_sub(4));
-                            items.push(
-                                ListItem::new(Line::from(format!("  ├─ {window_line}")))
-                                    .style(Style::default().fg(Color::Cyan)),
-                            );
-                        }
-                    }
-                }
-            }
-        }
-    } else {
-        let visible_indices = app.tag_filtered_sessions();
-        for (vis_idx, &session_idx) in visible_indices.iter().enumerate() {
-            if let Some(session) = app.sessions.get(session_idx) {
-                let is_expanded = app.expanded_sessions.contains(&session.name);
-                let arrow = if is_expanded { "▼" } else { "▶" };
-                let tags = app.config.get_tags(&session.name);
-
-                let line = if tags.is_empty() {
-                    let session_text =
-                        format_session_line(session, available_width.saturating_sub(2));
-                    Line::from(format!("{arrow} {session_text}"))
-                } else {
-                    build_session_line_with_tags(session, arrow, &tags, available_width)
-                };
-
-                if vis_idx == app.selected {
-                    selected_item_index = Some(items.len());
-                }
-                items.push(ListItem::new(line));
-
-                if is_expanded {
-                    if let Some(windows) = app.session_windows.get(&session.name) {
-                        for window in windows {
-                            let window_line =
-                                format_window_line(window, available_width.saturating_sub(4));
-                            items.push(
-                                ListItem::new(Line::from(format!("  ├─ {window_line}")))
-                                    .style(Style::default().fg(Color::Cyan)),
-                            );
-                        }
-                    }
-                }
-            }
-        }
-    }
+    let rows = session_rows(app);
+    let selected_row = rows.iter().position(
+        |row| matches!(row, SessionRow::Session { vis_idx, .. } if *vis_idx == app.selected),
+    );
 
-    let mut state = ListState::default();
-    state.select(selected_item_index);
+    // Only rows near the viewport become `ListItem`s: the list scrolls just
+    // far enough to keep the selection on its last row, as it would with
+    // every row built.
+    let height = usize::from(area.height.saturating_sub(2)).max(1);
+    let offset = selected_row.map_or(0, |row| (row + 1).saturating_sub(height));
+    let first = offset.saturating_sub(VIEWPORT_MARGIN);
+    let last = (offset + height + VIEWPORT_MARGIN).min(rows.len());
+    let items: Vec<ListItem> = rows[first..last]
+        .iter()
+        .map(|row| session_row_item(app, row, available_width))
+        .collect();
+    let selected_item_index = selected_row.map(|row| row - first);
+
+    let mut state = ListState::default()
+        .with_offset(offset - first)
+        .with_selected(selected_item_index);
 
     let list = List::new(items)
         .block(
@@ -498,17 +1174,159 @@ fn render_session_list(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_stateful_widget(list, area, &mut state);
 }
 
-fn build_highlighted_session_line<'a>(
-    session: &Session,
-    arrow: &str,
-    match_indices: &[u32],
-    tags: &[String],
-    _available_width: usize,
-) -> Line<'a> {
-    let status = if session.attached > 0 {
-        "attached"
-    } else {
-        "detached"
+/// One row of the sessions panel, before any text is built for it.
+enum SessionRow<'a> {
+    Session {
+        session: &'a Session,
+        /// Position among the visible sessions, as in `App::selected`.
+        vis_idx: usize,
+        /// Fuzzy-match positions while searching.
+        indices: Option<&'a [u32]>,
+    },
+    /// A window listed under its expanded session.
+    Window(&'a Window),
+    /// Directory heading the sessions under it in the path-grouped view,
+    /// or the server heading its sessions when several are listed.
+    Group(String),
+}
+
+/// Every row of the sessions panel in display order.
+fn session_rows(app: &App) -> Vec<SessionRow<'_>> {
+    let sessions: Vec<(&Session, Option<&[u32]>)> = if app.search_active {
+        app.filtered_results
+            .iter()
+            .filter_map(|m| {
+                let session = app.sessions.get(m.session_index)?;
+                Some((session, Some(m.indices.as_slice())))
+            })
+            .collect()
+    } else {
+        app.tag_filtered_sessions()
+            .into_iter()
+            .filter_map(|i| Some((app.sessions.get(i)?, None)))
+            .collect()
+    };
+
+    let grouped = app.config.group_by_path.unwrap_or(false) && !app.search_active;
+    let by_server = app.sessions.iter().any(|s| s.server.is_some()) && !app.search_active;
+    let mut rows = Vec::with_capacity(sessions.len());
+    let mut last_group = None;
+    let mut last_server = None;
+    for (vis_idx, (session, indices)) in sessions.into_iter().enumerate() {
+        if by_server && last_server != Some(&session.server) {
+            rows.push(SessionRow::Group(match &session.server {
+                Some(server) => format!("@{server}"),
+                None => "managed server".to_string(),
+            }));
+            last_server = Some(&session.server);
+            last_group = None;
+        }
+        if grouped {
+            let group = app.path_group(session);
+            if last_group.as_ref() != Some(&group) {
+                rows.push(SessionRow::Group(group.clone()));
+                last_group = Some(group);
+            }
+        }
+        rows.push(SessionRow::Session {
+            session,
+            vis_idx,
+            indices,
+        });
+        let key = session.key();
+        if app.expanded_sessions.contains(&key) {
+            if let Some(windows) = app.session_windows.get(&key) {
+                rows.extend(windows.iter().map(SessionRow::Window));
+            }
+        }
+    }
+    rows
+}
+
+fn session_row_item<'a>(app: &App, row: &SessionRow, available_width: usize) -> ListItem<'a> {
+    let (session, indices, vis_idx) = match row {
+        SessionRow::Group(group) => {
+            return ListItem::new(Line::from(group.clone())).style(
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            );
+        }
+        SessionRow::Window(window) => {
+            let window_line = format_window_line(window, available_width.saturating_sub(4));
+            return ListItem::new(Line::from(format!("  ├─ {window_line}")))
+                .style(Style::default().fg(Color::Cyan));
+        }
+        SessionRow::Session {
+            session,
+            indices,
+            vis_idx,
+        } => (*session, *indices, *vis_idx),
+    };
+    let numbered = app.config.session_numbers.is_some();
+    let visual = app.mode == AppMode::Visual;
+    let available_width = available_width
+        .saturating_sub(if numbered { 2 } else { 0 })
+        .saturating_sub(if visual { 2 } else { 0 });
+
+    let key = session.key();
+    let is_expanded = app.expanded_sessions.contains(&key);
+    let arrow = if is_expanded { "▼" } else { "▶" };
+    let tags = app.config.get_tags(&session.name);
+    let ports = app
+        .session_ports
+        .get(&session.name)
+        .map_or(&[][..], Vec::as_slice);
+
+    let line = match indices {
+        Some(indices) => {
+            build_highlighted_session_line(session, arrow, indices, tags, ports, available_width)
+        }
+        None if tags.is_empty()
+            && !session.runner
+            && ports.is_empty()
+            && session.group.is_none()
+            && session.server.is_none() =>
+        {
+            let session_text = format_session_line(session, available_width.saturating_sub(2));
+            Line::from(format!("{arrow} {session_text}"))
+        }
+        None => build_session_line_with_tags(session, arrow, tags, ports, available_width),
+    };
+    let mut line = with_heat(line, app.heat.get(&key));
+    if numbered {
+        let number = match vis_idx {
+            0..=8 => format!("{} ", vis_idx + 1),
+            _ => "  ".to_string(),
+        };
+        let style = Style::default().fg(Color::DarkGray);
+        line.spans.insert(0, Span::styled(number, style));
+    }
+    if visual {
+        let mark = if app.visual_marked.contains(&key) {
+            Span::styled("✓ ", Style::default().fg(Color::Green))
+        } else {
+            Span::raw("  ")
+        };
+        line.spans.insert(0, mark);
+    }
+    let is_current =
+        session.server.is_none() && app.current_session.as_ref() == Some(&session.name);
+    current_session_item(line, is_current)
+}
+
+fn build_highlighted_session_line<'a>(
+    session: &Session,
+    arrow: &str,
+    match_indices: &[u32],
+    tags: &[String],
+    ports: &[u16],
+    _available_width: usize,
+) -> Line<'a> {
+    let status = if session.attached > 0 {
+        "attached"
+    } else {
+        "detached"
     };
     let indicator = if session.attached > 0 { "●" } else { "○" };
 
@@ -518,16 +1336,72 @@ fn build_highlighted_session_line<'a>(
     spans.push(Span::raw(prefix));
 
     let highlight_style = Style::default().fg(Color::Red).add_modifier(Modifier::BOLD);
-    let normal_style = Style::default();
+    for (run, matched) in match_runs(&session.name, match_indices) {
+        let style = if matched {
+            highlight_style
+        } else {
+            Style::default()
+        };
+        spans.push(Span::styled(run.to_string(), style));
+    }
 
-    let indices_set: std::collections::HashSet<u32> = match_indices.iter().copied().collect();
+    push_badge_spans(&mut spans, session, tags, ports);
 
-    for (char_idx, ch) in session.name.chars().enumerate() {
-        if indices_set.contains(&(char_idx as u32)) {
-            spans.push(Span::styled(ch.to_string(), highlight_style));
-        } else {
-            spans.push(Span::styled(ch.to_string(), normal_style));
+    spans.push(Span::raw(format!(
+        "  {} windows  {status}",
+        session.windows
+    )));
+    Line::from(spans)
+}
+
+/// `text` split into maximal runs of matched or unmatched characters, given
+/// the matched character indices in any order.
+fn match_runs<'t>(text: &'t str, match_indices: &[u32]) -> Vec<(&'t str, bool)> {
+    let mut indices = match_indices.to_vec();
+    indices.sort_unstable();
+    let is_match = |char_idx: usize| indices.binary_search(&(char_idx as u32)).is_ok();
+
+    let mut runs = Vec::new();
+    let mut run_start = 0;
+    let mut run_matched = false;
+    for (char_idx, (byte_idx, _)) in text.char_indices().enumerate() {
+        let matched = is_match(char_idx);
+        if matched != run_matched && byte_idx > run_start {
+            runs.push((&text[run_start..byte_idx], run_matched));
+            run_start = byte_idx;
         }
+        run_matched = matched;
+    }
+    if run_start < text.len() {
+        runs.push((&text[run_start..], run_matched));
+    }
+    runs
+}
+
+/// Append the runner badge and tag badges that follow a session name.
+fn push_badge_spans(spans: &mut Vec<Span<'_>>, session: &Session, tags: &[String], ports: &[u16]) {
+    if let Some(group) = &session.group {
+        spans.push(Span::styled(
+            format!(" {{{group}}}"),
+            Style::default().fg(Color::Blue),
+        ));
+    }
+
+    if let Some(server) = &session.server {
+        spans.push(Span::styled(
+            format!(" @{server}"),
+            Style::default().fg(Color::Cyan),
+        ));
+    }
+
+    if session.runner {
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(
+            "[run]",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
     }
 
     for tag in tags {
@@ -540,15 +1414,55 @@ fn build_highlighted_session_line<'a>(
         ));
     }
 
-    spans.push(Span::raw(format!(
-        "  {} windows  {status}",
-        session.windows
-    )));
+    if !ports.is_empty() {
+        let hint: Vec<String> = ports.iter().map(|p| format!(":{p}")).collect();
+        spans.push(Span::styled(
+            format!(" {}", hint.join(" ")),
+            Style::default().fg(Color::Green),
+        ));
+    }
+}
+
+/// Mark the session tmui's own client is on with a `(current)` suffix and
+/// a distinct color.
+fn current_session_item(mut line: Line<'_>, is_current: bool) -> ListItem<'_> {
+    if !is_current {
+        return ListItem::new(line);
+    }
+    line.spans.push(Span::styled(
+        " (current)",
+        Style::default().add_modifier(Modifier::BOLD),
+    ));
+    ListItem::new(line).style(Style::default().fg(Color::Green))
+}
+
+/// Prefix a session row with its heat glyph once heat has been sampled.
+fn with_heat(line: Line<'_>, heat: Option<Heat>) -> Line<'_> {
+    let Some(heat) = heat else {
+        return line;
+    };
+    let color = match heat {
+        Heat::Idle => Color::DarkGray,
+        Heat::Low => Color::Yellow,
+        Heat::Busy => Color::Red,
+    };
+    let mut spans = vec![
+        Span::styled(heat.glyph(), Style::default().fg(color)),
+        Span::raw(" "),
+    ];
+    spans.extend(line.spans);
     Line::from(spans)
 }
 
 fn render_preview(frame: &mut Frame, app: &App, area: Rect) {
-    let block = Block::default().borders(Borders::ALL).title("Preview");
+    let title = match app.preview_window() {
+        Some(window) if window.alternate => match app.config.alternate_screen.unwrap_or_default() {
+            AlternateScreen::App => format!("Preview ({} full-screen)", window.active_command),
+            AlternateScreen::Shell => format!("Preview (shell under {})", window.active_command),
+        },
+        _ => "Preview".to_string(),
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
 
     if app.preview_content.is_empty() {
         let inner = block.inner(area);
@@ -572,17 +1486,129 @@ fn render_preview(frame: &mut Frame, app: &App, area: Rect) {
         return;
     }
 
-    let text = app
-        .preview_content
-        .as_bytes()
-        .into_text()
-        .unwrap_or_else(|_| ratatui::text::Text::raw("Failed to parse ANSI"));
+    let parsed = if is_garbled(&app.preview_content) {
+        None
+    } else {
+        app.preview_content.as_bytes().into_text().ok()
+    };
+    let mut text = parsed.unwrap_or_else(|| {
+        let notice = match app.preview_window() {
+            Some(window) => format!("interactive app running: {}", window.active_command),
+            None => "unreadable output".to_string(),
+        };
+        let mut lines = vec![Line::styled(
+            notice,
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::ITALIC),
+        )];
+        lines.extend(
+            sanitize(&app.preview_content)
+                .lines()
+                .map(|l| Line::raw(l.to_string())),
+        );
+        ratatui::text::Text::from(lines)
+    });
+
+    let block = if app.preview_scroll > 0 {
+        let inner = block.inner(area);
+        text.lines = scrolled_lines(text.lines, inner.width, inner.height, app.preview_scroll);
+        block.title(format!("Preview (history -{})", app.preview_scroll))
+    } else {
+        block
+    };
 
     let preview = Paragraph::new(text).block(block).wrap(Wrap { trim: false });
 
     frame.render_widget(preview, area);
 }
 
+/// Share of non-printable characters above which a capture is shown as
+/// sanitized text instead of being parsed.
+const GARBLED_RATIO: f64 = 0.3;
+
+/// Whether a capture is mostly bytes no terminal would print, as left by
+/// binary output or a program drawing with raw control codes.
+fn is_garbled(content: &str) -> bool {
+    let mut total = 0usize;
+    let mut unprintable = 0usize;
+    for ch in strip_escapes(content).chars() {
+        if ch.is_whitespace() {
+            continue;
+        }
+        total += 1;
+        if ch.is_control() || ch == char::REPLACEMENT_CHARACTER {
+            unprintable += 1;
+        }
+    }
+    total > 0 && unprintable as f64 / total as f64 > GARBLED_RATIO
+}
+
+/// `content` without escape sequences (CSI, OSC and two-byte escapes).
+fn strip_escapes(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\x1b' {
+            out.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Plain text of `content`: escape sequences and unprintable characters
+/// dropped, tabs and newlines kept.
+fn sanitize(content: &str) -> String {
+    strip_escapes(content)
+        .chars()
+        .filter(|&c| {
+            c == '\n' || c == '\t' || !(c.is_control() || c == char::REPLACEMENT_CHARACTER)
+        })
+        .collect()
+}
+
+/// The lines that fill `height` rows, wrapped at `width`, ending `scroll`
+/// lines above the last non-blank line of a scrollback capture.
+fn scrolled_lines(lines: Vec<Line<'_>>, width: u16, height: u16, scroll: usize) -> Vec<Line<'_>> {
+    let content_len = lines
+        .iter()
+        .rposition(|line| line.width() > 0)
+        .map_or(0, |last| last + 1);
+    let end = content_len.saturating_sub(scroll);
+
+    let width = usize::from(width.max(1));
+    let mut rows = 0;
+    let mut start = end;
+    while start > 0 {
+        let line_rows = lines[start - 1].width().div_ceil(width).max(1);
+        if rows + line_rows > usize::from(height) {
+            break;
+        }
+        rows += line_rows;
+        start -= 1;
+    }
+
+    lines.into_iter().skip(start).take(end - start).collect()
+}
+
 fn format_session_line(session: &Session, max_width: usize) -> String {
     let status = if session.attached > 0 {
         "attached"
@@ -602,6 +1628,7 @@ fn build_session_line_with_tags<'a>(
     session: &Session,
     arrow: &str,
     tags: &[String],
+    ports: &[u16],
     _available_width: usize,
 ) -> Line<'a> {
     let status = if session.attached > 0 {
@@ -613,15 +1640,7 @@ fn build_session_line_with_tags<'a>(
 
     let mut spans: Vec<Span> = vec![Span::raw(format!("{arrow} {indicator} {}", session.name))];
 
-    for tag in tags {
-        spans.push(Span::raw(" "));
-        spans.push(Span::styled(
-            format!("[{tag}]"),
-            Style::default()
-                .fg(Color::Magenta)
-                .add_modifier(Modifier::BOLD),
-        ));
-    }
+    push_badge_spans(&mut spans, session, tags, ports);
 
     spans.push(Span::raw(format!(
         "  {} windows  {status}",
@@ -632,10 +1651,12 @@ fn build_session_line_with_tags<'a>(
 }
 
 fn format_window_line(window: &Window, max_width: usize) -> String {
-    let active_mark = if window.active { "*" } else { " " };
     let full_line = format!(
-        "{}: {}{} ({})",
-        window.index, window.name, active_mark, window.active_command
+        "{}: {}{:<1} ({})",
+        window.index,
+        window.name,
+        window.status_flags(),
+        window.activity()
     );
     truncate_with_ellipsis(&full_line, max_width)
 }
@@ -671,7 +1692,7 @@ fn truncate_with_ellipsis(text: &str, max_width: usize) -> String {
 mod tests {
     use super::*;
     use crate::app::App;
-    use crate::types::Session;
+    use crate::types::{Client, PasteBuffer, Session};
     use ratatui::{backend::TestBackend, buffer::Buffer, Terminal};
 
     fn make_session(name: &str, windows: usize, attached: usize) -> Session {
@@ -684,6 +1705,8 @@ mod tests {
             last_attached: 0,
             group: None,
             path: "/tmp".to_string(),
+            runner: false,
+            server: None,
         }
     }
 
@@ -756,6 +1779,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_render_huge_list_scrolls_to_selection() {
+        let backend = TestBackend::new(120, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.sessions = (0..500)
+            .map(|i| make_session(&format!("s{i:03}"), 1, 0))
+            .collect();
+        app.selected = 300;
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let text = buffer_to_text(terminal.backend().buffer());
+        let selected_line = text
+            .lines()
+            .find(|line| line.contains(">>"))
+            .expect("selection should be visible");
+        assert!(selected_line.contains("s300"));
+        assert!(
+            text.contains("s290"),
+            "rows above the selection fill the list"
+        );
+        assert!(!text.contains("s289") && !text.contains("s301"));
+    }
+
     #[test]
     fn test_render_cjk_session_name() {
         let backend = TestBackend::new(80, 24);
@@ -859,6 +1910,130 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_preview_renders_captured_colors() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.sessions = vec![make_session("test", 1, 0)];
+        app.preview_content = "\x1b[31mfn\x1b[0m main()\n".to_string();
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render with colored preview should succeed");
+
+        let buffer = terminal.backend().buffer();
+        let text = buffer_to_text(buffer);
+        assert!(text.contains("fn main()"));
+        assert!(!text.contains("[31m"), "escape sequences should not leak");
+        let cell = buffer
+            .content
+            .iter()
+            .find(|c| c.symbol() == "f" && c.fg == Color::Red)
+            .expect("keyword should keep its color");
+        assert_eq!(cell.fg, Color::Red);
+    }
+
+    #[test]
+    fn test_preview_garbled_capture_falls_back_to_plain_text() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.sessions = vec![make_session("test", 1, 0)];
+        app.session_windows.insert(
+            "test".to_string(),
+            vec![crate::types::Window {
+                id: "@0".to_string(),
+                session_id: "$0".to_string(),
+                index: 0,
+                name: "top".to_string(),
+                active: true,
+                active_command: "htop".to_string(),
+                pane_title: String::new(),
+                remote_host: None,
+                flags: String::new(),
+                dead: false,
+                piped: false,
+                alternate: false,
+            }],
+        );
+        app.preview_content = "\x01\x02\x03\u{fffd}\u{fffd}\x1b[1mok\x7f\x04\n".to_string();
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render with garbled preview should succeed");
+
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains("interactive app running: htop"));
+        assert!(text.contains("ok"));
+        assert!(!text.contains("Failed to parse ANSI"));
+    }
+
+    #[test]
+    fn test_preview_title_names_full_screen_app() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.sessions = vec![make_session("test", 1, 0)];
+        app.session_windows.insert(
+            "test".to_string(),
+            vec![crate::types::Window {
+                id: "@0".to_string(),
+                session_id: "$0".to_string(),
+                index: 0,
+                name: "edit".to_string(),
+                active: true,
+                active_command: "vim".to_string(),
+                pane_title: String::new(),
+                remote_host: None,
+                flags: String::new(),
+                dead: false,
+                piped: false,
+                alternate: true,
+            }],
+        );
+        app.preview_content = "~\n".to_string();
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains("Preview (vim full-screen)"));
+
+        app.config.alternate_screen = Some(AlternateScreen::Shell);
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains("Preview (shell under vim)"));
+    }
+
+    #[test]
+    fn test_sanitize_strips_escapes_and_controls() {
+        assert!(!is_garbled("\x1b[31mfn\x1b[0m main()\n"));
+        assert_eq!(sanitize("\x1b]0;title\x07a\x1b[2Jb\x00\tc\n"), "ab\tc\n");
+    }
+
+    #[test]
+    fn test_scrolled_lines_window() {
+        let lines: Vec<Line> = (0..10)
+            .map(|i| Line::from(format!("line {i}")))
+            .chain(std::iter::repeat_n(Line::from(""), 3))
+            .collect();
+
+        let visible = scrolled_lines(lines.clone(), 20, 3, 2);
+        let text: Vec<String> = visible.iter().map(|l| l.to_string()).collect();
+        assert_eq!(text, ["line 5", "line 6", "line 7"]);
+
+        let wrapped = scrolled_lines(lines.clone(), 3, 4, 0);
+        assert_eq!(wrapped.len(), 2, "each line wraps onto two rows");
+
+        assert!(scrolled_lines(lines, 20, 3, 50).is_empty());
+    }
+
     #[test]
     fn test_preview_empty_pane() {
         let backend = TestBackend::new(80, 24);
@@ -914,6 +2089,12 @@ mod tests {
                     name: "editor".to_string(),
                     active: true,
                     active_command: "vim".to_string(),
+                    pane_title: String::new(),
+                    remote_host: None,
+                    flags: String::new(),
+                    dead: false,
+                    piped: false,
+                    alternate: false,
                 },
                 crate::types::Window {
                     id: "@1".to_string(),
@@ -922,6 +2103,12 @@ mod tests {
                     name: "shell".to_string(),
                     active: false,
                     active_command: "bash".to_string(),
+                    pane_title: String::new(),
+                    remote_host: None,
+                    flags: String::new(),
+                    dead: false,
+                    piped: false,
+                    alternate: false,
                 },
             ],
         );
@@ -957,6 +2144,12 @@ mod tests {
                 name: "editor".to_string(),
                 active: true,
                 active_command: "vim".to_string(),
+                pane_title: String::new(),
+                remote_host: None,
+                flags: String::new(),
+                dead: false,
+                piped: false,
+                alternate: false,
             }],
         );
 
@@ -972,6 +2165,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_render_window_flags() {
+        let backend = TestBackend::new(120, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.sessions = vec![make_session("dev", 2, 0)];
+        let window = |index: usize, name: &str, flags: &str| crate::types::Window {
+            id: format!("@{index}"),
+            session_id: "$0".to_string(),
+            index,
+            name: name.to_string(),
+            active: flags.contains('*'),
+            active_command: "zsh".to_string(),
+            pane_title: String::new(),
+            remote_host: None,
+            flags: flags.to_string(),
+            dead: false,
+            piped: name == "logs",
+            alternate: false,
+        };
+        app.session_windows.insert(
+            "dev".to_string(),
+            vec![window(0, "main", "*Z"), window(1, "logs", "-!")],
+        );
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains("0*Z main (zsh) [zoomed]"));
+        assert!(text.contains("1-! logs (zsh) [logging]"));
+        assert!(!text.contains("main (zsh) [zoomed] [logging]"));
+    }
+
     #[test]
     fn test_render_window_active_indicator() {
         let backend = TestBackend::new(80, 24);
@@ -989,6 +2218,12 @@ mod tests {
                 name: "main".to_string(),
                 active: true,
                 active_command: "vim".to_string(),
+                pane_title: String::new(),
+                remote_host: None,
+                flags: String::new(),
+                dead: false,
+                piped: false,
+                alternate: false,
             }],
         );
 
@@ -1002,32 +2237,98 @@ mod tests {
     }
 
     #[test]
-    fn test_render_expand_collapse_arrow() {
-        let backend = TestBackend::new(80, 24);
+    fn test_render_window_prefers_pane_title() {
+        let backend = TestBackend::new(120, 24);
         let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
 
         let mut app = App::new();
-        app.sessions = vec![make_session("alpha", 2, 0), make_session("beta", 1, 0)];
-        app.expanded_sessions.insert("alpha".to_string());
+        app.sessions = vec![make_session("ops", 1, 0)];
+        app.session_windows.insert(
+            "ops".to_string(),
+            vec![crate::types::Window {
+                id: "@0".to_string(),
+                session_id: "$0".to_string(),
+                index: 0,
+                name: "remote".to_string(),
+                active: true,
+                active_command: "ssh".to_string(),
+                pane_title: "deploy@prod-db".to_string(),
+                remote_host: None,
+                flags: String::new(),
+                dead: false,
+                piped: false,
+                alternate: false,
+            }],
+        );
 
         terminal
             .draw(|f| render(f, &app))
             .expect("render should succeed");
 
         let text = buffer_to_text(terminal.backend().buffer());
-        assert!(
-            text.contains("▼") || text.contains("▾"),
-            "expanded session should show down arrow"
-        );
-        assert!(
-            text.contains("▶") || text.contains("▸"),
-            "collapsed session should show right arrow"
-        );
+        assert!(text.contains("(deploy@prod-db)"));
     }
 
     #[test]
-    fn test_preview_layout_split() {
-        let backend = TestBackend::new(80, 24);
+    fn test_render_window_ssh_host() {
+        let backend = TestBackend::new(120, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.sessions = vec![make_session("ops", 1, 0)];
+        app.session_windows.insert(
+            "ops".to_string(),
+            vec![crate::types::Window {
+                id: "@0".to_string(),
+                session_id: "$0".to_string(),
+                index: 0,
+                name: "db".to_string(),
+                active: true,
+                active_command: "ssh".to_string(),
+                pane_title: String::new(),
+                remote_host: Some("prod-db".to_string()),
+                flags: String::new(),
+                dead: false,
+                piped: false,
+                alternate: false,
+            }],
+        );
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains("(ssh → prod-db)"));
+    }
+
+    #[test]
+    fn test_render_expand_collapse_arrow() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.sessions = vec![make_session("alpha", 2, 0), make_session("beta", 1, 0)];
+        app.expanded_sessions.insert("alpha".to_string());
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(
+            text.contains("▼") || text.contains("▾"),
+            "expanded session should show down arrow"
+        );
+        assert!(
+            text.contains("▶") || text.contains("▸"),
+            "collapsed session should show right arrow"
+        );
+    }
+
+    #[test]
+    fn test_preview_layout_split() {
+        let backend = TestBackend::new(80, 24);
         let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
 
         let mut app = App::new();
@@ -1095,6 +2396,503 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_render_runner_badge() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        let mut runner = make_session("run-1", 1, 0);
+        runner.runner = true;
+        app.sessions = vec![runner, make_session("work", 1, 0)];
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains("[run]"), "runner session should show a badge");
+    }
+
+    #[test]
+    fn test_render_taken_session_name_inline() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.sessions = vec![make_session("work", 1, 0)];
+        app.mode = AppMode::Input(InputPurpose::NewSession);
+        app.input_buffer = "work".to_string();
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains("✗ `work` already exists"));
+    }
+
+    #[test]
+    fn test_render_timeline_popup() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.sessions = vec![make_session("work", 1, 0)];
+        app.timeline.observe_sessions(&app.sessions, 0);
+        app.sessions[0].attached = 1;
+        app.timeline.observe_sessions(&app.sessions, 0);
+        app.mode = AppMode::Timeline("$work".to_string());
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains("Timeline of `work`"));
+        assert!(text.contains("ago  created"));
+        assert!(text.contains("client attached (1 attached)"));
+    }
+
+    #[test]
+    fn test_render_session_group_badge() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        let mut mirror = make_session("mirror", 1, 0);
+        mirror.group = Some("work".to_string());
+        let mut api = make_session("api", 1, 0);
+        api.server = Some("ci".to_string());
+        app.sessions = vec![mirror, make_session("solo", 1, 0), api];
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains("mirror {work}"));
+        assert!(!text.contains("solo {"));
+        assert!(
+            text.contains("api @ci"),
+            "sessions of extra servers name it"
+        );
+    }
+
+    #[test]
+    fn test_render_session_numbers() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.config.session_numbers = Some(crate::config::SessionNumbers::Select);
+        app.sessions = vec![make_session("work", 1, 0), make_session("play", 1, 0)];
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains("1 ▶ ○ work"));
+        assert!(text.contains("2 ▶ ○ play"));
+    }
+
+    #[test]
+    fn test_render_narrow_profile_hides_preview() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.config.profiles = vec![crate::config::LayoutProfile {
+            max_width: Some(99),
+            preview: Some(false),
+            ..Default::default()
+        }];
+        app.sessions = vec![make_session("work", 1, 0)];
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(!text.contains("Preview"));
+        assert!(list_border(Rect::new(0, 0, 80, 24), &app.config).is_none());
+
+        app.config.profiles[0].max_width = Some(79);
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains("Preview"), "wider screens keep the preview");
+    }
+
+    #[test]
+    fn test_render_sessions_grouped_by_path() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.config.group_by_path = Some(true);
+        let paths = [
+            ("web", "/srv/web"),
+            ("tools", "/opt/tools/bin"),
+            ("db", "/srv/db"),
+        ];
+        app.sessions = paths
+            .iter()
+            .map(|(name, path)| Session {
+                path: path.to_string(),
+                ..make_session(name, 0, 0)
+            })
+            .collect();
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let text = buffer_to_text(terminal.backend().buffer());
+        let position = |needle: &str| text.find(needle).expect(needle);
+        assert!(position("/opt") < position("tools"));
+        assert!(position("tools") < position("/srv"));
+        assert!(position("/srv") < position("web"));
+        assert!(
+            position("web") < position("db"),
+            "list order within a group"
+        );
+    }
+
+    #[test]
+    fn test_render_sessions_under_their_server() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.config.servers = vec!["work".to_string()];
+        let work_main = Session {
+            server: Some("work".to_string()),
+            ..make_session("main", 0, 0)
+        };
+        app.sessions = vec![
+            work_main,
+            make_session("main", 0, 0),
+            make_session("api", 0, 0),
+        ];
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let text = buffer_to_text(terminal.backend().buffer());
+        let position = |needle: &str| text.find(needle).expect(needle);
+        assert!(position("managed server") < position("main"));
+        assert!(position("api") < position("@work"), "managed server first");
+        assert_eq!(text.matches("○ main").count(), 2, "{text}");
+    }
+
+    #[test]
+    fn test_render_naming_feedback_inline() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.config.naming = crate::naming::NamingRule::new(Some("^[a-z-]+$"), Some("team-{name}"));
+        app.mode = AppMode::Input(InputPurpose::NewSession);
+        app.input_buffer = "api".to_string();
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains("→ team-api"), "valid name shows the result");
+
+        app.input_buffer = "API".to_string();
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains("✗"), "invalid name shows an inline error");
+    }
+
+    #[test]
+    fn test_input_places_terminal_cursor_after_text() {
+        let mut terminal =
+            Terminal::new(TestBackend::new(80, 24)).expect("test terminal should initialize");
+        let mut app = App::new();
+        app.sessions = vec![make_session("alpha", 1, 0)];
+        assert_eq!(terminal_title(&app), "tmui – alpha");
+
+        app.mode = AppMode::Input(InputPurpose::RenameSession);
+        app.input_buffer = "api".to_string();
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let position = terminal
+            .get_cursor_position()
+            .expect("cursor position should be readable");
+        let buffer = terminal.backend().buffer();
+        let before_cursor: String = (0..position.x)
+            .map(|x| buffer[(x, position.y)].symbol())
+            .collect();
+        assert!(before_cursor.ends_with("New name: api"), "{before_cursor}");
+    }
+
+    #[test]
+    fn test_match_runs_coalesce() {
+        assert_eq!(
+            match_runs("project-api", &[3, 0, 1, 2, 8]),
+            [("proj", true), ("ect-", false), ("a", true), ("pi", false)]
+        );
+        assert_eq!(
+            match_runs("작업공간", &[1, 2]),
+            [("작", false), ("업공", true), ("간", false)]
+        );
+        assert_eq!(match_runs("abc", &[]), [("abc", false)]);
+        assert!(match_runs("", &[0]).is_empty());
+    }
+
+    #[test]
+    fn test_with_heat_prefixes_glyph() {
+        let line = with_heat(Line::from("▶ work"), Some(Heat::Busy));
+        assert_eq!(line.to_string(), "█ ▶ work");
+        assert_eq!(with_heat(Line::from("▶ work"), None).to_string(), "▶ work");
+    }
+
+    #[test]
+    fn test_render_kill_server_confirm() {
+        let backend = TestBackend::new(100, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.sessions = vec![make_session("a", 1, 0), make_session("b", 1, 0)];
+        app.mode = AppMode::Confirm(ConfirmAction::KillServer);
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains("Kill the tmux server and all 2 sessions?"));
+        assert!(text.contains("Y: kill server"));
+    }
+
+    #[test]
+    fn test_render_current_session_marked() {
+        let backend = TestBackend::new(200, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.sessions = vec![make_session("here", 1, 1), make_session("other", 1, 0)];
+        app.current_session = Some("here".to_string());
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let text = buffer_to_text(terminal.backend().buffer());
+        let here = text
+            .lines()
+            .find(|l| l.contains("here"))
+            .unwrap_or_default();
+        let other = text
+            .lines()
+            .find(|l| l.contains("other"))
+            .unwrap_or_default();
+        assert!(here.contains("(current)"));
+        assert!(!other.contains("(current)"));
+    }
+
+    #[test]
+    fn test_render_servers_popup() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.servers = vec![
+            crate::servers::ServerSocket {
+                path: "/tmp/tmux-1000/default".into(),
+                alive: true,
+            },
+            crate::servers::ServerSocket {
+                path: "/tmp/tmux-1000/old".into(),
+                alive: false,
+            },
+        ];
+        app.mode = AppMode::Servers;
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains("> default"));
+        assert!(text.contains("running"));
+        assert!(text.contains("old"));
+        assert!(text.contains("dead"));
+    }
+
+    #[test]
+    fn test_color_mode_detect() {
+        assert_eq!(
+            ColorMode::detect(Some("xterm-256color"), false, 256),
+            ColorMode::Color
+        );
+        assert_eq!(
+            ColorMode::detect(Some("xterm-256color"), true, 256),
+            ColorMode::Monochrome
+        );
+        assert_eq!(
+            ColorMode::detect(Some("vt100"), false, 0),
+            ColorMode::Monochrome
+        );
+        assert_eq!(ColorMode::detect(Some("dumb"), false, 0), ColorMode::Ascii);
+        assert_eq!(ColorMode::detect(None, false, 0), ColorMode::Ascii);
+    }
+
+    #[test]
+    fn test_render_ascii_mode_drops_color_and_glyphs() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.sessions = vec![make_session("alpha", 1, 1)];
+        app.color_mode = ColorMode::Ascii;
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let buffer = terminal.backend().buffer();
+        let text = buffer_to_text(buffer);
+        assert!(text.contains("> * alpha"), "{text}");
+        assert!(!text.contains('│') && !text.contains('●'));
+        assert!(buffer
+            .content
+            .iter()
+            .all(|cell| cell.fg == Color::Reset && cell.bg == Color::Reset));
+    }
+
+    #[test]
+    fn test_format_idle() {
+        assert_eq!(format_idle(-3), "0s");
+        assert_eq!(format_idle(42), "42s");
+        assert_eq!(format_idle(300), "5m");
+        assert_eq!(format_idle(7200), "2h");
+        assert_eq!(format_idle(200_000), "2d");
+    }
+
+    #[test]
+    fn test_render_stats_popup_lists_least_used_first() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.sessions = vec![make_session("busy", 1, 1), make_session("stale", 1, 0)];
+        app.config.visits = std::collections::HashMap::from([(
+            "busy".to_string(),
+            Visit {
+                count: 7,
+                attached_secs: 7200,
+                ..Visit::default()
+            },
+        )]);
+        app.mode = AppMode::Stats;
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains("Usage, least used first"));
+        let table = &text[text.find("attaches").expect("header is shown")..];
+        let stale = table.find("stale").expect("stale is listed");
+        let busy = table.find("busy").expect("busy is listed");
+        assert!(stale < busy, "least used first:\n{text}");
+        assert!(text.contains("7        2h"), "count and time:\n{text}");
+    }
+
+    #[test]
+    fn test_render_clients_popup() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.sessions = vec![make_session("work", 1, 1)];
+        app.clients = vec![Client {
+            tty: "/dev/pts/3".to_string(),
+            session: "work".to_string(),
+            width: 200,
+            height: 50,
+            activity: 0,
+            created: 0,
+        }];
+        app.mode = AppMode::Clients(crate::types::SessionTarget {
+            server: None,
+            name: "work".to_string(),
+        });
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains("Clients of work"));
+        assert!(text.contains("/dev/pts/3"));
+        assert!(text.contains("200x50"));
+        assert!(text.contains("> /dev/pts/3"));
+    }
+
+    #[test]
+    fn test_render_buffers_popup_previews_selected() {
+        let backend = TestBackend::new(90, 30);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.sessions = vec![make_session("work", 1, 1)];
+        app.buffers = vec![
+            PasteBuffer {
+                name: "buffer1".to_string(),
+                size: 12,
+                created: 0,
+                sample: "make test\\012".to_string(),
+            },
+            PasteBuffer {
+                name: "buffer0".to_string(),
+                size: 3,
+                created: 0,
+                sample: "ls\\012".to_string(),
+            },
+        ];
+        app.buffer_preview = "make test\ncargo run\n".to_string();
+        app.mode = AppMode::Buffers("work".to_string());
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains("Paste Buffers → work"));
+        assert!(text.contains("> buffer1"));
+        assert!(text.contains("  buffer0"));
+        assert!(text.contains("  cargo run"));
+    }
+
+    #[test]
+    fn test_render_port_hints() {
+        let backend = TestBackend::new(100, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.sessions = vec![make_session("web", 1, 0)];
+        app.session_ports
+            .insert("web".to_string(), vec![3000, 5432]);
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains(":3000 :5432"));
+    }
+
     #[test]
     fn test_render_header_session_count() {
         let backend = TestBackend::new(80, 24);
@@ -1137,4 +2935,27 @@ mod tests {
             "status bar should show attach status"
         );
     }
+
+    #[test]
+    fn test_render_status_bar_pane_path_in_windows_focus() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).expect("test terminal should initialize");
+
+        let mut app = App::new();
+        app.sessions = vec![make_session("mywork", 2, 1)];
+        app.pane_path = Some("/srv/api".to_string());
+
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(!text.contains("/srv/api"), "sessions focus hides the path");
+
+        app.focus = FocusPanel::Windows;
+        terminal
+            .draw(|f| render(f, &app))
+            .expect("render should succeed");
+        let text = buffer_to_text(terminal.backend().buffer());
+        assert!(text.contains("mywork (attached) /srv/api"));
+    }
 }
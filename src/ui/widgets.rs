@@ -0,0 +1,360 @@
+//! Dialog building blocks: centered geometry over a cleared background,
+//! and the input, confirmation and picker layouts every flow shares.
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::form::Form;
+
+/// A `width` x `height` rectangle centered in `area`.
+pub fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 2;
+    Rect::new(x, y, width, height)
+}
+
+/// Dimmed key hint shown as a dialog's last line.
+pub fn hint_line<'a>(hint: impl Into<String>) -> Line<'a> {
+    Line::from(Span::styled(
+        format!("  {}", hint.into()),
+        Style::default().fg(Color::DarkGray),
+    ))
+}
+
+/// A bordered dialog sized to its lines, centered over a cleared
+/// background. An accent colors the border and a bold title.
+pub struct Popup<'a> {
+    pub title: String,
+    pub accent: Option<Color>,
+    /// Preferred width; narrower screens shrink it.
+    pub width: u16,
+    pub lines: Vec<Line<'a>>,
+    /// Line whose end gets the terminal cursor, for text being typed.
+    pub cursor_line: Option<usize>,
+}
+
+impl<'a> Popup<'a> {
+    pub fn new(title: impl Into<String>, width: u16, lines: Vec<Line<'a>>) -> Self {
+        Self {
+            title: format!(" {} ", title.into()),
+            accent: None,
+            width,
+            lines,
+            cursor_line: None,
+        }
+    }
+
+    pub fn cursor_at(mut self, line: usize) -> Self {
+        self.cursor_line = Some(line);
+        self
+    }
+
+    pub fn accent(mut self, color: Color) -> Self {
+        self.accent = Some(color);
+        self
+    }
+
+    pub fn render(self, frame: &mut Frame) {
+        let area = frame.area();
+        let width = self.width.min(area.width.saturating_sub(4));
+        let height = (self.lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+        let popup_area = centered_rect(area, width, height);
+
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .title(self.title)
+            .style(Style::default().bg(Color::Black).fg(Color::White));
+        if let Some(color) = self.accent {
+            block = block
+                .border_style(Style::default().fg(color))
+                .title_style(Style::default().fg(color).add_modifier(Modifier::BOLD));
+        }
+
+        let cursor = self.cursor_line.and_then(|row| {
+            let width = self.lines.get(row)?.width() as u16;
+            let x = (popup_area.x + 1 + width).min(popup_area.right().saturating_sub(2));
+            let y = popup_area.y + 1 + row as u16;
+            (y + 1 < popup_area.bottom()).then_some((x, y))
+        });
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(Paragraph::new(self.lines).block(block), popup_area);
+        if let Some(position) = cursor {
+            frame.set_cursor_position(position);
+        }
+    }
+}
+
+/// A single-line text prompt with an optional validation line under it.
+pub struct InputPopup<'a> {
+    pub title: &'a str,
+    pub label: &'a str,
+    pub value: &'a str,
+    pub feedback: Option<Line<'a>>,
+}
+
+impl InputPopup<'_> {
+    pub fn render(self, frame: &mut Frame) {
+        let mut lines = vec![
+            Line::from(""),
+            Line::from(vec![
+                Span::raw("  "),
+                Span::styled(
+                    format!("{}: ", self.label),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(self.value, Style::default().fg(Color::White)),
+            ]),
+        ];
+        lines.extend(self.feedback);
+        lines.push(hint_line("Enter: confirm  Esc: cancel"));
+        Popup::new(self.title, 40, lines)
+            .accent(Color::Cyan)
+            .cursor_at(1)
+            .render(frame);
+    }
+}
+
+/// A yes/no question, optionally listing what it applies to.
+pub struct ConfirmPopup<'a> {
+    pub message: String,
+    pub targets: &'a [String],
+    pub hint: &'a str,
+    /// Show the choices with "No" preselected.
+    pub default_no: bool,
+    /// Phrase to type and what has been typed so far.
+    pub typed: Option<(&'a str, &'a str)>,
+}
+
+impl ConfirmPopup<'_> {
+    pub fn render(self, frame: &mut Frame) {
+        let mut lines = vec![
+            Line::from(""),
+            Line::from(vec![
+                Span::raw("  "),
+                Span::styled(self.message, Style::default().fg(Color::Yellow)),
+            ]),
+        ];
+        lines.extend(
+            self.targets
+                .iter()
+                .map(|target| Line::from(format!("    - {target}"))),
+        );
+        if self.default_no {
+            lines.push(Line::from(vec![
+                Span::raw("  "),
+                Span::styled(" Yes ", Style::default().fg(Color::DarkGray)),
+                Span::raw("  "),
+                Span::styled(
+                    " No ",
+                    Style::default()
+                        .fg(Color::Black)
+                        .bg(Color::White)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]));
+        }
+        let mut cursor_line = None;
+        if let Some((phrase, input)) = self.typed {
+            let color = if input.trim() == phrase {
+                Color::Green
+            } else {
+                Color::White
+            };
+            lines.push(Line::from(vec![
+                Span::styled("  Type ", Style::default().fg(Color::DarkGray)),
+                Span::styled(phrase, Style::default().add_modifier(Modifier::BOLD)),
+                Span::styled(" to confirm", Style::default().fg(Color::DarkGray)),
+            ]));
+            lines.push(Line::from(vec![
+                Span::raw("  > "),
+                Span::styled(input, Style::default().fg(color)),
+            ]));
+            cursor_line = Some(lines.len() - 1);
+        }
+        lines.push(hint_line(self.hint));
+        let mut popup = Popup::new("Confirm", 50, lines).accent(Color::Red);
+        popup.cursor_line = cursor_line;
+        popup.render(frame);
+    }
+}
+
+/// A list with a `>` cursor on the selected row, scrolled to keep it on
+/// screen.
+pub struct PickerPopup<'a> {
+    pub title: String,
+    pub rows: Vec<Line<'a>>,
+    pub selected: usize,
+    /// Shown instead of the rows when there are none.
+    pub empty: &'a str,
+    pub hint: &'a str,
+    pub width: u16,
+    pub accent: Option<Color>,
+    /// Lines under the rows describing the selected one, e.g. a preview.
+    pub detail: Vec<Line<'a>>,
+}
+
+impl PickerPopup<'_> {
+    pub fn render(self, frame: &mut Frame) {
+        let mut lines = vec![Line::from("")];
+        if self.rows.is_empty() {
+            lines.push(Line::from(Span::styled(
+                format!("  {}", self.empty),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+        // Margin, borders, the blank first line, the detail and the hint.
+        let capacity = (frame.area().height as usize)
+            .saturating_sub(6 + self.detail.len())
+            .max(1);
+        let offset = (self.selected + 1).saturating_sub(capacity);
+        for (i, row) in self
+            .rows
+            .into_iter()
+            .enumerate()
+            .skip(offset)
+            .take(capacity)
+        {
+            let (marker, style) = if i == self.selected {
+                (
+                    "> ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )
+            } else {
+                ("  ", Style::default())
+            };
+            let mut spans = vec![Span::styled(marker, style)];
+            spans.extend(row.spans);
+            lines.push(Line::from(spans));
+        }
+        lines.extend(self.detail);
+        lines.push(hint_line(self.hint));
+
+        let mut popup = Popup::new(self.title, self.width, lines);
+        popup.accent = self.accent;
+        popup.render(frame);
+    }
+}
+
+/// Every field of a form, the focused one with a cursor, and the last
+/// validation error.
+pub struct FormPopup<'a> {
+    pub title: &'a str,
+    pub form: &'a Form,
+}
+
+impl FormPopup<'_> {
+    pub fn render(self, frame: &mut Frame) {
+        let label_width = self
+            .form
+            .fields
+            .iter()
+            .map(|field| field.label.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut lines = vec![Line::from("")];
+        for (i, field) in self.form.fields.iter().enumerate() {
+            let focused = i == self.form.focus;
+            let label_style = if focused {
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            let mut spans = vec![Span::styled(
+                format!("  {:>label_width$}: ", field.label),
+                label_style,
+            )];
+            if focused {
+                spans.push(Span::styled(
+                    field.value.as_str(),
+                    Style::default().fg(Color::White),
+                ));
+            } else if field.value.is_empty() {
+                spans.push(Span::styled(
+                    field.placeholder,
+                    Style::default().fg(Color::DarkGray),
+                ));
+            } else {
+                spans.push(Span::raw(field.value.as_str()));
+            }
+            lines.push(Line::from(spans));
+        }
+        if let Some(error) = &self.form.error {
+            lines.push(Line::from(Span::styled(
+                format!("  {error}"),
+                Style::default().fg(Color::Red),
+            )));
+        }
+        let submit = if self.form.is_last_field() {
+            "Enter: confirm"
+        } else {
+            "Enter: next"
+        };
+        lines.push(hint_line(format!("Tab/S-Tab: move  {submit}  Esc: cancel")));
+
+        // The blank first line comes before the fields.
+        Popup::new(self.title, 50, lines)
+            .accent(Color::Cyan)
+            .cursor_at(1 + self.form.focus)
+            .render(frame);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    #[test]
+    fn test_centered_rect() {
+        assert_eq!(
+            centered_rect(Rect::new(0, 0, 100, 30), 40, 10),
+            Rect::new(30, 10, 40, 10)
+        );
+        assert_eq!(
+            centered_rect(Rect::new(5, 2, 10, 4), 40, 10),
+            Rect::new(5, 2, 40, 10),
+            "oversized popups start at the area's corner"
+        );
+    }
+
+    #[test]
+    fn test_picker_marks_selected_row_and_shrinks_to_screen() {
+        let mut terminal =
+            Terminal::new(TestBackend::new(30, 8)).expect("test terminal should initialize");
+        terminal
+            .draw(|frame| {
+                PickerPopup {
+                    title: "Pick".to_string(),
+                    rows: vec![Line::from("first"), Line::from("second")],
+                    selected: 1,
+                    empty: "nothing",
+                    hint: "Enter: choose",
+                    width: 60,
+                    accent: None,
+                    detail: Vec::new(),
+                }
+                .render(frame)
+            })
+            .expect("render should succeed");
+
+        let text = crate::replay::screen_text(terminal.backend().buffer());
+        assert!(text.contains("  first"));
+        assert!(text.contains("> second"));
+        assert!(text.contains("Enter: choose"));
+        assert!(
+            text.lines().all(|line| line.starts_with(' ')),
+            "2-column margin"
+        );
+    }
+}